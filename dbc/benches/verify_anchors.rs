@@ -0,0 +1,97 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Comparison of sequential and parallel verification of 2000 tapret anchors,
+//! each committed by its own witness transaction.
+
+#[macro_use]
+extern crate criterion;
+
+use std::str::FromStr;
+
+use amplify::Wrapper;
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+use commit_verify::{ConsensusCommit, TryCommitVerify};
+use criterion::{black_box, Criterion};
+use dbc::anchor::{AnchorVerification, ANCHOR_MIN_LNPBP4_DEPTH};
+use dbc::tapret::tapret_commit;
+use dbc::{lnpbp4, verify_anchors, verify_anchors_par, Anchor, Proof};
+use secp256k1::XOnlyPublicKey;
+
+const ANCHOR_COUNT: u16 = 2000;
+
+fn fixture() -> Vec<AnchorVerification> {
+    let internal_key = XOnlyPublicKey::from_str(
+        "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+    )
+    .unwrap();
+    (0..ANCHOR_COUNT)
+        .map(|no| {
+            let protocol_id = lnpbp4::ProtocolId::from([1u8; 32]);
+            let message = lnpbp4::Message::hash(&no.to_le_bytes());
+            let source = lnpbp4::MultiSource {
+                min_depth: ANCHOR_MIN_LNPBP4_DEPTH,
+                messages: amplify::bmap! { protocol_id => message },
+            };
+            let tree = lnpbp4::MerkleTree::try_commit(&source).unwrap();
+            let (output_key, proof) = tapret_commit(
+                internal_key,
+                &mut None,
+                tree.consensus_commit().into_inner().into_inner(),
+            )
+            .unwrap();
+            let tx = Transaction {
+                version: 2,
+                lock_time: 0,
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(
+                        Txid::hash(&no.to_le_bytes()),
+                        0,
+                    ),
+                    ..Default::default()
+                }],
+                output: vec![TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new_v1_p2tr_tweaked(output_key),
+                }],
+            };
+            let anchor = Anchor {
+                txid: tx.txid(),
+                lnpbp4_proof: lnpbp4::MerkleBlock::from(&tree),
+                dbc_proof: Proof::TapretFirst(proof),
+            }
+            .into_merkle_proof(protocol_id)
+            .unwrap();
+            (anchor, protocol_id, message, tx)
+        })
+        .collect()
+}
+
+fn verify(c: &mut Criterion) {
+    let anchors = fixture();
+
+    let mut group = c.benchmark_group("2000 anchors");
+    group.bench_function("sequential", |b| {
+        b.iter(|| verify_anchors(black_box(&anchors)))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| verify_anchors_par(black_box(&anchors)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
@@ -18,14 +18,20 @@
 //! transaction which contains the commitment, and multi-protocol merkle tree as
 //! defined by LNPBP-4.
 
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::io::Write;
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+use std::iter;
 
 use amplify::Wrapper;
 use bitcoin::hashes::{sha256, sha256t};
-use bitcoin::{Script, Transaction, Txid};
+#[cfg(feature = "wallet")]
+use bitcoin::Script;
+use bitcoin::{Transaction, Txid};
 use commit_verify::convolve_commit::ConvolveCommitProof;
 use commit_verify::lnpbp4::{self, Message, ProtocolId};
+use commit_verify::merkle::MerkleNode;
 use commit_verify::{
     CommitEncode, CommitVerify, ConsensusCommit, PrehashedProtocol, TaggedHash,
 };
@@ -33,8 +39,12 @@ use commit_verify::{
 use commit_verify::{EmbedCommitProof, EmbedCommitVerify, TryCommitVerify};
 #[cfg(feature = "wallet")]
 use psbt::Psbt;
-use strict_encoding::StrictEncode;
+use strict_encoding::{
+    strict_deserialize, strict_serialize, StrictDecode, StrictEncode,
+};
 
+use crate::limits::MAX_LNPBP4_DEPTH;
+use crate::opret::OpretProof;
 #[cfg(feature = "wallet")]
 use crate::tapret::{Lnpbp6, PsbtCommitError, PsbtVerifyError};
 use crate::tapret::{TapretError, TapretProof};
@@ -42,6 +52,23 @@ use crate::tapret::{TapretError, TapretProof};
 /// Default depth of LNPBP-4 commitment tree
 pub const ANCHOR_MIN_LNPBP4_DEPTH: u8 = 3;
 
+/// Marker starting the versioned [`Anchor`] strict encoding, followed by the
+/// [`ANCHOR_VERSION`] byte.
+///
+/// Anchors encoded before the versioning start directly with the witness
+/// txid, so a single-byte marker can't distinguish them. Unversioned anchors
+/// are decoded whenever the data do not start with the marker; the only
+/// ambiguous ones are the anchors with witness txid starting with the marker
+/// bytes, which happens with 2^-32 probability.
+pub const ANCHOR_MARKER: [u8; 4] = *b"\xFFANC";
+
+/// Version of the [`Anchor`] strict encoding, which is written after the
+/// [`ANCHOR_MARKER`].
+///
+/// The version is not a part of the anchor commitment, so [`AnchorId`] of the
+/// already published anchors does not change with the encoding version.
+pub const ANCHOR_VERSION: u8 = 1;
+
 static MIDSTATE_ANCHOR_ID: [u8; 32] = [
     148, 72, 59, 59, 150, 173, 163, 140, 159, 237, 69, 118, 104, 132, 194, 110,
     250, 108, 1, 140, 74, 248, 152, 205, 70, 32, 184, 87, 20, 102, 127, 20,
@@ -98,6 +125,14 @@ pub enum Error {
     Lnpbp4(lnpbp4::Error),
 }
 
+/// Error decoding anchor with an unknown encoding version. Reported by the
+/// anchor strict decoding as [`strict_encoding::Error::DataIntegrityError`].
+#[derive(
+    Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error
+)]
+#[display("unsupported anchor encoding version {0}")]
+pub struct UnsupportedAnchorVersion(pub u8);
+
 /// Errors verifying anchors.
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(inner)]
@@ -109,13 +144,57 @@ pub enum VerifyError {
     /// LNPBP-4 invalid proof.
     #[from(lnpbp4::UnrelatedProof)]
     Lnpbp4UnrelatedProtocol,
+
+    /// The transaction is not the anchor witness transaction.
+    #[display("transaction {0} is not the anchor witness transaction")]
+    TxidMismatch(Txid),
+
+    /// The transaction does not contain commitment to the anchored data.
+    #[display(
+        "the transaction does not contain commitment to the anchor data"
+    )]
+    NoCommitment,
+}
+
+/// Errors converting anchors between [`AnchorBlock`] and [`AnchorProof`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum InvalidProof {
+    /// LNPBP-4 merkle proof of the anchor is not related to protocol {0}.
+    UnrelatedProtocol(ProtocolId),
+
+    /// LNPBP-4 merkle block of the anchor does not reveal commitment under
+    /// protocol {0}.
+    UnknownProtocol(ProtocolId),
+
+    /// anchor reconstructed from the merkle proof has id {found} instead of
+    /// {expected}, i.e. the proof does not commit to the provided message.
+    AnchorIdMismatch {
+        /// Id of the original anchor.
+        expected: AnchorId,
+        /// Id of the reconstructed anchor.
+        found: AnchorId,
+    },
 }
 
 /// Anchor is a data structure used in deterministic bitcoin commitments for
 /// keeping information about the proof of the commitment in connection to the
 /// transaction which contains the commitment, and multi-protocol merkle tree as
 /// defined by LNPBP-4.
-#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+///
+/// Strict encoding starts with [`ANCHOR_MARKER`] and [`ANCHOR_VERSION`]
+/// byte; decoding rejects other versions with [`UnsupportedAnchorVersion`]
+/// error, as well as LNPBP-4 data exceeding the limits from [`crate::limits`]
+/// or violating the tree structure. Data without the marker are decoded as an
+/// unversioned anchor, which uses the legacy layout of the tapret proof with
+/// a single-byte nonce (see [`Proof::strict_decode_legacy`]). Anchors with
+/// such proofs are encoded unversioned as well, so they keep their original
+/// encoding.
+///
+/// Anchor id ([`Anchor::anchor_id`]) commits only to the consensus data: the
+/// witness txid, DBC proof and LNPBP-4 commitment, but not to the encoding
+/// version.
+#[derive(Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
@@ -132,6 +211,12 @@ pub struct Anchor<L: lnpbp4::Proof> {
     pub dbc_proof: Proof,
 }
 
+/// Anchor containing full LNPBP-4 merkle block.
+pub type AnchorBlock = Anchor<lnpbp4::MerkleBlock>;
+
+/// Anchor containing LNPBP-4 merkle proof for a single protocol.
+pub type AnchorProof = Anchor<lnpbp4::MerkleProof>;
+
 impl CommitEncode for Anchor<lnpbp4::MerkleBlock> {
     fn commit_encode<E: Write>(&self, mut e: E) -> usize {
         let mut len = self
@@ -179,6 +264,379 @@ pub enum MergeError {
 
     /// anchors can't be merged since they have different proofs
     ProofMismatch,
+
+    /// anchors can't be merged since they reveal different commitments in
+    /// the LNPBP-4 tree slot {0}.
+    SlotConflict(u16),
+}
+
+/// Mirror of the [`lnpbp4::MerkleBlock`] strict encoding, providing access to
+/// the block cross-section which is not exposed by `commit_verify`.
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct MerkleBlockData {
+    depth: u8,
+    cross_section: Vec<MerkleBlockNode>,
+    entropy: Option<u64>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+enum MerkleBlockNode {
+    ConcealedNode {
+        depth: u8,
+        hash: MerkleNode,
+    },
+    CommitmentLeaf {
+        protocol_id: ProtocolId,
+        message: Message,
+    },
+}
+
+impl MerkleBlockData {
+    fn with(block: &lnpbp4::MerkleBlock) -> Self {
+        let data = strict_serialize(block)
+            .expect("strict encoding of merkle block must not fail");
+        strict_deserialize(&data)
+            .expect("merkle block strict encoding mirror is broken")
+    }
+
+    /// Number of tree leaves covered by the node, or `None` if the node is
+    /// deeper than the tree.
+    fn node_width(&self, node: &MerkleBlockNode) -> Option<u32> {
+        match node {
+            MerkleBlockNode::ConcealedNode { depth, .. } => {
+                self.depth.checked_sub(*depth).map(|h| 1u32 << h)
+            }
+            MerkleBlockNode::CommitmentLeaf { .. } => Some(1),
+        }
+    }
+
+    /// Checks that the block does not exceed [`MAX_LNPBP4_DEPTH`] and that its
+    /// cross-section covers all the tree leaves with nodes aligned to their
+    /// subtrees and commitment leaves placed into their protocol slots.
+    fn check(&self) -> Result<(), String> {
+        if self.depth > MAX_LNPBP4_DEPTH {
+            return Err(format!(
+                "LNPBP-4 block depth {} exceeds the limit of {}",
+                self.depth, MAX_LNPBP4_DEPTH
+            ));
+        }
+        let width = 1u32 << self.depth;
+        let mut offset = 0u32;
+        for node in &self.cross_section {
+            let node_width = self
+                .node_width(node)
+                .filter(|node_width| offset % node_width == 0)
+                .ok_or_else(|| {
+                    format!(
+                        "LNPBP-4 block node at slot {} is misplaced",
+                        offset
+                    )
+                })?;
+            if let MerkleBlockNode::CommitmentLeaf { protocol_id, .. } = node {
+                // Position is protocol id modulo the tree width, which is
+                // defined by the lower bits of its little-endian integer value
+                let low = protocol_id.as_slice();
+                let pos =
+                    u16::from_le_bytes([low[0], low[1]]) as u32 & (width - 1);
+                if pos != offset {
+                    return Err(format!(
+                        "LNPBP-4 block commitment leaf for protocol {} is \
+                         placed at slot {} instead of {}",
+                        protocol_id, offset, pos
+                    ));
+                }
+            }
+            offset += node_width;
+            if offset > width {
+                break;
+            }
+        }
+        if offset != width {
+            return Err(s!(
+                "LNPBP-4 block cross-section does not match the tree width"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Merges cross-sections of two blocks, taking the most revealed nodes
+    /// from each of them.
+    fn merge_reveal(&self, other: &Self) -> Result<Self, MergeError> {
+        if self.depth != other.depth {
+            return Err(MergeError::Lnpbp4Mismatch);
+        }
+        let width = |node: MerkleBlockNode| {
+            self.node_width(&node).ok_or(MergeError::Lnpbp4Mismatch)
+        };
+
+        let mut cross_section = Vec::with_capacity(
+            self.cross_section.len() + other.cross_section.len(),
+        );
+        let mut a = self.cross_section.iter().copied();
+        let mut b = other.cross_section.iter().copied();
+        let (mut node_a, mut node_b) = (a.next(), b.next());
+        let mut offset = 0u32;
+        while let (Some(n1), Some(n2)) = (node_a, node_b) {
+            let (w1, w2) = (width(n1)?, width(n2)?);
+            // Coarse node is concealed, so it is replaced with the nodes of
+            // the other block covering the same slots
+            let (coarse, fine) = match w1.cmp(&w2) {
+                Ordering::Equal => {
+                    match (n1, n2) {
+                        (
+                            MerkleBlockNode::CommitmentLeaf { .. },
+                            MerkleBlockNode::CommitmentLeaf { .. },
+                        ) if n1 != n2 => {
+                            return Err(MergeError::SlotConflict(offset as u16))
+                        }
+                        (
+                            MerkleBlockNode::ConcealedNode { .. },
+                            MerkleBlockNode::CommitmentLeaf { .. },
+                        ) => cross_section.push(n2),
+                        _ => cross_section.push(n1),
+                    }
+                    offset += w1;
+                    node_a = a.next();
+                    node_b = b.next();
+                    continue;
+                }
+                Ordering::Greater => (w1, &mut b),
+                Ordering::Less => (w2, &mut a),
+            };
+            let end = offset + coarse;
+            let mut next = if w1 > w2 { node_b } else { node_a };
+            while offset < end {
+                let node = next.ok_or(MergeError::Lnpbp4Mismatch)?;
+                offset += width(node)?;
+                cross_section.push(node);
+                next = fine.next();
+            }
+            if offset != end {
+                return Err(MergeError::Lnpbp4Mismatch);
+            }
+            if w1 > w2 {
+                node_a = a.next();
+                node_b = next;
+            } else {
+                node_a = next;
+                node_b = b.next();
+            }
+        }
+        if node_a.is_some() || node_b.is_some() || offset != 1 << self.depth {
+            return Err(MergeError::Lnpbp4Mismatch);
+        }
+
+        Ok(MerkleBlockData {
+            depth: self.depth,
+            cross_section,
+            entropy: self.entropy.or(other.entropy),
+        })
+    }
+}
+
+/// Mirror of the [`lnpbp4::MerkleProof`] strict encoding, providing access to
+/// the merkle path which is not exposed by `commit_verify`.
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct MerkleProofData {
+    pos: u16,
+    path: Vec<MerkleNode>,
+}
+
+fn check_merkle_block(block: &lnpbp4::MerkleBlock) -> Result<(), String> {
+    MerkleBlockData::with(block).check()
+}
+
+fn check_merkle_proof(proof: &lnpbp4::MerkleProof) -> Result<(), String> {
+    let data = strict_serialize(proof)
+        .expect("strict encoding of merkle proof must not fail");
+    let data: MerkleProofData = strict_deserialize(&data)
+        .expect("merkle proof strict encoding mirror is broken");
+    if data.path.len() > MAX_LNPBP4_DEPTH as usize {
+        return Err(format!(
+            "LNPBP-4 merkle proof length {} exceeds the limit of {}",
+            data.path.len(),
+            MAX_LNPBP4_DEPTH
+        ));
+    }
+    if data.pos as u32 >= 1 << data.path.len() {
+        return Err(format!(
+            "LNPBP-4 merkle proof position {} is outside of the tree",
+            data.pos
+        ));
+    }
+    Ok(())
+}
+
+fn check_merkle_tree(tree: &lnpbp4::MerkleTree) -> Result<(), String> {
+    if tree.depth() > MAX_LNPBP4_DEPTH {
+        return Err(format!(
+            "LNPBP-4 tree depth {} exceeds the limit of {}",
+            tree.depth(),
+            MAX_LNPBP4_DEPTH
+        ));
+    }
+    let mut slots = BTreeSet::new();
+    if !tree
+        .messages()
+        .keys()
+        .all(|protocol_id| slots.insert(tree.protocol_id_pos(*protocol_id)))
+    {
+        return Err(s!("LNPBP-4 tree has multiple messages in the same slot"));
+    }
+    Ok(())
+}
+
+impl<L> StrictEncode for Anchor<L>
+where
+    L: lnpbp4::Proof + StrictEncode,
+{
+    fn strict_encode<E: Write>(
+        &self,
+        mut e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        let mut len = 0;
+        if !self.dbc_proof.is_legacy() {
+            e.write_all(&ANCHOR_MARKER)?;
+            len +=
+                ANCHOR_MARKER.len() + ANCHOR_VERSION.strict_encode(&mut e)?;
+        }
+        len += self.txid.strict_encode(&mut e)?
+            + self.lnpbp4_proof.strict_encode(&mut e)?;
+        Ok(len + self.dbc_proof.strict_encode(e)?)
+    }
+}
+
+fn decode_anchor<L: lnpbp4::Proof>(
+    mut d: impl Read,
+    check: impl FnOnce(&L) -> Result<(), String>,
+) -> Result<Anchor<L>, strict_encoding::Error> {
+    let mut marker = [0u8; ANCHOR_MARKER.len()];
+    d.read_exact(&mut marker)?;
+    if marker != ANCHOR_MARKER {
+        // Unversioned encoding starting with the witness txid and using the
+        // legacy layout of the tapret proofs
+        return decode_anchor_fields((&marker[..]).chain(d), check, true);
+    }
+    let version = u8::strict_decode(&mut d)?;
+    if version != ANCHOR_VERSION {
+        return Err(strict_encoding::Error::DataIntegrityError(
+            UnsupportedAnchorVersion(version).to_string(),
+        ));
+    }
+    decode_anchor_fields(d, check, false)
+}
+
+fn decode_anchor_fields<L: lnpbp4::Proof>(
+    mut d: impl Read,
+    check: impl FnOnce(&L) -> Result<(), String>,
+    legacy: bool,
+) -> Result<Anchor<L>, strict_encoding::Error> {
+    let txid = Txid::strict_decode(&mut d)?;
+    let lnpbp4_proof = L::strict_decode(&mut d)?;
+    check(&lnpbp4_proof).map_err(strict_encoding::Error::DataIntegrityError)?;
+    let dbc_proof = if legacy {
+        Proof::strict_decode_legacy(d)?
+    } else {
+        Proof::strict_decode(d)?
+    };
+    Ok(Anchor {
+        txid,
+        lnpbp4_proof,
+        dbc_proof,
+    })
+}
+
+impl StrictDecode for Anchor<lnpbp4::MerkleBlock> {
+    fn strict_decode<D: Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        decode_anchor(d, check_merkle_block)
+    }
+}
+
+impl StrictDecode for Anchor<lnpbp4::MerkleProof> {
+    fn strict_decode<D: Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        decode_anchor(d, check_merkle_proof)
+    }
+}
+
+impl StrictDecode for Anchor<lnpbp4::MerkleTree> {
+    fn strict_decode<D: Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        decode_anchor(d, check_merkle_tree)
+    }
+}
+
+/// Strict-encodes `anchors` one by one into the `writer`, producing the same
+/// data as the strict encoding of the collection of the anchors (their number
+/// followed by the encoded anchors), but without collecting them in memory.
+///
+/// Returns number of the written bytes.
+///
+/// # Errors
+///
+/// If the number of anchors exceeds the maximal number of collection items
+/// supported by strict encoding, if the iterator produces other number of
+/// anchors than it reports or if the writer fails.
+pub fn write_anchors<L, W, I>(
+    mut writer: W,
+    anchors: I,
+) -> Result<usize, strict_encoding::Error>
+where
+    L: lnpbp4::Proof + StrictEncode,
+    W: Write,
+    I: IntoIterator,
+    I::IntoIter: ExactSizeIterator,
+    I::Item: Borrow<Anchor<L>>,
+{
+    let anchors = anchors.into_iter();
+    let count = anchors.len();
+    let mut len = count.strict_encode(&mut writer)?;
+    let mut written = 0usize;
+    for anchor in anchors {
+        len += anchor.borrow().strict_encode(&mut writer)?;
+        written += 1;
+    }
+    if written != count {
+        return Err(strict_encoding::Error::DataIntegrityError(format!(
+            "anchor iterator produced {} anchors instead of {}",
+            written, count
+        )));
+    }
+    Ok(len)
+}
+
+/// Reads anchors encoded with [`write_anchors`] (or as a strict-encoded
+/// collection) from the `reader` one by one, without collecting them in
+/// memory.
+///
+/// The iterator stops after the last anchor of the collection or after the
+/// first error; the `reader` is not read past the end of the collection.
+pub fn read_anchors<L, R>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<Anchor<L>, strict_encoding::Error>>
+where
+    L: lnpbp4::Proof,
+    Anchor<L>: StrictDecode,
+    R: Read,
+{
+    let mut remaining = None::<usize>;
+    iter::from_fn(move || {
+        let count = match remaining {
+            Some(count) => count,
+            None => match usize::strict_decode(&mut reader) {
+                Ok(count) => count,
+                Err(err) => {
+                    remaining = Some(0);
+                    return Some(Err(err));
+                }
+            },
+        };
+        if count == 0 {
+            remaining = Some(0);
+            return None;
+        }
+        let anchor = Anchor::<L>::strict_decode(&mut reader);
+        remaining = Some(if anchor.is_ok() { count - 1 } else { 0 });
+        Some(anchor)
+    })
 }
 
 impl Anchor<lnpbp4::MerkleBlock> {
@@ -207,7 +665,7 @@ impl Anchor<lnpbp4::MerkleProof> {
     pub fn anchor_id(
         &self,
         protocol_id: impl Into<ProtocolId>,
-        message: Message,
+        message: impl Into<Message>,
     ) -> Result<AnchorId, lnpbp4::UnrelatedProof> {
         Ok(self.to_merkle_block(protocol_id, message)?.anchor_id())
     }
@@ -216,12 +674,12 @@ impl Anchor<lnpbp4::MerkleProof> {
     pub fn into_merkle_block(
         self,
         protocol_id: impl Into<ProtocolId>,
-        message: Message,
+        message: impl Into<Message>,
     ) -> Result<Anchor<lnpbp4::MerkleBlock>, lnpbp4::UnrelatedProof> {
         let lnpbp4_proof = lnpbp4::MerkleBlock::with(
             &self.lnpbp4_proof,
             protocol_id.into(),
-            message,
+            message.into(),
         )?;
         Ok(Anchor {
             txid: self.txid,
@@ -234,25 +692,62 @@ impl Anchor<lnpbp4::MerkleProof> {
     pub fn to_merkle_block(
         &self,
         protocol_id: impl Into<ProtocolId>,
-        message: Message,
+        message: impl Into<Message>,
     ) -> Result<Anchor<lnpbp4::MerkleBlock>, lnpbp4::UnrelatedProof> {
         self.clone().into_merkle_block(protocol_id, message)
     }
 
+    /// Reconstructs anchor containing merkle block from the known message
+    /// committed under `protocol_id`.
+    ///
+    /// The merkle proof does not contain the message, so reconstruction with
+    /// a wrong message succeeds, producing anchor with a different
+    /// [`AnchorId`]; use [`AnchorProof::into_block_checked`] to detect this.
+    pub fn into_block(
+        self,
+        protocol_id: impl Into<ProtocolId>,
+        message: impl Into<Message>,
+    ) -> Result<AnchorBlock, InvalidProof> {
+        let protocol_id = protocol_id.into();
+        self.into_merkle_block(protocol_id, message)
+            .map_err(|_| InvalidProof::UnrelatedProtocol(protocol_id))
+    }
+
+    /// Reconstructs anchor containing merkle block like
+    /// [`AnchorProof::into_block`], checking that the reconstructed anchor
+    /// has the `expected` id of the original anchor.
+    pub fn into_block_checked(
+        self,
+        protocol_id: impl Into<ProtocolId>,
+        message: impl Into<Message>,
+        expected: AnchorId,
+    ) -> Result<AnchorBlock, InvalidProof> {
+        let anchor = self.into_block(protocol_id, message)?;
+        let found = anchor.anchor_id();
+        if found != expected {
+            return Err(InvalidProof::AnchorIdMismatch { expected, found });
+        }
+        Ok(anchor)
+    }
+
     /// Verifies that the transaction commits to the anchor and the anchor
     /// commits to the given message under the given protocol.
     pub fn verify(
         &self,
         protocol_id: impl Into<ProtocolId>,
-        message: Message,
+        message: impl Into<Message>,
         tx: Transaction,
-    ) -> Result<bool, VerifyError> {
-        self.dbc_proof
-            .verify(
-                &self.lnpbp4_proof.convolve(protocol_id.into(), message)?,
-                tx,
-            )
-            .map_err(VerifyError::from)
+    ) -> Result<(), VerifyError> {
+        if tx.txid() != self.txid {
+            return Err(VerifyError::TxidMismatch(tx.txid()));
+        }
+        let commitment = self
+            .lnpbp4_proof
+            .convolve(protocol_id.into(), message.into())?;
+        match self.dbc_proof.verify(&commitment, tx)? {
+            true => Ok(()),
+            false => Err(VerifyError::NoCommitment),
+        }
     }
 
     /// Verifies that the anchor commits to the given message under the given
@@ -260,12 +755,60 @@ impl Anchor<lnpbp4::MerkleProof> {
     pub fn convolve(
         &self,
         protocol_id: impl Into<ProtocolId>,
-        message: Message,
+        message: impl Into<Message>,
     ) -> Result<lnpbp4::CommitmentHash, lnpbp4::UnrelatedProof> {
-        self.lnpbp4_proof.convolve(protocol_id.into(), message)
+        self.lnpbp4_proof
+            .convolve(protocol_id.into(), message.into())
     }
 }
 
+/// Anchor together with the protocol id, message and witness transaction it is
+/// verified against.
+pub type AnchorVerification = (
+    Anchor<lnpbp4::MerkleProof>,
+    ProtocolId,
+    Message,
+    Transaction,
+);
+
+// Anchor verification does not keep any shared mutable state, so anchors can
+// be verified from multiple threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AnchorVerification>();
+    assert_send_sync::<VerifyError>();
+};
+
+/// Verifies each of the `anchors` with [`Anchor::verify`], returning
+/// verification results in the same order.
+pub fn verify_anchors(
+    anchors: &[AnchorVerification],
+) -> Vec<Result<(), VerifyError>> {
+    anchors
+        .iter()
+        .map(|(anchor, protocol_id, message, tx)| {
+            anchor.verify(*protocol_id, *message, tx.clone())
+        })
+        .collect()
+}
+
+/// Verifies each of the `anchors` with [`Anchor::verify`] using rayon thread
+/// pool. The results are returned in the same order as the anchors and are
+/// always equal to the results of [`verify_anchors`].
+#[cfg(feature = "rayon")]
+pub fn verify_anchors_par(
+    anchors: &[AnchorVerification],
+) -> Vec<Result<(), VerifyError>> {
+    use rayon::prelude::*;
+
+    anchors
+        .par_iter()
+        .map(|(anchor, protocol_id, message, tx)| {
+            anchor.verify(*protocol_id, *message, tx.clone())
+        })
+        .collect()
+}
+
 impl Anchor<lnpbp4::MerkleBlock> {
     /// Conceals all LNPBP-4 data except specific protocol and produces merkle
     /// proof anchor.
@@ -276,6 +819,18 @@ impl Anchor<lnpbp4::MerkleBlock> {
         self.clone().into_merkle_proof(protocol)
     }
 
+    /// Conceals all LNPBP-4 data except specific protocol and produces merkle
+    /// proof anchor, which has the same [`AnchorId`] when converted back with
+    /// [`AnchorProof::into_block`].
+    pub fn to_proof(
+        &self,
+        protocol_id: impl Into<ProtocolId>,
+    ) -> Result<AnchorProof, InvalidProof> {
+        let protocol_id = protocol_id.into();
+        self.to_merkle_proof(protocol_id)
+            .map_err(|_| InvalidProof::UnknownProtocol(protocol_id))
+    }
+
     /// Conceals all LNPBP-4 data except specific protocol and converts anchor
     /// into merkle proof anchor.
     pub fn into_merkle_proof(
@@ -291,6 +846,34 @@ impl Anchor<lnpbp4::MerkleBlock> {
         })
     }
 
+    /// Returns ids of the protocols which commitments are revealed in the
+    /// anchor.
+    pub fn known_protocols(&self) -> BTreeSet<ProtocolId> {
+        MerkleBlockData::with(&self.lnpbp4_proof)
+            .cross_section
+            .into_iter()
+            .filter_map(|node| match node {
+                MerkleBlockNode::ConcealedNode { .. } => None,
+                MerkleBlockNode::CommitmentLeaf { protocol_id, .. } => {
+                    Some(protocol_id)
+                }
+            })
+            .collect()
+    }
+
+    /// Constructs anchor revealing only the commitment under `protocol_id`,
+    /// concealing commitments of all other protocols and the entropy used for
+    /// the placeholders. The anchor keeps its id and verification for the
+    /// retained protocol.
+    pub fn restricted_to(
+        &self,
+        protocol_id: impl Into<ProtocolId>,
+    ) -> Result<Self, lnpbp4::LeafNotKnown> {
+        let mut anchor = self.clone();
+        anchor.conceal_except([protocol_id.into()])?;
+        Ok(anchor)
+    }
+
     /// Conceals all LNPBP-4 data except specific protocol.
     pub fn conceal_except(
         &mut self,
@@ -299,7 +882,14 @@ impl Anchor<lnpbp4::MerkleBlock> {
         self.lnpbp4_proof.conceal_except(protocols)
     }
 
-    /// Merges two anchors keeping revealed data.
+    /// Merges two anchors keeping revealed data. The merged anchor verifies
+    /// for every protocol which is revealed in any of the anchors.
+    ///
+    /// # Errors
+    ///
+    /// If the anchors have different witness transactions or DBC proofs, or
+    /// if their LNPBP-4 blocks reveal different commitments in the same slot
+    /// or commit to different LNPBP-4 trees.
     pub fn merge_reveal(mut self, other: Self) -> Result<Self, MergeError> {
         if self.txid != other.txid {
             return Err(MergeError::TxidMismatch);
@@ -307,7 +897,20 @@ impl Anchor<lnpbp4::MerkleBlock> {
         if self.dbc_proof != other.dbc_proof {
             return Err(MergeError::ProofMismatch);
         }
-        self.lnpbp4_proof.merge_reveal(other.lnpbp4_proof)?;
+        let merged = MerkleBlockData::with(&self.lnpbp4_proof)
+            .merge_reveal(&MerkleBlockData::with(&other.lnpbp4_proof))?;
+        let commitment = self.lnpbp4_proof.consensus_commit();
+        if other.lnpbp4_proof.consensus_commit() != commitment {
+            return Err(MergeError::Lnpbp4Mismatch);
+        }
+        let data = strict_serialize(&merged)
+            .expect("strict encoding of merkle block must not fail");
+        let lnpbp4_proof: lnpbp4::MerkleBlock = strict_deserialize(&data)
+            .map_err(|_| MergeError::Lnpbp4Mismatch)?;
+        if lnpbp4_proof.consensus_commit() != commitment {
+            return Err(MergeError::Lnpbp4Mismatch);
+        }
+        self.lnpbp4_proof = lnpbp4_proof;
         Ok(self)
     }
 }
@@ -330,7 +933,7 @@ impl EmbedCommitProof<PsbtEmbeddedMessage, Psbt, Lnpbp6>
         psbt: &Psbt,
     ) -> Result<Psbt, PsbtVerifyError> {
         match self.dbc_proof {
-            Proof::OpretFirst => Ok(psbt.clone()),
+            Proof::OpretFirst(_) => Ok(psbt.clone()),
             Proof::TapretFirst(ref proof) => {
                 let mut psbt = psbt.clone();
                 for output in &mut psbt.outputs {
@@ -387,7 +990,7 @@ impl EmbedCommitVerify<PsbtEmbeddedMessage, Lnpbp6> for Psbt {
             output.script = Script::new_op_return(commitment.as_slice());
             output.set_opret_commitment(commitment.into_array())?;
             output.set_lnpbp4_entropy(tree.entropy())?;
-            (Proof::OpretFirst, tree)
+            (Proof::OpretFirst(OpretProof), tree)
         } else {
             return Err(PsbtCommitError::CommitmentImpossible);
         };
@@ -413,13 +1016,57 @@ impl EmbedCommitVerify<PsbtEmbeddedMessage, Lnpbp6> for Psbt {
 #[non_exhaustive]
 pub enum Proof {
     /// Opret commitment (no extra-transaction proof is required).
-    OpretFirst,
+    OpretFirst(OpretProof),
 
     /// Tapret commitment and a proof of it.
     TapretFirst(TapretProof),
 }
 
 impl Proof {
+    /// Returns method of the deterministic bitcoin commitment used by the
+    /// proof.
+    pub fn method(&self) -> Method {
+        match self {
+            Proof::OpretFirst(_) => Method::OpretFirst,
+            Proof::TapretFirst(_) => Method::TapretFirst,
+        }
+    }
+
+    /// Checks whether the proof uses the given commitment `method`. Allows
+    /// detecting proofs not matching the method required by the data they
+    /// are paired with before running the proof verification.
+    #[inline]
+    pub fn matches_method(&self, method: impl Into<Method>) -> bool {
+        self.method() == method.into()
+    }
+
+    /// Detects whether the proof is a tapret proof with the legacy single-byte
+    /// nonce, which is strict-encoded in the legacy layout.
+    pub fn is_legacy(&self) -> bool {
+        match self {
+            Proof::OpretFirst(_) => false,
+            Proof::TapretFirst(proof) => {
+                proof.path_proof.tapret_nonce().is_legacy()
+            }
+        }
+    }
+
+    /// Decodes proof strict-encoded with the legacy single-byte tapret nonce
+    /// (see [`TapretProof::strict_decode_legacy`]). Opret proofs have the same
+    /// encoding in both layouts.
+    pub fn strict_decode_legacy<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        match u8::strict_decode(&mut d)? {
+            0 => Ok(Proof::OpretFirst(OpretProof)),
+            1 => Ok(Proof::TapretFirst(TapretProof::strict_decode_legacy(d)?)),
+            tag => Err(strict_encoding::Error::EnumValueNotKnown(
+                "Proof",
+                tag as usize,
+            )),
+        }
+    }
+
     /// Verifies validity of the proof.
     pub fn verify(
         &self,
@@ -427,15 +1074,14 @@ impl Proof {
         tx: Transaction,
     ) -> Result<bool, TapretError> {
         match self {
-            Proof::OpretFirst => {
-                for txout in &tx.output {
-                    if txout.script_pubkey.is_op_return() {
-                        return Ok(txout.script_pubkey
-                            == Script::new_op_return(msg.as_slice()));
-                    }
-                }
-                Ok(false)
-            }
+            Proof::OpretFirst(proof) => Ok(tx
+                .output
+                .iter()
+                .find(|txout| txout.script_pubkey.is_op_return())
+                .map(|txout| {
+                    proof.verify(msg.into_array(), &txout.script_pubkey)
+                })
+                .unwrap_or_default()),
             Proof::TapretFirst(proof) => {
                 ConvolveCommitProof::<_, Transaction, _>::verify(proof, msg, tx)
             }
@@ -443,15 +1089,826 @@ impl Proof {
     }
 }
 
+/// Method of the deterministic bitcoin commitment, matching the variants of
+/// [`Proof`].
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[derive(StrictEncode, StrictDecode)]
+#[strict_encoding(by_value)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum Method {
+    /// OP_RETURN commitment present in the first OP_RETURN-containing
+    /// transaction output.
+    #[display("opret1st")]
+    OpretFirst = 0x00,
+
+    /// Taproot-based OP_RETURN commitment present in the first Taproot
+    /// transaction output.
+    #[display("tapret1st")]
+    TapretFirst = 0x01,
+}
+
 #[cfg(test)]
 mod test {
-    use commit_verify::tagged_hash;
+    use std::str::FromStr;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use commit_verify::{tagged_hash, TryCommitVerify};
+    use secp256k1::XOnlyPublicKey;
+    use strict_encoding::{strict_deserialize, strict_serialize};
 
     use super::*;
+    use crate::tapret::tapret_commit;
+
+    fn protocol_id(no: u8) -> ProtocolId { ProtocolId::from([no; 32]) }
+
+    fn message(no: u8) -> Message { Message::hash(&[no]) }
+
+    fn merkle_tree() -> lnpbp4::MerkleTree {
+        let source = lnpbp4::MultiSource {
+            min_depth: ANCHOR_MIN_LNPBP4_DEPTH,
+            messages: bmap! {
+                protocol_id(1) => message(1),
+                protocol_id(2) => message(2),
+                protocol_id(3) => message(3)
+            },
+        };
+        lnpbp4::MerkleTree::try_commit(&source).unwrap()
+    }
+
+    fn tx(script_pubkey: Script) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::hash(b"prevout"), 0),
+                ..default!()
+            }],
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new(),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey,
+                },
+            ],
+        }
+    }
+
+    fn anchor(
+        tx: &Transaction,
+        tree: &lnpbp4::MerkleTree,
+        dbc_proof: Proof,
+    ) -> Anchor<lnpbp4::MerkleBlock> {
+        Anchor {
+            txid: tx.txid(),
+            lnpbp4_proof: lnpbp4::MerkleBlock::from(tree),
+            dbc_proof,
+        }
+    }
+
+    fn check_anchor(anchor: &Anchor<lnpbp4::MerkleBlock>, tx: &Transaction) {
+        let data = strict_serialize(anchor).unwrap();
+        assert_eq!(&strict_deserialize::<Anchor<_>>(&data).unwrap(), anchor);
+
+        for no in 1..=3 {
+            let proof = anchor.to_merkle_proof(protocol_id(no)).unwrap();
+            let data = strict_serialize(&proof).unwrap();
+            assert_eq!(strict_deserialize::<Anchor<_>>(&data).unwrap(), proof);
+
+            assert_eq!(
+                proof.anchor_id(protocol_id(no), message(no)),
+                Ok(anchor.anchor_id())
+            );
+            assert_eq!(
+                proof.verify(protocol_id(no), message(no), tx.clone()),
+                Ok(())
+            );
+            assert_eq!(
+                proof.verify(protocol_id(no), message(0), tx.clone()),
+                Err(VerifyError::NoCommitment)
+            );
+        }
+
+        let mut concealed = anchor.clone();
+        concealed.conceal_except([protocol_id(2)]).unwrap();
+        assert_eq!(concealed.anchor_id(), anchor.anchor_id());
+        assert!(concealed.to_merkle_proof(protocol_id(1)).is_err());
+        let merged = concealed.merge_reveal(anchor.clone()).unwrap();
+        assert_eq!(merged.anchor_id(), anchor.anchor_id());
+        assert!(merged.to_merkle_proof(protocol_id(1)).is_ok());
+    }
+
+    #[test]
+    fn lnpbp4_placement() {
+        let hash = |round: usize, no: usize| {
+            sha256::Hash::hash(format!("{}:{}", round, no).as_bytes())
+        };
+        for round in 0..4 {
+            for count in 1..=16 {
+                let messages = (0..count)
+                    .map(|no| {
+                        let protocol_id =
+                            ProtocolId::from(hash(round, no).into_inner());
+                        (protocol_id, Message::hash(&[no as u8]))
+                    })
+                    .collect::<lnpbp4::MessageMap>();
+                let source = lnpbp4::MultiSource {
+                    min_depth: ANCHOR_MIN_LNPBP4_DEPTH,
+                    messages: messages.clone(),
+                };
+                let tree = lnpbp4::MerkleTree::try_commit(&source).unwrap();
+                let root = tree.consensus_commit();
+                let block = lnpbp4::MerkleBlock::from(&tree);
+
+                let mut slots = messages
+                    .keys()
+                    .map(|protocol_id| block.protocol_id_pos(*protocol_id))
+                    .collect::<Vec<_>>();
+                slots.sort_unstable();
+                slots.dedup();
+                assert_eq!(slots.len(), count);
+                assert!(slots
+                    .iter()
+                    .all(|pos| (*pos as usize) < block.width()));
+
+                for (protocol_id, message) in messages {
+                    let proof = block.to_merkle_proof(protocol_id).unwrap();
+                    assert_eq!(proof.convolve(protocol_id, message), Ok(root));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn opret_anchor() {
+        let tree = merkle_tree();
+        let commitment = tree.consensus_commit();
+        let tx = tx(Script::new_op_return(commitment.as_slice()));
+        let anchor = anchor(&tx, &tree, Proof::OpretFirst(OpretProof));
+        check_anchor(&anchor, &tx);
+
+        let proof = anchor.to_merkle_proof(protocol_id(1)).unwrap();
+        let other_tx = self::tx(Script::new_op_return(&[0u8; 32]));
+        assert_eq!(
+            proof.verify(protocol_id(1), message(1), other_tx.clone()),
+            Err(VerifyError::TxidMismatch(other_tx.txid()))
+        );
+        let mut proof = proof;
+        proof.txid = other_tx.txid();
+        assert_eq!(
+            proof.verify(protocol_id(1), message(1), other_tx),
+            Err(VerifyError::NoCommitment)
+        );
+    }
+
+    #[test]
+    fn tapret_anchor() {
+        let internal_key = XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let tree = merkle_tree();
+        let commitment = tree.consensus_commit();
+        let (output_key, tapret_proof) = tapret_commit(
+            internal_key,
+            &mut None,
+            commitment.into_inner().into_inner(),
+        )
+        .unwrap();
+        let tx = tx(Script::new_v1_p2tr_tweaked(output_key));
+        let anchor = anchor(&tx, &tree, Proof::TapretFirst(tapret_proof));
+        check_anchor(&anchor, &tx);
+    }
+
+    fn opret_anchor_revealing(
+        tree: &lnpbp4::MerkleTree,
+        protocols: impl IntoIterator<Item = u8>,
+    ) -> (Anchor<lnpbp4::MerkleBlock>, Transaction) {
+        let tx = tx(Script::new_op_return(tree.consensus_commit().as_slice()));
+        let mut anchor = anchor(&tx, tree, Proof::OpretFirst(OpretProof));
+        anchor
+            .conceal_except(
+                protocols.into_iter().map(protocol_id).collect::<Vec<_>>(),
+            )
+            .unwrap();
+        (anchor, tx)
+    }
+
+    fn check_revealed(
+        anchor: &Anchor<lnpbp4::MerkleBlock>,
+        tx: &Transaction,
+        protocols: impl IntoIterator<Item = u8>,
+    ) {
+        for no in protocols {
+            let proof = anchor.to_merkle_proof(protocol_id(no)).unwrap();
+            assert_eq!(
+                proof.verify(protocol_id(no), message(no), tx.clone()),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn merkle_block_mirror() {
+        let tree = merkle_tree();
+        for protocols in [vec![], vec![1], vec![2, 3], vec![1, 2, 3]] {
+            let (anchor, _) = opret_anchor_revealing(&tree, protocols);
+            let block = &anchor.lnpbp4_proof;
+            assert_eq!(
+                strict_serialize(&MerkleBlockData::with(block)).unwrap(),
+                strict_serialize(block).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn merge_disjoint() {
+        let tree = merkle_tree();
+        for (a, b) in [(1, 2), (1, 3), (2, 3)] {
+            let (anchor_a, tx) = opret_anchor_revealing(&tree, [a]);
+            let (anchor_b, _) = opret_anchor_revealing(&tree, [b]);
+            assert!(anchor_a.to_merkle_proof(protocol_id(b)).is_err());
+
+            let merged =
+                anchor_a.clone().merge_reveal(anchor_b.clone()).unwrap();
+            assert_eq!(merged.anchor_id(), anchor_a.anchor_id());
+            check_revealed(&merged, &tx, [a, b]);
+            assert_eq!(merged, anchor_b.merge_reveal(anchor_a).unwrap());
+        }
+    }
+
+    #[test]
+    fn merge_overlapping() {
+        let tree = merkle_tree();
+        let (anchor_a, tx) = opret_anchor_revealing(&tree, [1, 2]);
+        let (anchor_b, _) = opret_anchor_revealing(&tree, [2, 3]);
+        let merged = anchor_a.clone().merge_reveal(anchor_b).unwrap();
+        check_revealed(&merged, &tx, [1, 2, 3]);
+
+        assert_eq!(
+            anchor_a.clone().merge_reveal(anchor_a.clone()),
+            Ok(anchor_a)
+        );
+    }
+
+    #[test]
+    fn merge_conflicting() {
+        let tree = merkle_tree();
+        let (anchor, tx) = opret_anchor_revealing(&tree, [1, 2]);
+
+        let mut other_tx = anchor.clone();
+        other_tx.txid = self::tx(Script::new()).txid();
+        assert_eq!(
+            anchor.clone().merge_reveal(other_tx),
+            Err(MergeError::TxidMismatch)
+        );
+
+        let mut other_proof = anchor.clone();
+        other_proof.dbc_proof = method_proof(Method::TapretFirst);
+        assert_eq!(
+            anchor.clone().merge_reveal(other_proof),
+            Err(MergeError::ProofMismatch)
+        );
+
+        // Same witness transaction and proof, but a different message under
+        // protocol 1
+        let other_tree = lnpbp4::MerkleTree::try_commit(&lnpbp4::MultiSource {
+            min_depth: ANCHOR_MIN_LNPBP4_DEPTH,
+            messages: bmap! {
+                protocol_id(1) => message(9),
+                protocol_id(2) => message(2),
+                protocol_id(3) => message(3)
+            },
+        })
+        .unwrap();
+        let (mut conflicting, _) = opret_anchor_revealing(&other_tree, [1]);
+        conflicting.txid = tx.txid();
+        assert_eq!(
+            anchor.clone().merge_reveal(conflicting),
+            Err(MergeError::SlotConflict(
+                tree.protocol_id_pos(protocol_id(1))
+            ))
+        );
+
+        let (mut unrelated, _) = opret_anchor_revealing(&other_tree, [2]);
+        unrelated.txid = tx.txid();
+        assert_eq!(
+            anchor.merge_reveal(unrelated),
+            Err(MergeError::Lnpbp4Mismatch)
+        );
+    }
+
+    #[test]
+    fn restricted_to() {
+        let tree = merkle_tree();
+        let (anchor, tx) = opret_anchor_revealing(&tree, [1, 2, 3]);
+        assert_eq!(
+            anchor.known_protocols(),
+            (1..=3).map(protocol_id).collect()
+        );
+
+        let restricted = anchor.restricted_to(protocol_id(2)).unwrap();
+        assert_eq!(restricted.anchor_id(), anchor.anchor_id());
+        assert_eq!(restricted.known_protocols(), bset! { protocol_id(2) });
+        assert_eq!(restricted.lnpbp4_proof.entropy(), None);
+        check_revealed(&restricted, &tx, [2]);
+        for no in [1, 3] {
+            assert!(restricted.to_merkle_proof(protocol_id(no)).is_err());
+        }
+
+        // Neither ids nor messages of the removed protocols are present in
+        // the serialized data
+        let data = strict_serialize(&restricted).unwrap();
+        for no in [1, 3] {
+            for needle in [&protocol_id(no)[..], &message(no)[..]] {
+                assert!(!data.windows(32).any(|window| window == needle));
+            }
+        }
+
+        assert!(restricted.restricted_to(protocol_id(1)).is_err());
+    }
+
+    // Both matches must not have wildcard arms, so adding a new commitment
+    // method or proof type fails compilation until the tests are updated.
+    fn method_proof(method: Method) -> Proof {
+        match method {
+            Method::OpretFirst => Proof::OpretFirst(OpretProof),
+            Method::TapretFirst => Proof::TapretFirst(TapretProof {
+                path_proof: default!(),
+                internal_key: XOnlyPublicKey::from_str(
+                    "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf8\
+                     42723f3",
+                )
+                .unwrap(),
+            }),
+        }
+    }
+
+    #[test]
+    fn proof_methods() {
+        for method in [Method::OpretFirst, Method::TapretFirst] {
+            let proof = method_proof(method);
+            assert_eq!(proof.method(), method);
+            for other in [Method::OpretFirst, Method::TapretFirst] {
+                assert_eq!(proof.matches_method(other), other == method);
+            }
+            let expected = match proof {
+                Proof::OpretFirst(_) => Method::OpretFirst,
+                Proof::TapretFirst(_) => Method::TapretFirst,
+            };
+            assert_eq!(expected, method);
+            // Strict encoding of the proof starts with the method id
+            assert_eq!(
+                strict_serialize(&proof).unwrap()[0],
+                strict_serialize(&method).unwrap()[0]
+            );
+        }
+        assert_eq!(
+            strict_serialize(&method_proof(Method::OpretFirst)).unwrap(),
+            [0x00]
+        );
+    }
+
+    fn anchor_data(lnpbp4_data: impl StrictEncode) -> Vec<u8> {
+        let mut data = ANCHOR_MARKER.to_vec();
+        data.push(ANCHOR_VERSION);
+        data.extend(strict_serialize(&Txid::hash(b"witness")).unwrap());
+        data.extend(strict_serialize(&lnpbp4_data).unwrap());
+        data.extend(strict_serialize(&Proof::OpretFirst(OpretProof)).unwrap());
+        data
+    }
+
+    #[test]
+    fn decode_limits() {
+        let node = |depth| MerkleBlockNode::ConcealedNode {
+            depth,
+            hash: MerkleNode::hash(b"node"),
+        };
+        let block = |depth, cross_section| MerkleBlockData {
+            depth,
+            cross_section,
+            entropy: None,
+        };
+
+        let valid = anchor_data(block(1, vec![node(1), node(1)]));
+        assert!(
+            strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(&valid).is_ok()
+        );
+        for invalid in [
+            block(200, vec![node(0)]),
+            block(MAX_LNPBP4_DEPTH + 1, vec![node(0)]),
+            block(1, vec![node(1)]),
+            block(1, vec![node(1), node(1), node(1)]),
+            block(1, vec![node(2), node(2)]),
+            block(1, vec![]),
+        ] {
+            assert!(matches!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(
+                    &anchor_data(invalid)
+                ),
+                Err(strict_encoding::Error::DataIntegrityError(_))
+            ));
+        }
+
+        let proof = |pos, len| MerkleProofData {
+            pos,
+            path: vec![MerkleNode::hash(b"node"); len],
+        };
+        for (pos, len, valid) in [
+            (0, 0, true),
+            (3, 2, true),
+            (4, 2, false),
+            (0, MAX_LNPBP4_DEPTH as usize, true),
+            (0, MAX_LNPBP4_DEPTH as usize + 1, false),
+        ] {
+            let data = anchor_data(proof(pos, len));
+            assert_eq!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(&data)
+                    .is_ok(),
+                valid
+            );
+        }
+
+        // Tree encoding is depth, entropy and the message map
+        let tree = |depth: u8, messages: lnpbp4::MessageMap| {
+            let mut data = strict_serialize(&depth).unwrap();
+            data.extend(strict_serialize(&0u64).unwrap());
+            data.extend(strict_serialize(&messages).unwrap());
+            let mut anchor = ANCHOR_MARKER.to_vec();
+            anchor.push(ANCHOR_VERSION);
+            anchor.extend(strict_serialize(&Txid::hash(b"witness")).unwrap());
+            anchor.extend(data);
+            anchor.extend(
+                strict_serialize(&Proof::OpretFirst(OpretProof)).unwrap(),
+            );
+            anchor
+        };
+        let messages = bmap! { protocol_id(1) => message(1), protocol_id(2) => message(2) };
+        assert!(strict_deserialize::<Anchor<lnpbp4::MerkleTree>>(&tree(
+            ANCHOR_MIN_LNPBP4_DEPTH,
+            messages.clone()
+        ))
+        .is_ok());
+        assert!(strict_deserialize::<Anchor<lnpbp4::MerkleTree>>(&tree(
+            0,
+            messages.clone()
+        ))
+        .is_err());
+        assert!(strict_deserialize::<Anchor<lnpbp4::MerkleTree>>(&tree(
+            MAX_LNPBP4_DEPTH + 1,
+            messages
+        ))
+        .is_err());
+    }
+
+    fn anchor_verifications() -> Vec<AnchorVerification> {
+        let tree = merkle_tree();
+        let tx = tx(Script::new_op_return(tree.consensus_commit().as_slice()));
+        let other_tx = self::tx(Script::new_op_return(&[0u8; 32]));
+        let anchor = anchor(&tx, &tree, Proof::OpretFirst(OpretProof));
+        (0..64u8)
+            .map(|no| {
+                let protocol = no % 4;
+                let proof = anchor
+                    .to_merkle_proof(protocol_id(protocol.max(1)))
+                    .unwrap();
+                let witness_tx = match no % 3 {
+                    0 => other_tx.clone(),
+                    _ => tx.clone(),
+                };
+                (proof, protocol_id(protocol), message(no % 5), witness_tx)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn verify_anchors_sequential() {
+        let anchors = anchor_verifications();
+        let results = verify_anchors(&anchors);
+        assert_eq!(results.len(), anchors.len());
+        for ((anchor, protocol_id, message, tx), result) in
+            anchors.iter().zip(&results)
+        {
+            assert_eq!(
+                *result,
+                anchor.verify(*protocol_id, *message, tx.clone())
+            );
+        }
+        assert!(results.contains(&Ok(())));
+        assert!(results.contains(&Err(VerifyError::NoCommitment)));
+        assert!(results.contains(&Err(VerifyError::Lnpbp4UnrelatedProtocol)));
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, Err(VerifyError::TxidMismatch(_)))));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn verify_anchors_determinism() {
+        let anchors = anchor_verifications();
+        let sequential = verify_anchors(&anchors);
+        for _ in 0..8 {
+            assert_eq!(verify_anchors_par(&anchors), sequential);
+        }
+        assert_eq!(verify_anchors_par(&[]), vec![]);
+    }
 
     #[test]
     fn test_anchor_id_midstate() {
         let midstate = tagged_hash::Midstate::with(b"bp:dbc:anchor");
         assert_eq!(midstate.into_inner().into_inner(), MIDSTATE_ANCHOR_ID);
     }
+
+    fn fixture_anchor() -> Anchor<lnpbp4::MerkleBlock> {
+        let block = MerkleBlockData {
+            depth: 1,
+            cross_section: vec![
+                MerkleBlockNode::CommitmentLeaf {
+                    protocol_id: protocol_id(2),
+                    message: message(2),
+                },
+                MerkleBlockNode::ConcealedNode {
+                    depth: 1,
+                    hash: MerkleNode::hash(b"node"),
+                },
+            ],
+            entropy: None,
+        };
+        Anchor {
+            txid: Txid::hash(b"witness"),
+            lnpbp4_proof: strict_deserialize(strict_serialize(&block).unwrap())
+                .unwrap(),
+            dbc_proof: Proof::OpretFirst(OpretProof),
+        }
+    }
+
+    #[test]
+    fn anchor_id_golden() {
+        const ANCHOR_ID: &str =
+            "2e81811c9a8d3d0d282e49797e24846bc89647f6d7e1afbe671eab4609fbe640";
+
+        let anchor = fixture_anchor();
+        assert_eq!(anchor.anchor_id().to_string(), ANCHOR_ID);
+        let proof = anchor.to_merkle_proof(protocol_id(2)).unwrap();
+        assert_eq!(
+            proof
+                .anchor_id(protocol_id(2), message(2))
+                .unwrap()
+                .to_string(),
+            ANCHOR_ID
+        );
+
+        // Anchor id does not depend on the encoding version and LNPBP-4
+        // concealment
+        let data = strict_serialize(&anchor).unwrap();
+        assert_eq!(data[..4], ANCHOR_MARKER);
+        assert_eq!(data[4], ANCHOR_VERSION);
+        let decoded: Anchor<lnpbp4::MerkleBlock> =
+            strict_deserialize(&data).unwrap();
+        assert_eq!(decoded.anchor_id().to_string(), ANCHOR_ID);
+        let mut concealed = anchor;
+        concealed.conceal_except(Vec::<ProtocolId>::new()).unwrap();
+        assert_eq!(concealed.anchor_id().to_string(), ANCHOR_ID);
+    }
+
+    #[test]
+    fn anchor_legacy_tapret_golden() {
+        use bitcoin::hashes::hex::{FromHex, ToHex};
+        use bitcoin_scripts::LeafScript;
+
+        use crate::tapret::{TapretNodePartner, TapretPathProof};
+
+        // Unversioned anchor with a single-byte nonce tapret proof and its id,
+        // as produced before the introduction of the encoding versions
+        const ANCHOR: &str = "81961dbd89a6edf75ed3d94f6393d14ef4258ee45701986e\
+            978269eb624c81ee010200010202020202020202020202020202020202020202\
+            020202020202020202020202dbc1b4c900ffe48d575b5da5c638040125f65db0\
+            fe3e24494b76ea986457d9860001545ea538461003efdc8c81c244531b003f6f\
+            26cfccf6c0073b3239fdedf4944600010101c0000058c5f93479093e2b8f724a\
+            79844cc10928dd44e9a390b539843fb83fbf842723f3";
+        const ANCHOR_ID: &str =
+            "9dd9902ec16247ee12173b8947c8b9f782390c7de33db50ec0a2e5797a5729f8";
+
+        let data = Vec::<u8>::from_hex(ANCHOR).unwrap();
+        let mut anchor = fixture_anchor();
+        anchor.dbc_proof = Proof::TapretFirst(TapretProof {
+            path_proof: TapretPathProof::with_legacy(
+                TapretNodePartner::RightLeaf(LeafScript::tapscript(default!())),
+                88,
+            )
+            .unwrap(),
+            internal_key: XOnlyPublicKey::from_str(
+                "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+            )
+            .unwrap(),
+        });
+        assert!(anchor.dbc_proof.is_legacy());
+        assert_eq!(anchor.anchor_id().to_string(), ANCHOR_ID);
+
+        let decoded: Anchor<lnpbp4::MerkleBlock> =
+            strict_deserialize(&data).unwrap();
+        assert_eq!(decoded, anchor);
+        assert_eq!(decoded.anchor_id().to_string(), ANCHOR_ID);
+        assert_eq!(strict_serialize(&decoded).unwrap().to_hex(), data.to_hex());
+
+        let proof = anchor.to_merkle_proof(protocol_id(2)).unwrap();
+        let proof_data = strict_serialize(&proof).unwrap();
+        assert_ne!(proof_data[..4], ANCHOR_MARKER);
+        assert_eq!(
+            strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(&proof_data),
+            Ok(proof.clone())
+        );
+        assert_eq!(
+            proof
+                .anchor_id(protocol_id(2), message(2))
+                .unwrap()
+                .to_string(),
+            ANCHOR_ID
+        );
+
+        // Anchors with the extended nonce are versioned
+        let mut current = anchor;
+        if let Proof::TapretFirst(ref mut proof) = current.dbc_proof {
+            proof.path_proof = TapretPathProof::with(
+                TapretNodePartner::RightLeaf(LeafScript::tapscript(default!())),
+                87,
+            )
+            .unwrap();
+        }
+        assert!(!current.dbc_proof.is_legacy());
+        assert_ne!(current.anchor_id().to_string(), ANCHOR_ID);
+        let data = strict_serialize(&current).unwrap();
+        assert_eq!(data[..4], ANCHOR_MARKER);
+        assert_eq!(strict_deserialize(&data), Ok(current));
+    }
+
+    #[test]
+    fn block_proof_conversions() {
+        let tree = merkle_tree();
+        let tx = tx(Script::new_op_return(tree.consensus_commit().as_slice()));
+        let anchor: AnchorBlock =
+            anchor(&tx, &tree, Proof::OpretFirst(OpretProof));
+        let anchor_id = anchor.anchor_id();
+        for no in 1..=3 {
+            let proof: AnchorProof = anchor.to_proof(protocol_id(no)).unwrap();
+            assert_eq!(proof, anchor.to_merkle_proof(protocol_id(no)).unwrap());
+            let block = proof
+                .clone()
+                .into_block(protocol_id(no), message(no))
+                .unwrap();
+            assert_eq!(block.anchor_id(), anchor_id);
+            assert_eq!(block.to_proof(protocol_id(no)), Ok(proof.clone()));
+            assert_eq!(
+                proof.clone().into_block_checked(
+                    protocol_id(no),
+                    message(no),
+                    anchor_id
+                ),
+                Ok(block)
+            );
+
+            // Wrong message produces anchor with other id
+            let wrong = proof
+                .clone()
+                .into_block(protocol_id(no), message(no + 1))
+                .unwrap();
+            assert_ne!(wrong.anchor_id(), anchor_id);
+            assert_eq!(
+                proof.into_block_checked(
+                    protocol_id(no),
+                    message(no + 1),
+                    anchor_id
+                ),
+                Err(InvalidProof::AnchorIdMismatch {
+                    expected: anchor_id,
+                    found: wrong.anchor_id()
+                })
+            );
+        }
+
+        let proof = anchor.to_proof(protocol_id(1)).unwrap();
+        let unrelated = (4..=u8::MAX)
+            .map(protocol_id)
+            .find(|id| proof.clone().into_block(*id, message(1)).is_err())
+            .unwrap();
+        assert_eq!(
+            proof.into_block(unrelated, message(1)),
+            Err(InvalidProof::UnrelatedProtocol(unrelated))
+        );
+        assert_eq!(
+            anchor.to_proof(protocol_id(4)),
+            Err(InvalidProof::UnknownProtocol(protocol_id(4)))
+        );
+    }
+
+    #[test]
+    fn anchor_version() {
+        let anchor = fixture_anchor();
+        let proof = anchor.to_merkle_proof(protocol_id(2)).unwrap();
+        let mut block_data = strict_serialize(&anchor).unwrap();
+        let mut proof_data = strict_serialize(&proof).unwrap();
+        assert_eq!(proof_data[..4], ANCHOR_MARKER);
+        assert_eq!(proof_data[4], ANCHOR_VERSION);
+        assert_eq!(
+            strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(&proof_data),
+            Ok(proof.clone())
+        );
+
+        // Unversioned anchors are decoded as well, including the ones with
+        // txid starting with the version or the first marker byte
+        let mut legacy = anchor.clone();
+        let mut txid = legacy.txid.into_inner();
+        for first in [ANCHOR_VERSION, ANCHOR_MARKER[0]] {
+            txid[0] = first;
+            legacy.txid = Txid::from_inner(txid);
+            let data = strict_serialize(&legacy).unwrap();
+            assert_eq!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(&data[5..]),
+                Ok(legacy.clone())
+            );
+            assert_eq!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(&data[5..])
+                    .unwrap()
+                    .anchor_id(),
+                legacy.anchor_id()
+            );
+        }
+        assert_eq!(
+            strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(&proof_data[5..]),
+            Ok(proof)
+        );
+
+        for version in [0u8, 2, 0xFF] {
+            block_data[4] = version;
+            proof_data[4] = version;
+            let err = strict_encoding::Error::DataIntegrityError(
+                UnsupportedAnchorVersion(version).to_string(),
+            );
+            assert_eq!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(&block_data),
+                Err(err.clone())
+            );
+            assert_eq!(
+                strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(&proof_data),
+                Err(err)
+            );
+        }
+    }
+
+    #[test]
+    fn stream_anchors() {
+        let anchors = (0u8..4)
+            .map(|no| Anchor {
+                txid: Txid::hash(&[no]),
+                ..fixture_anchor()
+            })
+            .collect::<Vec<_>>();
+        let data = strict_serialize(&anchors).unwrap();
+
+        let mut stream = vec![];
+        assert_eq!(write_anchors(&mut stream, &anchors), Ok(data.len()));
+        assert_eq!(stream, data);
+        let mut owned = vec![];
+        write_anchors(&mut owned, anchors.clone()).unwrap();
+        assert_eq!(owned, data);
+
+        // Reader stops at the end of the collection
+        stream.extend([0xFF; 4]);
+        let mut cursor = std::io::Cursor::new(&stream);
+        let decoded = read_anchors::<lnpbp4::MerkleBlock, _>(&mut cursor)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, anchors);
+        assert_eq!(cursor.position() as usize, data.len());
+
+        let mut empty = vec![];
+        write_anchors(&mut empty, Vec::<Anchor<lnpbp4::MerkleBlock>>::new())
+            .unwrap();
+        assert_eq!(empty, strict_serialize(&Vec::<u8>::new()).unwrap());
+        assert_eq!(
+            read_anchors::<lnpbp4::MerkleBlock, _>(&empty[..]).count(),
+            0
+        );
+
+        // Iteration stops after the first error
+        let truncated = &data[..data.len() - 1];
+        let mut iter = read_anchors::<lnpbp4::MerkleBlock, _>(truncated);
+        for anchor in &anchors[..3] {
+            assert_eq!(iter.next(), Some(Ok(anchor.clone())));
+        }
+        assert!(matches!(iter.next(), Some(Err(_))));
+        assert_eq!(iter.next(), None);
+        let mut iter = read_anchors::<lnpbp4::MerkleBlock, _>(&[0u8][..]);
+        assert!(matches!(iter.next(), Some(Err(_))));
+        assert_eq!(iter.next(), None);
+    }
 }
@@ -37,8 +37,23 @@ extern crate strict_encoding;
 
 pub mod anchor;
 pub mod keytweak;
+pub mod limits;
+pub mod mpc;
+pub mod msg;
 pub mod opret;
+#[cfg(feature = "wallet")]
+pub mod psbt;
+pub mod scan;
 pub mod sigtweak;
+pub mod spv;
 pub mod tapret;
 
-pub use anchor::{Anchor, AnchorId, Proof};
+#[cfg(feature = "rayon")]
+pub use anchor::verify_anchors_par;
+pub use anchor::{
+    read_anchors, verify_anchors, write_anchors, Anchor, AnchorBlock, AnchorId,
+    AnchorProof, InvalidProof, Method, Proof, UnsupportedAnchorVersion,
+    ANCHOR_MARKER, ANCHOR_VERSION,
+};
+/// LNPBP-4 multi-protocol commitments used by the anchors.
+pub use commit_verify::lnpbp4;
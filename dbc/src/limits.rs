@@ -0,0 +1,30 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Limits enforced when decoding data from untrusted sources.
+//!
+//! Strict encoding prefixes collections with 16-bit lengths, so the amount of
+//! memory allocated by a decoder is always bounded; the limits here guard the
+//! data consistency which is assumed by the operations on decoded values.
+
+use crate::lnpbp4;
+
+/// Maximal depth of LNPBP-4 tree (and thus the maximal length of LNPBP-4
+/// merkle proof) accepted by the anchor decoding.
+pub const MAX_LNPBP4_DEPTH: u8 = lnpbp4::MAX_TREE_DEPTH;
+
+/// Maximal number of messages in LNPBP-4 tree, which is the tree width at
+/// [`MAX_LNPBP4_DEPTH`].
+pub const MAX_LNPBP4_MESSAGES: usize = 1 << MAX_LNPBP4_DEPTH;
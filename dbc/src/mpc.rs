@@ -0,0 +1,203 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Typed protocol ids and messages participating in multi-protocol (LNPBP-4)
+//! commitments.
+//!
+//! LNPBP-4 represents both protocol ids and messages as 32-byte values, so it
+//! is easy to swap them by mistake. The newtypes defined here are not
+//! convertible into each other, so such mistakes are caught by the compiler:
+//!
+//! ```compile_fail
+//! # use dbc::mpc::Message;
+//! # use dbc::lnpbp4::ProtocolId;
+//! let message = Message::from([0u8; 32]);
+//! let protocol_id: ProtocolId = message.into();
+//! ```
+//!
+//! ```compile_fail
+//! # use dbc::mpc::ProtocolId;
+//! # use dbc::lnpbp4::Message;
+//! let protocol_id = ProtocolId::from([0u8; 32]);
+//! let message: Message = protocol_id.into();
+//! ```
+//!
+//! Anchor APIs accept both these types and the raw LNPBP-4 types they are
+//! convertible into, so the existing code passing raw values keeps working.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use amplify::Wrapper;
+use bitcoin::hashes::hex::{self, FromHex, ToHex};
+use bitcoin::hashes::{sha256, Hash};
+use commit_verify::CommitEncode;
+
+use crate::lnpbp4;
+
+/// Identifier of the protocol participating in the multi-protocol commitment.
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug, From
+)]
+#[derive(StrictEncode, StrictDecode)]
+pub struct ProtocolId([u8; 32]);
+
+impl ProtocolId {
+    /// Returns byte representation of the protocol id.
+    #[inline]
+    pub fn to_byte_array(&self) -> [u8; 32] { self.0 }
+}
+
+impl From<lnpbp4::ProtocolId> for ProtocolId {
+    #[inline]
+    fn from(id: lnpbp4::ProtocolId) -> Self { ProtocolId(id.into_inner()) }
+}
+
+// Provides conversion into `lnpbp4::ProtocolId` through the blanket `Slice32`
+// implementation. `Message` intentionally has no such conversion, so it can't
+// be used in place of the protocol id.
+impl From<ProtocolId> for [u8; 32] {
+    #[inline]
+    fn from(id: ProtocolId) -> Self { id.0 }
+}
+
+impl Display for ProtocolId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_hex())
+    }
+}
+
+impl FromStr for ProtocolId {
+    type Err = hex::Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <[u8; 32]>::from_hex(s).map(ProtocolId)
+    }
+}
+
+/// Message committed under some protocol in the multi-protocol commitment.
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug, From
+)]
+#[derive(StrictEncode, StrictDecode)]
+pub struct Message([u8; 32]);
+
+impl Message {
+    /// Constructs message as a SHA256 hash of the commitment-encoded `data`.
+    #[inline]
+    pub fn from_commitment(data: impl CommitEncode) -> Message {
+        Message(sha256::Hash::hash(&data.commit_serialize()).into_inner())
+    }
+
+    /// Returns byte representation of the message.
+    #[inline]
+    pub fn to_byte_array(&self) -> [u8; 32] { self.0 }
+}
+
+impl From<lnpbp4::Message> for Message {
+    #[inline]
+    fn from(msg: lnpbp4::Message) -> Self { Message(msg.into_inner()) }
+}
+
+impl From<Message> for lnpbp4::Message {
+    #[inline]
+    fn from(msg: Message) -> Self { lnpbp4::Message::from_inner(msg.0) }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_hex())
+    }
+}
+
+impl FromStr for Message {
+    type Err = hex::Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <[u8; 32]>::from_hex(s).map(Message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+
+    const HEX: &str =
+        "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+
+    #[test]
+    fn hex_roundtrip() {
+        let protocol_id = ProtocolId::from_str(HEX).unwrap();
+        assert_eq!(protocol_id.to_string(), HEX);
+        assert_eq!(
+            protocol_id,
+            ProtocolId::from(<[u8; 32]>::from_hex(HEX).unwrap())
+        );
+        let message = Message::from_str(HEX).unwrap();
+        assert_eq!(message.to_string(), HEX);
+        assert_eq!(message.to_byte_array(), protocol_id.to_byte_array());
+
+        assert_eq!(
+            Message::from_str(&HEX[..62]),
+            Err(hex::Error::InvalidLength(64, 62))
+        );
+        assert!(ProtocolId::from_str(&HEX.replace('6', "x")).is_err());
+    }
+
+    #[test]
+    fn lnpbp4_conversions() {
+        let protocol_id = ProtocolId::from([7u8; 32]);
+        let raw = lnpbp4::ProtocolId::from(protocol_id);
+        assert_eq!(raw, lnpbp4::ProtocolId::from([7u8; 32]));
+        assert_eq!(ProtocolId::from(raw), protocol_id);
+
+        let message = Message::from(lnpbp4::Message::hash(b"message"));
+        assert_eq!(
+            lnpbp4::Message::from(message),
+            lnpbp4::Message::hash(b"message")
+        );
+    }
+
+    #[test]
+    fn strict_encoding() {
+        let message = Message::from_str(HEX).unwrap();
+        let data = strict_serialize(&message).unwrap();
+        assert_eq!(data, message.to_byte_array());
+        assert_eq!(strict_deserialize::<Message>(&data).unwrap(), message);
+        assert_eq!(
+            strict_deserialize::<ProtocolId>(&data).unwrap(),
+            ProtocolId::from_str(HEX).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_commitment() {
+        struct Data;
+        impl CommitEncode for Data {
+            fn commit_encode<E: std::io::Write>(&self, mut e: E) -> usize {
+                e.write_all(b"data").unwrap();
+                4
+            }
+        }
+        assert_eq!(
+            Message::from_commitment(Data),
+            Message::from(lnpbp4::Message::hash(b"data"))
+        );
+    }
+}
@@ -0,0 +1,58 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Sizes of the messages committed with opret and tapret commitments.
+//!
+//! The commitment functions are generic over the message size `N`, which must
+//! be either 32 bytes (the default, used by LNPBP-4 commitments) or 64 bytes
+//! (for protocols committing to two hashes at once). Other sizes are rejected
+//! at compile time:
+//!
+//! ```compile_fail
+//! # use dbc::opret::OpretHost;
+//! let txout = OpretHost::new([0u8; 48]);
+//! ```
+//!
+//! ```
+//! # use dbc::opret::OpretHost;
+//! let txout = OpretHost::new([0u8; 64]);
+//! assert_eq!(txout.script_pubkey.len(), 66);
+//! ```
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::MsgSize<32> {}
+    impl Sealed for super::MsgSize<64> {}
+}
+
+/// Marker type for the messages of `N` bytes.
+pub struct MsgSize<const N: usize>;
+
+/// Trait implemented by [`MsgSize`] for the message sizes supported by the
+/// commitments. The trait is sealed and can't be implemented for other sizes.
+pub trait SupportedMsgSize: private::Sealed {}
+
+impl SupportedMsgSize for MsgSize<32> {}
+impl SupportedMsgSize for MsgSize<64> {}
+
+/// Default size of the committed messages.
+pub const DEFAULT_MSG_SIZE: usize = 32;
+
+/// Message of the default size, used by LNPBP-4 commitments.
+pub type Msg32 = [u8; DEFAULT_MSG_SIZE];
+
+/// Message of 64 bytes, consisting of two hashes.
+pub type Msg64 = [u8; 64];
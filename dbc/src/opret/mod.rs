@@ -22,3 +22,422 @@
 //! **Convolve-commit:**
 //! d) `Tx, Amount, Msg -> Tx'`;
 //! e) `Psbt, Amount, Msg -> Psbt'`.
+
+use std::io;
+
+use bitcoin::blockdata::opcodes::all;
+use bitcoin::blockdata::script;
+use bitcoin::{Script, Transaction, TxOut};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::msg::{MsgSize, SupportedMsgSize};
+
+/// Errors during verification of OP_RETURN commitments.
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
+#[display(doc_comments)]
+pub enum OpretError {
+    /// the transaction does not contain any OP_RETURN outputs.
+    NoOpretOutput,
+
+    /// the first OP_RETURN output {0} does not have a canonical form of a
+    /// single push of the message following OP_RETURN.
+    InvalidOpretScript(u32),
+
+    /// the first OP_RETURN output {0} commits to a different message.
+    CommitmentMismatch(u32),
+
+    /// OP_RETURN payload of {0} bytes exceeds standardness limit of
+    /// {MAX_OPRET_PAYLOAD} bytes.
+    PayloadTooLarge(usize),
+
+    /// outputs {0} and {1} are both OP_RETURN outputs, while a standard
+    /// transaction may contain only a single one.
+    MultipleOpretOutputs(u32, u32),
+}
+
+/// Maximal size of OP_RETURN output payload relayed by the nodes under the
+/// default standardness policy.
+pub const MAX_OPRET_PAYLOAD: usize = 80;
+
+/// Constructor of the standard zero-value OP_RETURN outputs hosting opret
+/// commitments.
+pub struct OpretHost;
+
+impl OpretHost {
+    /// Constructs zero-value output with the canonical `OP_RETURN <N bytes>`
+    /// script committing to the message `msg`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<const N: usize>(msg: [u8; N]) -> TxOut
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        TxOut {
+            value: 0,
+            script_pubkey: OpretCommitment::script_pubkey(msg),
+        }
+    }
+
+    /// Constructs zero-value OP_RETURN output with a single push of the
+    /// `payload`, which must not exceed [`MAX_OPRET_PAYLOAD`] bytes.
+    pub fn with_payload(payload: &[u8]) -> Result<TxOut, OpretError> {
+        if payload.len() > MAX_OPRET_PAYLOAD {
+            return Err(OpretError::PayloadTooLarge(payload.len()));
+        }
+        Ok(TxOut {
+            value: 0,
+            script_pubkey: Script::new_op_return(payload),
+        })
+    }
+}
+
+/// Ensures that the transaction contains exactly one OP_RETURN output, as
+/// required by the standardness policy, returning the number of this output.
+pub fn ensure_single_opret(tx: &Transaction) -> Result<u32, OpretError> {
+    let mut oprets = tx
+        .output
+        .iter()
+        .enumerate()
+        .filter(|(_, txout)| txout.script_pubkey.is_op_return())
+        .map(|(vout, _)| vout as u32);
+    let vout = oprets.next().ok_or(OpretError::NoOpretOutput)?;
+    match oprets.next() {
+        Some(other) => Err(OpretError::MultipleOpretOutputs(vout, other)),
+        None => Ok(vout),
+    }
+}
+
+/// Commitment to a message of `N` bytes (32 by default, see [`crate::msg`])
+/// in form of `OP_RETURN <N bytes>` scriptPubkey.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct OpretCommitment<const N: usize = 32>([u8; N]);
+
+// `Wrapper` and `From` derives do not support const generics
+impl<const N: usize> amplify::Wrapper for OpretCommitment<N> {
+    type Inner = [u8; N];
+
+    #[inline]
+    fn from_inner(inner: Self::Inner) -> Self { OpretCommitment(inner) }
+
+    #[inline]
+    fn as_inner(&self) -> &Self::Inner { &self.0 }
+
+    #[inline]
+    fn as_inner_mut(&mut self) -> &mut Self::Inner { &mut self.0 }
+
+    #[inline]
+    fn into_inner(self) -> Self::Inner { self.0 }
+}
+
+impl<const N: usize> From<[u8; N]> for OpretCommitment<N> {
+    #[inline]
+    fn from(msg: [u8; N]) -> Self { OpretCommitment(msg) }
+}
+
+impl<const N: usize> OpretCommitment<N>
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    /// Constructs canonical `OP_RETURN <N bytes>` scriptPubkey committing to
+    /// the message `msg`.
+    pub fn script_pubkey(msg: [u8; N]) -> Script {
+        script::Builder::new()
+            .push_opcode(all::OP_RETURN)
+            .push_slice(&msg)
+            .into_script()
+    }
+
+    /// Constructs canonical scriptPubkey for this commitment.
+    #[inline]
+    pub fn to_script_pubkey(&self) -> Script {
+        OpretCommitment::script_pubkey(self.0)
+    }
+
+    /// Extracts commitment from the canonical `OP_RETURN <N bytes>`
+    /// scriptPubkey. Returns `None` for any other script, including
+    /// commitments to messages of other sizes.
+    pub fn from_script_pubkey(script_pubkey: &Script) -> Option<Self> {
+        let bytes = script_pubkey.as_bytes();
+        // Messages of the supported sizes are pushed with `OP_PUSHBYTES_N`
+        // opcode, which byte value is equal to `N`
+        if bytes.len() != N + 2
+            || bytes[0] != all::OP_RETURN.into_u8()
+            || bytes[1] as usize != N
+        {
+            return None;
+        }
+        let mut msg = [0u8; N];
+        msg.copy_from_slice(&bytes[2..]);
+        Some(OpretCommitment(msg))
+    }
+}
+
+/// Proof of the opret commitment, which is empty since OP_RETURN commitments
+/// do not require any extra-transaction data for their verification.
+///
+/// Exists for the symmetry with [`crate::tapret::TapretProof`] in
+/// [`crate::Proof`]; strict-encodes into zero bytes.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct OpretProof;
+
+impl StrictEncode for OpretProof {
+    #[inline]
+    fn strict_encode<E: io::Write>(
+        &self,
+        _: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        Ok(0)
+    }
+}
+
+impl StrictDecode for OpretProof {
+    #[inline]
+    fn strict_decode<D: io::Read>(
+        _: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(OpretProof)
+    }
+}
+
+impl OpretProof {
+    /// Verifies that `script_pubkey` is a canonical OP_RETURN commitment to
+    /// the message `msg`.
+    #[inline]
+    pub fn verify<const N: usize>(
+        &self,
+        msg: [u8; N],
+        script_pubkey: &Script,
+    ) -> bool
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        OpretCommitment::from_script_pubkey(script_pubkey)
+            == Some(OpretCommitment(msg))
+    }
+}
+
+/// Verifies that the first OP_RETURN output of the transaction is a canonical
+/// commitment to the message `msg`, returning the number of this output.
+///
+/// Only the first OP_RETURN output is considered; OP_RETURN outputs following
+/// it are ignored even if they commit to the message.
+pub fn verify_opret_first<const N: usize>(
+    tx: &Transaction,
+    msg: [u8; N],
+) -> Result<u32, OpretError>
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    let (vout, txout) = tx
+        .output
+        .iter()
+        .enumerate()
+        .find(|(_, txout)| txout.script_pubkey.is_op_return())
+        .ok_or(OpretError::NoOpretOutput)?;
+    let vout = vout as u32;
+    let commitment = OpretCommitment::from_script_pubkey(&txout.script_pubkey)
+        .ok_or(OpretError::InvalidOpretScript(vout))?;
+    if commitment.0 != msg {
+        return Err(OpretError::CommitmentMismatch(vout));
+    }
+    Ok(vout)
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::TxOut;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+
+    fn tx(scripts: Vec<Script>) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: scripts
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: 0,
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn other_script() -> Script {
+        script::Builder::new().push_int(1).into_script()
+    }
+
+    #[test]
+    fn script_pubkey() {
+        let script = OpretCommitment::script_pubkey([0xAB; 32]);
+        assert!(script.is_op_return());
+        assert_eq!(script.len(), 34);
+        assert_eq!(&script[..2], &[0x6a, 0x20]);
+        assert_eq!(&script[2..], &[0xAB; 32]);
+        assert_eq!(
+            OpretCommitment::from_script_pubkey(&script),
+            Some(OpretCommitment::from([0xAB; 32]))
+        );
+        assert_eq!(
+            OpretCommitment::from([0xAB; 32]).to_script_pubkey(),
+            script
+        );
+    }
+
+    #[test]
+    fn proof() {
+        let msg = [0x01; 32];
+        assert!(strict_serialize(&OpretProof).unwrap().is_empty());
+        assert_eq!(strict_deserialize::<OpretProof>(&[]), Ok(OpretProof));
+        assert!(OpretProof.verify(msg, &OpretCommitment::script_pubkey(msg)));
+        assert!(!OpretProof
+            .verify(msg, &OpretCommitment::script_pubkey([0x02; 32])));
+        assert!(!OpretProof.verify(msg, &other_script()));
+        assert!(!OpretProof.verify(msg, &Script::new_op_return(&msg[..31])));
+    }
+
+    #[test]
+    fn verify_first() {
+        let msg = [0x01; 32];
+        let tx = tx(vec![
+            other_script(),
+            OpretCommitment::script_pubkey(msg),
+            other_script(),
+        ]);
+        assert_eq!(verify_opret_first(&tx, msg), Ok(1));
+        assert_eq!(
+            verify_opret_first(&tx, [0x02; 32]),
+            Err(OpretError::CommitmentMismatch(1))
+        );
+    }
+
+    #[test]
+    fn multiple_oprets() {
+        let msg = [0x01; 32];
+        let tx = tx(vec![
+            OpretCommitment::script_pubkey([0x02; 32]),
+            OpretCommitment::script_pubkey(msg),
+        ]);
+        assert_eq!(
+            verify_opret_first(&tx, msg),
+            Err(OpretError::CommitmentMismatch(0))
+        );
+        assert_eq!(verify_opret_first(&tx, [0x02; 32]), Ok(0));
+    }
+
+    #[test]
+    fn non_canonical_oprets() {
+        let msg = [0x01; 32];
+        let extra_push = script::Builder::new()
+            .push_opcode(all::OP_RETURN)
+            .push_slice(&msg)
+            .push_slice(&[0x00])
+            .into_script();
+        let short_push = script::Builder::new()
+            .push_opcode(all::OP_RETURN)
+            .push_slice(&msg[..31])
+            .into_script();
+        let no_push = script::Builder::new()
+            .push_opcode(all::OP_RETURN)
+            .into_script();
+        for script in [extra_push, short_push, no_push] {
+            let tx = tx(vec![
+                other_script(),
+                script,
+                OpretCommitment::script_pubkey(msg),
+            ]);
+            assert_eq!(
+                verify_opret_first(&tx, msg),
+                Err(OpretError::InvalidOpretScript(1))
+            );
+        }
+    }
+
+    #[test]
+    fn host_payload() {
+        let msg = [0xAB; 32];
+        let txout = OpretHost::new(msg);
+        assert_eq!(txout.value, 0);
+        assert!(OpretProof.verify(msg, &txout.script_pubkey));
+        assert_eq!(OpretHost::with_payload(&msg), Ok(txout));
+
+        let txout = OpretHost::with_payload(&[0x01; 80]).unwrap();
+        assert_eq!(txout.value, 0);
+        assert!(txout.script_pubkey.is_op_return());
+        // OP_RETURN OP_PUSHDATA1 <80> <payload>
+        assert_eq!(txout.script_pubkey.len(), 83);
+        assert_eq!(
+            OpretHost::with_payload(&[0x01; 81]),
+            Err(OpretError::PayloadTooLarge(81))
+        );
+    }
+
+    #[test]
+    fn single_opret() {
+        let msg = [0x01; 32];
+        let single =
+            tx(vec![other_script(), OpretCommitment::script_pubkey(msg)]);
+        assert_eq!(ensure_single_opret(&single), Ok(1));
+        let multiple = tx(vec![
+            Script::new_op_return(&[]),
+            other_script(),
+            OpretCommitment::script_pubkey(msg),
+        ]);
+        assert_eq!(
+            ensure_single_opret(&multiple),
+            Err(OpretError::MultipleOpretOutputs(0, 2))
+        );
+        assert_eq!(
+            ensure_single_opret(&tx(vec![other_script()])),
+            Err(OpretError::NoOpretOutput)
+        );
+    }
+
+    #[test]
+    fn no_oprets() {
+        assert_eq!(
+            verify_opret_first(&tx(vec![]), [0x01; 32]),
+            Err(OpretError::NoOpretOutput)
+        );
+        assert_eq!(
+            verify_opret_first(&tx(vec![other_script()]), [0x01; 32]),
+            Err(OpretError::NoOpretOutput)
+        );
+    }
+
+    #[test]
+    fn msg64() {
+        let msg = [0xAB; 64];
+        let script = OpretCommitment::script_pubkey(msg);
+        assert_eq!(script.len(), 66);
+        assert_eq!(&script[..2], &[0x6a, 0x40]);
+        assert_eq!(
+            OpretCommitment::from_script_pubkey(&script),
+            Some(OpretCommitment::from(msg))
+        );
+        assert!(OpretProof.verify(msg, &script));
+
+        let tx64 = tx(vec![other_script(), script]);
+        assert_eq!(verify_opret_first(&tx64, msg), Ok(1));
+
+        // Commitments to the messages of other size are rejected
+        let tx32 = tx(vec![OpretCommitment::script_pubkey([0xAB; 32])]);
+        assert_eq!(
+            verify_opret_first(&tx64, [0xAB; 32]),
+            Err(OpretError::InvalidOpretScript(1))
+        );
+        assert_eq!(
+            verify_opret_first(&tx32, msg),
+            Err(OpretError::InvalidOpretScript(0))
+        );
+        assert!(!OpretProof.verify([0xAB; 32], &tx64.output[1].script_pubkey));
+    }
+}
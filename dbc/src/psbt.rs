@@ -0,0 +1,1334 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! PSBT proprietary keys declaring transaction outputs which host tapret and
+//! opret commitments, and finalization of PSBT with the commitment.
+//!
+//! The keys are defined by the `psbt` crate and use `TAPRET` and `OPRET`
+//! proprietary key prefixes; this module adds PSBT-level management of the
+//! host outputs, ensuring that there is at most a single host for each of the
+//! commitment types.
+
+use amplify::Wrapper;
+use bitcoin::{Amount, OutPoint, Script, Transaction, TxIn, TxOut};
+use bitcoin_scripts::taproot::DfsPath;
+use commit_verify::{
+    ConsensusCommit, EmbedCommitVerify, TaggedHash, TryCommitVerify,
+};
+pub use psbt::commit::{
+    OpretKeyError, ProprietaryKeyOpret, ProprietaryKeyTapret, TapretKeyError,
+    PSBT_OPRET_PREFIX, PSBT_OUT_OPRET_COMMITMENT, PSBT_OUT_OPRET_HOST,
+    PSBT_OUT_TAPRET_COMMITMENT, PSBT_OUT_TAPRET_HOST, PSBT_OUT_TAPRET_PROOF,
+    PSBT_TAPRET_PREFIX,
+};
+use psbt::{Output, ProprietaryKey, Psbt, PsbtVersion};
+use secp256k1::XOnlyPublicKey;
+use strict_encoding::StrictEncode;
+
+use crate::anchor::ANCHOR_MIN_LNPBP4_DEPTH;
+use crate::opret::{ensure_single_opret, OpretError, OpretProof};
+use crate::tapret::{Lnpbp6, PsbtCommitError, TapretProof};
+use crate::{lnpbp4, Anchor, Method, Proof};
+
+/// Errors declaring commitment host outputs in PSBT.
+#[derive(
+    Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error,
+    From
+)]
+#[display(doc_comments)]
+pub enum HostError {
+    /// PSBT does not contain output #{0}.
+    OutputAbsent(usize),
+
+    /// outputs #{0} and #{1} are both marked as tapret commitment hosts, while
+    /// PSBT may contain only a single tapret host.
+    MultipleTapretHosts(usize, usize),
+
+    /// outputs #{0} and #{1} are both marked as opret commitment hosts, while
+    /// PSBT may contain only a single opret host.
+    MultipleOpretHosts(usize, usize),
+
+    /// Invalid tapret proprietary key data.
+    #[from]
+    #[display(inner)]
+    TapretKey(TapretKeyError),
+
+    /// Invalid opret proprietary key data.
+    #[from]
+    #[display(inner)]
+    OpretKey(OpretKeyError),
+}
+
+/// Errors finalizing PSBT with a deterministic bitcoin commitment.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum DbcCommitError {
+    /// PSBT does not contain an output marked as {0} commitment host.
+    NoHost(Method),
+
+    /// output #{0} already contains a commitment; PSBT can't be committed
+    /// twice.
+    AlreadyCommitted(usize),
+
+    /// Invalid information about commitment host outputs.
+    #[from]
+    #[display(inner)]
+    Host(HostError),
+
+    /// Failure embedding the commitment into the host output.
+    #[from]
+    #[display(inner)]
+    Commit(PsbtCommitError),
+
+    /// Opret commitment would produce non-standard transaction.
+    #[from]
+    #[display(inner)]
+    Opret(OpretError),
+}
+
+/// Extension trait for PSBT outputs declaring them as commitment hosts.
+pub trait OutputHost {
+    /// Marks the output as a host for tapret commitment, allowing the
+    /// commitment to be placed anywhere within the taproot script tree.
+    /// Returns whether the output was a tapret host before.
+    ///
+    /// The flag is stored in [`PSBT_OUT_TAPRET_HOST`] key; use
+    /// [`Output::set_tapret_dfs_path`] to restrict the commitment to a
+    /// specific part of the tree.
+    fn set_tapret_host(&mut self) -> Result<bool, TapretKeyError>;
+}
+
+impl OutputHost for Output {
+    fn set_tapret_host(&mut self) -> Result<bool, TapretKeyError> {
+        if self.is_tapret_host() {
+            return Ok(true);
+        }
+        self.set_tapret_dfs_path(&DfsPath::new())?;
+        Ok(false)
+    }
+}
+
+/// Extension trait for PSBT outputs storing tapret commitment proofs.
+///
+/// The stored proof is read back with [`Output::tapret_proof`], which fails
+/// with [`TapretKeyError::InvalidKeyValue`] on corrupted key data.
+pub trait OutputTapret {
+    /// Stores strict-encoded tapret `proof` under [`PSBT_OUT_TAPRET_PROOF`]
+    /// key, replacing previously stored proof, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the output is not marked as a tapret commitment host.
+    fn set_tapret_proof(
+        &mut self,
+        proof: &TapretProof,
+    ) -> Result<(), TapretKeyError>;
+}
+
+impl OutputTapret for Output {
+    fn set_tapret_proof(
+        &mut self,
+        proof: &TapretProof,
+    ) -> Result<(), TapretKeyError> {
+        if !self.is_tapret_host() {
+            return Err(TapretKeyError::TapretProhibited);
+        }
+        self.proprietary
+            .insert(ProprietaryKey::tapret_proof(), proof.strict_serialize()?);
+        Ok(())
+    }
+}
+
+/// Checks that the output scriptPubkey contains the tapret commitment stored
+/// in [`PSBT_OUT_TAPRET_COMMITMENT`] key under the proof from
+/// [`PSBT_OUT_TAPRET_PROOF`] key.
+///
+/// Returns `false` if either of the keys is absent or contains invalid data.
+pub fn verify_tapret_output(output: &Output) -> bool {
+    let commitment = match output.tapret_commitment() {
+        Some(commitment) => commitment,
+        None => return false,
+    };
+    match output.tapret_proof::<TapretProof>() {
+        Ok(Some(proof)) => proof
+            .verify(commitment.into_inner(), &output.script)
+            .is_ok(),
+        _ => false,
+    }
+}
+
+/// Extension trait for PSBT managing outputs which host commitments.
+pub trait PsbtHosts {
+    /// Returns number of the output marked as a tapret commitment host, if
+    /// any.
+    ///
+    /// # Errors
+    ///
+    /// If more than one output is marked as a tapret host.
+    fn tapret_host(&self) -> Result<Option<usize>, HostError>;
+
+    /// Returns number of the output marked as an opret commitment host, if
+    /// any.
+    ///
+    /// # Errors
+    ///
+    /// If more than one output is marked as an opret host.
+    fn opret_host(&self) -> Result<Option<usize>, HostError>;
+
+    /// Marks output number `output_no` as a tapret commitment host.
+    ///
+    /// # Errors
+    ///
+    /// If the output is absent or some other output is already marked as a
+    /// tapret host.
+    fn set_tapret_host(&mut self, output_no: usize) -> Result<(), HostError>;
+
+    /// Marks output number `output_no` as an opret commitment host.
+    ///
+    /// # Errors
+    ///
+    /// If the output is absent, is not an OP_RETURN output or some other
+    /// output is already marked as an opret host.
+    fn set_opret_host(&mut self, output_no: usize) -> Result<(), HostError>;
+}
+
+fn single_host(
+    psbt: &Psbt,
+    is_host: impl Fn(&Output) -> bool,
+    multiple_hosts: impl Fn(usize, usize) -> HostError,
+) -> Result<Option<usize>, HostError> {
+    let mut hosts = psbt
+        .outputs
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| is_host(output))
+        .map(|(no, _)| no);
+    match (hosts.next(), hosts.next()) {
+        (Some(first), Some(second)) => Err(multiple_hosts(first, second)),
+        (host, _) => Ok(host),
+    }
+}
+
+impl PsbtHosts for Psbt {
+    fn tapret_host(&self) -> Result<Option<usize>, HostError> {
+        single_host(
+            self,
+            Output::is_tapret_host,
+            HostError::MultipleTapretHosts,
+        )
+    }
+
+    fn opret_host(&self) -> Result<Option<usize>, HostError> {
+        single_host(self, Output::is_opret_host, HostError::MultipleOpretHosts)
+    }
+
+    fn set_tapret_host(&mut self, output_no: usize) -> Result<(), HostError> {
+        let output = self
+            .outputs
+            .get(output_no)
+            .ok_or(HostError::OutputAbsent(output_no))?;
+        match self.tapret_host()? {
+            Some(host) if host != output_no => {
+                return Err(HostError::MultipleTapretHosts(host, output_no))
+            }
+            _ if output.is_tapret_host() => return Ok(()),
+            _ => {}
+        }
+        OutputHost::set_tapret_host(&mut self.outputs[output_no])?;
+        Ok(())
+    }
+
+    fn set_opret_host(&mut self, output_no: usize) -> Result<(), HostError> {
+        if output_no >= self.outputs.len() {
+            return Err(HostError::OutputAbsent(output_no));
+        }
+        match self.opret_host()? {
+            Some(host) if host != output_no => {
+                return Err(HostError::MultipleOpretHosts(host, output_no))
+            }
+            _ => {}
+        }
+        self.outputs[output_no].set_opret_host()?;
+        Ok(())
+    }
+}
+
+/// Extension trait for PSBT finalizing it with a deterministic bitcoin
+/// commitment.
+pub trait PsbtDbc {
+    /// Commits to the `messages` under their protocol ids with the given
+    /// commitment `method`. The commitment is embedded into the PSBT output
+    /// marked as a host for the method: tapret commitment is joined with the
+    /// taproot script tree from `PSBT_OUT_TAP_TREE` (if any), which is
+    /// rewritten, and tweaks the output key made from
+    /// `PSBT_OUT_TAP_INTERNAL_KEY`; opret commitment replaces OP_RETURN
+    /// script. All other outputs are
+    /// left intact. The messages, LNPBP-4 entropy and the commitment are stored
+    /// into the host output proprietary keys.
+    ///
+    /// # Errors
+    ///
+    /// If the PSBT does not have an output marked as a host for the method, or
+    /// if some of the outputs already contain a commitment. Tapret commitment
+    /// also fails if the host output does not provide an internal key or if
+    /// its script tree already has the maximal depth. PSBT is not modified in
+    /// case of an error.
+    fn dbc_commit(
+        &mut self,
+        method: Method,
+        messages: lnpbp4::MessageMap,
+    ) -> Result<Anchor<lnpbp4::MerkleBlock>, DbcCommitError>;
+}
+
+/// Errors re-committing an anchor into the replacement PSBT with
+/// [`Anchor::rebind`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum RebindError {
+    /// the new PSBT does not contain output #{0} which is used by a seal.
+    MissingVout(u32),
+
+    /// Failure committing to the anchor in the new PSBT.
+    #[from]
+    #[display(inner)]
+    Commit(DbcCommitError),
+}
+
+/// Returns number of the output hosting commitment with the given `method`,
+/// ensuring that PSBT does not contain commitments yet.
+fn commit_host(psbt: &Psbt, method: Method) -> Result<usize, DbcCommitError> {
+    if let Some(no) = psbt.outputs.iter().position(|output| {
+        output.has_tapret_commitment()
+            || output.has_opret_commitment() == Ok(true)
+    }) {
+        return Err(DbcCommitError::AlreadyCommitted(no));
+    }
+
+    match method {
+        Method::OpretFirst => psbt.opret_host()?,
+        Method::TapretFirst => psbt.tapret_host()?,
+    }
+    .ok_or(DbcCommitError::NoHost(method))
+}
+
+/// Checks that replacing the `host` output of the PSBT with the `output`
+/// committed with the given `method` keeps the transaction standard; only
+/// opret commitments require the check.
+fn check_standard(
+    psbt: &Psbt,
+    host: usize,
+    output: &Output,
+    method: Method,
+) -> Result<(), DbcCommitError> {
+    if method == Method::OpretFirst {
+        let mut tx = psbt.to_unsigned_tx();
+        tx.output[host].script_pubkey = output.script.clone();
+        ensure_single_opret(&tx)?;
+    }
+    Ok(())
+}
+
+/// Embeds LNPBP-4 `commitment` into the host `output`, storing the commitment
+/// and its proof into the output proprietary keys.
+fn embed_commitment(
+    output: &mut Output,
+    method: Method,
+    commitment: lnpbp4::CommitmentHash,
+) -> Result<Proof, DbcCommitError> {
+    Ok(match method {
+        Method::TapretFirst => {
+            let proof = EmbedCommitVerify::<_, Lnpbp6>::embed_commit(
+                output,
+                &commitment,
+            )?;
+            output
+                .set_tapret_commitment(commitment.into_array(), &proof)
+                .map_err(PsbtCommitError::from)?;
+            Proof::TapretFirst(proof)
+        }
+        Method::OpretFirst => {
+            output.script = Script::new_op_return(commitment.as_slice());
+            output
+                .set_opret_commitment(commitment.into_array())
+                .map_err(PsbtCommitError::from)?;
+            Proof::OpretFirst(OpretProof)
+        }
+    })
+}
+
+impl PsbtDbc for Psbt {
+    fn dbc_commit(
+        &mut self,
+        method: Method,
+        messages: lnpbp4::MessageMap,
+    ) -> Result<Anchor<lnpbp4::MerkleBlock>, DbcCommitError> {
+        let host = commit_host(self, method)?;
+
+        // We work on a copy so the PSBT stays unchanged on errors
+        let mut output = self.outputs[host].clone();
+        for (protocol_id, message) in messages {
+            output
+                .set_lnpbp4_message(protocol_id, message)
+                .map_err(PsbtCommitError::from)?;
+        }
+        let multi_source = lnpbp4::MultiSource {
+            min_depth: output
+                .lnpbp4_min_tree_depth()
+                .map_err(PsbtCommitError::from)?
+                .unwrap_or(ANCHOR_MIN_LNPBP4_DEPTH),
+            messages: output
+                .lnpbp4_message_map()
+                .map_err(PsbtCommitError::from)?,
+        };
+        let tree = lnpbp4::MerkleTree::try_commit(&multi_source)
+            .map_err(PsbtCommitError::from)?;
+        let dbc_proof =
+            embed_commitment(&mut output, method, tree.consensus_commit())?;
+        output
+            .set_lnpbp4_entropy(tree.entropy())
+            .map_err(PsbtCommitError::from)?;
+        check_standard(self, host, &output, method)?;
+
+        self.outputs[host] = output;
+        Ok(Anchor {
+            txid: self.to_txid(),
+            lnpbp4_proof: lnpbp4::MerkleBlock::from(tree),
+            dbc_proof,
+        })
+    }
+}
+
+impl Anchor<lnpbp4::MerkleBlock> {
+    /// Re-commits the anchor into `new_psbt` replacing the anchor witness
+    /// transaction, for instance after an RBF fee bump, which may reorder
+    /// outputs or change the host output internal key.
+    ///
+    /// The commitment uses the same method as the anchor and is embedded into
+    /// the output of `new_psbt` marked as the host for the method; the LNPBP-4
+    /// block of the anchor is reused as is, so the returned anchor differs
+    /// only by the witness txid and the DBC proof. Only the commitment and
+    /// its proof are stored into the host output proprietary keys.
+    ///
+    /// # Errors
+    ///
+    /// If some of the `seal_vouts`, i.e. outputs used by the seals defined
+    /// over the witness transaction, are absent in `new_psbt`, or if the
+    /// commitment can't be embedded for the same reasons as in
+    /// [`PsbtDbc::dbc_commit`]. PSBT is not modified in case of an error.
+    pub fn rebind(
+        &self,
+        new_psbt: &mut Psbt,
+        seal_vouts: impl IntoIterator<Item = u32>,
+    ) -> Result<Anchor<lnpbp4::MerkleBlock>, RebindError> {
+        if let Some(vout) = seal_vouts
+            .into_iter()
+            .find(|vout| *vout as usize >= new_psbt.outputs.len())
+        {
+            return Err(RebindError::MissingVout(vout));
+        }
+
+        let method = self.dbc_proof.method();
+        let host = commit_host(new_psbt, method)?;
+        let mut output = new_psbt.outputs[host].clone();
+        let dbc_proof = embed_commitment(
+            &mut output,
+            method,
+            self.lnpbp4_proof.consensus_commit(),
+        )?;
+        check_standard(new_psbt, host, &output, method)?;
+
+        new_psbt.outputs[host] = output;
+        Ok(Anchor {
+            txid: new_psbt.to_txid(),
+            lnpbp4_proof: self.lnpbp4_proof.clone(),
+            dbc_proof,
+        })
+    }
+}
+
+/// Errors building commitment transaction with [`CommitTxBuilder`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum CommitTxError {
+    /// transaction does not contain output #{0}.
+    OutputAbsent(usize),
+
+    /// outputs #{0} and #{1} are both marked as commitment hosts, while the
+    /// transaction may contain only a single host.
+    MultipleHosts(usize, usize),
+
+    /// none of the transaction outputs is marked as a commitment host.
+    NoHost,
+
+    /// output #{0} can't host {1} commitment.
+    InvalidHost(usize, Method),
+
+    /// transaction does not have any inputs.
+    NoInputs,
+
+    /// sum of the transaction input or output amounts exceeds the range of
+    /// 64-bit unsigned integer.
+    AmountOverflow,
+
+    /// invalid transaction structure.
+    #[from]
+    #[display(inner)]
+    Tx(psbt::TxError),
+
+    /// unable to compute transaction fee.
+    #[from]
+    #[display(inner)]
+    Fee(psbt::FeeError),
+
+    /// Failure embedding the commitment into the host output.
+    #[from]
+    #[display(inner)]
+    Commit(DbcCommitError),
+}
+
+/// Builder of transactions containing deterministic bitcoin commitment in a
+/// single host output, producing PSBT ready for signing.
+///
+/// The builder does not select coins nor estimates fees: the transaction fee
+/// is the difference between the provided input and output amounts, see
+/// [`CommitTxBuilder::fee`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct CommitTxBuilder {
+    inputs: Vec<(OutPoint, TxOut)>,
+    outputs: Vec<(TxOut, Option<XOnlyPublicKey>)>,
+    host: Option<(usize, Method)>,
+}
+
+impl CommitTxBuilder {
+    /// Constructs builder for an empty transaction.
+    #[inline]
+    pub fn new() -> CommitTxBuilder { CommitTxBuilder::default() }
+
+    /// Adds input spending `outpoint`, which must contain `prevout` output.
+    /// Returns number of the added input.
+    pub fn add_input(&mut self, outpoint: OutPoint, prevout: TxOut) -> usize {
+        self.inputs.push((outpoint, prevout));
+        self.inputs.len() - 1
+    }
+
+    /// Adds output paying `amount` to `script_pubkey`. Returns number of the
+    /// added output.
+    pub fn add_output(
+        &mut self,
+        script_pubkey: Script,
+        amount: Amount,
+    ) -> usize {
+        self.outputs.push((
+            TxOut {
+                value: amount.as_sat(),
+                script_pubkey,
+            },
+            None,
+        ));
+        self.outputs.len() - 1
+    }
+
+    /// Adds key-only taproot output with the given `internal_key` paying
+    /// `amount`, which can be used as a tapret commitment host. Returns number
+    /// of the added output.
+    pub fn add_taproot_output(
+        &mut self,
+        internal_key: XOnlyPublicKey,
+        amount: Amount,
+    ) -> usize {
+        let script_pubkey =
+            Script::new_v1_p2tr(secp256k1::SECP256K1, internal_key, None);
+        let no = self.add_output(script_pubkey, amount);
+        self.outputs[no].1 = Some(internal_key);
+        no
+    }
+
+    /// Marks output number `output_no` as the host for the commitment made
+    /// with the given `method`, replacing the method if the output was marked
+    /// before.
+    ///
+    /// Tapret commitments can be hosted only by the outputs added with
+    /// [`CommitTxBuilder::add_taproot_output`], and opret commitments only by
+    /// OP_RETURN outputs.
+    ///
+    /// # Errors
+    ///
+    /// If the output is absent, can't host commitments made with the method or
+    /// if some other output is already marked as a host.
+    pub fn mark_host(
+        &mut self,
+        output_no: usize,
+        method: Method,
+    ) -> Result<(), CommitTxError> {
+        let (txout, internal_key) = self
+            .outputs
+            .get(output_no)
+            .ok_or(CommitTxError::OutputAbsent(output_no))?;
+        match self.host {
+            Some((host, _)) if host != output_no => {
+                return Err(CommitTxError::MultipleHosts(host, output_no))
+            }
+            _ => {}
+        }
+        let can_host = match method {
+            Method::TapretFirst => internal_key.is_some(),
+            Method::OpretFirst => txout.script_pubkey.is_op_return(),
+        };
+        if !can_host {
+            return Err(CommitTxError::InvalidHost(output_no, method));
+        }
+        self.host = Some((output_no, method));
+        Ok(())
+    }
+
+    /// Returns transaction fee, i.e. the difference between the amounts of
+    /// the inputs and the outputs.
+    ///
+    /// # Errors
+    ///
+    /// If the outputs spend more than the inputs provide or if the sum of the
+    /// input or output amounts overflows.
+    pub fn fee(&self) -> Result<Amount, CommitTxError> {
+        fn total<'a>(
+            mut values: impl Iterator<Item = &'a TxOut>,
+        ) -> Result<u64, CommitTxError> {
+            values.try_fold(0u64, |sum, txout| {
+                sum.checked_add(txout.value)
+                    .ok_or(CommitTxError::AmountOverflow)
+            })
+        }
+        let inputs = total(self.inputs.iter().map(|(_, txout)| txout))?;
+        let outputs = total(self.outputs.iter().map(|(txout, _)| txout))?;
+        u64::checked_sub(inputs, outputs)
+            .map(Amount::from_sat)
+            .ok_or(CommitTxError::Fee(psbt::FeeError::InputsLessThanOutputs))
+    }
+
+    /// Constructs PSBT for the transaction and commits to the `messages`
+    /// under their protocol ids in the host output, see
+    /// [`PsbtDbc::dbc_commit`]. Inputs of the PSBT contain their previous
+    /// outputs as `PSBT_IN_WITNESS_UTXO`.
+    ///
+    /// # Errors
+    ///
+    /// If the transaction has no inputs or no host output, if the outputs
+    /// spend more than the inputs provide, or if the commitment can't be
+    /// embedded into the host output.
+    pub fn commit(
+        &self,
+        messages: lnpbp4::MessageMap,
+    ) -> Result<(Psbt, Anchor<lnpbp4::MerkleBlock>), CommitTxError> {
+        if self.inputs.is_empty() {
+            return Err(CommitTxError::NoInputs);
+        }
+        let (host, method) = self.host.ok_or(CommitTxError::NoHost)?;
+        self.fee()?;
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: self
+                .inputs
+                .iter()
+                .map(|(outpoint, _)| TxIn {
+                    previous_output: *outpoint,
+                    ..default!()
+                })
+                .collect(),
+            output: self
+                .outputs
+                .iter()
+                .map(|(txout, _)| txout.clone())
+                .collect(),
+        };
+        let mut psbt = Psbt::with(tx, PsbtVersion::V0)?;
+        for (input, (_, prevout)) in psbt.inputs.iter_mut().zip(&self.inputs) {
+            input.witness_utxo = Some(prevout.clone());
+        }
+        for (output, (_, internal_key)) in
+            psbt.outputs.iter_mut().zip(&self.outputs)
+        {
+            output.tap_internal_key = *internal_key;
+        }
+        match method {
+            Method::TapretFirst => PsbtHosts::set_tapret_host(&mut psbt, host),
+            Method::OpretFirst => psbt.set_opret_host(host),
+        }
+        .map_err(DbcCommitError::from)?;
+
+        let anchor = psbt.dbc_commit(method, messages)?;
+        Ok((psbt, anchor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use amplify::Wrapper;
+    use bitcoin::blockdata::opcodes::all;
+    use bitcoin::blockdata::script;
+    use bitcoin::hashes::Hash;
+    use bitcoin::psbt::TapTree;
+    use bitcoin::util::taproot::{
+        TaprootBuilder, TAPROOT_CONTROL_MAX_NODE_COUNT,
+    };
+    use bitcoin::{
+        OutPoint, Script, Transaction, TxIn, TxOut, Txid, WPubkeyHash,
+    };
+    use commit_verify::EmbedCommitProof;
+    use psbt::PsbtVersion;
+    use secp256k1::{XOnlyPublicKey, SECP256K1};
+
+    use super::*;
+    use crate::tapret::{TapretCommitment, TapretProof, TapretSourceError};
+
+    fn internal_key() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap()
+    }
+
+    fn tx() -> Transaction {
+        let internal_key = internal_key();
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::hash(b"prevout"), 0),
+                ..default!()
+            }],
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new_v1_p2tr(
+                        SECP256K1,
+                        internal_key,
+                        None,
+                    ),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: Script::new_op_return(&[0u8; 32]),
+                },
+                TxOut {
+                    value: 2000,
+                    script_pubkey: Script::new_v0_p2wpkh(&WPubkeyHash::hash(
+                        b"change",
+                    )),
+                },
+            ],
+        }
+    }
+
+    fn psbt() -> Psbt { Psbt::with(tx(), PsbtVersion::V0).unwrap() }
+
+    #[test]
+    fn hosts() {
+        let mut psbt = psbt();
+        assert_eq!(psbt.tapret_host(), Ok(None));
+        assert_eq!(psbt.opret_host(), Ok(None));
+
+        psbt.set_tapret_host(0).unwrap();
+        psbt.set_tapret_host(0).unwrap();
+        psbt.set_opret_host(1).unwrap();
+        assert_eq!(psbt.tapret_host(), Ok(Some(0)));
+        assert_eq!(psbt.opret_host(), Ok(Some(1)));
+        assert!(!psbt.outputs[2].is_tapret_host());
+        assert!(!psbt.outputs[2].is_opret_host());
+
+        assert_eq!(
+            psbt.set_tapret_host(2),
+            Err(HostError::MultipleTapretHosts(0, 2))
+        );
+        assert_eq!(psbt.set_opret_host(3), Err(HostError::OutputAbsent(3)));
+        assert_eq!(
+            psbt.set_opret_host(2),
+            Err(HostError::MultipleOpretHosts(1, 2))
+        );
+        assert_eq!(psbt.tapret_host(), Ok(Some(0)));
+        assert_eq!(psbt.opret_host(), Ok(Some(1)));
+    }
+
+    #[test]
+    fn non_opret_host() {
+        let mut psbt = psbt();
+        assert_eq!(
+            psbt.set_opret_host(2),
+            Err(HostError::OpretKey(OpretKeyError::NonOpReturnOutput))
+        );
+        assert_eq!(psbt.opret_host(), Ok(None));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut psbt = psbt();
+        psbt.set_tapret_host(0).unwrap();
+        psbt.set_opret_host(1).unwrap();
+
+        let key = psbt.outputs[0].proprietary.keys().next().cloned().unwrap();
+        assert_eq!(key.prefix, PSBT_TAPRET_PREFIX.to_vec());
+        assert_eq!(key.subtype, PSBT_OUT_TAPRET_HOST);
+        assert!(psbt.outputs[1]
+            .proprietary
+            .contains_key(&ProprietaryKey::opret_host()));
+
+        let psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        assert_eq!(psbt.tapret_host(), Ok(Some(0)));
+        assert_eq!(psbt.opret_host(), Ok(Some(1)));
+        assert_eq!(psbt.outputs[0].tapret_dfs_path(), Some(Ok(DfsPath::new())));
+    }
+
+    #[test]
+    fn duplicate_hosts() {
+        let mut psbt = psbt();
+        psbt.set_tapret_host(0).unwrap();
+        psbt.outputs[2]
+            .proprietary
+            .insert(ProprietaryKey::tapret_host(), vec![]);
+        let psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        assert_eq!(
+            psbt.tapret_host(),
+            Err(HostError::MultipleTapretHosts(0, 2))
+        );
+    }
+
+    fn messages() -> lnpbp4::MessageMap {
+        (1u8..=3)
+            .map(|no| {
+                (
+                    lnpbp4::ProtocolId::from([no; 32]),
+                    lnpbp4::Message::hash(&[no]),
+                )
+            })
+            .collect()
+    }
+
+    fn check_commit(psbt: &mut Psbt, method: Method, host: usize) {
+        let orig_psbt = psbt.clone();
+        let orig_tx = psbt.to_unsigned_tx();
+
+        let anchor = psbt.dbc_commit(method, messages()).unwrap();
+        assert_eq!(anchor.dbc_proof.method(), method);
+
+        let tx = psbt.to_unsigned_tx();
+        assert_eq!(anchor.txid, tx.txid());
+        assert_eq!(tx.input, orig_tx.input);
+        assert_eq!(tx.output.len(), orig_tx.output.len());
+        for no in 0..psbt.outputs.len() {
+            if no == host {
+                assert_ne!(tx.output[no], orig_tx.output[no]);
+                assert_eq!(tx.output[no].value, orig_tx.output[no].value);
+            } else {
+                assert_eq!(psbt.outputs[no], orig_psbt.outputs[no]);
+                assert_eq!(tx.output[no], orig_tx.output[no]);
+            }
+        }
+        assert_eq!(psbt.outputs[host].lnpbp4_message_map(), Ok(messages()));
+
+        for (protocol_id, message) in messages() {
+            let proof = anchor.to_merkle_proof(protocol_id).unwrap();
+            assert_eq!(proof.verify(protocol_id, message, tx.clone()), Ok(()));
+        }
+
+        let committed = psbt.clone();
+        for method in [Method::OpretFirst, Method::TapretFirst] {
+            assert_eq!(
+                psbt.dbc_commit(method, messages()),
+                Err(DbcCommitError::AlreadyCommitted(host))
+            );
+            assert_eq!(psbt, &committed);
+        }
+        let mut psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        assert_eq!(
+            psbt.dbc_commit(method, messages()),
+            Err(DbcCommitError::AlreadyCommitted(host))
+        );
+    }
+
+    #[test]
+    fn commit_opret() {
+        let mut psbt = psbt();
+        psbt.set_opret_host(1).unwrap();
+        check_commit(&mut psbt, Method::OpretFirst, 1);
+    }
+
+    #[test]
+    fn commit_tapret() {
+        let mut psbt = psbt();
+        psbt.outputs[0].tap_internal_key = Some(internal_key());
+        psbt.set_tapret_host(0).unwrap();
+        check_commit(&mut psbt, Method::TapretFirst, 0);
+    }
+
+    fn tap_tree(depth_map: impl IntoIterator<Item = u8>) -> TapTree {
+        let builder = depth_map.into_iter().enumerate().fold(
+            TaprootBuilder::new(),
+            |builder, (no, depth)| {
+                let script = script::Builder::new()
+                    .push_int(no as i64 + 144)
+                    .push_opcode(all::OP_CSV)
+                    .push_opcode(all::OP_DROP)
+                    .push_x_only_key(&internal_key())
+                    .push_opcode(all::OP_CHECKSIG)
+                    .into_script();
+                builder.add_leaf(depth, script).unwrap()
+            },
+        );
+        TapTree::from_builder(builder).unwrap()
+    }
+
+    fn tapret_host_with_tree(psbt: &mut Psbt, tap_tree: TapTree) {
+        let merkle_root = tap_tree
+            .clone()
+            .into_builder()
+            .finalize(SECP256K1, internal_key())
+            .unwrap()
+            .merkle_root();
+        psbt.outputs[0].script =
+            Script::new_v1_p2tr(SECP256K1, internal_key(), merkle_root);
+        psbt.outputs[0].tap_internal_key = Some(internal_key());
+        psbt.outputs[0].tap_tree = Some(tap_tree);
+        psbt.set_tapret_host(0).unwrap();
+    }
+
+    #[test]
+    fn commit_tapret_tree() {
+        let original_tree = tap_tree([1, 1]);
+        let mut psbt = psbt();
+        tapret_host_with_tree(&mut psbt, original_tree.clone());
+        let original = psbt.outputs[0].clone();
+        check_commit(&mut psbt, Method::TapretFirst, 0);
+
+        let output = &psbt.outputs[0];
+        let tap_tree = output.tap_tree.clone().unwrap();
+        let leaves = tap_tree
+            .script_leaves()
+            .map(|leaf| (leaf.depth(), leaf.script().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(leaves.len(), 3);
+        for leaf in original_tree.script_leaves() {
+            assert!(leaves.contains(&(2, leaf.script().clone())));
+        }
+        let proof: TapretProof = output.tapret_proof().unwrap().unwrap();
+        let commitment = TapretCommitment::with(
+            output.tapret_commitment().unwrap().into_inner(),
+            proof.path_proof.nonce(),
+        );
+        assert!(leaves.contains(&(1, commitment.to_tap_script().into_inner())));
+
+        let output_key = tap_tree
+            .into_builder()
+            .finalize(SECP256K1, internal_key())
+            .unwrap()
+            .output_key();
+        assert_eq!(output.script, Script::new_v1_p2tr_tweaked(output_key));
+
+        let restored =
+            EmbedCommitProof::<_, _, Lnpbp6>::restore_original_container(
+                &proof, output,
+            )
+            .unwrap();
+        assert_eq!(restored.tap_tree, original.tap_tree);
+        assert_eq!(restored.script, original.script);
+
+        let psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        assert_eq!(&psbt.outputs[0], output);
+    }
+
+    #[test]
+    fn commit_tapret_max_depth() {
+        let depth_map = (1..=TAPROOT_CONTROL_MAX_NODE_COUNT as u8)
+            .chain([TAPROOT_CONTROL_MAX_NODE_COUNT as u8]);
+        let mut psbt = psbt();
+        tapret_host_with_tree(&mut psbt, tap_tree(depth_map));
+        let orig = psbt.clone();
+        assert_eq!(
+            psbt.dbc_commit(Method::TapretFirst, messages()),
+            Err(DbcCommitError::Commit(PsbtCommitError::SourceError(
+                TapretSourceError::TreeTooDeep(TAPROOT_CONTROL_MAX_NODE_COUNT)
+            )))
+        );
+        assert_eq!(psbt, orig);
+    }
+
+    #[test]
+    fn commit_tapret_no_internal_key() {
+        let mut psbt = psbt();
+        tapret_host_with_tree(&mut psbt, tap_tree([1, 1]));
+        psbt.outputs[0].tap_internal_key = None;
+        let orig = psbt.clone();
+        assert_eq!(
+            psbt.dbc_commit(Method::TapretFirst, messages()),
+            Err(DbcCommitError::Commit(PsbtCommitError::InternalKeyMissed))
+        );
+        assert_eq!(psbt, orig);
+    }
+
+    #[test]
+    fn commit_no_host() {
+        let mut psbt = psbt();
+        let orig = psbt.clone();
+        assert_eq!(
+            psbt.dbc_commit(Method::TapretFirst, messages()),
+            Err(DbcCommitError::NoHost(Method::TapretFirst))
+        );
+        assert_eq!(
+            psbt.dbc_commit(Method::OpretFirst, messages()),
+            Err(DbcCommitError::NoHost(Method::OpretFirst))
+        );
+        assert_eq!(psbt, orig);
+
+        psbt.set_opret_host(1).unwrap();
+        assert_eq!(
+            psbt.dbc_commit(Method::TapretFirst, messages()),
+            Err(DbcCommitError::NoHost(Method::TapretFirst))
+        );
+    }
+
+    #[test]
+    fn commit_opret_nonstandard() {
+        let mut tx = tx();
+        tx.output[2].script_pubkey = Script::new_op_return(b"other");
+        let mut psbt = Psbt::with(tx, PsbtVersion::V0).unwrap();
+        psbt.set_opret_host(1).unwrap();
+        let orig = psbt.clone();
+        assert_eq!(
+            psbt.dbc_commit(Method::OpretFirst, messages()),
+            Err(DbcCommitError::Opret(OpretError::MultipleOpretOutputs(
+                1, 2
+            )))
+        );
+        assert_eq!(psbt, orig);
+
+        // Existing OP_RETURN output does not prevent tapret commitments
+        psbt.outputs[0].tap_internal_key = Some(internal_key());
+        psbt.set_tapret_host(0).unwrap();
+        check_commit(&mut psbt, Method::TapretFirst, 0);
+    }
+
+    #[test]
+    fn commit_failure() {
+        let mut psbt = psbt();
+        psbt.set_tapret_host(0).unwrap();
+        let orig = psbt.clone();
+        assert_eq!(
+            psbt.dbc_commit(Method::TapretFirst, messages()),
+            Err(DbcCommitError::Commit(PsbtCommitError::InternalKeyMissed))
+        );
+        assert_eq!(psbt, orig);
+    }
+
+    fn tapret_committed() -> (Psbt, TapretProof) {
+        let mut psbt = psbt();
+        tapret_host_with_tree(&mut psbt, tap_tree([1, 1]));
+        let anchor = psbt.dbc_commit(Method::TapretFirst, messages()).unwrap();
+        match anchor.dbc_proof {
+            Proof::TapretFirst(proof) => (psbt, proof),
+            Proof::OpretFirst(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn tapret_proof_roundtrip() {
+        let (psbt, proof) = tapret_committed();
+        let psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        let output = &psbt.outputs[0];
+        assert_eq!(output.tapret_proof(), Ok(Some(proof.clone())));
+        assert!(verify_tapret_output(output));
+
+        // Proof transferred separately from the commitment
+        let mut transferred = self::psbt();
+        transferred.outputs[0] = output.clone();
+        transferred.outputs[0]
+            .proprietary
+            .remove(&ProprietaryKey::tapret_proof());
+        assert_eq!(
+            transferred.outputs[0].tapret_proof::<TapretProof>(),
+            Ok(None)
+        );
+        assert!(!verify_tapret_output(&transferred.outputs[0]));
+        transferred.outputs[0].set_tapret_proof(&proof).unwrap();
+        let transferred = Psbt::from_str(&transferred.to_string()).unwrap();
+        assert_eq!(transferred.outputs[0].tapret_proof(), Ok(Some(proof)));
+        assert!(verify_tapret_output(&transferred.outputs[0]));
+    }
+
+    #[test]
+    fn tapret_proof_invalid() {
+        let (mut psbt, _) = tapret_committed();
+        let mut corrupted = psbt.clone();
+        corrupted.outputs[0]
+            .proprietary
+            .insert(ProprietaryKey::tapret_proof(), vec![0xFF; 3]);
+        let corrupted = Psbt::from_str(&corrupted.to_string()).unwrap();
+        assert_eq!(
+            corrupted.outputs[0].tapret_proof::<TapretProof>(),
+            Err(TapretKeyError::InvalidKeyValue)
+        );
+        assert!(!verify_tapret_output(&corrupted.outputs[0]));
+
+        let mut tampered = psbt.clone();
+        tampered.outputs[0]
+            .proprietary
+            .insert(ProprietaryKey::tapret_commitment(), vec![0x77; 32]);
+        assert!(!verify_tapret_output(&tampered.outputs[0]));
+
+        psbt.outputs[0].script = self::psbt().outputs[0].script.clone();
+        assert!(!verify_tapret_output(&psbt.outputs[0]));
+
+        let (_, proof) = tapret_committed();
+        let mut psbt = self::psbt();
+        assert_eq!(
+            psbt.outputs[0].set_tapret_proof(&proof),
+            Err(TapretKeyError::TapretProhibited)
+        );
+        assert!(!verify_tapret_output(&psbt.outputs[0]));
+    }
+
+    fn check_rebind(
+        anchor: &Anchor<lnpbp4::MerkleBlock>,
+        mut tx: Transaction,
+        host: usize,
+    ) {
+        let method = anchor.dbc_proof.method();
+        tx.output[2].value -= 500;
+        let mut psbt = Psbt::with(tx, PsbtVersion::V0).unwrap();
+        psbt.outputs[host].tap_internal_key = Some(internal_key());
+        match method {
+            Method::OpretFirst => psbt.set_opret_host(host).unwrap(),
+            Method::TapretFirst => {
+                PsbtHosts::set_tapret_host(&mut psbt, host).unwrap()
+            }
+        }
+
+        let rebound = anchor.rebind(&mut psbt, [0, 1, 2]).unwrap();
+        let tx = psbt.to_unsigned_tx();
+        assert_eq!(rebound.txid, tx.txid());
+        assert_ne!(rebound.txid, anchor.txid);
+        assert_eq!(rebound.dbc_proof.method(), method);
+        assert_eq!(
+            strict_encoding::strict_serialize(&rebound.lnpbp4_proof).unwrap(),
+            strict_encoding::strict_serialize(&anchor.lnpbp4_proof).unwrap()
+        );
+        for (protocol_id, message) in messages() {
+            let proof = rebound.to_merkle_proof(protocol_id).unwrap();
+            assert_eq!(proof.verify(protocol_id, message, tx.clone()), Ok(()));
+        }
+        if method == Method::TapretFirst {
+            assert!(verify_tapret_output(&psbt.outputs[host]));
+        }
+
+        assert_eq!(
+            anchor.rebind(&mut psbt, []),
+            Err(RebindError::Commit(DbcCommitError::AlreadyCommitted(host)))
+        );
+    }
+
+    #[test]
+    fn rebind_same_outputs() {
+        for (method, host) in
+            [(Method::OpretFirst, 1), (Method::TapretFirst, 0)]
+        {
+            let mut psbt = psbt();
+            psbt.outputs[0].tap_internal_key = Some(internal_key());
+            match method {
+                Method::OpretFirst => psbt.set_opret_host(1).unwrap(),
+                Method::TapretFirst => {
+                    PsbtHosts::set_tapret_host(&mut psbt, 0).unwrap()
+                }
+            }
+            let anchor = psbt.dbc_commit(method, messages()).unwrap();
+            check_rebind(&anchor, tx(), host);
+        }
+    }
+
+    #[test]
+    fn rebind_reordered_outputs() {
+        let mut psbt = psbt();
+        psbt.outputs[0].tap_internal_key = Some(internal_key());
+        psbt.set_tapret_host(0).unwrap();
+        let anchor = psbt.dbc_commit(Method::TapretFirst, messages()).unwrap();
+
+        let mut tx = tx();
+        tx.output.rotate_left(1);
+        tx.output.swap(1, 2);
+        check_rebind(&anchor, tx, 1);
+    }
+
+    #[test]
+    fn rebind_dropped_output() {
+        let mut psbt = psbt();
+        psbt.set_opret_host(1).unwrap();
+        let anchor = psbt.dbc_commit(Method::OpretFirst, messages()).unwrap();
+
+        let mut tx = tx();
+        tx.output.pop();
+        let mut psbt = Psbt::with(tx, PsbtVersion::V0).unwrap();
+        psbt.set_opret_host(1).unwrap();
+        let orig = psbt.clone();
+        assert_eq!(
+            anchor.rebind(&mut psbt, [1, 2]),
+            Err(RebindError::MissingVout(2))
+        );
+        assert_eq!(psbt, orig);
+        assert!(anchor.rebind(&mut psbt, [0, 1]).is_ok());
+
+        let mut psbt = orig;
+        psbt.outputs[1].proprietary.clear();
+        assert_eq!(
+            anchor.rebind(&mut psbt, [1]),
+            Err(RebindError::Commit(DbcCommitError::NoHost(
+                Method::OpretFirst
+            )))
+        );
+    }
+
+    fn prevout(value: u64) -> TxOut {
+        TxOut {
+            value,
+            script_pubkey: Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"input")),
+        }
+    }
+
+    #[test]
+    fn commit_tx_builder() {
+        let mut builder = CommitTxBuilder::new();
+        builder
+            .add_input(OutPoint::new(Txid::hash(b"first"), 0), prevout(5000));
+        builder
+            .add_input(OutPoint::new(Txid::hash(b"second"), 3), prevout(4000));
+        let payment = builder.add_output(
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"payment")),
+            Amount::from_sat(3000),
+        );
+        let change =
+            builder.add_taproot_output(internal_key(), Amount::from_sat(5000));
+        assert_eq!((payment, change), (0, 1));
+        assert_eq!(builder.fee(), Ok(Amount::from_sat(1000)));
+
+        assert_eq!(
+            builder.mark_host(payment, Method::TapretFirst),
+            Err(CommitTxError::InvalidHost(payment, Method::TapretFirst))
+        );
+        assert_eq!(
+            builder.mark_host(change, Method::OpretFirst),
+            Err(CommitTxError::InvalidHost(change, Method::OpretFirst))
+        );
+        assert_eq!(
+            builder.mark_host(2, Method::TapretFirst),
+            Err(CommitTxError::OutputAbsent(2))
+        );
+        assert_eq!(builder.commit(messages()), Err(CommitTxError::NoHost));
+        builder.mark_host(change, Method::TapretFirst).unwrap();
+        assert_eq!(
+            builder.mark_host(payment, Method::TapretFirst),
+            Err(CommitTxError::MultipleHosts(change, payment))
+        );
+
+        let (psbt, anchor) = builder.commit(messages()).unwrap();
+        assert_eq!(psbt.fee(), Ok(1000));
+        assert_eq!(psbt.tapret_host(), Ok(Some(change)));
+        assert!(verify_tapret_output(&psbt.outputs[change]));
+        assert_eq!(psbt.inputs[1].witness_utxo, Some(prevout(4000)),);
+
+        let tx = psbt.to_unsigned_tx();
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.input[1].previous_output.vout, 3);
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[payment], builder.outputs[payment].0);
+        assert_ne!(
+            tx.output[change].script_pubkey,
+            builder.outputs[change].0.script_pubkey
+        );
+        assert!(tx.output[change].script_pubkey.is_v1_p2tr());
+        assert_eq!(anchor.txid, tx.txid());
+        for (protocol_id, message) in messages() {
+            let anchor = anchor.clone().into_merkle_proof(protocol_id).unwrap();
+            assert_eq!(anchor.verify(protocol_id, message, tx.clone()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn commit_tx_builder_errors() {
+        let mut builder = CommitTxBuilder::new();
+        let host = builder
+            .add_output(Script::new_op_return(&[0u8; 32]), Amount::from_sat(0));
+        builder.mark_host(host, Method::OpretFirst).unwrap();
+        builder.add_output(
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"payment")),
+            Amount::from_sat(3000),
+        );
+        assert_eq!(builder.commit(messages()), Err(CommitTxError::NoInputs));
+
+        builder
+            .add_input(OutPoint::new(Txid::hash(b"first"), 0), prevout(2000));
+        assert_eq!(
+            builder.commit(messages()),
+            Err(CommitTxError::Fee(psbt::FeeError::InputsLessThanOutputs))
+        );
+
+        builder
+            .add_input(OutPoint::new(Txid::hash(b"second"), 0), prevout(2000));
+        let (psbt, anchor) = builder.commit(messages()).unwrap();
+        assert_eq!(psbt.fee(), Ok(1000));
+        assert_eq!(anchor.dbc_proof, Proof::OpretFirst(OpretProof));
+
+        // Second OP_RETURN output makes the transaction non-standard
+        builder
+            .add_output(Script::new_op_return(&[1u8; 8]), Amount::from_sat(0));
+        assert_eq!(
+            builder.commit(messages()),
+            Err(CommitTxError::Commit(DbcCommitError::Opret(
+                OpretError::MultipleOpretOutputs(0, 2)
+            )))
+        );
+    }
+
+    #[test]
+    fn commit_tx_builder_overflow() {
+        let mut builder = CommitTxBuilder::new();
+        builder.add_input(
+            OutPoint::new(Txid::hash(b"first"), 0),
+            prevout(u64::MAX),
+        );
+        builder.add_input(OutPoint::new(Txid::hash(b"second"), 0), prevout(1));
+        assert_eq!(builder.fee(), Err(CommitTxError::AmountOverflow));
+
+        let mut builder = CommitTxBuilder::new();
+        builder.add_input(
+            OutPoint::new(Txid::hash(b"first"), 0),
+            prevout(u64::MAX),
+        );
+        let host = builder
+            .add_output(Script::new_op_return(&[0u8; 32]), Amount::from_sat(0));
+        builder.mark_host(host, Method::OpretFirst).unwrap();
+        builder.add_output(
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"payment")),
+            Amount::from_sat(u64::MAX),
+        );
+        assert_eq!(builder.fee(), Ok(Amount::ZERO));
+        builder.add_output(
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"change")),
+            Amount::from_sat(1),
+        );
+        assert_eq!(builder.fee(), Err(CommitTxError::AmountOverflow));
+        assert_eq!(
+            builder.commit(messages()),
+            Err(CommitTxError::AmountOverflow)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Detection of candidate commitments in the existing transactions, which
+//! allows indexers to find LNP/BP commitment transactions without knowing any
+//! seals in advance.
+//!
+//! The detection is asymmetric. Opret commitments are fully visible on-chain,
+//! so the message committed by the first OP_RETURN output can be extracted
+//! (though any other data of the same size is indistinguishable from a
+//! commitment). Tapret commitments are hidden inside the taproot output key
+//! and can't be detected without the [`crate::tapret::TapretProof`], so the
+//! first taproot output is reported only as a possible commitment host, and
+//! every transaction with taproot outputs is a candidate.
+
+use amplify::Wrapper;
+use bitcoin::{Transaction, TxOut};
+
+use crate::msg::{Msg32, Msg64};
+use crate::opret::OpretCommitment;
+use crate::Method;
+
+/// Candidate commitment found in the transaction output.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CommitmentCandidate {
+    /// First OP_RETURN output of the transaction contains a single push of
+    /// 32 bytes, which may be the committed message.
+    Msg32(Msg32),
+
+    /// First OP_RETURN output of the transaction contains a single push of
+    /// 64 bytes, which may be the committed message.
+    Msg64(Msg64),
+
+    /// First taproot output of the transaction, which may contain tapret
+    /// commitment. The commitment can be checked only with the proof.
+    PossibleTapret,
+}
+
+/// Scans transaction for the candidate commitments, returning their methods,
+/// numbers of the hosting outputs and the committed messages when they can be
+/// extracted. See the [module documentation](self) for the detection
+/// limitations.
+///
+/// Since commitments always use the first output of the corresponding type,
+/// the returned vector contains at most one candidate per [`Method`], in the
+/// order of the methods.
+pub fn scan_tx_for_commitments(
+    tx: &Transaction,
+) -> Vec<(Method, u32, CommitmentCandidate)> {
+    let opret = opret_candidate(tx)
+        .map(|(vout, candidate)| (Method::OpretFirst, vout, candidate));
+    let tapret = tapret_candidate(tx).map(|vout| {
+        (
+            Method::TapretFirst,
+            vout,
+            CommitmentCandidate::PossibleTapret,
+        )
+    });
+    opret.into_iter().chain(tapret).collect()
+}
+
+/// Detects whether the transaction may contain a commitment, i.e. whether
+/// [`scan_tx_for_commitments`] returns any candidates for it.
+pub fn is_possible_commitment_tx(tx: &Transaction) -> bool {
+    tapret_candidate(tx).is_some() || opret_candidate(tx).is_some()
+}
+
+fn first_output(
+    tx: &Transaction,
+    filter: impl Fn(&TxOut) -> bool,
+) -> Option<(u32, &TxOut)> {
+    tx.output
+        .iter()
+        .enumerate()
+        .find(|(_, txout)| filter(txout))
+        .map(|(vout, txout)| (vout as u32, txout))
+}
+
+fn opret_candidate(tx: &Transaction) -> Option<(u32, CommitmentCandidate)> {
+    let (vout, txout) =
+        first_output(tx, |txout| txout.script_pubkey.is_op_return())?;
+    let script_pubkey = &txout.script_pubkey;
+    OpretCommitment::<32>::from_script_pubkey(script_pubkey)
+        .map(|msg| CommitmentCandidate::Msg32(msg.into_inner()))
+        .or_else(|| {
+            OpretCommitment::<64>::from_script_pubkey(script_pubkey)
+                .map(|msg| CommitmentCandidate::Msg64(msg.into_inner()))
+        })
+        .map(|candidate| (vout, candidate))
+}
+
+fn tapret_candidate(tx: &Transaction) -> Option<u32> {
+    first_output(tx, |txout| txout.script_pubkey.is_v1_p2tr())
+        .map(|(vout, _)| vout)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::blockdata::opcodes::all;
+    use bitcoin::blockdata::script;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{PubkeyHash, Script, WPubkeyHash};
+    use secp256k1::{XOnlyPublicKey, SECP256K1};
+
+    use super::*;
+    use crate::tapret::tapret_commit;
+
+    fn tx(scripts: Vec<Script>) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: scripts
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: 1000,
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn internal_key() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap()
+    }
+
+    fn p2wpkh(no: u8) -> Script {
+        Script::new_v0_p2wpkh(&WPubkeyHash::hash(&[no]))
+    }
+
+    fn p2pkh(no: u8) -> Script { Script::new_p2pkh(&PubkeyHash::hash(&[no])) }
+
+    fn tapret(msg: [u8; 32]) -> Script {
+        let (output_key, _) =
+            tapret_commit(internal_key(), &mut None, msg).unwrap();
+        Script::new_v1_p2tr_tweaked(output_key)
+    }
+
+    #[test]
+    fn opret_candidates() {
+        let msg = [0xA5; 32];
+        let tx32 = tx(vec![p2wpkh(1), OpretCommitment::script_pubkey(msg)]);
+        assert_eq!(scan_tx_for_commitments(&tx32), vec![(
+            Method::OpretFirst,
+            1,
+            CommitmentCandidate::Msg32(msg)
+        )]);
+
+        let msg = [0x5A; 64];
+        let tx64 = tx(vec![OpretCommitment::script_pubkey(msg), p2pkh(1)]);
+        assert_eq!(scan_tx_for_commitments(&tx64), vec![(
+            Method::OpretFirst,
+            0,
+            CommitmentCandidate::Msg64(msg)
+        )]);
+
+        // Only the first OP_RETURN output may host the commitment
+        let second = tx(vec![
+            Script::new_op_return(&[0x01; 20]),
+            OpretCommitment::script_pubkey([0xA5; 32]),
+        ]);
+        assert_eq!(scan_tx_for_commitments(&second), vec![]);
+        assert!(!is_possible_commitment_tx(&second));
+    }
+
+    #[test]
+    fn tapret_candidates() {
+        let tx = tx(vec![p2wpkh(1), tapret([0xA5; 32]), tapret([0x5A; 32])]);
+        assert_eq!(scan_tx_for_commitments(&tx), vec![(
+            Method::TapretFirst,
+            1,
+            CommitmentCandidate::PossibleTapret
+        )]);
+        assert!(is_possible_commitment_tx(&tx));
+    }
+
+    #[test]
+    fn block_scan() {
+        let opret_msg = [0x01; 32];
+        let tapret_msg = [0x02; 32];
+        let key_only = Script::new_v1_p2tr(SECP256K1, internal_key(), None);
+        let opret_push = |len: usize| Script::new_op_return(&vec![0xEE; len]);
+        let block = vec![
+            // Coinbase-like transaction with the witness commitment
+            tx(vec![p2pkh(0), Script::new_op_return(&[0xAA; 36])]),
+            // Commitments
+            tx(vec![p2wpkh(1), OpretCommitment::script_pubkey(opret_msg)]),
+            tx(vec![tapret(tapret_msg), p2wpkh(2)]),
+            tx(vec![
+                OpretCommitment::script_pubkey([0x03; 64]),
+                tapret([0x04; 32]),
+            ]),
+            // Plain transactions
+            tx(vec![p2wpkh(3), p2pkh(4)]),
+            tx(vec![p2pkh(5)]),
+            tx(vec![]),
+            // OP_RETURN outputs which can't be commitments
+            tx(vec![opret_push(31), p2wpkh(6)]),
+            tx(vec![opret_push(33)]),
+            tx(vec![opret_push(63), opret_push(32)]),
+            tx(vec![opret_push(80)]),
+            tx(vec![script::Builder::new()
+                .push_opcode(all::OP_RETURN)
+                .push_slice(&[0xEE; 32])
+                .push_slice(&[0x00])
+                .into_script()]),
+            // False positives: arbitrary 32-byte OP_RETURN data and
+            // key-only taproot outputs are indistinguishable from commitments
+            tx(vec![opret_push(32)]),
+            tx(vec![p2wpkh(7), key_only]),
+        ];
+
+        let found = block
+            .iter()
+            .enumerate()
+            .flat_map(|(no, tx)| {
+                assert_eq!(
+                    is_possible_commitment_tx(tx),
+                    !scan_tx_for_commitments(tx).is_empty()
+                );
+                scan_tx_for_commitments(tx)
+                    .into_iter()
+                    .map(move |candidate| (no, candidate))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![
+            (
+                1,
+                (Method::OpretFirst, 1, CommitmentCandidate::Msg32(opret_msg))
+            ),
+            (
+                2,
+                (Method::TapretFirst, 0, CommitmentCandidate::PossibleTapret)
+            ),
+            (
+                3,
+                (
+                    Method::OpretFirst,
+                    0,
+                    CommitmentCandidate::Msg64([0x03; 64])
+                )
+            ),
+            (
+                3,
+                (Method::TapretFirst, 1, CommitmentCandidate::PossibleTapret)
+            ),
+            (
+                12,
+                (
+                    Method::OpretFirst,
+                    0,
+                    CommitmentCandidate::Msg32([0xEE; 32])
+                )
+            ),
+            (
+                13,
+                (Method::TapretFirst, 1, CommitmentCandidate::PossibleTapret)
+            ),
+        ]);
+    }
+}
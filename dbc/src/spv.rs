@@ -0,0 +1,312 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Verification of anchors by light clients using SPV merkle inclusion proofs
+//! of the anchor witness transaction into a block.
+
+use std::collections::HashMap;
+
+use bitcoin::util::merkleblock::{MerkleBlock, MerkleBlockError};
+use bitcoin::{BlockHash, Transaction, Txid};
+use commit_verify::lnpbp4::{self, Message, ProtocolId};
+
+use crate::anchor::{Anchor, VerifyError};
+
+/// Source of the block headers forming the best known chain.
+pub trait HeaderSource {
+    /// Returns height of the block with the given hash if the block is a part
+    /// of the best known chain, or `None` otherwise.
+    fn block_height(&self, block_hash: &BlockHash) -> Option<u32>;
+}
+
+impl HeaderSource for HashMap<BlockHash, u32> {
+    #[inline]
+    fn block_height(&self, block_hash: &BlockHash) -> Option<u32> {
+        self.get(block_hash).copied()
+    }
+}
+
+/// Errors verifying anchor SPV proof.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum SpvError {
+    /// merkle inclusion proof does not match the block header ({0:?}).
+    #[from]
+    InvalidTxProof(MerkleBlockError),
+
+    /// transaction {0} is not included into the block by the merkle proof.
+    TxNotIncluded(Txid),
+
+    /// block {0} is not a part of the known header chain.
+    UnknownBlock(BlockHash),
+
+    /// {0}
+    #[from]
+    Anchor(VerifyError),
+}
+
+/// Anchor accompanied with the witness transaction and the proof of its
+/// inclusion into a block, allowing verification by the light clients which
+/// can't access full transactions from a trusted node.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnchoredProof {
+    /// Anchor for the specific protocol.
+    pub anchor: Anchor<lnpbp4::MerkleProof>,
+
+    /// Witness transaction containing the anchor commitment.
+    pub witness_tx: Transaction,
+
+    /// Header of the block mining the witness transaction and the partial
+    /// merkle tree proving the transaction inclusion.
+    pub tx_proof: MerkleBlock,
+}
+
+impl AnchoredProof {
+    /// Verifies that the witness transaction is included into a block from
+    /// the chain known to the `headers`, and that it commits to the anchor
+    /// committing to the given message under the given protocol.
+    ///
+    /// Returns height of the block mining the witness transaction.
+    pub fn verify_spv(
+        &self,
+        protocol_id: impl Into<ProtocolId>,
+        message: impl Into<Message>,
+        headers: &impl HeaderSource,
+    ) -> Result<u32, SpvError> {
+        let txid = self.witness_tx.txid();
+        let mut matches = vec![];
+        let mut indexes = vec![];
+        self.tx_proof.extract_matches(&mut matches, &mut indexes)?;
+        if !matches.contains(&txid) {
+            return Err(SpvError::TxNotIncluded(txid));
+        }
+
+        let block_hash = self.tx_proof.header.block_hash();
+        let height = headers
+            .block_height(&block_hash)
+            .ok_or(SpvError::UnknownBlock(block_hash))?;
+
+        self.anchor
+            .verify(protocol_id, message, self.witness_tx.clone())?;
+        Ok(height)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::Wrapper;
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Block, Network, OutPoint, Script, TxIn, TxOut};
+    use commit_verify::{ConsensusCommit, TryCommitVerify};
+    use strict_encoding::strict_serialize;
+
+    use super::*;
+    use crate::anchor::{Proof, ANCHOR_MIN_LNPBP4_DEPTH};
+    use crate::mpc;
+    use crate::opret::OpretProof;
+
+    fn protocol_id() -> ProtocolId { ProtocolId::from([0x11; 32]) }
+
+    fn message() -> Message { Message::hash(b"anchored message") }
+
+    fn fixture() -> (AnchoredProof, Block) {
+        let source = lnpbp4::MultiSource {
+            min_depth: ANCHOR_MIN_LNPBP4_DEPTH,
+            messages: bmap! { protocol_id() => message() },
+        };
+        let tree = lnpbp4::MerkleTree::try_commit(&source).unwrap();
+        let witness_tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::hash(b"prevout"), 0),
+                ..default!()
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Script::new_op_return(
+                    &tree.consensus_commit().into_inner().into_inner(),
+                ),
+            }],
+        };
+        let anchor = Anchor {
+            txid: witness_tx.txid(),
+            lnpbp4_proof: lnpbp4::MerkleBlock::from(&tree),
+            dbc_proof: Proof::OpretFirst(OpretProof),
+        }
+        .into_merkle_proof(protocol_id())
+        .unwrap();
+
+        let mut block = genesis_block(Network::Regtest);
+        block.txdata.push(witness_tx.clone());
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        let txid = witness_tx.txid();
+        let tx_proof =
+            MerkleBlock::from_block_with_predicate(&block, |id| *id == txid);
+        let proof = AnchoredProof {
+            anchor,
+            witness_tx,
+            tx_proof,
+        };
+        (proof, block)
+    }
+
+    fn headers(block: &Block, height: u32) -> HashMap<BlockHash, u32> {
+        bmap! { block.block_hash() => height }.into_iter().collect()
+    }
+
+    #[test]
+    fn verify_spv() {
+        let (proof, block) = fixture();
+        assert_eq!(
+            proof.verify_spv(protocol_id(), message(), &headers(&block, 1234)),
+            Ok(1234)
+        );
+
+        // Typed protocol id and message are accepted as well
+        assert_eq!(
+            proof.verify_spv(
+                mpc::ProtocolId::from(protocol_id()),
+                mpc::Message::from(message()),
+                &headers(&block, 1234)
+            ),
+            Ok(1234)
+        );
+
+        // Block is not in the known chain
+        assert_eq!(
+            proof.verify_spv(protocol_id(), message(), &HashMap::new()),
+            Err(SpvError::UnknownBlock(block.block_hash()))
+        );
+
+        // Wrong message
+        assert_eq!(
+            proof.verify_spv(
+                protocol_id(),
+                Message::hash(b"other message"),
+                &headers(&block, 1234)
+            ),
+            Err(SpvError::Anchor(VerifyError::NoCommitment))
+        );
+    }
+
+    #[test]
+    fn spv_data_anchor_id() {
+        let (proof, block) = fixture();
+        let anchor_id =
+            proof.anchor.anchor_id(protocol_id(), message()).unwrap();
+        let anchor_data = strict_serialize(&proof.anchor).unwrap();
+
+        // Attaching SPV data from another block mining the same witness
+        // transaction does not change the anchor and its id
+        let mut other_block = block;
+        other_block.header.time += 600;
+        let txid = proof.witness_tx.txid();
+        let other = AnchoredProof {
+            tx_proof: MerkleBlock::from_block_with_predicate(
+                &other_block,
+                |id| *id == txid,
+            ),
+            ..proof.clone()
+        };
+        assert_ne!(other.tx_proof, proof.tx_proof);
+        assert_eq!(
+            other.verify_spv(
+                protocol_id(),
+                message(),
+                &headers(&other_block, 1235)
+            ),
+            Ok(1235)
+        );
+        assert_eq!(
+            other.anchor.anchor_id(protocol_id(), message()),
+            Ok(anchor_id)
+        );
+        assert_eq!(strict_serialize(&other.anchor).unwrap(), anchor_data);
+        assert_eq!(
+            other
+                .anchor
+                .into_block(protocol_id(), message())
+                .unwrap()
+                .anchor_id(),
+            anchor_id
+        );
+    }
+
+    #[test]
+    fn invalid_tx_proof() {
+        let (proof, block) = fixture();
+        let headers = headers(&block, 1);
+
+        // Header does not match partial merkle tree
+        let mut invalid = proof.clone();
+        invalid.tx_proof.header.merkle_root = Hash::hash(b"other root");
+        assert_eq!(
+            invalid.verify_spv(protocol_id(), message(), &headers),
+            Err(SpvError::InvalidTxProof(
+                MerkleBlockError::MerkleRootMismatch
+            ))
+        );
+
+        // Proof of inclusion of some other transaction of the same block
+        let mut invalid = proof.clone();
+        let coinbase = block.txdata[0].txid();
+        invalid.tx_proof =
+            MerkleBlock::from_block_with_predicate(&block, |id| {
+                *id == coinbase
+            });
+        assert_eq!(
+            invalid.verify_spv(protocol_id(), message(), &headers),
+            Err(SpvError::TxNotIncluded(proof.witness_tx.txid()))
+        );
+
+        // Included transaction is not the anchor witness transaction
+        let mut invalid = proof;
+        invalid.witness_tx = block.txdata[0].clone();
+        invalid.tx_proof =
+            MerkleBlock::from_block_with_predicate(&block, |_| true);
+        assert_eq!(
+            invalid.verify_spv(protocol_id(), message(), &headers),
+            Err(SpvError::Anchor(VerifyError::TxidMismatch(coinbase)))
+        );
+    }
+
+    #[test]
+    fn mainnet_genesis() {
+        // Mainnet genesis block: the inclusion proof and the chain membership
+        // are verified against the real header, while the coinbase does not
+        // contain any commitment
+        let genesis = genesis_block(Network::Bitcoin);
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        let coinbase = genesis.txdata[0].clone();
+        assert_eq!(
+            coinbase.txid().to_string(),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        let (mut proof, _) = fixture();
+        proof.anchor.txid = coinbase.txid();
+        proof.witness_tx = coinbase;
+        proof.tx_proof =
+            MerkleBlock::from_block_with_predicate(&genesis, |_| true);
+        assert_eq!(
+            proof.verify_spv(protocol_id(), message(), &headers(&genesis, 0)),
+            Err(SpvError::Anchor(VerifyError::NoCommitment))
+        );
+    }
+}
@@ -0,0 +1,451 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! `EmbedCommit: UntweakedPublicKey, TapTree, Msg -> TweakedPublicKey,
+//! TapretProof`
+
+use amplify::Wrapper;
+use bitcoin::hashes::{sha256t, Hash};
+use bitcoin::schnorr::{TapTweak, TweakedPublicKey, UntweakedPublicKey};
+use bitcoin::secp256k1::SECP256K1;
+use bitcoin_scripts::taproot::TaprootScriptTree;
+use bitcoin_scripts::{LeafScript, TapScript};
+use commit_verify::{lnpbp4, CommitVerify};
+
+use super::taptree::TapretSourceInfo;
+use super::{tapret_script, TapretProof, TapretSourceError};
+use crate::msg::{MsgSize, SupportedMsgSize};
+
+/// Tapret commitment leaf data: the committed message together with the nonce
+/// used to put the commitment leaf on the right side of the taproot script
+/// tree.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[derive(StrictEncode, StrictDecode)]
+pub struct TapretCommitment {
+    /// LNPBP-4 multi-protocol commitment hash.
+    pub msg: lnpbp4::CommitmentHash,
+
+    /// Nonce value used for ordering the commitment leaf in the tree.
+    pub nonce: u16,
+}
+
+impl TapretCommitment {
+    /// Constructs commitment from a raw 32-byte message and a nonce.
+    #[inline]
+    pub fn with(msg: [u8; 32], nonce: u16) -> TapretCommitment {
+        TapretCommitment {
+            msg: lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(
+                msg,
+            )),
+            nonce,
+        }
+    }
+
+    /// Constructs tapscript containing the commitment.
+    #[inline]
+    pub fn to_tap_script(&self) -> TapScript {
+        TapScript::commit(&(self.msg, self.nonce))
+    }
+
+    /// Constructs taproot script tree leaf containing the commitment.
+    #[inline]
+    pub fn to_leaf_script(&self) -> LeafScript {
+        LeafScript::tapscript(self.to_tap_script())
+    }
+}
+
+/// Embeds tapret commitment to the message `msg` of `N` bytes (see
+/// [`crate::msg`]) into the taproot script tree, and tweaks the internal key
+/// with the merkle root of the modified tree.
+///
+/// If no tree is provided, a new tree consisting of a single commitment leaf
+/// is created. Otherwise, the commitment leaf is joined with the existing tree
+/// using the nonce which puts the leaf on the right side of the tree, such
+/// that the level 1 partner node can't contain an alternative commitment.
+/// The nonce is searched over the whole `u16` range; if none of the values
+/// works (which may happen only with an adversarially constructed tree),
+/// [`TapretSourceError::NonceExhausted`] is returned.
+///
+/// On success `tap_tree` contains the modified tree; on error it is left
+/// untouched.
+///
+/// Returns the output key of the modified taproot output together with the
+/// proof containing all data required for the commitment verification.
+pub fn tapret_commit<const N: usize>(
+    internal_key: UntweakedPublicKey,
+    tap_tree: &mut Option<TaprootScriptTree>,
+    msg: [u8; N],
+) -> Result<(TweakedPublicKey, TapretProof), TapretSourceError>
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    let mut source =
+        TapretSourceInfo::<TaprootScriptTree>::with(tap_tree.clone())?;
+    let path_proof = source.embed_script(|nonce| tapret_script(msg, nonce))?;
+    let merkle_root = path_proof
+        .script_merkle_root(path_proof.commitment_script(msg))
+        .expect("tapret embed-commit procedure is broken");
+    let (output_key, _) = internal_key.tap_tweak(SECP256K1, Some(merkle_root));
+    *tap_tree = source.into_script_tree();
+    Ok((output_key, TapretProof {
+        path_proof,
+        internal_key,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::blockdata::script;
+    use bitcoin::psbt::TapTree;
+    use bitcoin::util::taproot::TaprootBuilder;
+    use bitcoin::Script;
+    use bitcoin_scripts::taproot::{Node, TreeNode};
+    use bitcoin_scripts::{IntoNodeHash, TapNodeHash};
+    use commit_verify::convolve_commit::ConvolveCommitProof;
+    use commit_verify::embed_commit::EmbedCommitVerify;
+    use secp256k1::{XOnlyPublicKey, SECP256K1};
+
+    use super::*;
+    use crate::tapret::taptree::TapretProofError;
+    use crate::tapret::{
+        tapret_script_legacy, Lnpbp6, TapretNodePartner, TapretPathProof,
+        TapretVerifyError, MAX_PARTNER_DEPTH,
+    };
+
+    fn internal_key() -> UntweakedPublicKey {
+        XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap()
+    }
+
+    fn full_tree(depth: u8) -> TaprootScriptTree {
+        let builder =
+            (0..(1u32 << depth)).fold(TaprootBuilder::new(), |builder, no| {
+                let script =
+                    script::Builder::new().push_int(no as i64).into_script();
+                builder.add_leaf(depth, script).unwrap()
+            });
+        TaprootScriptTree::from(TapTree::from_builder(builder).unwrap())
+    }
+
+    fn expected_output_key(tap_tree: &TaprootScriptTree) -> TweakedPublicKey {
+        TapTree::from(tap_tree.clone())
+            .into_builder()
+            .finalize(SECP256K1, internal_key())
+            .unwrap()
+            .output_key()
+    }
+
+    #[test]
+    fn key_only() {
+        let msg = [0xA5; 32];
+        let mut tap_tree = None;
+        let (output_key, proof) =
+            tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+
+        let tap_tree = tap_tree.expect("commitment tree must be created");
+        assert_eq!(output_key, expected_output_key(&tap_tree));
+        assert_eq!(proof.internal_key, internal_key());
+        assert_eq!(proof.path_proof.original_merkle_root(), None);
+        assert_eq!(
+            TapTree::from(tap_tree)
+                .script_leaves()
+                .map(|leaf| leaf.script().clone())
+                .collect::<Vec<_>>(),
+            vec![TapretCommitment::with(msg, 0).to_tap_script().into_inner()]
+        );
+    }
+
+    #[test]
+    fn key_only_verify() {
+        let msg = [0xA5; 32];
+        let commitment = TapretCommitment::with(msg, 0).msg;
+        let mut tap_tree = None;
+        let (output_key, proof) =
+            tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+        let modified =
+            TapretSourceInfo::<TaprootScriptTree>::with(tap_tree.clone())
+                .unwrap();
+        assert_eq!(
+            modified.verify(&commitment, proof.path_proof.clone()),
+            Ok(true)
+        );
+        let script_pubkey = Script::new_v1_p2tr_tweaked(output_key);
+        assert_eq!(proof.verify(msg, &script_pubkey), Ok(()));
+
+        // Key-only proof is encoded as the absent partner tag, the nonce and
+        // the internal key, without any dummy node hashes
+        let mut expected = vec![0u8, 0, 0];
+        expected.extend(internal_key().serialize());
+        assert_eq!(
+            strict_encoding::strict_serialize(&proof).unwrap(),
+            expected
+        );
+
+        // Commitment combined with other leaves can't be proven with a
+        // key-only proof
+        let mut tap_tree = Some(full_tree(1));
+        let (output_key, _) =
+            tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+        let modified =
+            TapretSourceInfo::<TaprootScriptTree>::with(tap_tree).unwrap();
+        assert_eq!(
+            modified.verify(&commitment, proof.path_proof.clone()),
+            Err(TapretProofError::UnexpectedPartner)
+        );
+        assert_eq!(
+            proof.verify(msg, &Script::new_v1_p2tr_tweaked(output_key)),
+            Err(TapretVerifyError::OutputKeyMismatch(output_key))
+        );
+    }
+
+    #[test]
+    fn tree_depths() {
+        let msg = [0x5A; 32];
+        for depth in 0..=8u8 {
+            let original = full_tree(depth);
+            let original_root = original.as_root_node().node_hash();
+            let mut tap_tree = Some(original);
+            let (output_key, proof) =
+                tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+
+            let tap_tree = tap_tree.unwrap();
+            assert_eq!(output_key, expected_output_key(&tap_tree));
+            assert_eq!(proof.internal_key, internal_key());
+            assert_eq!(
+                proof.path_proof.original_merkle_root(),
+                Some(original_root)
+            );
+            assert!(proof.path_proof.check_no_commitment());
+
+            let commitment =
+                TapretCommitment::with(msg, proof.path_proof.nonce());
+            assert!(TapTree::from(tap_tree)
+                .script_leaves()
+                .any(|leaf| leaf.depth() == 1
+                    && *leaf.script()
+                        == commitment.to_tap_script().into_inner()));
+        }
+    }
+
+    #[test]
+    fn proof_len() {
+        let (_, proof) =
+            tapret_commit(internal_key(), &mut None, [0; 32]).unwrap();
+        assert_eq!(proof.serialized_len(), 35);
+        assert_eq!(
+            proof.original_pubkey_script(),
+            Script::new_v1_p2tr(SECP256K1, internal_key(), None).into()
+        );
+
+        for depth in [0, 1, 8] {
+            let original = full_tree(depth);
+            let merkle_root = TapTree::from(original.clone())
+                .into_builder()
+                .finalize(SECP256K1, internal_key())
+                .unwrap()
+                .merkle_root();
+            let (_, proof) =
+                tapret_commit(internal_key(), &mut Some(original), [0; 32])
+                    .unwrap();
+            assert_eq!(proof.serialized_len(), 68);
+            assert_eq!(
+                proof.serialized_len(),
+                strict_encoding::strict_serialize(&proof).unwrap().len()
+            );
+            assert_eq!(
+                proof.original_pubkey_script(),
+                Script::new_v1_p2tr(SECP256K1, internal_key(), merkle_root)
+                    .into()
+            );
+        }
+    }
+
+    /// Constructs a tree with two leaves at `depth` and single leaves at each
+    /// of the upper levels.
+    fn chain_tree(depth: u8) -> TaprootScriptTree {
+        let builder = (1..=depth).chain([depth]).enumerate().fold(
+            TaprootBuilder::new(),
+            |builder, (no, depth)| {
+                let script =
+                    script::Builder::new().push_int(no as i64).into_script();
+                builder.add_leaf(depth, script).unwrap()
+            },
+        );
+        TaprootScriptTree::from(TapTree::from_builder(builder).unwrap())
+    }
+
+    #[test]
+    fn tree_too_deep() {
+        let mut tap_tree = Some(chain_tree(MAX_PARTNER_DEPTH as u8));
+        assert!(tapret_commit(internal_key(), &mut tap_tree, [0; 32]).is_ok());
+
+        let original = chain_tree(MAX_PARTNER_DEPTH as u8 + 1);
+        let mut tap_tree = Some(original.clone());
+        assert_eq!(
+            tapret_commit(internal_key(), &mut tap_tree, [0; 32]),
+            Err(TapretSourceError::TreeTooDeep(MAX_PARTNER_DEPTH + 1))
+        );
+        assert_eq!(tap_tree, Some(original));
+    }
+
+    #[test]
+    fn adversarial_partner() {
+        let msg = [0x5A; 32];
+        let commitment_hash = |nonce| {
+            TapretCommitment::with(msg, nonce)
+                .to_leaf_script()
+                .tap_leaf_hash()
+                .into_node_hash()
+        };
+
+        // Hidden partner which is lexicographically larger than most of the
+        // commitment leaf hashes forces the embedder to grind the nonce.
+        let mut partner = [0u8; 32];
+        partner[0] = 0xF0;
+        let partner = TapNodeHash::from_inner(partner);
+        let expected_nonce = (0..=u16::MAX)
+            .find(|nonce| commitment_hash(*nonce) > partner)
+            .unwrap();
+        assert_ne!(expected_nonce, 0);
+
+        let original =
+            TaprootScriptTree::with(TreeNode::Hidden(partner, 0)).unwrap();
+        let mut tap_tree = Some(original);
+        let (output_key, proof) =
+            tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+
+        assert_eq!(proof.path_proof.nonce(), expected_nonce);
+        assert_eq!(proof.path_proof.original_merkle_root(), Some(partner));
+        let tap_tree = tap_tree.unwrap();
+        let branch = tap_tree.as_root_node().as_branch().unwrap();
+        assert_eq!(branch.as_left_node().node_hash(), partner);
+        assert_eq!(
+            branch.as_right_node().node_hash(),
+            commitment_hash(expected_nonce)
+        );
+
+        let msg = TapretCommitment::with(msg, 0).msg;
+        assert!(
+            ConvolveCommitProof::<_, UntweakedPublicKey, Lnpbp6>::verify(
+                &proof, &msg, output_key
+            )
+            .unwrap()
+        );
+
+        // Verification must use the nonce from the proof
+        let zero_nonce = TapretProof {
+            path_proof: TapretPathProof::with(
+                TapretNodePartner::LeftNode(partner),
+                0,
+            )
+            .unwrap(),
+            internal_key: internal_key(),
+        };
+        assert!(
+            !ConvolveCommitProof::<_, UntweakedPublicKey, Lnpbp6>::verify(
+                &zero_nonce,
+                &msg,
+                output_key
+            )
+            .unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn nonce_exhausted() {
+        // No commitment leaf hash can be larger than this partner node
+        let partner = TapNodeHash::from_inner([0xFF; 32]);
+        let original =
+            TaprootScriptTree::with(TreeNode::Hidden(partner, 0)).unwrap();
+        let mut tap_tree = Some(original.clone());
+
+        assert_eq!(
+            tapret_commit(internal_key(), &mut tap_tree, [0x5A; 32]),
+            Err(TapretSourceError::NonceExhausted(partner))
+        );
+        assert_eq!(tap_tree, Some(original));
+    }
+
+    #[test]
+    fn commitment_leaf() {
+        let commitment = TapretCommitment::with([0x11; 32], 0x0107);
+        let script = commitment.to_tap_script().into_inner();
+        // 30 x OP_RESERVED, OP_RETURN, OP_PUSHBYTES_34 <msg> <nonce LE>
+        assert_eq!(script.len(), 66);
+        assert_eq!(&script[..30], &[0x50; 30][..]);
+        assert_eq!(&script[30..32], &[0x6a, 0x22][..]);
+        assert_eq!(&script[32..64], &[0x11; 32][..]);
+        assert_eq!(&script[64..], &[0x07, 0x01][..]);
+        assert_eq!(
+            commitment.to_leaf_script(),
+            LeafScript::tapscript(commitment.to_tap_script())
+        );
+
+        // Legacy commitments: 30 x OP_RESERVED, OP_RETURN, OP_PUSHBYTES_33
+        // <msg> <nonce>
+        let script = tapret_script_legacy([0x11; 32], 7).into_inner();
+        assert_eq!(script.len(), 65);
+        assert_eq!(&script[..30], &[0x50; 30][..]);
+        assert_eq!(&script[30..32], &[0x6a, 0x21][..]);
+        assert_eq!(&script[32..64], &[0x11; 32][..]);
+        assert_eq!(script[64], 7);
+        assert_eq!(
+            TapScript::commit(&(commitment.msg, 7u8)).into_inner(),
+            script
+        );
+    }
+
+    #[test]
+    fn msg64() {
+        let msg = [0xA5; 64];
+        assert_eq!(
+            tapret_script([0x11; 32], 0x0107),
+            TapretCommitment::with([0x11; 32], 0x0107).to_tap_script()
+        );
+        let script = tapret_script([0x11; 64], 0x0107).into_inner();
+        assert_eq!(script.len(), 98);
+        assert_eq!(&script[30..32], &[0x6a, 0x42][..]);
+        assert_eq!(&script[96..], &[0x07, 0x01][..]);
+
+        for mut tap_tree in [None, Some(full_tree(2))] {
+            let (output_key, proof) =
+                tapret_commit(internal_key(), &mut tap_tree, msg).unwrap();
+            assert_eq!(output_key, expected_output_key(&tap_tree.unwrap()));
+            let script_pubkey = Script::new_v1_p2tr_tweaked(output_key);
+            assert_eq!(proof.verify(msg, &script_pubkey), Ok(()));
+
+            // Commitments to the messages of other size never match
+            let mut short = [0u8; 32];
+            short.copy_from_slice(&msg[..32]);
+            assert_eq!(
+                proof.verify(short, &script_pubkey),
+                Err(TapretVerifyError::OutputKeyMismatch(output_key))
+            );
+            let (output_key, proof) =
+                tapret_commit(internal_key(), &mut None, short).unwrap();
+            let script_pubkey = Script::new_v1_p2tr_tweaked(output_key);
+            assert!(proof.verify(msg, &script_pubkey).is_err());
+        }
+    }
+}
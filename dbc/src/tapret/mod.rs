@@ -55,36 +55,58 @@
 //! b) `TapretProof` / `TweakedPublicKey'`
 //! b) `XOnlyPublicKey` / `TapretProof`
 
+mod commitment;
 #[cfg(feature = "wallet")]
 mod psbtout;
+mod scriptpk;
+mod spend;
 mod tapscript;
 mod taptree;
 mod tx;
 mod txout;
 mod xonlypk;
 
+pub use commitment::{tapret_commit, TapretCommitment};
 #[cfg(feature = "wallet")]
 pub use psbtout::{PsbtCommitError, PsbtVerifyError};
-pub use tapscript::TAPRET_SCRIPT_COMMITMENT_PREFIX;
-pub use taptree::TapretTreeError;
+pub use scriptpk::{find_tapret_output, TapretVerifyError};
+pub use spend::TapretSpendError;
+pub use tapscript::{
+    tapret_script, tapret_script_legacy, TAPRET_SCRIPT_COMMITMENT_PREFIX,
+    TAPRET_SCRIPT_LEGACY_PUSH_OPCODES, TAPRET_SCRIPT_PUSH_OPCODES,
+};
+pub use taptree::{TapretSourceError, TapretTreeError};
 pub use tx::TapretError;
 
 /// Marker non-instantiable enum defining LNPBP-6 taproot OP_RETURN (`tapret`)
 /// protocol.
 pub enum Lnpbp6 {}
 
-use std::io::Read;
+/// Maximal depth of the taproot script tree nodes which may get a tapret
+/// commitment. Joining the tree with the commitment leaf moves all its nodes
+/// one level down, so script path spendings of a committed output have their
+/// control blocks inflated by a single 32-byte hash.
+pub const MAX_PARTNER_DEPTH: usize = TAPROOT_CONTROL_MAX_NODE_COUNT - 1;
+
+use std::io::{self, Read, Write};
 
 use bitcoin::hashes::sha256::Midstate;
 use bitcoin::hashes::Hash;
 use bitcoin::schnorr::UntweakedPublicKey;
-use bitcoin::util::taproot::{TapBranchHash, TaprootMerkleBranch};
+use bitcoin::util::taproot::{
+    TapBranchHash, TaprootMerkleBranch, TAPROOT_CONTROL_MAX_NODE_COUNT,
+};
 use bitcoin::Script;
 use bitcoin_scripts::taproot::TreeNode;
-use bitcoin_scripts::{IntoNodeHash, LeafScript, PubkeyScript, TapNodeHash};
+use bitcoin_scripts::{
+    IntoNodeHash, LeafScript, PubkeyScript, TapNodeHash, TapScript,
+};
 use commit_verify::CommitmentProtocol;
 use secp256k1::SECP256K1;
-use strict_encoding::{self, StrictDecode};
+use strict_encoding::{self, StrictDecode, StrictEncode};
+
+use self::tapscript::is_tapret_script_prefix;
+use crate::msg::{MsgSize, SupportedMsgSize};
 
 impl CommitmentProtocol for Lnpbp6 {
     // TaggedHash("LNPBP6")
@@ -216,24 +238,19 @@ impl TapretNodePartner {
 
     /// Checks that the sibling data does not contain another tapret commitment.
     ///
-    /// The check ensures that if the sibling data are present, their first 32
-    /// bytes are not equal to [`TAPRET_SCRIPT_COMMITMENT_PREFIX`], and if
-    /// the sibling is another node, the hash of its first child in the proof
-    /// is smaller than the hash of the other.
+    /// The check ensures that if the sibling data are present, they do not
+    /// start with [`TAPRET_SCRIPT_COMMITMENT_PREFIX`] followed by one of
+    /// [`TAPRET_SCRIPT_PUSH_OPCODES`] or [`TAPRET_SCRIPT_LEGACY_PUSH_OPCODES`],
+    /// and if the sibling is another node, the hash of its first child in the
+    /// proof does not start with them either.
     pub fn check_no_commitment(&self) -> bool {
         match self {
             TapretNodePartner::LeftNode(_) => true,
-            TapretNodePartner::RightLeaf(LeafScript { script, .. })
-                if script.len() < 32 =>
-            {
-                true
-            }
             TapretNodePartner::RightLeaf(LeafScript { script, .. }) => {
-                script[0..32] != TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
+                !is_tapret_script_prefix(&script[..])
             }
             TapretNodePartner::RightBranch(right_branch) => {
-                right_branch.left_node_hash()[..]
-                    != TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
+                !is_tapret_script_prefix(&right_branch.left_node_hash()[..])
             }
         }
     }
@@ -283,25 +300,93 @@ impl TapretNodePartner {
     }
 }
 
+/// Nonce of the tapret commitment, which also defines the layout of the
+/// commitment script.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(inner)]
+pub enum TapretNonce {
+    /// Single-byte nonce of the commitments created before the nonce was
+    /// extended to two bytes, see [`tapret_script_legacy`].
+    Legacy(u8),
+
+    /// Two-byte nonce used by the commitments created with [`tapret_script`].
+    Extended(u16),
+}
+
+impl Default for TapretNonce {
+    #[inline]
+    fn default() -> Self { TapretNonce::Extended(0) }
+}
+
+impl TapretNonce {
+    /// Returns numeric value of the nonce.
+    #[inline]
+    pub fn value(self) -> u16 {
+        match self {
+            TapretNonce::Legacy(nonce) => nonce as u16,
+            TapretNonce::Extended(nonce) => nonce,
+        }
+    }
+
+    /// Detects whether the nonce uses the legacy single-byte layout.
+    #[inline]
+    pub fn is_legacy(self) -> bool { matches!(self, TapretNonce::Legacy(_)) }
+}
+
 /// Structure proving that a merkle path to the tapret commitment inside the
 /// taproot script tree does not have an alternative commitment.
 ///
 /// Holds information about the sibling at level 1 of the tree in form of
 /// [`TapretNodePartner`].
+///
+/// Strict encoding puts the nonce after the partner node data using one byte
+/// for the legacy nonces and two bytes otherwise. Since the encoding does not
+/// carry the nonce layout, [`StrictDecode`] reads the two-byte nonce; proofs
+/// with the legacy nonce are decoded with
+/// [`TapretPathProof::strict_decode_legacy`].
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
-#[derive(StrictEncode, StrictDecode)]
 pub struct TapretPathProof {
     /// Information about the sibling at level 1 of the tree
     partner_node: Option<TapretNodePartner>,
 
     /// A nonce value used to put the tapret commitment into the right side of
     /// the tree.
-    nonce: u8,
+    nonce: TapretNonce,
+}
+
+impl StrictEncode for TapretPathProof {
+    fn strict_encode<E: Write>(
+        &self,
+        mut e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        let len = self.partner_node.strict_encode(&mut e)?;
+        Ok(len
+            + match self.nonce {
+                TapretNonce::Legacy(nonce) => nonce.strict_encode(e)?,
+                TapretNonce::Extended(nonce) => nonce.strict_encode(e)?,
+            })
+    }
+}
+
+impl StrictDecode for TapretPathProof {
+    fn strict_decode<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(TapretPathProof {
+            partner_node: StrictDecode::strict_decode(&mut d)?,
+            nonce: TapretNonce::Extended(u16::strict_decode(d)?),
+        })
+    }
 }
 
 impl TapretPathProof {
@@ -309,10 +394,36 @@ impl TapretPathProof {
     #[inline]
     pub fn new() -> TapretPathProof { TapretPathProof::default() }
 
+    /// Construct new empty path proof for the legacy commitment with a
+    /// single-byte nonce.
+    #[inline]
+    pub fn new_legacy() -> TapretPathProof {
+        TapretPathProof {
+            partner_node: None,
+            nonce: TapretNonce::Legacy(0),
+        }
+    }
+
     /// Adds element to the path proof.
     pub fn with(
+        elem: TapretNodePartner,
+        nonce: u16,
+    ) -> Result<TapretPathProof, TapretPathError> {
+        TapretPathProof::with_nonce(elem, TapretNonce::Extended(nonce))
+    }
+
+    /// Adds element to the path proof of the legacy commitment with a
+    /// single-byte nonce.
+    pub fn with_legacy(
         elem: TapretNodePartner,
         nonce: u8,
+    ) -> Result<TapretPathProof, TapretPathError> {
+        TapretPathProof::with_nonce(elem, TapretNonce::Legacy(nonce))
+    }
+
+    fn with_nonce(
+        elem: TapretNodePartner,
+        nonce: TapretNonce,
     ) -> Result<TapretPathProof, TapretPathError> {
         if !elem.check_no_commitment() {
             return Err(TapretPathError::InvalidNodePartner(elem));
@@ -323,6 +434,38 @@ impl TapretPathProof {
         })
     }
 
+    /// Decodes path proof of the legacy commitment, strict-encoded with a
+    /// single-byte nonce.
+    pub fn strict_decode_legacy<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(TapretPathProof {
+            partner_node: StrictDecode::strict_decode(&mut d)?,
+            nonce: TapretNonce::Legacy(u8::strict_decode(d)?),
+        })
+    }
+
+    /// Returns nonce value used to put the tapret commitment into the right
+    /// side of the tree.
+    #[inline]
+    pub fn nonce(&self) -> u16 { self.nonce.value() }
+
+    /// Returns the nonce together with its layout.
+    #[inline]
+    pub fn tapret_nonce(&self) -> TapretNonce { self.nonce }
+
+    /// Constructs tapret commitment script for the message `msg` of `N` bytes
+    /// (see [`crate::msg`]) using the nonce from the proof in its layout.
+    pub fn commitment_script<const N: usize>(&self, msg: [u8; N]) -> TapScript
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        match self.nonce {
+            TapretNonce::Legacy(nonce) => tapret_script_legacy(msg, nonce),
+            TapretNonce::Extended(nonce) => tapret_script(msg, nonce),
+        }
+    }
+
     /// Checks that the sibling data does not contain another tapret commitment
     /// for any step of the mekrle path.
     #[inline]
@@ -403,6 +546,26 @@ impl TapretProof {
             .map(TapBranchHash::from_inner);
         Script::new_v1_p2tr(SECP256K1, self.internal_key, merkle_root).into()
     }
+
+    /// Decodes proof of the legacy commitment, strict-encoded with a
+    /// single-byte nonce (see [`TapretPathProof::strict_decode_legacy`]).
+    pub fn strict_decode_legacy<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(TapretProof {
+            path_proof: TapretPathProof::strict_decode_legacy(&mut d)?,
+            internal_key: StrictDecode::strict_decode(d)?,
+        })
+    }
+
+    /// Returns length of the proof strict encoding, which is 35 bytes for the
+    /// outputs without script path spendings (34 bytes for the legacy
+    /// proofs).
+    #[inline]
+    pub fn serialized_len(&self) -> usize {
+        self.strict_encode(io::sink())
+            .expect("strict encoding of tapret proof into a sink must not fail")
+    }
 }
 
 /// Tapret value: a final tweak applied to the internal taproot key which
@@ -416,10 +579,97 @@ pub struct TapretTweak(TaprootMerkleBranch);
 #[cfg(test)]
 mod test {
     use amplify::Wrapper;
-    use commit_verify::tagged_hash;
+    use bitcoin::blockdata::opcodes::all;
+    use bitcoin::blockdata::script;
+    use commit_verify::{lnpbp4, tagged_hash};
 
     use super::*;
 
+    fn prefixed_script(push: u8) -> bitcoin_scripts::TapScript {
+        let mut data = TAPRET_SCRIPT_COMMITMENT_PREFIX.to_vec();
+        data.push(push);
+        data.extend([0x5A; 66]);
+        Script::from(data).into()
+    }
+
+    #[test]
+    fn partner_commitment() {
+        for script in [
+            tapret_script([0xA5; 32], 0),
+            tapret_script([0xA5; 64], 7),
+            tapret_script_legacy([0xA5; 32], 0),
+            tapret_script_legacy([0xA5; 64], 7),
+            prefixed_script(0x21),
+            prefixed_script(0x22),
+            prefixed_script(0x41),
+            prefixed_script(0x42),
+        ] {
+            let partner =
+                TapretNodePartner::from(LeafScript::tapscript(script));
+            assert!(!partner.check_no_commitment());
+            assert_eq!(
+                TapretPathProof::with(partner.clone(), 0),
+                Err(TapretPathError::InvalidNodePartner(partner.clone()))
+            );
+            assert_eq!(
+                TapretPathProof::with_legacy(partner.clone(), 0),
+                Err(TapretPathError::InvalidNodePartner(partner.clone()))
+            );
+
+            // Proofs with the commitment partner constructed bypassing the
+            // check are rejected during verification
+            let path_proof = TapretPathProof {
+                partner_node: Some(partner.clone()),
+                nonce: TapretNonce::Extended(0),
+            };
+            assert!(!path_proof.check_no_commitment());
+            assert_eq!(
+                path_proof.commitment_merkle_root(
+                    &lnpbp4::CommitmentHash::from_inner(Hash::hash(b"msg"))
+                ),
+                Err(TapretTreeError::AlternativeCommitment(partner))
+            );
+        }
+
+        // Scripts with the same prefix but a different push are not produced
+        // by the commitment procedure
+        for push in [0x20, 0x23, 0x40, 0x4c] {
+            assert!(TapretNodePartner::from(LeafScript::tapscript(
+                prefixed_script(push)
+            ))
+            .check_no_commitment());
+        }
+        let short = script::Builder::new()
+            .push_opcode(all::OP_RESERVED)
+            .push_opcode(all::OP_RETURN)
+            .into_script();
+        assert!(TapretNodePartner::from(LeafScript::tapscript(short.into()))
+            .check_no_commitment());
+
+        let mut left = [0xFFu8; 32];
+        left[..31].copy_from_slice(&TAPRET_SCRIPT_COMMITMENT_PREFIX);
+        left[31] = 0x22;
+        let left = TapNodeHash::from_inner(left);
+        let right = TapNodeHash::from_inner([0xFF; 32]);
+        assert!(
+            !TapretNodePartner::right_branch(left, right).check_no_commitment()
+        );
+        let mut legacy = left.into_inner();
+        legacy[31] = 0x21;
+        assert!(!TapretNodePartner::right_branch(
+            TapNodeHash::from_inner(legacy),
+            right
+        )
+        .check_no_commitment());
+        let mut other = left.into_inner();
+        other[31] = 0x20;
+        assert!(TapretNodePartner::right_branch(
+            TapNodeHash::from_inner(other),
+            right
+        )
+        .check_no_commitment());
+    }
+
     #[test]
     fn test_lnpbp6_midstate() {
         let midstate = tagged_hash::Midstate::with(b"LNPBP6");
@@ -115,8 +115,9 @@ impl EmbedCommitProof<lnpbp4::CommitmentHash, psbt::Output, Lnpbp6>
             .tap_internal_key
             .ok_or(PsbtCommitError::InternalKeyMissed)?;
         if internal_key != self.internal_key {
-            return Err(PsbtCommitError::InternalKeyMismatch)
-                .map_err(PsbtVerifyError::from);
+            return Err(PsbtVerifyError::from(
+                PsbtCommitError::InternalKeyMismatch,
+            ));
         }
 
         let tap_tree = original_container.tap_tree.map(TaprootScriptTree::from);
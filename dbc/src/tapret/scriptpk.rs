@@ -0,0 +1,320 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! `Verify: PubkeyScript', TapretProof, Msg -> bool`
+
+use bitcoin::schnorr::{TapTweak, TweakedPublicKey};
+use bitcoin::{Script, Transaction};
+use secp256k1::{Parity, XOnlyPublicKey, SECP256K1};
+
+use super::{TapretProof, TapretTreeError};
+use crate::msg::{MsgSize, SupportedMsgSize};
+
+/// Errors during tapret commitment verification against a transaction output.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum TapretVerifyError {
+    /// the tapret proof is invalid. Details: {0}
+    #[from]
+    InvalidProof(TapretTreeError),
+
+    /// the output scriptPubkey {0} is not a P2TR (taproot) script.
+    NonTaprootScript(Script),
+
+    /// the output key {0} does not contain commitment to the message under
+    /// the provided tapret proof.
+    OutputKeyMismatch(TweakedPublicKey),
+
+    /// the parity of the output key committing to the message does not match
+    /// the provided parity.
+    WrongParity,
+}
+
+impl TapretProof {
+    /// Computes output key, and its parity, of the taproot output committing
+    /// to the message `msg` of `N` bytes (see [`crate::msg`]) under this
+    /// proof.
+    pub fn output_key<const N: usize>(
+        &self,
+        msg: [u8; N],
+    ) -> Result<(TweakedPublicKey, Parity), TapretTreeError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        let merkle_root = self
+            .path_proof
+            .script_merkle_root(self.path_proof.commitment_script(msg))?;
+        Ok(self.internal_key.tap_tweak(SECP256K1, Some(merkle_root)))
+    }
+
+    /// Verifies that P2TR `script_pubkey` contains a tapret commitment to the
+    /// message `msg` defined by this proof.
+    ///
+    /// The verification recomputes the output key from the internal key, the
+    /// commitment leaf and the partner node data and compares it against the
+    /// output key from the `script_pubkey`. Commitments to the messages of
+    /// other size than `N` never match.
+    pub fn verify<const N: usize>(
+        &self,
+        msg: [u8; N],
+        script_pubkey: &Script,
+    ) -> Result<(), TapretVerifyError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        if !script_pubkey.is_v1_p2tr() {
+            return Err(TapretVerifyError::NonTaprootScript(
+                script_pubkey.clone(),
+            ));
+        }
+        let actual = XOnlyPublicKey::from_slice(&script_pubkey[2..])
+            .map(TweakedPublicKey::dangerous_assume_tweaked)
+            .map_err(|_| {
+                TapretVerifyError::NonTaprootScript(script_pubkey.clone())
+            })?;
+        let (expected, _) = self.output_key(msg)?;
+        if expected != actual {
+            return Err(TapretVerifyError::OutputKeyMismatch(actual));
+        }
+        Ok(())
+    }
+
+    /// Constructs P2TR `script_pubkey` of the output committing to the message
+    /// `msg` under this proof.
+    ///
+    /// The script is computed from the internal key and the partner node data
+    /// kept in the proof only, so wallets can recognize their tapret outputs
+    /// (for instance, change outputs during recovery) without knowing the rest
+    /// of the taproot script tree.
+    pub fn derive_script_pubkey<const N: usize>(
+        &self,
+        msg: [u8; N],
+    ) -> Result<Script, TapretTreeError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        let (output_key, _) = self.output_key(msg)?;
+        Ok(Script::new_v1_p2tr_tweaked(output_key))
+    }
+
+    /// Detects whether `script_pubkey` is the P2TR output committing to the
+    /// message `msg` under this proof, see [`TapretProof::verify`].
+    #[inline]
+    pub fn matches<const N: usize>(
+        &self,
+        msg: [u8; N],
+        script_pubkey: &Script,
+    ) -> bool
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        self.verify(msg, script_pubkey).is_ok()
+    }
+
+    /// Verifies that the output key and its parity, known for instance from
+    /// a control block, contain a tapret commitment to the message `msg`
+    /// defined by this proof.
+    pub fn verify_output_key<const N: usize>(
+        &self,
+        msg: [u8; N],
+        output_key: TweakedPublicKey,
+        parity: Parity,
+    ) -> Result<(), TapretVerifyError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        let (expected, expected_parity) = self.output_key(msg)?;
+        if expected != output_key {
+            return Err(TapretVerifyError::OutputKeyMismatch(output_key));
+        }
+        if expected_parity != parity {
+            return Err(TapretVerifyError::WrongParity);
+        }
+        Ok(())
+    }
+}
+
+/// Finds the first output of the transaction committing to the message `msg`
+/// under the `proof`, see [`TapretProof::matches`].
+pub fn find_tapret_output<const N: usize>(
+    tx: &Transaction,
+    proof: &TapretProof,
+    msg: [u8; N],
+) -> Option<u32>
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    let script_pubkey = proof.derive_script_pubkey(msg).ok()?;
+    tx.output
+        .iter()
+        .position(|txout| txout.script_pubkey == script_pubkey)
+        .map(|vout| vout as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::blockdata::script;
+    use bitcoin::hashes::Hash;
+    use bitcoin::psbt::TapTree;
+    use bitcoin::util::taproot::TaprootBuilder;
+    use bitcoin::{PubkeyHash, TxOut, WPubkeyHash};
+    use bitcoin_scripts::taproot::TaprootScriptTree;
+    use bitcoin_scripts::TapNodeHash;
+
+    use super::*;
+    use crate::tapret::{tapret_commit, TapretNodePartner, TapretPathProof};
+
+    fn internal_key() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap()
+    }
+
+    fn tree() -> TaprootScriptTree {
+        let builder = (0..4i64).fold(TaprootBuilder::new(), |builder, no| {
+            let script = script::Builder::new().push_int(no).into_script();
+            builder.add_leaf(2, script).unwrap()
+        });
+        TaprootScriptTree::from(TapTree::from_builder(builder).unwrap())
+    }
+
+    fn commit(tap_tree: Option<TaprootScriptTree>) -> (Script, TapretProof) {
+        let mut tap_tree = tap_tree;
+        let (output_key, proof) =
+            tapret_commit(internal_key(), &mut tap_tree, [0x77; 32]).unwrap();
+        (Script::new_v1_p2tr_tweaked(output_key), proof)
+    }
+
+    #[test]
+    fn verify() {
+        for tap_tree in [None, Some(tree())] {
+            let (script_pubkey, proof) = commit(tap_tree);
+            assert_eq!(proof.verify([0x77; 32], &script_pubkey), Ok(()));
+
+            let (output_key, parity) = proof.output_key([0x77; 32]).unwrap();
+            assert_eq!(
+                proof.verify_output_key([0x77; 32], output_key, parity),
+                Ok(())
+            );
+            let wrong_parity = match parity {
+                Parity::Even => Parity::Odd,
+                Parity::Odd => Parity::Even,
+            };
+            assert_eq!(
+                proof.verify_output_key([0x77; 32], output_key, wrong_parity),
+                Err(TapretVerifyError::WrongParity)
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_message() {
+        let (script_pubkey, proof) = commit(Some(tree()));
+        assert!(matches!(
+            proof.verify([0x78; 32], &script_pubkey),
+            Err(TapretVerifyError::OutputKeyMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn tampered_partner() {
+        let (script_pubkey, mut proof) = commit(Some(tree()));
+        let tampered = TapNodeHash::hash(b"tampered");
+        for partner in [
+            TapretNodePartner::LeftNode(tampered),
+            TapretNodePartner::right_branch(tampered, tampered),
+        ] {
+            proof.path_proof =
+                TapretPathProof::with(partner, proof.path_proof.nonce())
+                    .unwrap();
+            assert!(matches!(
+                proof.verify([0x77; 32], &script_pubkey),
+                Err(TapretVerifyError::OutputKeyMismatch(_))
+                    | Err(TapretVerifyError::InvalidProof(_))
+            ));
+        }
+
+        // Removing the partner turns the proof into a key-only spending one
+        proof.path_proof = TapretPathProof::new();
+        assert!(matches!(
+            proof.verify([0x77; 32], &script_pubkey),
+            Err(TapretVerifyError::OutputKeyMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn recovery() {
+        // Wallet keeps only the internal key and the proof for its change
+        // output; the tap tree is lost
+        let (change, proof) = commit(Some(tree()));
+        assert_eq!(proof.derive_script_pubkey([0x77; 32]), Ok(change.clone()));
+        assert!(proof.matches([0x77; 32], &change));
+        assert!(!proof.matches([0x78; 32], &change));
+
+        let (key_only, key_only_proof) = commit(None);
+        assert!(key_only_proof.matches([0x77; 32], &key_only));
+        assert!(!key_only_proof.matches([0x77; 32], &change));
+        assert!(!proof.matches([0x77; 32], &key_only));
+
+        let txout = |script_pubkey: Script| TxOut {
+            value: 1000,
+            script_pubkey,
+        };
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: vec![
+                txout(Script::new_v1_p2tr(SECP256K1, internal_key(), None)),
+                txout(Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"payment"))),
+                txout(change.clone()),
+                txout(key_only),
+                txout(change),
+            ],
+        };
+        assert_eq!(find_tapret_output(&tx, &proof, [0x77; 32]), Some(2));
+        assert_eq!(
+            find_tapret_output(&tx, &key_only_proof, [0x77; 32]),
+            Some(3)
+        );
+        assert_eq!(find_tapret_output(&tx, &proof, [0x78; 32]), None);
+
+        // Proofs with an invalid partner never match
+        let mut invalid = proof;
+        invalid.path_proof = TapretPathProof::with(
+            TapretNodePartner::LeftNode(TapNodeHash::hash(b"node")),
+            invalid.path_proof.nonce(),
+        )
+        .unwrap();
+        assert_eq!(find_tapret_output(&tx, &invalid, [0x77; 32]), None);
+    }
+
+    #[test]
+    fn non_taproot_output() {
+        let (_, proof) = commit(Some(tree()));
+        let p2wpkh =
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"non-taproot output"));
+        let p2pkh = Script::new_p2pkh(&PubkeyHash::hash(b"non-taproot output"));
+        for script_pubkey in [p2wpkh, p2pkh, Script::new()] {
+            assert_eq!(
+                proof.verify([0x77; 32], &script_pubkey),
+                Err(TapretVerifyError::NonTaprootScript(script_pubkey))
+            );
+        }
+    }
+}
@@ -0,0 +1,326 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! `Spend: TapretProof + ControlBlock -> ControlBlock'` and key-path signing
+//! of the tapret-tweaked outputs.
+
+use amplify::Wrapper;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::util::taproot::{ControlBlock, TaprootMerkleBranch};
+use bitcoin_scripts::LeafScript;
+#[cfg(feature = "sign")]
+use secp256k1::KeyPair;
+use secp256k1::{XOnlyPublicKey, SECP256K1};
+
+use super::{TapretProof, TapretTreeError};
+use crate::msg::{MsgSize, SupportedMsgSize};
+
+/// Errors spending outputs containing tapret commitment.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum TapretSpendError {
+    /// the tapret proof is invalid. Details: {0}
+    #[from]
+    InvalidProof(TapretTreeError),
+
+    /// the key pair does not match the internal key {0} of the tapret proof.
+    KeyMismatch(XOnlyPublicKey),
+
+    /// the leaf script with the provided merkle branch is not a part of the
+    /// script tree existing before the tapret commitment.
+    UnrelatedLeaf,
+}
+
+impl TapretProof {
+    /// Tweaks the key pair corresponding to the internal key of this proof,
+    /// producing key pair for the key path spending of the taproot output
+    /// committing to the message `msg`.
+    #[cfg(feature = "sign")]
+    pub fn tweak_keypair<const N: usize>(
+        &self,
+        keypair: &KeyPair,
+        msg: [u8; N],
+    ) -> Result<KeyPair, TapretSpendError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        use bitcoin::schnorr::TapTweak;
+
+        let internal_key = XOnlyPublicKey::from_keypair(keypair);
+        if internal_key != self.internal_key {
+            return Err(TapretSpendError::KeyMismatch(self.internal_key));
+        }
+        let merkle_root = self
+            .path_proof
+            .script_merkle_root(self.path_proof.commitment_script(msg))?;
+        Ok(keypair.tap_tweak(SECP256K1, Some(merkle_root)).into_inner())
+    }
+
+    /// Constructs control block for the script path spending of the `leaf`
+    /// from the script tree which existed before the tapret commitment to the
+    /// message `msg` was added.
+    ///
+    /// The `merkle_branch` is the path of the leaf in the original tree, as
+    /// present in the leaf control block before the commitment. Since the
+    /// commitment leaf becomes the level 1 partner of the original tree, the
+    /// resulting control block has the commitment leaf hash appended to it.
+    ///
+    /// The constructed control block is verified against the output key
+    /// committing to `msg`, so leaves which are not a part of the original
+    /// tree result in [`TapretSpendError::UnrelatedLeaf`].
+    pub fn control_block<const N: usize>(
+        &self,
+        msg: [u8; N],
+        leaf: &LeafScript,
+        merkle_branch: &TaprootMerkleBranch,
+    ) -> Result<ControlBlock, TapretSpendError>
+    where
+        MsgSize<N>: SupportedMsgSize,
+    {
+        let commitment =
+            LeafScript::tapscript(self.path_proof.commitment_script(msg));
+        let (output_key, output_key_parity) = self.output_key(msg)?;
+
+        let mut path = merkle_branch.as_inner().to_vec();
+        path.push(sha256::Hash::from_inner(
+            commitment.tap_leaf_hash().into_inner(),
+        ));
+        let merkle_branch = TaprootMerkleBranch::from_inner(path)
+            .map_err(|_| TapretSpendError::UnrelatedLeaf)?;
+
+        let control_block = ControlBlock {
+            leaf_version: leaf.version,
+            output_key_parity,
+            internal_key: self.internal_key,
+            merkle_branch,
+        };
+        if !control_block.verify_taproot_commitment(
+            SECP256K1,
+            output_key.to_inner(),
+            leaf.script.as_inner(),
+        ) {
+            return Err(TapretSpendError::UnrelatedLeaf);
+        }
+        Ok(control_block)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::blockdata::script;
+    use bitcoin::psbt::TapTree;
+    use bitcoin::util::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::Script;
+    use bitcoin_scripts::taproot::TaprootScriptTree;
+
+    use super::*;
+    use crate::tapret::tapret_commit;
+
+    const MSG: [u8; 32] = [0x77; 32];
+
+    fn internal_key() -> XOnlyPublicKey {
+        secp256k1::KeyPair::from_seckey_slice(SECP256K1, &[0x11; 32])
+            .map(|keypair| XOnlyPublicKey::from_keypair(&keypair))
+            .unwrap()
+    }
+
+    fn scripts() -> Vec<Script> {
+        (0..4i64)
+            .map(|no| script::Builder::new().push_int(no).into_script())
+            .collect()
+    }
+
+    fn builder() -> TaprootBuilder {
+        scripts()
+            .into_iter()
+            .fold(TaprootBuilder::new(), |builder, script| {
+                builder.add_leaf(2, script).unwrap()
+            })
+    }
+
+    fn commit(
+        tap_tree: Option<TaprootScriptTree>,
+    ) -> (Option<TaprootScriptTree>, TapretProof) {
+        let mut tap_tree = tap_tree;
+        let (_, proof) =
+            tapret_commit(internal_key(), &mut tap_tree, MSG).unwrap();
+        (tap_tree, proof)
+    }
+
+    fn leaf(script: &Script) -> LeafScript {
+        LeafScript::with(LeafVersion::TapScript, script.clone().into())
+    }
+
+    #[test]
+    fn control_block() {
+        let original = builder().finalize(SECP256K1, internal_key()).unwrap();
+        let tree =
+            TaprootScriptTree::from(TapTree::from_builder(builder()).unwrap());
+        let (tap_tree, proof) = commit(Some(tree));
+
+        // Control blocks must match the ones computed by rust-bitcoin for the
+        // modified tree
+        let committed = TapTree::from(tap_tree.unwrap())
+            .into_builder()
+            .finalize(SECP256K1, internal_key())
+            .unwrap();
+        let (output_key, _) = proof.output_key(MSG).unwrap();
+        assert_eq!(committed.output_key(), output_key);
+        for script in scripts() {
+            let script_ver = (script.clone(), LeafVersion::TapScript);
+            let original_cb = original.control_block(&script_ver).unwrap();
+            let control_block = proof
+                .control_block(MSG, &leaf(&script), &original_cb.merkle_branch)
+                .unwrap();
+            assert_eq!(
+                Some(control_block.clone()),
+                committed.control_block(&script_ver)
+            );
+            assert!(control_block.verify_taproot_commitment(
+                SECP256K1,
+                output_key.to_inner(),
+                &script
+            ));
+            assert_eq!(
+                control_block.merkle_branch.as_inner().len(),
+                original_cb.merkle_branch.as_inner().len() + 1
+            );
+        }
+    }
+
+    #[test]
+    fn control_block_unrelated() {
+        let original = builder().finalize(SECP256K1, internal_key()).unwrap();
+        let tree =
+            TaprootScriptTree::from(TapTree::from_builder(builder()).unwrap());
+        let (_, proof) = commit(Some(tree));
+        let merkle_branch = original
+            .control_block(&(scripts()[0].clone(), LeafVersion::TapScript))
+            .unwrap()
+            .merkle_branch;
+
+        // Leaf which is not a part of the original tree
+        let other = script::Builder::new().push_int(5).into_script();
+        assert_eq!(
+            proof.control_block(MSG, &leaf(&other), &merkle_branch),
+            Err(TapretSpendError::UnrelatedLeaf)
+        );
+        // Leaf with a wrong merkle branch
+        assert_eq!(
+            proof.control_block(
+                MSG,
+                &leaf(&scripts()[1]),
+                &TaprootMerkleBranch::from_inner(vec![]).unwrap()
+            ),
+            Err(TapretSpendError::UnrelatedLeaf)
+        );
+        // Control block for other message does not spend the output
+        let (output_key, _) = proof.output_key(MSG).unwrap();
+        let control_block = proof
+            .control_block([0x78; 32], &leaf(&scripts()[0]), &merkle_branch)
+            .unwrap();
+        assert!(!control_block.verify_taproot_commitment(
+            SECP256K1,
+            output_key.to_inner(),
+            &scripts()[0]
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "sign")]
+    fn key_path_signing() {
+        use bitcoin::blockdata::transaction::{OutPoint, TxIn, TxOut};
+        use bitcoin::hashes::Hash;
+        use bitcoin::schnorr::TapTweak;
+        use bitcoin::util::sighash::{Prevouts, SighashCache};
+        use bitcoin::{SchnorrSighashType, Transaction, Txid};
+        use secp256k1::Message;
+
+        let keypair =
+            KeyPair::from_seckey_slice(SECP256K1, &[0x11; 32]).unwrap();
+        let tree =
+            TaprootScriptTree::from(TapTree::from_builder(builder()).unwrap());
+        for tap_tree in [None, Some(tree)] {
+            let (tap_tree, proof) = commit(tap_tree);
+            let (output_key, _) = proof.output_key(MSG).unwrap();
+            let prevout = TxOut {
+                value: 10_000,
+                script_pubkey: Script::new_v1_p2tr_tweaked(output_key),
+            };
+            let tx = Transaction {
+                version: 2,
+                lock_time: 0,
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(
+                        Txid::hash(b"tapret output"),
+                        0,
+                    ),
+                    ..TxIn::default()
+                }],
+                output: vec![TxOut {
+                    value: 9_000,
+                    script_pubkey: Script::new_op_return(&[]),
+                }],
+            };
+            let sighash = SighashCache::new(&tx)
+                .taproot_key_spend_signature_hash(
+                    0,
+                    &Prevouts::All(&[prevout]),
+                    SchnorrSighashType::Default,
+                )
+                .unwrap();
+            let msg = Message::from_slice(&sighash[..]).unwrap();
+
+            let tweaked = proof.tweak_keypair(&keypair, MSG).unwrap();
+            assert_eq!(
+                XOnlyPublicKey::from_keypair(&tweaked),
+                output_key.to_inner()
+            );
+            let sig = SECP256K1.sign_schnorr_no_aux_rand(&msg, &tweaked);
+            assert!(SECP256K1
+                .verify_schnorr(&sig, &msg, &output_key.to_inner())
+                .is_ok());
+
+            // The tweaked key pair must match the one produced by rust-bitcoin
+            // from the merkle root of the modified tree
+            let merkle_root = tap_tree.map(|tap_tree| {
+                TapTree::from(tap_tree)
+                    .into_builder()
+                    .finalize(SECP256K1, internal_key())
+                    .unwrap()
+                    .merkle_root()
+                    .unwrap()
+            });
+            let expected = keypair.tap_tweak(SECP256K1, merkle_root);
+            assert_eq!(
+                tweaked.secret_bytes(),
+                expected.into_inner().secret_bytes()
+            );
+
+            // Untweaked key pair can't sign for the output
+            let sig = SECP256K1.sign_schnorr_no_aux_rand(&msg, &keypair);
+            assert!(SECP256K1
+                .verify_schnorr(&sig, &msg, &output_key.to_inner())
+                .is_err());
+        }
+
+        let other = KeyPair::from_seckey_slice(SECP256K1, &[0x22; 32]).unwrap();
+        let (_, proof) = commit(None);
+        assert_eq!(
+            proof.tweak_keypair(&other, MSG),
+            Err(TapretSpendError::KeyMismatch(internal_key()))
+        );
+    }
+}
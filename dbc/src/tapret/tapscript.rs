@@ -13,36 +13,97 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+use amplify::Wrapper;
 use bitcoin::blockdata::opcodes::all;
 use bitcoin::blockdata::script;
+use bitcoin::hashes::Hash;
 use bitcoin_scripts::TapScript;
-use commit_verify::{lnpbp4, CommitEncode, CommitVerify};
+use commit_verify::{lnpbp4, CommitVerify};
 
 use super::Lnpbp6;
+use crate::msg::{MsgSize, SupportedMsgSize};
 
 /// Hardcoded tapret script prefix consisting of 30 `OP_RESERVED` pushes,
-/// followed by `OP_RETURN` and `OP_PUSHBYTES_32`.
-pub const TAPRET_SCRIPT_COMMITMENT_PREFIX: [u8; 32] = [
+/// followed by `OP_RETURN`. In the commitment scripts the prefix is followed by
+/// one of [`TAPRET_SCRIPT_PUSH_OPCODES`] (or, in the legacy commitment
+/// scripts, [`TAPRET_SCRIPT_LEGACY_PUSH_OPCODES`]).
+pub const TAPRET_SCRIPT_COMMITMENT_PREFIX: [u8; 31] = [
     0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50,
     0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50,
-    0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x6a, 0x20,
+    0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x6a,
 ];
 
+/// Push opcodes following [`TAPRET_SCRIPT_COMMITMENT_PREFIX`] in the tapret
+/// commitment scripts: `OP_PUSHBYTES_34` for 32-byte messages and
+/// `OP_PUSHBYTES_66` for 64-byte messages (both followed by the 2-byte nonce).
+pub const TAPRET_SCRIPT_PUSH_OPCODES: [u8; 2] = [0x22, 0x42];
+
+/// Push opcodes following [`TAPRET_SCRIPT_COMMITMENT_PREFIX`] in the legacy
+/// tapret commitment scripts with a single-byte nonce (see
+/// [`tapret_script_legacy`]): `OP_PUSHBYTES_33` for 32-byte messages and
+/// `OP_PUSHBYTES_65` for 64-byte messages.
+pub const TAPRET_SCRIPT_LEGACY_PUSH_OPCODES: [u8; 2] = [0x21, 0x41];
+
+/// Detects whether the data start with the first 32 bytes of a tapret
+/// commitment script produced by [`tapret_script`] or
+/// [`tapret_script_legacy`].
+pub(super) fn is_tapret_script_prefix(data: &[u8]) -> bool {
+    data.len() >= 32
+        && data[..31] == TAPRET_SCRIPT_COMMITMENT_PREFIX
+        && (TAPRET_SCRIPT_PUSH_OPCODES.contains(&data[31])
+            || TAPRET_SCRIPT_LEGACY_PUSH_OPCODES.contains(&data[31]))
+}
+
+/// Constructs tapret commitment script for the message `msg` of `N` bytes (see
+/// [`crate::msg`]) and the `nonce`, which is `OP_RETURN` pushing the message
+/// followed by the nonce, prefixed with 30 `OP_RESERVED`.
+pub fn tapret_script<const N: usize>(msg: [u8; N], nonce: u16) -> TapScript
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    commitment_script(&msg, &nonce.to_le_bytes())
+}
+
+/// Constructs legacy tapret commitment script with a single-byte `nonce`, as
+/// produced before the nonce was extended to two bytes. Used only to verify
+/// the existing commitments; new commitments are created with
+/// [`tapret_script`].
+pub fn tapret_script_legacy<const N: usize>(
+    msg: [u8; N],
+    nonce: u8,
+) -> TapScript
+where
+    MsgSize<N>: SupportedMsgSize,
+{
+    commitment_script(&msg, &[nonce])
+}
+
+fn commitment_script(msg: &[u8], nonce: &[u8]) -> TapScript {
+    let mut builder = script::Builder::new();
+    for _ in 0..30 {
+        // Filling first 30 bytes with OP_RESERVED in order to avoid
+        // representation of sibling partner script as child hashes.
+        builder = builder.push_opcode(all::OP_RESERVED);
+    }
+    let mut data = msg.to_vec();
+    data.extend(nonce);
+    builder
+        .push_opcode(all::OP_RETURN)
+        .push_slice(&data)
+        .into_script()
+        .into()
+}
+
+impl CommitVerify<(lnpbp4::CommitmentHash, u16), Lnpbp6> for TapScript {
+    fn commit(msg: &(lnpbp4::CommitmentHash, u16)) -> Self {
+        let (msg, nonce) = msg;
+        tapret_script(msg.into_inner().into_inner(), *nonce)
+    }
+}
+
 impl CommitVerify<(lnpbp4::CommitmentHash, u8), Lnpbp6> for TapScript {
     fn commit(msg: &(lnpbp4::CommitmentHash, u8)) -> Self {
         let (msg, nonce) = msg;
-        let mut builder = script::Builder::new();
-        for _ in 0..30 {
-            // Filling first 30 bytes with OP_RESERVED in order to avoid
-            // representation of sibling partner script as child hashes.
-            builder = builder.push_opcode(all::OP_RESERVED);
-        }
-        let mut data = msg.commit_serialize();
-        data.push(*nonce);
-        builder
-            .push_opcode(all::OP_RETURN)
-            .push_slice(&data)
-            .into_script()
-            .into()
+        tapret_script_legacy(msg.into_inner().into_inner(), *nonce)
     }
 }
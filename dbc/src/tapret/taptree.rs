@@ -30,7 +30,7 @@ use commit_verify::{
     lnpbp4, CommitVerify, EmbedCommitProof, EmbedCommitVerify,
 };
 
-use super::{Lnpbp6, TapretNodePartner, TapretPathProof};
+use super::{Lnpbp6, TapretNodePartner, TapretPathProof, MAX_PARTNER_DEPTH};
 use crate::tapret::TapretPathError;
 
 // TODO: Re-check the use of all error variants
@@ -82,6 +82,14 @@ pub enum TapretProofError {
     #[from(UnsplittableTree)]
     UnsplittableTree,
 
+    /// the provided taproot script tree does not contain tapret node partner
+    /// {0} at the level 1.
+    PartnerAbsent(TapretNodePartner),
+
+    /// the provided tapret proof is for a key-only taproot output, while the
+    /// taproot script tree contains other nodes besides the commitment leaf.
+    UnexpectedPartner,
+
     /// Errors in the taproot script tree and tapret path proof
     /// correspondences. See [`TapretSourceError`] for details.
     #[from]
@@ -124,6 +132,15 @@ pub enum TapretSourceError {
     /// the provided tapret commitment path {1} points at the leaf node {0}
     /// and can't be used for tapret commit instillation.
     LeafNode(LeafScript, DfsPath),
+
+    /// the taproot script tree has depth {0}, while tapret commitment
+    /// requires it to not exceed 127.
+    TreeTooDeep(usize),
+
+    /// none of 65536 possible nonce values puts the tapret commitment to the
+    /// right side of the taproot script tree with the level 1 partner node
+    /// {0}.
+    NonceExhausted(TapNodeHash),
 }
 
 impl From<InstillError> for TapretSourceError {
@@ -203,6 +220,10 @@ impl TapretSourceInfo<TaprootScriptTree> {
     /// Releases internal [`TapTree`] data, if present.
     #[inline]
     pub fn into_tap_tree(self) -> Option<TapTree> { self.0.map(TapTree::from) }
+
+    /// Releases internal [`TaprootScriptTree`] data, if present.
+    #[inline]
+    pub fn into_script_tree(self) -> Option<TaprootScriptTree> { self.0 }
 }
 
 impl TapretSourceInfo<TapTree> {
@@ -263,12 +284,26 @@ impl
             .ok_or(TapretProofError::EmptyTree)?;
 
         match self.partner_node {
-            // Taproot has key-only spending
-            None => Ok(TapretSourceInfo(None)),
+            // Taproot has key-only spending, so the commitment must be the
+            // sole leaf of the tree
+            None if matches!(tap_tree.as_root_node(), TreeNode::Leaf(..)) => {
+                Ok(TapretSourceInfo(None))
+            }
+            None => Err(TapretProofError::UnexpectedPartner),
             // Taproot has script spendings
-            Some(_) => {
-                let (original_tree, _) = tap_tree.split()?;
-                Ok(TapretSourceInfo(Some(original_tree)))
+            Some(ref partner) => {
+                // DFS ordering of the tree is not preserved by conversions
+                // from `TapTree`, so the original tree is detected by its
+                // root hash.
+                let partner_hash = partner.node_hash();
+                let (first, last) = tap_tree.split()?;
+                if first.as_root_node().node_hash() == partner_hash {
+                    Ok(TapretSourceInfo(Some(first)))
+                } else if last.as_root_node().node_hash() == partner_hash {
+                    Ok(TapretSourceInfo(Some(last)))
+                } else {
+                    Err(TapretProofError::PartnerAbsent(partner.clone()))
+                }
             }
         }
     }
@@ -285,59 +320,73 @@ impl EmbedCommitVerify<lnpbp4::CommitmentHash, Lnpbp6>
         &mut self,
         msg: &lnpbp4::CommitmentHash,
     ) -> Result<Self::Proof, Self::CommitError> {
-        for nonce in 0..=u8::MAX {
-            let commitment_script = TapScript::commit(&(*msg, nonce));
+        self.embed_script(|nonce| TapScript::commit(&(*msg, nonce)))
+    }
+}
 
+impl TapretSourceInfo<TaprootScriptTree> {
+    /// Embeds tapret commitment script produced by `commitment_script` for a
+    /// nonce into the tree, see [`EmbedCommitVerify::embed_commit`].
+    pub(super) fn embed_script(
+        &mut self,
+        commitment_script: impl Fn(u16) -> TapScript,
+    ) -> Result<TapretPathProof, TapretSourceError> {
+        let original_tree = if let Some(ref original_tree) = self.0 {
+            original_tree.clone()
+        } else {
             let commitment_node =
-                TreeNode::with_tap_script(commitment_script, 0);
-            let commitment_subtree = TaprootScriptTree::with(commitment_node)
+                TreeNode::with_tap_script(commitment_script(0), 0);
+            let commitment_tree = TaprootScriptTree::with(commitment_node)
                 .expect("invalid commitment node construction");
+            self.0 = Some(commitment_tree);
+            return Ok(TapretPathProof::new());
+        };
+        let original_hash = original_tree.as_root_node().node_hash();
+
+        let depth = original_tree
+            .nodes()
+            .map(|(node, _)| node.node_depth() as usize)
+            .max()
+            .unwrap_or_default();
+        if depth > MAX_PARTNER_DEPTH {
+            return Err(TapretSourceError::TreeTooDeep(depth));
+        }
 
-            let tap_tree = if let Some(ref mut tap_tree) = self.0 {
-                tap_tree
-            } else {
-                self.0 = Some(commitment_subtree);
-                return Ok(TapretPathProof::new());
-            };
+        for nonce in 0..=u16::MAX {
+            let commitment_node =
+                TreeNode::with_tap_script(commitment_script(nonce), 0);
+            let commitment_subtree = TaprootScriptTree::with(commitment_node)
+                .expect("invalid commitment node construction");
 
-            let original_tree = tap_tree.clone();
-            *tap_tree =
-                original_tree.join(commitment_subtree, DfsOrder::Last)?;
+            // Each attempt must start from the original tree, so the
+            // commitments with rejected nonces do not end up in the tree.
+            let tap_tree = original_tree
+                .clone()
+                .join(commitment_subtree, DfsOrder::Last)?;
 
             let branch = tap_tree
                 .as_root_node()
                 .as_branch()
                 .expect("instill algorithm is broken");
-            let partner = branch.as_dfs_child_node(DfsOrder::First);
-
-            let partner_is_left_node =
-                branch.dfs_ordering() == DfsOrdering::LeftRight;
 
-            let partner_proof = match (partner_is_left_node, partner) {
-                (true, node) => TapretNodePartner::LeftNode(node.node_hash()),
-                (false, TreeNode::Leaf(script, _)) => {
-                    TapretNodePartner::RightLeaf(script.clone())
-                }
-                (false, TreeNode::Hidden(partner_hash, _)) => {
-                    return Err(TapretSourceError::HiddenNode(
-                        *partner_hash,
-                        vec![DfsOrder::First].into(),
-                    ))
-                }
-                (false, TreeNode::Branch(partner_branch, _)) => {
-                    TapretNodePartner::right_branch(
-                        partner_branch.as_left_node().node_hash(),
-                        partner_branch.as_right_node().node_hash(),
-                    )
-                }
-            };
-
-            if partner_is_left_node || nonce == u8::MAX {
-                return TapretPathProof::with(partner_proof, nonce)
-                    .map_err(TapretSourceError::from);
+            // The commitment must be the right node at level 1, such that its
+            // partner is committed with just a hash and does not have to be
+            // proven not to contain an alternative commitment.
+            if branch.dfs_ordering() != DfsOrdering::LeftRight {
+                continue;
             }
+
+            let partner = branch.as_dfs_child_node(DfsOrder::First);
+            let proof = TapretPathProof::with(
+                TapretNodePartner::LeftNode(partner.node_hash()),
+                nonce,
+            )
+            .map_err(TapretSourceError::from)?;
+            self.0 = Some(tap_tree);
+            return Ok(proof);
         }
-        unreachable!("for cycle always returns before exiting")
+
+        Err(TapretSourceError::NonceExhausted(original_hash))
     }
 }
 
@@ -21,7 +21,7 @@ use bitcoin_scripts::TapScript;
 use commit_verify::convolve_commit::{
     ConvolveCommitProof, ConvolveCommitVerify,
 };
-use commit_verify::{lnpbp4, CommitVerify};
+use commit_verify::{lnpbp4, TaggedHash};
 use secp256k1::SECP256K1;
 
 use super::{Lnpbp6, TapretPathProof, TapretProof, TapretTreeError};
@@ -38,20 +38,24 @@ impl ConvolveCommitProof<lnpbp4::CommitmentHash, UntweakedPublicKey, Lnpbp6>
     fn extract_supplement(&self) -> &Self::Suppl { &self.path_proof }
 }
 
-impl ConvolveCommitVerify<lnpbp4::CommitmentHash, TapretProof, Lnpbp6>
-    for UntweakedPublicKey
-{
-    type Commitment = TweakedPublicKey;
-    type CommitError = TapretTreeError;
-
-    fn convolve_commit(
+impl TapretPathProof {
+    /// Computes merkle root of the taproot script tree containing the tapret
+    /// commitment to `msg`, checking that the partner node can't contain an
+    /// alternative commitment and has a correct ordering.
+    pub fn commitment_merkle_root(
         &self,
-        supplement: &TapretPathProof,
         msg: &lnpbp4::CommitmentHash,
-    ) -> Result<(TweakedPublicKey, TapretProof), Self::CommitError> {
-        let script_commitment = TapScript::commit(&(*msg, supplement.nonce));
+    ) -> Result<TapBranchHash, TapretTreeError> {
+        self.script_merkle_root(self.commitment_script(msg.into_array()))
+    }
 
-        let root = if let Some(ref partner) = supplement.partner_node {
+    /// Computes merkle root of the taproot script tree containing the tapret
+    /// commitment script like [`TapretPathProof::commitment_merkle_root`].
+    pub(super) fn script_merkle_root(
+        &self,
+        script_commitment: TapScript,
+    ) -> Result<TapBranchHash, TapretTreeError> {
+        let root = if let Some(ref partner) = self.partner_node {
             if !partner.check_no_commitment() {
                 return Err(TapretTreeError::AlternativeCommitment(
                     partner.clone(),
@@ -78,8 +82,22 @@ impl ConvolveCommitVerify<lnpbp4::CommitmentHash, TapretProof, Lnpbp6>
         // accepted by the tap_tweak API.
         //
         // Details: <https://github.com/rust-bitcoin/rust-bitcoin/issues/1393>
-        let merkle_root =
-            TapBranchHash::from_inner(root.node_hash().into_inner());
+        Ok(TapBranchHash::from_inner(root.node_hash().into_inner()))
+    }
+}
+
+impl ConvolveCommitVerify<lnpbp4::CommitmentHash, TapretProof, Lnpbp6>
+    for UntweakedPublicKey
+{
+    type Commitment = TweakedPublicKey;
+    type CommitError = TapretTreeError;
+
+    fn convolve_commit(
+        &self,
+        supplement: &TapretPathProof,
+        msg: &lnpbp4::CommitmentHash,
+    ) -> Result<(TweakedPublicKey, TapretProof), Self::CommitError> {
+        let merkle_root = supplement.commitment_merkle_root(msg)?;
         // TODO: Use secp instance from Lnpbp6
         let (output_key, _parity_not_used) =
             self.tap_tweak(SECP256K1, Some(merkle_root));
@@ -101,6 +119,7 @@ mod test {
     use bitcoin::hashes::Hash;
     use bitcoin_scripts::LeafScript;
     use commit_verify::lnpbp4::CommitmentHash;
+    use commit_verify::CommitVerify;
     use secp256k1::XOnlyPublicKey;
 
     use super::*;
@@ -118,7 +137,7 @@ mod test {
         let (outer_key, proof) =
             internal_key.convolve_commit(&path_proof, &msg).unwrap();
 
-        let script_commitment = TapScript::commit(&(msg, 0));
+        let script_commitment = TapScript::commit(&(msg, 0u16));
         let root = TreeNode::with_tap_script(script_commitment, 0);
         let merkle_root =
             TapBranchHash::from_inner(root.node_hash().into_inner());
@@ -149,7 +168,7 @@ mod test {
         let msg = CommitmentHash::from_inner(Hash::hash(b""));
         let path_proof = TapretPathProof::with(
             TapretNodePartner::RightLeaf(LeafScript::tapscript(default!())),
-            88,
+            87,
         )
         .unwrap();
 
@@ -169,6 +188,83 @@ mod test {
         .unwrap());
     }
 
+    #[test]
+    fn legacy_proofs() {
+        use bitcoin::hashes::hex::FromHex;
+        use bitcoin::hashes::sha256t;
+        use strict_encoding::{strict_deserialize, strict_serialize};
+
+        use crate::tapret::TapretNonce;
+
+        let internal_key = XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        // Proofs and output keys produced before the nonce was extended to
+        // two bytes
+        for (path_proof, msg, data, output_key) in [
+            (
+                TapretPathProof::new_legacy(),
+                CommitmentHash::from_inner(sha256t::Hash::from_inner(
+                    [0x5A; 32],
+                )),
+                "0000c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+                "98b248d469b9377e18cc5df5a30120d97fd0f4174b0761832434aea338b06786",
+            ),
+            (
+                TapretPathProof::with_legacy(
+                    TapretNodePartner::RightLeaf(LeafScript::tapscript(
+                        default!(),
+                    )),
+                    88,
+                )
+                .unwrap(),
+                CommitmentHash::from_inner(Hash::hash(b"")),
+                "0101c0000058c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+                "e0eeb551b2ef736fe64ce5469ef5dc166f0f38abb6b0741b7d58e381fcf6d3e4",
+            ),
+        ] {
+            let data = Vec::<u8>::from_hex(data).unwrap();
+            let output_key = TweakedPublicKey::dangerous_assume_tweaked(
+                XOnlyPublicKey::from_str(output_key).unwrap(),
+            );
+
+            let proof = TapretProof::strict_decode_legacy(&data[..]).unwrap();
+            assert!(proof.path_proof.tapret_nonce().is_legacy());
+            assert_eq!(proof, TapretProof {
+                path_proof: path_proof.clone(),
+                internal_key
+            });
+            assert_eq!(strict_serialize(&proof).unwrap(), data);
+            assert!(strict_deserialize::<TapretProof>(&data).is_err());
+
+            let (outer_key, _) =
+                internal_key.convolve_commit(&path_proof, &msg).unwrap();
+            assert_eq!(outer_key, output_key);
+            assert!(ConvolveCommitProof::<
+                CommitmentHash,
+                UntweakedPublicKey,
+                Lnpbp6,
+            >::verify(&proof, &msg, output_key)
+            .unwrap());
+
+            // The same nonce in the extended layout commits to another script
+            let nonce = path_proof.nonce();
+            let extended = match path_proof.partner_node {
+                None => TapretPathProof::new(),
+                Some(partner) => TapretPathProof::with(partner, nonce).unwrap(),
+            };
+            assert_eq!(extended.tapret_nonce(), TapretNonce::Extended(nonce));
+            assert_ne!(
+                internal_key
+                    .convolve_commit(&extended, &msg)
+                    .map(|(outer_key, _)| outer_key)
+                    .ok(),
+                Some(output_key)
+            );
+        }
+    }
+
     #[test]
     #[should_panic(expected = "IncorrectOrdering")]
     fn invalid_partner_ordering() {
@@ -179,7 +275,7 @@ mod test {
         let msg = CommitmentHash::from_inner(Hash::hash(b""));
         let path_proof = TapretPathProof::with(
             TapretNodePartner::RightLeaf(LeafScript::tapscript(default!())),
-            1,
+            3,
         )
         .unwrap();
 
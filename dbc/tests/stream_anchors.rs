@@ -0,0 +1,126 @@
+// Deterministic bitcoin commitments library, implementing LNPBP standards
+// Part of bitcoin protocol core library (BP Core Lib)
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Streaming encoding of large anchor sets. The test binary uses counting
+//! global allocator to check that the streaming does not keep the anchors in
+//! memory, so it must contain a single test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bitcoin::hashes::Hash;
+use bitcoin::Txid;
+use commit_verify::lnpbp4::{self, Message, ProtocolId};
+use commit_verify::TryCommitVerify;
+use dbc::opret::OpretProof;
+use dbc::{read_anchors, write_anchors, Anchor, Proof};
+use strict_encoding::strict_serialize;
+
+const ANCHOR_COUNT: u32 = 50_000;
+
+/// Maximal memory allowed to be allocated by streaming over the allocations
+/// existing before the streaming started.
+const PEAK_MEMORY_LIMIT: usize = 64 * 1024;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED
+                .fetch_add(layout.size(), Ordering::SeqCst)
+                + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Runs `f` returning its result together with the peak memory allocated
+/// during its execution over the memory allocated before.
+fn peak_memory<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let base = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+    let res = f();
+    (res, PEAK.load(Ordering::SeqCst) - base)
+}
+
+fn base_anchor() -> Anchor<lnpbp4::MerkleBlock> {
+    let source = lnpbp4::MultiSource {
+        min_depth: 3,
+        messages: (0u8..3)
+            .map(|no| (ProtocolId::from([no; 32]), Message::hash(&[no])))
+            .collect(),
+    };
+    let tree = lnpbp4::MerkleTree::try_commit(&source).unwrap();
+    Anchor {
+        txid: Txid::hash(b"witness"),
+        lnpbp4_proof: lnpbp4::MerkleBlock::from(&tree),
+        dbc_proof: Proof::OpretFirst(OpretProof),
+    }
+}
+
+fn synthetic_anchor(
+    base: &Anchor<lnpbp4::MerkleBlock>,
+    no: u32,
+) -> Anchor<lnpbp4::MerkleBlock> {
+    Anchor {
+        txid: Txid::hash(&no.to_le_bytes()),
+        ..base.clone()
+    }
+}
+
+#[test]
+fn stream_large_anchor_set() {
+    let base = base_anchor();
+    let anchors = || (0..ANCHOR_COUNT).map(|no| synthetic_anchor(&base, no));
+
+    let (len, peak) =
+        peak_memory(|| write_anchors(io::sink(), anchors()).unwrap());
+    assert!(peak < PEAK_MEMORY_LIMIT, "writing used {} bytes", peak);
+
+    // Byte compatibility with the non-streaming encoding
+    let collection = anchors().collect::<Vec<_>>();
+    let data = strict_serialize(&collection).unwrap();
+    drop(collection);
+    assert_eq!(len, data.len());
+    let mut stream = Vec::with_capacity(data.len());
+    write_anchors(&mut stream, anchors()).unwrap();
+    assert_eq!(stream, data);
+    drop(stream);
+
+    let (count, peak) = peak_memory(|| {
+        read_anchors::<lnpbp4::MerkleBlock, _>(&data[..])
+            .zip(anchors())
+            .map(|(decoded, anchor)| assert_eq!(decoded.unwrap(), anchor))
+            .count()
+    });
+    assert!(peak < PEAK_MEMORY_LIMIT, "reading used {} bytes", peak);
+    assert_eq!(count, ANCHOR_COUNT as usize);
+    assert!(data.len() > PEAK_MEMORY_LIMIT * 10);
+}
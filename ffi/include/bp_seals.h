@@ -0,0 +1,181 @@
+/*
+ * C bindings for BP Core single-use-seals library.
+ *
+ * Memory ownership:
+ * - all pointers passed to the library are borrowed for the duration of the
+ *   call only and are never freed by the library;
+ * - strings returned by the library via `out_err` arguments are owned by the
+ *   caller, who must release them with `bp_string_free`;
+ * - structures and buffers for the output values are allocated by the caller.
+ */
+
+#ifndef BP_SEALS_H
+#define BP_SEALS_H
+
+/* Generated with cbindgen from ffi/src/lib.rs; do not edit manually. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Result codes returned by the library functions. The values are stable and
+// will not change in the future versions.
+typedef enum BpSealError {
+  // Operation completed successfully.
+  BP_SEAL_ERROR_OK = 0,
+  // Required pointer argument is `NULL`.
+  BP_SEAL_ERROR_NULL_POINTER = 1,
+  // Provided string is not a valid UTF-8 string.
+  BP_SEAL_ERROR_INVALID_UTF8 = 2,
+  // Seal string must start with method name.
+  BP_SEAL_ERROR_METHOD_REQUIRED = 100,
+  // Seal string does not contain transaction id.
+  BP_SEAL_ERROR_TXID_REQUIRED = 101,
+  // Seal string does not contain blinding factor.
+  BP_SEAL_ERROR_BLINDING_REQUIRED = 102,
+  // Unknown seal close method name.
+  BP_SEAL_ERROR_WRONG_METHOD = 103,
+  // Invalid blinding factor value.
+  BP_SEAL_ERROR_WRONG_BLINDING = 104,
+  // Invalid transaction id.
+  BP_SEAL_ERROR_WRONG_TXID = 105,
+  // Invalid transaction output number.
+  BP_SEAL_ERROR_WRONG_VOUT = 106,
+  // Wrong structure of the seal string.
+  BP_SEAL_ERROR_WRONG_STRUCTURE = 107,
+  // Not returned since decimal blinding factors are accepted; the code is
+  // kept reserved.
+  BP_SEAL_ERROR_NON_HEX_BLINDING = 108,
+  // Invalid encoding of the seal data.
+  BP_SEAL_ERROR_WRONG_ENCODING = 109,
+  // Unknown seal close method id.
+  BP_SEAL_ERROR_UNKNOWN_METHOD = 200,
+  // Witness transaction data can't be consensus-decoded.
+  BP_SEAL_ERROR_INVALID_TX = 201,
+  // Commitment proof data can't be strict-decoded.
+  BP_SEAL_ERROR_INVALID_PROOF = 202,
+  // Seals have inconsistent close methods.
+  BP_SEAL_ERROR_INCONSISTENT_CLOSE_METHOD = 300,
+  // Witness transaction is not known.
+  BP_SEAL_ERROR_WITNESS_TX_UNKNOWN = 301,
+  // Witness transaction does not close the seal.
+  BP_SEAL_ERROR_WITNESS_NOT_CLOSING_SEAL = 302,
+  // Seal close method does not match the commitment proof.
+  BP_SEAL_ERROR_METHOD_MISMATCH = 303,
+  // Commitment proof type is not supported.
+  BP_SEAL_ERROR_UNSUPPORTED_PROOF = 304,
+  // Witness transaction does not contain commitment to the message.
+  BP_SEAL_ERROR_NO_COMMITMENT = 305,
+  // Seal is defined over non-existing transaction output.
+  BP_SEAL_ERROR_SEAL_OUTPUT_UNKNOWN = 306,
+  // Tapret commitment is invalid.
+  BP_SEAL_ERROR_INVALID_TAPRET_COMMITMENT = 307,
+  // Failure accessing the commitment publication medium.
+  BP_SEAL_ERROR_RESOLVER_FAILURE = 308,
+  // Seal output is not spent by any transaction.
+  BP_SEAL_ERROR_SEAL_NOT_CLOSED = 309,
+  // Witness transaction is malformed.
+  BP_SEAL_ERROR_MALFORMED_WITNESS_TX = 310,
+  // Witness transaction does not have enough confirmations.
+  BP_SEAL_ERROR_INSUFFICIENT_CONFIRMATIONS = 311,
+  // No seals are provided for the verification.
+  BP_SEAL_ERROR_NO_SEALS = 312,
+  // Confirmation policy can't be checked without a transaction resolver.
+  BP_SEAL_ERROR_CONFIRMATIONS_UNCHECKED = 313,
+  // Transaction resolver reports inconsistent block heights.
+  BP_SEAL_ERROR_INCONSISTENT_HEIGHT = 314,
+} BpSealError;
+
+// Seal data in C representation.
+typedef struct BpSeal {
+  // Close method id (`0` for `opret1st`, `1` for `tapret1st`).
+  uint8_t method;
+  // Whether the seal has a transaction id; seals without it are defined
+  // over the witness transaction outputs.
+  bool has_txid;
+  // Transaction id in the internal byte order (reverse to the order used
+  // by the hexadecimal representation).
+  uint8_t txid[32];
+  // Transaction output number.
+  uint32_t vout;
+  // Whether the seal has a blinding factor.
+  bool has_blinding;
+  // Blinding factor; zero if `has_blinding` is not set.
+  uint64_t blinding;
+} BpSeal;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parses seal string in `method:txid:vout` form or, if it contains a
+// blinding factor, in `method:txid:vout#blinding` form, writing the result
+// into `out_seal`.
+//
+// If `out_err` is not `NULL`, it receives `NULL` on success or a newly
+// allocated error description, which must be freed with [`bp_string_free`].
+//
+// # Safety
+//
+// `seal_str` must be a valid NUL-terminated string; `out_seal` must point to
+// a writable [`BpSeal`]; `out_err` must be either `NULL` or a writable
+// pointer.
+enum BpSealError bp_seal_parse(const char *seal_str, struct BpSeal *out_seal, char **out_err);
+
+// Conceals seal, which must have a blinding factor, writing 32 bytes of the
+// concealed seal into `out_concealed`.
+//
+// # Safety
+//
+// `seal` must point to a valid [`BpSeal`]; `out_concealed` must point to a
+// writable buffer of at least 32 bytes.
+enum BpSealError bp_seal_conceal(const struct BpSeal *seal, uint8_t *out_concealed);
+
+// Verifies that the witness transaction closes the seal over the 32-byte
+// message `msg32`, using the strict-encoded commitment proof.
+//
+// `seal_str` has the same format as for [`bp_seal_parse`]; `tx` contains
+// consensus-encoded witness transaction. If `out_err` is not `NULL`, it
+// receives `NULL` on success or a newly allocated error description, which
+// must be freed with [`bp_string_free`].
+//
+// # Safety
+//
+// `seal_str` must be a valid NUL-terminated string; `msg32` must point to
+// 32 readable bytes; `proof` and `tx` must point to `proof_len` and `tx_len`
+// readable bytes; `out_err` must be either `NULL` or a writable pointer.
+enum BpSealError bp_seal_verify_closure(const char *seal_str,
+                                        const uint8_t *msg32,
+                                        const uint8_t *proof,
+                                        size_t proof_len,
+                                        const uint8_t *tx,
+                                        size_t tx_len,
+                                        char **out_err);
+
+// Verifies seal closing like [`bp_seal_verify_closure`], using the
+// commitment proof strict-encoded with the legacy single-byte tapret nonce.
+//
+// # Safety
+//
+// The same requirements as for [`bp_seal_verify_closure`] apply.
+enum BpSealError bp_seal_verify_closure_legacy(const char *seal_str,
+                                               const uint8_t *msg32,
+                                               const uint8_t *proof,
+                                               size_t proof_len,
+                                               const uint8_t *tx,
+                                               size_t tx_len,
+                                               char **out_err);
+
+// Releases string allocated by the library. Does nothing if `s` is `NULL`.
+//
+// # Safety
+//
+// `s` must be either `NULL` or a string returned by the library, which was
+// not freed before.
+void bp_string_free(char *s);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* BP_SEALS_H */
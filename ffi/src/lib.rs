@@ -0,0 +1,772 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! C bindings for parsing, concealing and verifying closing of the TxOut
+//! single-use-seals. The C header is generated with `cbindgen` into
+//! `include/bp_seals.h`.
+//!
+//! # Memory ownership
+//!
+//! - All pointers passed to the library are borrowed for the duration of the
+//!   call only and are never freed by the library.
+//! - Strings returned by the library via `out_err` arguments are owned by the
+//!   caller, who must release them with [`bp_string_free`].
+//! - Structures and buffers for the output values are allocated by the caller.
+
+// Coding conventions
+#![recursion_limit = "256"]
+#![deny(dead_code, missing_docs, warnings)]
+
+#[cfg(test)]
+#[macro_use]
+extern crate amplify;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::str::FromStr;
+
+use amplify::Wrapper;
+use bitcoin::blockdata::transaction::ParseOutPointError;
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::{sha256t, Hash};
+use bitcoin::{Transaction, Txid};
+use commit_verify::lnpbp4;
+use dbc::Proof;
+use seals::txout::blind::{self, RevealedSeal};
+use seals::txout::explicit::{self, ExplicitSeal};
+use seals::txout::{verify_seal_closure, CloseMethod, VerifyError};
+
+/// Result codes returned by the library functions. The values are stable and
+/// will not change in the future versions.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BpSealError {
+    /// Operation completed successfully.
+    Ok = 0,
+    /// Required pointer argument is `NULL`.
+    NullPointer = 1,
+    /// Provided string is not a valid UTF-8 string.
+    InvalidUtf8 = 2,
+
+    /// Seal string must start with method name.
+    MethodRequired = 100,
+    /// Seal string does not contain transaction id.
+    TxidRequired = 101,
+    /// Seal string does not contain blinding factor.
+    BlindingRequired = 102,
+    /// Unknown seal close method name.
+    WrongMethod = 103,
+    /// Invalid blinding factor value.
+    WrongBlinding = 104,
+    /// Invalid transaction id.
+    WrongTxid = 105,
+    /// Invalid transaction output number.
+    WrongVout = 106,
+    /// Wrong structure of the seal string.
+    WrongStructure = 107,
+    /// Not returned since decimal blinding factors are accepted; the code is
+    /// kept reserved.
+    NonHexBlinding = 108,
+    /// Invalid encoding of the seal data.
+    WrongEncoding = 109,
+
+    /// Unknown seal close method id.
+    UnknownMethod = 200,
+    /// Witness transaction data can't be consensus-decoded.
+    InvalidTx = 201,
+    /// Commitment proof data can't be strict-decoded.
+    InvalidProof = 202,
+
+    /// Seals have inconsistent close methods.
+    InconsistentCloseMethod = 300,
+    /// Witness transaction is not known.
+    WitnessTxUnknown = 301,
+    /// Witness transaction does not close the seal.
+    WitnessNotClosingSeal = 302,
+    /// Seal close method does not match the commitment proof.
+    MethodMismatch = 303,
+    /// Commitment proof type is not supported.
+    UnsupportedProof = 304,
+    /// Witness transaction does not contain commitment to the message.
+    NoCommitment = 305,
+    /// Seal is defined over non-existing transaction output.
+    SealOutputUnknown = 306,
+    /// Tapret commitment is invalid.
+    InvalidTapretCommitment = 307,
+    /// Failure accessing the commitment publication medium.
+    ResolverFailure = 308,
+    /// Seal output is not spent by any transaction.
+    SealNotClosed = 309,
+    /// Witness transaction is malformed.
+    MalformedWitnessTx = 310,
+    /// Witness transaction does not have enough confirmations.
+    InsufficientConfirmations = 311,
+    /// No seals are provided for the verification.
+    NoSeals = 312,
+    /// Confirmation policy can't be checked without a transaction resolver.
+    ConfirmationsUnchecked = 313,
+    /// Transaction resolver reports inconsistent block heights.
+    InconsistentHeight = 314,
+}
+
+impl From<&explicit::ParseError> for BpSealError {
+    fn from(err: &explicit::ParseError) -> Self {
+        match err {
+            explicit::ParseError::MethodRequired => BpSealError::MethodRequired,
+            explicit::ParseError::TxidRequired => BpSealError::TxidRequired,
+            explicit::ParseError::WrongMethod(_)
+            | explicit::ParseError::MethodMismatch { .. } => {
+                BpSealError::WrongMethod
+            }
+            explicit::ParseError::WrongTxid(_) => BpSealError::WrongTxid,
+            explicit::ParseError::WrongVout(_)
+            | explicit::ParseError::VoutOutOfRange(_)
+            | explicit::ParseError::WrongVoutRange(_) => BpSealError::WrongVout,
+            explicit::ParseError::WrongOutPoint(ParseOutPointError::Txid(
+                _,
+            )) => BpSealError::WrongTxid,
+            explicit::ParseError::WrongOutPoint(
+                ParseOutPointError::Vout(_)
+                | ParseOutPointError::VoutNotCanonical,
+            ) => BpSealError::WrongVout,
+            explicit::ParseError::WrongOutPoint(_)
+            | explicit::ParseError::TooManySeals(_)
+            | explicit::ParseError::WrongStructure(_)
+            | explicit::ParseError::UnexpectedTxid
+            | explicit::ParseError::WrongChain(_)
+            | explicit::ParseError::ChainRequired
+            | explicit::ParseError::ChainMismatch { .. } => {
+                BpSealError::WrongStructure
+            }
+            explicit::ParseError::Bech32(_)
+            | explicit::ParseError::UriSchemeRequired
+            | explicit::ParseError::WrongPercentEncoding => {
+                BpSealError::WrongEncoding
+            }
+        }
+    }
+}
+
+impl From<&blind::ParseError> for BpSealError {
+    fn from(err: &blind::ParseError) -> Self {
+        match err {
+            blind::ParseError::MethodRequired => BpSealError::MethodRequired,
+            blind::ParseError::TxidRequired => BpSealError::TxidRequired,
+            blind::ParseError::BlindingRequired => {
+                BpSealError::BlindingRequired
+            }
+            blind::ParseError::WrongMethod(_) => BpSealError::WrongMethod,
+            blind::ParseError::WrongBlinding => BpSealError::WrongBlinding,
+            blind::ParseError::WrongTxid(_) => BpSealError::WrongTxid,
+            blind::ParseError::WrongVout(_)
+            | blind::ParseError::VoutOutOfRange(_) => BpSealError::WrongVout,
+            blind::ParseError::WrongStructure(_) => BpSealError::WrongStructure,
+            blind::ParseError::Bech32(_)
+            | blind::ParseError::WrongHrp(_)
+            | blind::ParseError::NetworkMismatch { .. }
+            | blind::ParseError::UriSchemeRequired
+            | blind::ParseError::WrongPercentEncoding => {
+                BpSealError::WrongEncoding
+            }
+        }
+    }
+}
+
+impl From<&VerifyError> for BpSealError {
+    fn from(err: &VerifyError) -> Self {
+        match err {
+            VerifyError::InconsistentCloseMethod => {
+                BpSealError::InconsistentCloseMethod
+            }
+            VerifyError::NoSeals => BpSealError::NoSeals,
+            VerifyError::WitnessTxUnknown(_) => BpSealError::WitnessTxUnknown,
+            VerifyError::SealNotClosed(_) => BpSealError::SealNotClosed,
+            VerifyError::WitnessNotSpendingSeal { .. } => {
+                BpSealError::WitnessNotClosingSeal
+            }
+            VerifyError::MethodMismatch { .. } => BpSealError::MethodMismatch,
+            VerifyError::UnsupportedProof => BpSealError::UnsupportedProof,
+            VerifyError::InvalidCommitment(_) => BpSealError::NoCommitment,
+            VerifyError::SealOutputUnknown(_) => BpSealError::SealOutputUnknown,
+            VerifyError::InvalidTapretCommitment(_) => {
+                BpSealError::InvalidTapretCommitment
+            }
+            VerifyError::WitnessCheck(_) => BpSealError::MalformedWitnessTx,
+            VerifyError::InsufficientConfirmations { .. } => {
+                BpSealError::InsufficientConfirmations
+            }
+            VerifyError::ConfirmationsUnchecked(_) => {
+                BpSealError::ConfirmationsUnchecked
+            }
+            VerifyError::InconsistentHeight { .. } => {
+                BpSealError::InconsistentHeight
+            }
+            VerifyError::ResolverError(_) => BpSealError::ResolverFailure,
+        }
+    }
+}
+
+/// Seal data in C representation.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BpSeal {
+    /// Close method id (`0` for `opret1st`, `1` for `tapret1st`).
+    pub method: u8,
+    /// Whether the seal has a transaction id; seals without it are defined
+    /// over the witness transaction outputs.
+    pub has_txid: bool,
+    /// Transaction id in the internal byte order (reverse to the order used
+    /// by the hexadecimal representation).
+    pub txid: [u8; 32],
+    /// Transaction output number.
+    pub vout: u32,
+    /// Whether the seal has a blinding factor.
+    pub has_blinding: bool,
+    /// Blinding factor; zero if `has_blinding` is not set.
+    pub blinding: u64,
+}
+
+impl From<ExplicitSeal> for BpSeal {
+    fn from(seal: ExplicitSeal) -> Self {
+        BpSeal {
+            method: seal.method as u8,
+            has_txid: seal.txid.is_some(),
+            txid: seal.txid.map(Txid::into_inner).unwrap_or_default(),
+            vout: seal.vout.into_u32(),
+            has_blinding: false,
+            blinding: 0,
+        }
+    }
+}
+
+impl From<RevealedSeal> for BpSeal {
+    fn from(seal: RevealedSeal) -> Self {
+        BpSeal {
+            has_blinding: true,
+            blinding: seal.blinding,
+            ..BpSeal::from(seal.to_explicit())
+        }
+    }
+}
+
+impl TryFrom<&BpSeal> for RevealedSeal {
+    type Error = BpSealError;
+
+    fn try_from(seal: &BpSeal) -> Result<Self, Self::Error> {
+        if !seal.has_blinding {
+            return Err(BpSealError::BlindingRequired);
+        }
+        Ok(RevealedSeal {
+            method: CloseMethod::try_from(seal.method)
+                .map_err(|_| BpSealError::UnknownMethod)?,
+            txid: seal.has_txid.then(|| Txid::from_inner(seal.txid)),
+            vout: seal.vout.into(),
+            blinding: seal.blinding,
+        })
+    }
+}
+
+/// Error with its human-readable description.
+struct Failure(BpSealError, String);
+
+impl<E> From<&E> for Failure
+where
+    for<'a> BpSealError: From<&'a E>,
+    E: ToString,
+{
+    fn from(err: &E) -> Self {
+        Failure(BpSealError::from(err), err.to_string())
+    }
+}
+
+impl From<BpSealError> for Failure {
+    fn from(code: BpSealError) -> Self {
+        let msg = match code {
+            BpSealError::NullPointer => "required argument is NULL",
+            BpSealError::InvalidUtf8 => "seal string is not valid UTF-8",
+            BpSealError::BlindingRequired => "seal has no blinding factor",
+            BpSealError::UnknownMethod => "unknown seal close method id",
+            BpSealError::InvalidTx => "invalid witness transaction data",
+            BpSealError::InvalidProof => "invalid commitment proof data",
+            _ => "unknown error",
+        };
+        Failure(code, msg.to_owned())
+    }
+}
+
+/// Reports the result via the `out_err` pointer, if it is not `NULL`.
+unsafe fn report(
+    res: Result<(), Failure>,
+    out_err: *mut *mut c_char,
+) -> BpSealError {
+    let (code, msg) = match res {
+        Ok(()) => (BpSealError::Ok, None),
+        Err(Failure(code, msg)) => (code, Some(msg)),
+    };
+    if !out_err.is_null() {
+        *out_err = msg
+            .and_then(|msg| CString::new(msg).ok())
+            .map(CString::into_raw)
+            .unwrap_or(ptr::null_mut());
+    }
+    code
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Result<&'a str, Failure> {
+    if s.is_null() {
+        return Err(BpSealError::NullPointer.into());
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| BpSealError::InvalidUtf8.into())
+}
+
+unsafe fn slice_arg<'a>(
+    data: *const u8,
+    len: usize,
+) -> Result<&'a [u8], Failure> {
+    if data.is_null() {
+        return Err(BpSealError::NullPointer.into());
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+/// Parses seal in `method:txid:vout` or `method:txid:vout#blinding` form.
+fn parse(s: &str) -> Result<BpSeal, Failure> {
+    if s.contains('#') {
+        RevealedSeal::from_str(s)
+            .map(BpSeal::from)
+            .map_err(|err| Failure::from(&err))
+    } else {
+        ExplicitSeal::from_str(s)
+            .map(BpSeal::from)
+            .map_err(|err| Failure::from(&err))
+    }
+}
+
+/// Parses seal string in `method:txid:vout` form or, if it contains a
+/// blinding factor, in `method:txid:vout#blinding` form, writing the result
+/// into `out_seal`.
+///
+/// If `out_err` is not `NULL`, it receives `NULL` on success or a newly
+/// allocated error description, which must be freed with [`bp_string_free`].
+///
+/// # Safety
+///
+/// `seal_str` must be a valid NUL-terminated string; `out_seal` must point to
+/// a writable [`BpSeal`]; `out_err` must be either `NULL` or a writable
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_seal_parse(
+    seal_str: *const c_char,
+    out_seal: *mut BpSeal,
+    out_err: *mut *mut c_char,
+) -> BpSealError {
+    let res = (|| {
+        if out_seal.is_null() {
+            return Err(BpSealError::NullPointer.into());
+        }
+        *out_seal = parse(str_arg(seal_str)?)?;
+        Ok(())
+    })();
+    report(res, out_err)
+}
+
+/// Conceals seal, which must have a blinding factor, writing 32 bytes of the
+/// concealed seal into `out_concealed`.
+///
+/// # Safety
+///
+/// `seal` must point to a valid [`BpSeal`]; `out_concealed` must point to a
+/// writable buffer of at least 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_seal_conceal(
+    seal: *const BpSeal,
+    out_concealed: *mut u8,
+) -> BpSealError {
+    if seal.is_null() || out_concealed.is_null() {
+        return BpSealError::NullPointer;
+    }
+    match RevealedSeal::try_from(&*seal) {
+        Ok(seal) => {
+            let concealed = seal.conceal().to_byte_array();
+            ptr::copy_nonoverlapping(concealed.as_ptr(), out_concealed, 32);
+            BpSealError::Ok
+        }
+        Err(code) => code,
+    }
+}
+
+/// Verifies that the witness transaction closes the seal over the 32-byte
+/// message `msg32`, using the strict-encoded commitment proof.
+///
+/// `seal_str` has the same format as for [`bp_seal_parse`]; `tx` contains
+/// consensus-encoded witness transaction. If `out_err` is not `NULL`, it
+/// receives `NULL` on success or a newly allocated error description, which
+/// must be freed with [`bp_string_free`].
+///
+/// # Safety
+///
+/// `seal_str` must be a valid NUL-terminated string; `msg32` must point to
+/// 32 readable bytes; `proof` and `tx` must point to `proof_len` and `tx_len`
+/// readable bytes; `out_err` must be either `NULL` or a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_seal_verify_closure(
+    seal_str: *const c_char,
+    msg32: *const u8,
+    proof: *const u8,
+    proof_len: usize,
+    tx: *const u8,
+    tx_len: usize,
+    out_err: *mut *mut c_char,
+) -> BpSealError {
+    verify_closure(
+        seal_str,
+        msg32,
+        slice_arg(proof, proof_len).and_then(|data| {
+            strict_encoding::strict_deserialize(data)
+                .map_err(|_| Failure::from(BpSealError::InvalidProof))
+        }),
+        tx,
+        tx_len,
+        out_err,
+    )
+}
+
+/// Verifies seal closing like [`bp_seal_verify_closure`], using the
+/// commitment proof strict-encoded with the legacy single-byte tapret nonce.
+///
+/// # Safety
+///
+/// The same requirements as for [`bp_seal_verify_closure`] apply.
+#[no_mangle]
+pub unsafe extern "C" fn bp_seal_verify_closure_legacy(
+    seal_str: *const c_char,
+    msg32: *const u8,
+    proof: *const u8,
+    proof_len: usize,
+    tx: *const u8,
+    tx_len: usize,
+    out_err: *mut *mut c_char,
+) -> BpSealError {
+    verify_closure(
+        seal_str,
+        msg32,
+        slice_arg(proof, proof_len).and_then(|mut data| {
+            Proof::strict_decode_legacy(&mut data)
+                .ok()
+                .filter(|_| data.is_empty())
+                .ok_or_else(|| Failure::from(BpSealError::InvalidProof))
+        }),
+        tx,
+        tx_len,
+        out_err,
+    )
+}
+
+unsafe fn verify_closure(
+    seal_str: *const c_char,
+    msg32: *const u8,
+    proof: Result<Proof, Failure>,
+    tx: *const u8,
+    tx_len: usize,
+    out_err: *mut *mut c_char,
+) -> BpSealError {
+    let res = (|| {
+        let seal = parse(str_arg(seal_str)?)?;
+        let seal = ExplicitSeal::with(
+            CloseMethod::try_from(seal.method)
+                .map_err(|_| BpSealError::UnknownMethod)?,
+            seal.has_txid.then(|| Txid::from_inner(seal.txid)),
+            seal.vout,
+        );
+        let mut msg = [0u8; 32];
+        msg.copy_from_slice(slice_arg(msg32, 32)?);
+        let msg =
+            lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(msg));
+        let proof = proof?;
+        let tx: Transaction = deserialize(slice_arg(tx, tx_len)?)
+            .map_err(|_| Failure::from(BpSealError::InvalidTx))?;
+        verify_seal_closure(&seal, &msg, &proof, &tx)
+            .map_err(|err| Failure::from(&err))
+    })();
+    report(res, out_err)
+}
+
+/// Releases string allocated by the library. Does nothing if `s` is `NULL`.
+///
+/// # Safety
+///
+/// `s` must be either `NULL` or a string returned by the library, which was
+/// not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn bp_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::consensus::serialize;
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use dbc::tapret::tapret_commit;
+
+    use super::*;
+
+    const MSG: [u8; 32] = [0x5a; 32];
+
+    fn seal_outpoint() -> OutPoint {
+        OutPoint::new(Txid::hash(b"seal funding"), 1)
+    }
+
+    fn tapret_witness() -> (Transaction, Proof) {
+        let internal_key = XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let (output_key, proof) =
+            tapret_commit(internal_key, &mut None, MSG).unwrap();
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: seal_outpoint(),
+                ..TxIn::default()
+            }],
+            output: vec![TxOut {
+                value: 1_000,
+                script_pubkey: Script::new_v1_p2tr_tweaked(output_key),
+            }],
+        };
+        (tx, Proof::TapretFirst(proof))
+    }
+
+    fn seal_str(method: &str) -> CString {
+        let outpoint = seal_outpoint();
+        CString::new(format!("{}:{}:{}", method, outpoint.txid, outpoint.vout))
+            .unwrap()
+    }
+
+    unsafe fn verify(seal: &CString, msg: [u8; 32]) -> (BpSealError, String) {
+        let (tx, proof) = tapret_witness();
+        let tx = serialize(&tx);
+        let proof = strict_encoding::strict_serialize(&proof).unwrap();
+        let mut err = ptr::null_mut();
+        let code = bp_seal_verify_closure(
+            seal.as_ptr(),
+            msg.as_ptr(),
+            proof.as_ptr(),
+            proof.len(),
+            tx.as_ptr(),
+            tx.len(),
+            &mut err,
+        );
+        let msg = if err.is_null() {
+            s!("")
+        } else {
+            let msg = CStr::from_ptr(err).to_string_lossy().into_owned();
+            bp_string_free(err);
+            msg
+        };
+        (code, msg)
+    }
+
+    #[test]
+    fn parse_conceal() {
+        let s = CString::new(
+            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:5#0x31bbed7e7b2d",
+        )
+        .unwrap();
+        let revealed = RevealedSeal::from_str(s.to_str().unwrap()).unwrap();
+        let mut seal = BpSeal::default();
+        let mut concealed = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                bp_seal_parse(s.as_ptr(), &mut seal, ptr::null_mut()),
+                BpSealError::Ok
+            );
+            assert_eq!(seal, BpSeal::from(revealed));
+            assert_eq!(
+                bp_seal_conceal(&seal, concealed.as_mut_ptr()),
+                BpSealError::Ok
+            );
+        }
+        assert_eq!(concealed, revealed.to_concealed_seal().to_byte_array());
+
+        let s = CString::new("opret1st:~:5").unwrap();
+        unsafe {
+            assert_eq!(
+                bp_seal_parse(s.as_ptr(), &mut seal, ptr::null_mut()),
+                BpSealError::Ok
+            );
+            assert!(!seal.has_txid && !seal.has_blinding);
+            assert_eq!(
+                bp_seal_conceal(&seal, concealed.as_mut_ptr()),
+                BpSealError::BlindingRequired
+            );
+            seal.has_blinding = true;
+            seal.method = 7;
+            assert_eq!(
+                bp_seal_conceal(&seal, concealed.as_mut_ptr()),
+                BpSealError::UnknownMethod
+            );
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut seal = BpSeal::default();
+        let mut err = ptr::null_mut();
+        for (s, code) in [
+            ("tapret2nd:~:5", BpSealError::WrongMethod),
+            ("tapret1st:xyz:5", BpSealError::WrongTxid),
+            ("tapret1st:~:x", BpSealError::WrongVout),
+            ("tapret1st:~", BpSealError::WrongStructure),
+            ("tapret1st:~:1#0x5g", BpSealError::WrongBlinding),
+        ] {
+            let s = CString::new(s).unwrap();
+            unsafe {
+                assert_eq!(
+                    bp_seal_parse(s.as_ptr(), &mut seal, &mut err),
+                    code
+                );
+                assert!(!err.is_null());
+                bp_string_free(err);
+            }
+        }
+        unsafe {
+            assert_eq!(
+                bp_seal_parse(ptr::null(), &mut seal, &mut err),
+                BpSealError::NullPointer
+            );
+            bp_string_free(err);
+            let s = CString::new(vec![0xff, 0xfe]).unwrap();
+            assert_eq!(
+                bp_seal_parse(s.as_ptr(), &mut seal, ptr::null_mut()),
+                BpSealError::InvalidUtf8
+            );
+        }
+    }
+
+    #[test]
+    fn verify_tapret() {
+        unsafe {
+            assert_eq!(
+                verify(&seal_str("tapret1st"), MSG),
+                (BpSealError::Ok, s!(""))
+            );
+            let (code, msg) = verify(&seal_str("tapret1st"), [0u8; 32]);
+            assert_eq!(code, BpSealError::NoCommitment);
+            assert!(msg.contains("does not match the message"));
+            assert_eq!(
+                verify(&seal_str("opret1st"), MSG).0,
+                BpSealError::MethodMismatch
+            );
+            assert_eq!(
+                verify(&CString::new("tapret1st:~:0").unwrap(), MSG).0,
+                BpSealError::WitnessNotClosingSeal
+            );
+        }
+    }
+
+    #[test]
+    fn verify_tapret_legacy() {
+        use bitcoin::hashes::hex::FromHex;
+
+        // Witness and proof with a single-byte nonce produced before the
+        // nonce was extended to two bytes
+        let tx = Vec::<u8>::from_hex(
+            "0200000001b1444229f70e462e28d390bfe8675f1738727a3440cc2afcb4b08e063f0eebb90100000000ffffffff01e80300000000000022512098b248d469b9377e18cc5df5a30120d97fd0f4174b0761832434aea338b0678600000000",
+        )
+        .unwrap();
+        let proof = Vec::<u8>::from_hex(
+            "010000c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let (_, current) = tapret_witness();
+        let current = strict_encoding::strict_serialize(&current).unwrap();
+        let seal = seal_str("tapret1st");
+        let verify = |legacy: bool, proof: &[u8], msg: [u8; 32]| unsafe {
+            let f = if legacy {
+                bp_seal_verify_closure_legacy
+            } else {
+                bp_seal_verify_closure
+            };
+            f(
+                seal.as_ptr(),
+                msg.as_ptr(),
+                proof.as_ptr(),
+                proof.len(),
+                tx.as_ptr(),
+                tx.len(),
+                ptr::null_mut(),
+            )
+        };
+
+        assert_eq!(verify(true, &proof, MSG), BpSealError::Ok);
+        assert_eq!(verify(true, &proof, [0u8; 32]), BpSealError::NoCommitment);
+        assert_eq!(verify(false, &proof, MSG), BpSealError::InvalidProof);
+        assert_eq!(verify(true, &current, MSG), BpSealError::InvalidProof);
+    }
+
+    #[test]
+    fn verify_invalid_data() {
+        let seal = seal_str("tapret1st");
+        let (tx, proof) = tapret_witness();
+        let tx = serialize(&tx);
+        let proof = strict_encoding::strict_serialize(&proof).unwrap();
+        unsafe {
+            assert_eq!(
+                bp_seal_verify_closure(
+                    seal.as_ptr(),
+                    MSG.as_ptr(),
+                    proof.as_ptr(),
+                    proof.len() - 1,
+                    tx.as_ptr(),
+                    tx.len(),
+                    ptr::null_mut(),
+                ),
+                BpSealError::InvalidProof
+            );
+            assert_eq!(
+                bp_seal_verify_closure(
+                    seal.as_ptr(),
+                    MSG.as_ptr(),
+                    proof.as_ptr(),
+                    proof.len(),
+                    tx.as_ptr(),
+                    tx.len() - 1,
+                    ptr::null_mut(),
+                ),
+                BpSealError::InvalidTx
+            );
+            assert_eq!(
+                bp_seal_verify_closure(
+                    seal.as_ptr(),
+                    ptr::null(),
+                    proof.as_ptr(),
+                    proof.len(),
+                    tx.as_ptr(),
+                    tx.len(),
+                    ptr::null_mut(),
+                ),
+                BpSealError::NullPointer
+            );
+        }
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * BP Core Library implementing LNP/BP specifications & standards related to
+ * bitcoin protocol
+ *
+ * Written in 2020-2022 by
+ *     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+ *
+ * To the extent possible under law, the author(s) have dedicated all
+ * copyright and related and neighboring rights to this software to
+ * the public domain worldwide. This software is distributed without
+ * any warranty.
+ *
+ * You should have received a copy of the Apache 2.0 License
+ * along with this software.
+ * If not, see <https://opensource.org/licenses/Apache-2.0>.
+ */
+
+/*
+ * Round-trip of the tapret seal closing verification via C API. Build with
+ *
+ *     cargo build -p bp-seals-ffi
+ *     cc ffi/tests/c/verify_tapret.c -Iffi/include -Ltarget/debug \
+ *         -l:libbp_seals_ffi.a -lpthread -ldl -lm -o verify_tapret
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "bp_seals.h"
+
+static const char *SEAL =
+    "tapret1st:b9eb0e3f068eb0b4fc2acc40347a7238175f67e8bf90d3282e460ef7294244b1:1";
+
+static const char *TX =
+    "0200000001b1444229f70e462e28d390bfe8675f1738727a3440cc2afcb4b08e063f0eeb"
+    "b90100000000ffffffff01e803000000000000225120ae24c7f121636ff4396030b6e7ba"
+    "89a1534b5ca24efe973dedc421a470b26d7000000000";
+
+static const char *PROOF =
+    "01000000c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3";
+
+/* Witness and proof with the legacy single-byte tapret nonce */
+static const char *LEGACY_TX =
+    "0200000001b1444229f70e462e28d390bfe8675f1738727a3440cc2afcb4b08e063f0eeb"
+    "b90100000000ffffffff01e80300000000000022512098b248d469b9377e18cc5df5a301"
+    "20d97fd0f4174b0761832434aea338b0678600000000";
+
+static const char *LEGACY_PROOF =
+    "010000c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3";
+
+static size_t from_hex(const char *hex, uint8_t *out) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len; i++) {
+        unsigned int byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return len;
+}
+
+static int check(const char *name, BpSealError code, BpSealError expected,
+                 char *err) {
+    int ok = code == expected;
+    printf("%s: %s (code %d%s%s)\n", name, ok ? "ok" : "FAILED", (int)code,
+           err ? ", " : "", err ? err : "");
+    bp_string_free(err);
+    return ok;
+}
+
+int main(void) {
+    uint8_t tx[256], proof[64], msg[32];
+    size_t tx_len = from_hex(TX, tx);
+    size_t proof_len = from_hex(PROOF, proof);
+    char *err = NULL;
+    int ok = 1;
+
+    memset(msg, 0x5a, sizeof(msg));
+    BpSealError code = bp_seal_verify_closure(SEAL, msg, proof, proof_len, tx,
+                                              tx_len, &err);
+    ok &= check("closing", code, BP_SEAL_ERROR_OK, err);
+    ok &= err == NULL;
+
+    msg[0] = 0;
+    code = bp_seal_verify_closure(SEAL, msg, proof, proof_len, tx, tx_len,
+                                  &err);
+    ok &= check("other message", code, BP_SEAL_ERROR_NO_COMMITMENT, err);
+
+    tx_len = from_hex(LEGACY_TX, tx);
+    proof_len = from_hex(LEGACY_PROOF, proof);
+    memset(msg, 0x5a, sizeof(msg));
+    code = bp_seal_verify_closure_legacy(SEAL, msg, proof, proof_len, tx,
+                                         tx_len, &err);
+    ok &= check("legacy closing", code, BP_SEAL_ERROR_OK, err);
+
+    code = bp_seal_verify_closure(SEAL, msg, proof, proof_len, tx, tx_len,
+                                  &err);
+    ok &= check("legacy proof", code, BP_SEAL_ERROR_INVALID_PROOF, err);
+
+    BpSeal seal;
+    code = bp_seal_parse("opret1st:~:0#0x31bbed7e7b2d", &seal, &err);
+    ok &= check("parse", code, BP_SEAL_ERROR_OK, err);
+    ok &= seal.method == 0 && !seal.has_txid && seal.blinding == 0x31bbed7e7b2d;
+
+    uint8_t concealed[32];
+    code = bp_seal_conceal(&seal, concealed);
+    ok &= check("conceal", code, BP_SEAL_ERROR_OK, NULL);
+
+    code = bp_seal_parse("tapret2nd:~:0", &seal, &err);
+    ok &= check("wrong method", code, BP_SEAL_ERROR_WRONG_METHOD, err);
+
+    return ok ? EXIT_SUCCESS : EXIT_FAILURE;
+}
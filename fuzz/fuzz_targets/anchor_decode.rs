@@ -0,0 +1,26 @@
+#![no_main]
+
+use bitcoin::hashes::Hash;
+use dbc::{lnpbp4, Anchor};
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::strict_deserialize;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(anchor) = strict_deserialize::<Anchor<lnpbp4::MerkleBlock>>(data)
+    {
+        // Decoded anchors must be consistent enough for all the operations
+        let _ = anchor.anchor_id();
+        let _ = anchor.known_protocols();
+        for protocol_id in anchor.known_protocols() {
+            let _ = anchor.to_merkle_proof(protocol_id).unwrap();
+        }
+        let _ = anchor.clone().merge_reveal(anchor);
+    }
+    if let Ok(anchor) = strict_deserialize::<Anchor<lnpbp4::MerkleProof>>(data)
+    {
+        let _ = anchor.convolve(
+            lnpbp4::ProtocolId::from([0u8; 32]),
+            lnpbp4::Message::hash(b"fuzz"),
+        );
+    }
+});
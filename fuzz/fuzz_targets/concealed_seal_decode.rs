@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use seals::txout::blind::ConcealedSeal;
+use strict_encoding::{strict_deserialize, strict_serialize};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(seal) = strict_deserialize::<ConcealedSeal>(data) {
+        assert_eq!(strict_serialize(&seal).unwrap(), data);
+        assert_eq!(seal.to_string().parse::<ConcealedSeal>(), Ok(seal));
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use seals::txout::ExplicitSeal;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(seal) = ExplicitSeal::from_str(s) {
+            let reparsed = ExplicitSeal::from_str(&seal.to_string())
+                .expect("displayed seal must parse back");
+            assert_eq!(reparsed, seal);
+        }
+    }
+});
@@ -0,0 +1,88 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Comparison of batch concealment with [`conceal_all`] with the naive
+//! per-seal concealment initializing a new tagged hash context for each seal,
+//! for 10k seals.
+
+#[macro_use]
+extern crate criterion;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::Txid;
+use criterion::{black_box, Criterion};
+use seals::txout::blind::{
+    conceal_all, ConcealedSeal, RevealedSeal, CONCEALED_SEAL_TAG,
+};
+use seals::txout::CloseMethod;
+
+const SEAL_COUNT: u32 = 10_000;
+
+fn fixture() -> Vec<RevealedSeal> {
+    let txid = Txid::hash(b"transfer inputs");
+    (0..SEAL_COUNT)
+        .map(|vout| RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: if vout % 4 == 0 { None } else { Some(txid) },
+            vout: vout.into(),
+            blinding: 0x31bbed7e7b2d + vout as u64,
+        })
+        .collect()
+}
+
+fn conceal_naive(seal: &RevealedSeal) -> ConcealedSeal {
+    let tag = sha256::Hash::hash(CONCEALED_SEAL_TAG.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag[..]);
+    engine.input(&tag[..]);
+    // Concealment commits to the encoding without the version marker
+    engine.input(&seal.encode_v1()[1..]);
+    ConcealedSeal::from_byte_array(
+        sha256::Hash::from_engine(engine).into_inner(),
+    )
+}
+
+fn conceal(c: &mut Criterion) {
+    let seals = fixture();
+    assert_eq!(
+        seals.iter().map(conceal_naive).collect::<Vec<_>>(),
+        conceal_all(&seals)
+    );
+
+    let mut group = c.benchmark_group("10k seals");
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            black_box(&seals)
+                .iter()
+                .map(conceal_naive)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("conceal", |b| {
+        b.iter(|| {
+            black_box(&seals)
+                .iter()
+                .map(RevealedSeal::conceal)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("conceal_all", |b| {
+        b.iter(|| conceal_all(black_box(&seals)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, conceal);
+criterion_main!(benches);
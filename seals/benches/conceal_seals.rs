@@ -0,0 +1,86 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Comparison of seal concealment using precomputed tagged hash midstate with
+//! the concealment recomputing the tag hash for each seal, for 100k seals.
+
+#[macro_use]
+extern crate criterion;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::{OutPoint, Txid};
+use criterion::{black_box, Criterion};
+use seals::txout::blind::{ConcealedSeal, RevealedSeal, CONCEALED_SEAL_TAG};
+use seals::txout::CloseMethod;
+
+const SEAL_COUNT: u32 = 100_000;
+
+fn fixture() -> Vec<RevealedSeal> {
+    let txid = Txid::hash(b"funding transaction");
+    (0..SEAL_COUNT)
+        .map(|vout| {
+            let mut seal = RevealedSeal::from(OutPoint::new(txid, vout));
+            seal.method = CloseMethod::TapretFirst;
+            seal
+        })
+        .collect()
+}
+
+fn conceal_uncached(seal: &RevealedSeal) -> ConcealedSeal {
+    let tag = sha256::Hash::hash(CONCEALED_SEAL_TAG.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag[..]);
+    engine.input(&tag[..]);
+    // Concealment commits to the encoding without the version marker
+    engine.input(&seal.encode_v1()[1..]);
+    ConcealedSeal::from_byte_array(
+        sha256::Hash::from_engine(engine).into_inner(),
+    )
+}
+
+fn conceal(c: &mut Criterion) {
+    let seals = fixture();
+
+    let mut group = c.benchmark_group("100k seals");
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            black_box(&seals)
+                .iter()
+                .map(conceal_uncached)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("conceal", |b| {
+        b.iter(|| {
+            black_box(&seals)
+                .iter()
+                .map(RevealedSeal::conceal)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("conceal_with_engine", |b| {
+        b.iter(|| {
+            let engine = RevealedSeal::conceal_engine();
+            black_box(&seals)
+                .iter()
+                .map(|seal| seal.conceal_with_engine(&engine))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, conceal);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Comparison of the seal string parser with the previously used one, which
+//! lowercased method names and parsed txids via the generic hex decoder, for
+//! 100k seals.
+
+#[macro_use]
+extern crate criterion;
+
+use std::str::FromStr;
+
+use bitcoin::Txid;
+use criterion::{black_box, Criterion};
+use seals::txout::{CloseMethod, ExplicitSeal, Vout};
+
+const SEAL_COUNT: usize = 100_000;
+
+fn fixture() -> Vec<String> {
+    (0..SEAL_COUNT)
+        .map(|no| {
+            let method = if no % 2 == 0 { "TapRet1st" } else { "opret1st" };
+            format!(
+                "{}:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:{}",
+                method, no
+            )
+        })
+        .collect()
+}
+
+fn legacy_parse(s: &str) -> Option<ExplicitSeal> {
+    let mut split = s.split(&[':', '#'][..]);
+    match (split.next(), split.next(), split.next(), split.next()) {
+        (Some(method), Some(txid), Some(vout), None) => {
+            let method = match method.to_lowercase().as_str() {
+                "tapret1st" => CloseMethod::TapretFirst,
+                "opret1st" => CloseMethod::OpretFirst,
+                _ => return None,
+            };
+            let txid = match txid {
+                "~" => None,
+                txid => Some(Txid::from_str(txid).ok()?),
+            };
+            Some(ExplicitSeal::with(method, txid, Vout::from_str(vout).ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn parse_seals(c: &mut Criterion) {
+    let seals = fixture();
+
+    let mut group = c.benchmark_group("100k seals");
+    group.sample_size(20);
+    group.bench_function("legacy", |b| {
+        b.iter(|| {
+            for s in &seals {
+                black_box(legacy_parse(black_box(s)));
+            }
+        })
+    });
+    group.bench_function("from_str", |b| {
+        b.iter(|| {
+            for s in &seals {
+                black_box(ExplicitSeal::from_str(black_box(s)).ok());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse_seals);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Comparison of batch seal verification with verification of each seal
+//! independently, for 500 seals closed by a single witness transaction.
+
+#[macro_use]
+extern crate criterion;
+
+use std::collections::BTreeMap;
+
+use amplify::Wrapper;
+use bitcoin::hashes::{sha256t, Hash};
+use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+use commit_verify::lnpbp4;
+use criterion::{black_box, Criterion};
+use dbc::opret::OpretProof;
+use dbc::Proof;
+use seals::txout::{
+    verify_seal_closure, verify_seals_batch, CloseMethod, ExplicitSeal, Witness,
+};
+
+const SEAL_COUNT: u32 = 500;
+
+type Seals = Vec<(ExplicitSeal, lnpbp4::CommitmentHash, Witness)>;
+
+fn fixture() -> (Seals, BTreeMap<Txid, Transaction>) {
+    let msg = lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(
+        [1u8; 32],
+    ));
+    let prev_txid = Txid::hash(b"previous transaction");
+    let tx = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: (0..SEAL_COUNT)
+            .map(|vout| TxIn {
+                previous_output: OutPoint::new(prev_txid, vout),
+                ..Default::default()
+            })
+            .collect(),
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: Script::new_op_return(&msg[..]),
+        }],
+    };
+    let witness = Witness {
+        txid: tx.txid(),
+        proof: Proof::OpretFirst(OpretProof),
+    };
+    let seals = (0..SEAL_COUNT)
+        .map(|vout| {
+            let outpoint = OutPoint::new(prev_txid, vout);
+            let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+            (seal, msg, witness.clone())
+        })
+        .collect();
+    let mut witnesses = BTreeMap::new();
+    witnesses.insert(tx.txid(), tx);
+    (seals, witnesses)
+}
+
+fn verify(c: &mut Criterion) {
+    let (seals, witnesses) = fixture();
+
+    let mut group = c.benchmark_group("1 tx x 500 seals");
+    group.bench_function("independent", |b| {
+        b.iter(|| {
+            seals
+                .iter()
+                .map(|(seal, msg, witness)| {
+                    let tx = &witnesses[&witness.txid];
+                    verify_seal_closure(seal, msg, &witness.proof, tx)
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| verify_seals_batch(black_box(&seals), black_box(&witnesses)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
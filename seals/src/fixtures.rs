@@ -0,0 +1,229 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Deterministic seal and anchor fixtures for the test suites of the
+//! downstream crates. The module is available with `test-utils` feature.
+//!
+//! All fixtures are parameterized by an index `n` and are consistent with each
+//! other: [`commit_tx`] spends the outpoint of [`seal_explicit`] (and of the
+//! revealed and concealed seals derived from it) and contains tapret
+//! commitment to [`commitment`], which is the LNPBP-4 commitment of the
+//! [`anchor`] to [`message`] under [`protocol_id`]. Thus the fixtures verify
+//! with [`crate::txout::verify_seal_closure`], [`Witness::verify`] and
+//! [`Anchor::verify`].
+//!
+//! Canonical seal test vectors with their golden commitments are provided by
+//! [`crate::txout::test_helpers`] under the same feature.
+
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::{KeyPair, XOnlyPublicKey, SECP256K1};
+use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+use commit_verify::lnpbp4::{self, Message, ProtocolId};
+use commit_verify::{ConsensusCommit, TaggedHash};
+use dbc::anchor::ANCHOR_MIN_LNPBP4_DEPTH;
+use dbc::tapret::tapret_commit;
+use dbc::{Anchor, Proof};
+use strict_encoding::{strict_deserialize, StrictEncode};
+
+use crate::txout::blind::{ConcealedSeal, RevealedSeal};
+use crate::txout::{CloseMethod, ExplicitSeal, Witness};
+
+/// Seed used for deterministic derivation of [`seal_revealed`] blinding
+/// factors.
+pub const FIXTURE_BLINDING_SEED: &[u8] = b"bp-seals fixture";
+
+fn fixture_hash(tag: &[u8], n: u32) -> sha256::Hash {
+    let mut data = tag.to_vec();
+    data.extend(n.to_le_bytes());
+    sha256::Hash::hash(&data)
+}
+
+/// Returns id of the transaction funding the fixture seal `n`.
+pub fn funding_txid(n: u32) -> Txid {
+    Txid::from_inner(fixture_hash(b"fixture funding", n).into_inner())
+}
+
+/// Returns LNPBP-4 protocol id of the fixture `n`.
+pub fn protocol_id(n: u32) -> ProtocolId {
+    ProtocolId::from(fixture_hash(b"fixture protocol", n).into_inner())
+}
+
+/// Returns message committed under [`protocol_id`] by the fixture `n`.
+pub fn message(n: u32) -> Message {
+    Message::hash(&fixture_hash(b"fixture message", n)[..])
+}
+
+/// Returns outpoint of the fixture seal `n`, which is the output `n` of
+/// [`funding_txid`].
+pub fn seal_outpoint(n: u32) -> OutPoint { OutPoint::new(funding_txid(n), n) }
+
+/// Returns tapret seal defined over [`seal_outpoint`].
+pub fn seal_explicit(n: u32) -> ExplicitSeal {
+    ExplicitSeal::new(CloseMethod::TapretFirst, seal_outpoint(n))
+}
+
+/// Returns [`seal_explicit`] with the blinding factor derived from
+/// [`FIXTURE_BLINDING_SEED`].
+pub fn seal_revealed(n: u32) -> RevealedSeal {
+    RevealedSeal::with_deterministic_blinding(
+        CloseMethod::TapretFirst,
+        seal_outpoint(n),
+        FIXTURE_BLINDING_SEED,
+        n as u64,
+    )
+}
+
+/// Returns [`seal_revealed`] concealed with [`RevealedSeal::conceal`].
+pub fn seal_concealed(n: u32) -> ConcealedSeal { seal_revealed(n).conceal() }
+
+fn merkle_tree(n: u32) -> lnpbp4::MerkleTree {
+    // Tree is constructed from its strict encoding (depth, entropy and the
+    // message map), since the commit procedure uses random entropy.
+    let mut data = vec![ANCHOR_MIN_LNPBP4_DEPTH];
+    data.extend((n as u64).to_le_bytes());
+    bmap! { protocol_id(n) => message(n) }
+        .strict_encode(&mut data)
+        .expect("memory encoders do not fail");
+    strict_deserialize(data).expect("fixture LNPBP-4 tree")
+}
+
+/// Returns LNPBP-4 commitment of the [`anchor`] `n`, which is the message the
+/// fixture seals are closed over.
+pub fn commitment(n: u32) -> lnpbp4::CommitmentHash {
+    merkle_tree(n).consensus_commit()
+}
+
+fn internal_key(n: u32) -> XOnlyPublicKey {
+    let keypair = KeyPair::from_seckey_slice(
+        SECP256K1,
+        &fixture_hash(b"fixture key", n)[..],
+    )
+    .expect("fixture key");
+    XOnlyPublicKey::from_keypair(&keypair)
+}
+
+fn commit(n: u32) -> (Transaction, Proof) {
+    let (output_key, proof) =
+        tapret_commit(internal_key(n), &mut None, commitment(n).into_array())
+            .expect("tapret commitment to the fixture");
+    let tx = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: seal_outpoint(n),
+            ..Default::default()
+        }],
+        output: vec![TxOut {
+            value: 10_000,
+            script_pubkey: Script::new_v1_p2tr_tweaked(output_key),
+        }],
+    };
+    (tx, Proof::TapretFirst(proof))
+}
+
+/// Returns transaction closing the seal `n`, which contains tapret commitment
+/// to [`commitment`] in its only output.
+pub fn commit_tx(n: u32) -> Transaction { commit(n).0 }
+
+/// Returns anchor of the [`commit_tx`] commitment to [`message`] under
+/// [`protocol_id`].
+pub fn anchor(n: u32) -> Anchor<lnpbp4::MerkleBlock> {
+    let (tx, dbc_proof) = commit(n);
+    Anchor {
+        txid: tx.txid(),
+        lnpbp4_proof: lnpbp4::MerkleBlock::from(&merkle_tree(n)),
+        dbc_proof,
+    }
+}
+
+/// Returns witness of the seal `n` closing by [`commit_tx`].
+pub fn witness(n: u32) -> Witness { Witness::from(anchor(n)) }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::txout::{verify_seal_closure, TxoSeal};
+
+    #[test]
+    fn fixtures_verify() {
+        for n in 0..8 {
+            let tx = commit_tx(n);
+            let anchor = anchor(n);
+            let witness = witness(n);
+            assert_eq!(anchor.txid, tx.txid());
+            assert_eq!(
+                anchor.to_merkle_proof(protocol_id(n)).unwrap().verify(
+                    protocol_id(n),
+                    message(n),
+                    tx.clone()
+                ),
+                Ok(())
+            );
+            assert!(verify_seal_closure(
+                &seal_explicit(n),
+                &commitment(n),
+                &witness.proof,
+                &tx
+            )
+            .is_ok());
+            assert!(verify_seal_closure(
+                &seal_revealed(n),
+                &commitment(n),
+                &witness.proof,
+                &tx
+            )
+            .is_ok());
+            assert!(matches!(
+                witness.verify([&seal_explicit(n)], &commitment(n), &tx),
+                Ok(())
+            ));
+            assert_eq!(
+                seal_revealed(n).outpoint(),
+                seal_explicit(n).outpoint()
+            );
+            assert_eq!(seal_concealed(n), seal_revealed(n).conceal());
+
+            // Fixtures with other index do not verify
+            assert!(verify_seal_closure(
+                &seal_explicit(n + 1),
+                &commitment(n),
+                &witness.proof,
+                &tx
+            )
+            .is_err());
+            assert!(verify_seal_closure(
+                &seal_explicit(n),
+                &commitment(n + 1),
+                &witness.proof,
+                &tx
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn fixtures_deterministic() {
+        for n in 0..4 {
+            assert_eq!(seal_explicit(n), seal_explicit(n));
+            assert_eq!(seal_revealed(n), seal_revealed(n));
+            assert_eq!(commit_tx(n), commit_tx(n));
+            assert_eq!(anchor(n), anchor(n));
+            assert_eq!(anchor(n).anchor_id(), anchor(n).anchor_id());
+            assert_ne!(seal_revealed(n), seal_revealed(n + 1));
+            assert_ne!(seal_concealed(n), seal_concealed(n + 1));
+            assert_ne!(anchor(n).anchor_id(), anchor(n + 1).anchor_id());
+        }
+    }
+}
@@ -24,10 +24,17 @@ extern crate amplify;
 #[macro_use]
 extern crate strict_encoding;
 #[cfg(feature = "async")]
-// #[macro_use]
+#[macro_use]
 extern crate async_trait;
 #[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde_crate as serde;
+#[cfg(feature = "descriptors")]
+extern crate miniscript_crate as miniscript;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures;
+pub mod limits;
 pub mod txout;
+#[cfg(feature = "wasm")]
+pub mod wasm;
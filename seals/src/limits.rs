@@ -0,0 +1,37 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Limits enforced when decoding and parsing seal data from untrusted
+//! sources.
+
+pub use dbc::limits::{MAX_LNPBP4_DEPTH, MAX_LNPBP4_MESSAGES};
+
+/// Maximal number of seals in a [`crate::txout::SealSet`], matching the
+/// maximal length of strict-encoded collections.
+pub const MAX_SEALS: usize = u16::MAX as usize;
+
+/// Maximal transaction output number accepted when parsing seal strings and
+/// decoding seal data with the checked decoders. Although the consensus rules
+/// allow any `u32` value, transactions can't have that many outputs due to the
+/// block size limit; larger values are rejected as most likely being typos.
+///
+/// Parsing limit can be overridden with
+/// [`crate::txout::ExplicitSeal::from_str_with_limits`]. Strict decoding
+/// applies the limit only with
+/// [`crate::txout::ExplicitSeal::decode_checked`] and
+/// [`crate::txout::blind::RevealedSeal::decode_checked`]; seal data
+/// constructed programmatically and decoded with the plain strict or serde
+/// decoders accept any `u32` value, so that the existing data round-trip.
+pub const MAX_VOUT: u32 = u16::MAX as u32;
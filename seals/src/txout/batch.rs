@@ -0,0 +1,319 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Batch verification of seals closed by a small set of witness transactions.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bitcoin::{Transaction, Txid};
+use commit_verify::lnpbp4;
+use dbc::tapret::TapretError;
+use dbc::Proof;
+
+use crate::txout::witness::{check_closing, check_commitment};
+use crate::txout::{TxoSeal, VerifyError, VerifyOpts, Witness};
+
+/// Witness transaction shared by all seals closed by the transaction, together
+/// with the cached results of the commitment proof verification.
+struct ParsedTx<'a> {
+    tx: &'a Transaction,
+    txid: Txid,
+    commitments: Vec<(
+        &'a lnpbp4::CommitmentHash,
+        &'a Proof,
+        Result<bool, TapretError>,
+    )>,
+}
+
+impl<'a> ParsedTx<'a> {
+    fn with(tx: &'a Transaction, txid: Txid) -> Self {
+        ParsedTx {
+            tx,
+            txid,
+            commitments: vec![],
+        }
+    }
+
+    fn verify(
+        &mut self,
+        seal: &impl TxoSeal,
+        msg: &'a lnpbp4::CommitmentHash,
+        witness: &'a Witness,
+    ) -> Result<(), VerifyError> {
+        let proof = &witness.proof;
+        check_closing(seal, proof, self.tx, self.txid, VerifyOpts::default())?;
+
+        let cached = self
+            .commitments
+            .iter()
+            .find(|(m, p, _)| *m == msg && *p == proof)
+            .map(|(_, _, verified)| verified.clone());
+        let verified = match cached {
+            Some(verified) => verified,
+            None => {
+                let verified = proof.verify(msg, self.tx.clone());
+                self.commitments.push((msg, proof, verified.clone()));
+                verified
+            }
+        };
+        check_commitment(seal.method(), verified)
+    }
+}
+
+/// Verifies closing of multiple seals by the witness transactions from
+/// `witnesses`, returning verification results in the same order as the
+/// `seals`. Each seal is provided together with the message it must be closed
+/// over and the witness of the closing.
+///
+/// Unlike repeated calls to [`crate::txout::verify_seal_closure`], witness
+/// transactions are identified only once, and each commitment proof is
+/// verified only once per witness transaction and message, with the result
+/// shared by all of the seals closed with it. The seals pass through the same
+/// checks as with [`crate::txout::verify_seal_closure`], producing the same
+/// results, except that seals with witness transaction missed from
+/// `witnesses` fail with [`VerifyError::WitnessTxUnknown`].
+pub fn verify_seals_batch<Seal>(
+    seals: &[(Seal, lnpbp4::CommitmentHash, Witness)],
+    witnesses: &BTreeMap<Txid, Transaction>,
+) -> Vec<Result<(), VerifyError>>
+where
+    Seal: TxoSeal,
+{
+    let mut parsed = HashMap::<Txid, Option<ParsedTx>>::new();
+    seals
+        .iter()
+        .map(|(seal, msg, witness)| {
+            parsed
+                .entry(witness.txid)
+                .or_insert_with(|| {
+                    witnesses
+                        .get(&witness.txid)
+                        .filter(|tx| tx.txid() == witness.txid)
+                        .map(|tx| ParsedTx::with(tx, witness.txid))
+                })
+                .as_mut()
+                .ok_or(VerifyError::WitnessTxUnknown(witness.txid))?
+                .verify(seal, msg, witness)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use amplify::Wrapper;
+    use bitcoin::hashes::{sha256t, Hash};
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use dbc::opret::OpretProof;
+    use dbc::tapret::tapret_commit;
+
+    use super::*;
+    use crate::txout::{
+        verify_seal_closure, CloseMethod, ExplicitSeal, WitnessCheckError,
+    };
+
+    fn msg(byte: u8) -> lnpbp4::CommitmentHash {
+        lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(
+            [byte; 32],
+        ))
+    }
+
+    fn outpoint(vout: u32) -> OutPoint {
+        OutPoint::new(Txid::hash(b"previous transaction"), vout)
+    }
+
+    fn tx(inputs: impl IntoIterator<Item = u32>, host: Script) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: inputs
+                .into_iter()
+                .map(|vout| TxIn {
+                    previous_output: outpoint(vout),
+                    ..default!()
+                })
+                .collect(),
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new(),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: host,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn batch_order() {
+        let opret_tx = tx(0..4, Script::new_op_return(&msg(1)[..]));
+        let opret = Witness {
+            txid: opret_tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+
+        let internal_key = XOnlyPublicKey::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let (output_key, proof) = tapret_commit(
+            internal_key,
+            &mut None,
+            msg(2).into_inner().into_inner(),
+        )
+        .unwrap();
+        let tapret_tx = tx(4..6, Script::new_v1_p2tr_tweaked(output_key));
+        let tapret = Witness {
+            txid: tapret_tx.txid(),
+            proof: Proof::TapretFirst(proof),
+        };
+        let unknown = Witness {
+            txid: Txid::hash(b"unknown witness"),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+
+        let opret_seal =
+            |vout| ExplicitSeal::new(CloseMethod::OpretFirst, outpoint(vout));
+        let tapret_seal =
+            |vout| ExplicitSeal::new(CloseMethod::TapretFirst, outpoint(vout));
+        let seals = vec![
+            (opret_seal(0), msg(1), opret.clone()),
+            (tapret_seal(4), msg(2), tapret.clone()),
+            (opret_seal(1), msg(2), opret.clone()),
+            (opret_seal(4), msg(1), opret.clone()),
+            (tapret_seal(2), msg(1), opret.clone()),
+            (opret_seal(3), msg(1), unknown.clone()),
+            (tapret_seal(5), msg(1), tapret.clone()),
+            (opret_seal(3), msg(1), opret.clone()),
+            (tapret_seal(5), msg(2), tapret.clone()),
+        ];
+        let witnesses = bmap! {
+            opret_tx.txid() => opret_tx.clone(),
+            tapret_tx.txid() => tapret_tx.clone()
+        };
+
+        let results = verify_seals_batch(&seals, &witnesses);
+        assert_eq!(results.len(), seals.len());
+        for ((seal, msg, witness), result) in seals.iter().zip(&results) {
+            let expected = match witnesses.get(&witness.txid) {
+                Some(tx) => verify_seal_closure(seal, msg, &witness.proof, tx),
+                None => Err(VerifyError::WitnessTxUnknown(witness.txid)),
+            };
+            assert_eq!(format!("{:?}", result), format!("{:?}", expected));
+        }
+
+        assert!(matches!(results[0], Ok(())));
+        assert!(matches!(results[1], Ok(())));
+        assert!(matches!(
+            results[2],
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+        assert!(matches!(
+            results[3],
+            Err(VerifyError::WitnessNotSpendingSeal { witness: txid, seal: op })
+                if txid == opret.txid && op == outpoint(4)
+        ));
+        assert!(matches!(
+            results[4],
+            Err(VerifyError::MethodMismatch { .. })
+        ));
+        assert!(matches!(
+            results[5],
+            Err(VerifyError::WitnessTxUnknown(txid)) if txid == unknown.txid
+        ));
+        assert!(matches!(
+            results[6],
+            Err(VerifyError::InvalidCommitment(CloseMethod::TapretFirst))
+        ));
+        assert!(matches!(results[7], Ok(())));
+        assert!(matches!(results[8], Ok(())));
+    }
+
+    #[test]
+    fn batch_txid_mismatch() {
+        let tx = tx(0..1, Script::new_op_return(&msg(1)[..]));
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        let seals = [(
+            ExplicitSeal::new(CloseMethod::OpretFirst, outpoint(0)),
+            msg(1),
+            witness,
+        )];
+
+        let witnesses = bmap! { tx.txid() => tx.clone() };
+        assert!(matches!(verify_seals_batch(&seals, &witnesses)[..], [Ok(
+            ()
+        )]));
+
+        let mut other_tx = tx.clone();
+        other_tx.lock_time = 1;
+        let witnesses = bmap! { tx.txid() => other_tx };
+        assert!(matches!(
+            verify_seals_batch(&seals, &witnesses)[..],
+            [Err(VerifyError::WitnessTxUnknown(txid))] if txid == tx.txid()
+        ));
+        assert!(verify_seals_batch::<ExplicitSeal>(&[], &witnesses).is_empty());
+    }
+
+    #[test]
+    fn batch_witness_check() {
+        let mut coinbase = tx(0..1, Script::new_op_return(&msg(1)[..]));
+        coinbase.input[0].previous_output = OutPoint::null();
+        let mut no_outputs = tx(1..2, Script::new());
+        no_outputs.output.clear();
+
+        let opret = |tx: &Transaction| Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        let seals = vec![
+            (
+                ExplicitSeal::new(CloseMethod::OpretFirst, OutPoint::null()),
+                msg(1),
+                opret(&coinbase),
+            ),
+            (
+                ExplicitSeal::new(CloseMethod::OpretFirst, outpoint(1)),
+                msg(1),
+                opret(&no_outputs),
+            ),
+        ];
+        let witnesses = bmap! {
+            coinbase.txid() => coinbase.clone(),
+            no_outputs.txid() => no_outputs.clone()
+        };
+
+        let results = verify_seals_batch(&seals, &witnesses);
+        for ((seal, msg, witness), result) in seals.iter().zip(&results) {
+            let tx = &witnesses[&witness.txid];
+            let expected = verify_seal_closure(seal, msg, &witness.proof, tx);
+            assert_eq!(format!("{:?}", result), format!("{:?}", expected));
+        }
+        assert!(matches!(
+            results[0],
+            Err(VerifyError::WitnessCheck(WitnessCheckError::Coinbase(_)))
+        ));
+        assert!(matches!(
+            results[1],
+            Err(VerifyError::WitnessCheck(WitnessCheckError::NoOutputs(_)))
+        ));
+    }
+}
@@ -15,32 +15,58 @@
 
 //! TxOut seals which are blinded with additional entropy.
 
+use std::cmp::Ordering;
 use std::convert::{TryFrom, TryInto};
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{Read, Write};
 use std::str::FromStr;
 
+use amplify::DumbDefault;
+use bech32::{FromBase32, ToBase32, Variant};
+#[cfg(feature = "serde")]
+use bitcoin::hashes::hex::{FromHex, ToHex};
 use bitcoin::hashes::{sha256, sha256t, Hash, HashEngine};
-use bitcoin::secp256k1::rand::{thread_rng, RngCore};
-use bitcoin::{OutPoint, Txid};
-use commit_verify::{commit_encode, CommitConceal, CommitVerify, TaggedHash};
+use bitcoin::secp256k1::rand::thread_rng;
+use bitcoin::{Network, OutPoint, Txid};
+use commit_verify::merkle::{merklize, MerkleNode};
+use commit_verify::{commit_encode, CommitConceal, CommitVerify};
 use dbc::tapret::Lnpbp6;
-use lnpbp_bech32::{FromBech32Str, ToBech32String};
-
-use super::{CloseMethod, MethodParseError, WitnessVoutError};
-use crate::txout::{ExplicitSeal, TxoSeal};
+use lnpbp_bech32::ToBech32String;
+use strict_encoding::{
+    strict_deserialize, strict_serialize, StrictDecode, StrictEncode,
+};
+
+use super::{
+    uri, CloseMethod, MethodParseError, RevealMismatch, SealDecodeError, Vout,
+    WitnessVoutError, SEAL_URI_SCHEME,
+};
+use crate::limits::MAX_VOUT;
+use crate::txout::explicit::{
+    parse_txid, split_field, ParseContext, SEPARATORS,
+};
+use crate::txout::{Blinder, ExplicitSeal, TxoSeal};
 
 /// Revealed seal definition which may point to a witness transactions and
 /// contains blinding data.
 ///
 /// Revealed seal means that the seal definition containing explicit information
 /// about the bitcoin transaction output.
+///
+/// Strict encoding of the seal starts with [`RevealedSeal::VERSION_MARKER`]
+/// and [`RevealedSeal::VERSION`] bytes, followed by the seal fields in the
+/// order of their declaration. Seals encoded before the versioning, which
+/// start directly with the close method, are decoded as well.
+///
+/// Derived [`PartialEq`] and [`Eq`] implementations are not constant-time and
+/// may leak the secret blinding factor through the comparison timing; use
+/// [`RevealedSeal::ct_eq`] where the timing can be observed by an attacker.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-#[derive(StrictEncode, StrictDecode)]
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RevealedSeal {
     /// Commitment to the specific seal close method [`CloseMethod`] which must
     /// be used to close this seal.
@@ -52,17 +78,161 @@ pub struct RevealedSeal {
     /// but the transaction still can be identified by some other means (for
     /// instance it is a transaction spending specific outpoint, like other
     /// seal definition).
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::txout::schema::TxidHex>")
+    )]
     pub txid: Option<Txid>,
 
     /// Tx output number, which should be always known.
-    pub vout: u32,
+    pub vout: Vout,
 
     /// Blinding factor providing confidentiality of the seal definition.
     /// Prevents rainbow table bruteforce attack based on the existing
     /// blockchain txid set.
+    ///
+    /// The field will become private in the future releases; use
+    /// [`RevealedSeal::blinding`] and [`RevealedSeal::set_blinding`]
+    /// accessors instead.
+    ///
+    /// Human-readable serde formats represent the blinding as a decimal
+    /// string, since JSON numbers above 2^53 lose precision in JavaScript;
+    /// numeric values are still accepted on input.
+    #[cfg_attr(feature = "serde", serde(with = "serde_blinding"))]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::txout::schema::BlindingString")
+    )]
     pub blinding: u64,
 }
 
+#[cfg(feature = "serde")]
+mod serde_blinding {
+    use serde::de::{self, Unexpected};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(
+        blinding: &u64,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(blinding)
+        } else {
+            serializer.serialize_u64(*blinding)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl de::Visitor<'_> for Visitor {
+            type Value = u64;
+
+            fn expecting(
+                &self,
+                formatter: &mut std::fmt::Formatter,
+            ) -> std::fmt::Result {
+                formatter.write_str(
+                    "64-bit blinding factor as a decimal string or a number",
+                )
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(v)
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v < 0 {
+                    return Err(E::invalid_value(Unexpected::Signed(v), &self));
+                }
+                Ok(v as u64)
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                v.parse()
+                    .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_u64(Visitor)
+        }
+    }
+}
+
+impl StrictEncode for RevealedSeal {
+    fn strict_encode<E: Write>(
+        &self,
+        mut e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        let mut len = RevealedSeal::VERSION_MARKER.strict_encode(&mut e)?;
+        len += RevealedSeal::VERSION.strict_encode(&mut e)?;
+        len += self.strict_encode_fields(e)?;
+        Ok(len)
+    }
+}
+
+impl StrictDecode for RevealedSeal {
+    fn strict_decode<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        let marker = u8::strict_decode(&mut d)?;
+        if marker != RevealedSeal::VERSION_MARKER {
+            // Unversioned encoding starting with the close method
+            return RevealedSeal::strict_decode_fields(
+                (&[marker][..]).chain(d),
+            );
+        }
+        let version = u8::strict_decode(&mut d)?;
+        if version != RevealedSeal::VERSION {
+            return Err(strict_encoding::Error::DataIntegrityError(
+                SealDecodeError::UnsupportedSealVersion(version).to_string(),
+            ));
+        }
+        RevealedSeal::strict_decode_fields(d)
+    }
+}
+
+impl RevealedSeal {
+    fn strict_encode_fields(
+        &self,
+        mut e: impl Write,
+    ) -> Result<usize, strict_encoding::Error> {
+        let mut len = self.method.strict_encode(&mut e)?;
+        len += self.txid.strict_encode(&mut e)?;
+        len += self.vout.strict_encode(&mut e)?;
+        len += self.blinding.strict_encode(&mut e)?;
+        Ok(len)
+    }
+
+    fn strict_decode_fields(
+        mut d: impl Read,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(RevealedSeal {
+            method: CloseMethod::strict_decode(&mut d)?,
+            txid: Option::<Txid>::strict_decode(&mut d)?,
+            vout: Vout::strict_decode(&mut d)?,
+            blinding: u64::strict_decode(&mut d)?,
+        })
+    }
+}
+
 impl TryFrom<&RevealedSeal> for OutPoint {
     type Error = WitnessVoutError;
 
@@ -70,7 +240,7 @@ impl TryFrom<&RevealedSeal> for OutPoint {
     fn try_from(reveal: &RevealedSeal) -> Result<Self, Self::Error> {
         reveal
             .txid
-            .map(|txid| OutPoint::new(txid, reveal.vout as u32))
+            .map(|txid| OutPoint::new(txid, reveal.vout.into_u32()))
             .ok_or(WitnessVoutError)
     }
 }
@@ -87,12 +257,7 @@ impl TryFrom<RevealedSeal> for OutPoint {
 impl From<&OutPoint> for RevealedSeal {
     #[inline]
     fn from(outpoint: &OutPoint) -> Self {
-        Self {
-            method: CloseMethod::TapretFirst,
-            blinding: thread_rng().next_u64(),
-            txid: Some(outpoint.txid),
-            vout: outpoint.vout as u32,
-        }
+        RevealedSeal::new(CloseMethod::TapretFirst, *outpoint)
     }
 }
 
@@ -104,12 +269,7 @@ impl From<OutPoint> for RevealedSeal {
 impl From<&ExplicitSeal> for RevealedSeal {
     #[inline]
     fn from(seal: &ExplicitSeal) -> Self {
-        Self {
-            method: seal.method,
-            blinding: thread_rng().next_u64(),
-            txid: seal.txid,
-            vout: seal.vout,
-        }
+        seal.blind_random(&mut thread_rng())
     }
 }
 
@@ -118,13 +278,27 @@ impl From<ExplicitSeal> for RevealedSeal {
     fn from(seal: ExplicitSeal) -> Self { RevealedSeal::from(&seal) }
 }
 
+impl From<&RevealedSeal> for ExplicitSeal {
+    #[inline]
+    fn from(seal: &RevealedSeal) -> Self { seal.to_explicit() }
+}
+
+impl From<RevealedSeal> for ExplicitSeal {
+    #[inline]
+    fn from(seal: RevealedSeal) -> Self { seal.to_explicit() }
+}
+
 impl CommitConceal for RevealedSeal {
     type ConcealedCommitment = ConcealedSeal;
 
     #[inline]
-    fn commit_conceal(&self) -> Self::ConcealedCommitment {
-        ConcealedSeal::commit(self)
-    }
+    fn commit_conceal(&self) -> Self::ConcealedCommitment { self.conceal() }
+}
+
+impl DumbDefault for RevealedSeal {
+    /// Returns placeholder seal over the output `0` of the all-zero txid using
+    /// [`CloseMethod::OpretFirst`] and zero blinding factor.
+    fn dumb_default() -> Self { ExplicitSeal::dumb_default().blind(0) }
 }
 
 impl commit_encode::Strategy for RevealedSeal {
@@ -139,7 +313,7 @@ impl TxoSeal for RevealedSeal {
     fn txid(&self) -> Option<Txid> { self.txid }
 
     #[inline]
-    fn vout(&self) -> usize { self.vout as usize }
+    fn vout(&self) -> Vout { self.vout }
 
     #[inline]
     fn outpoint(&self) -> Option<OutPoint> { self.try_into().ok() }
@@ -151,43 +325,243 @@ impl TxoSeal for RevealedSeal {
 
     #[inline]
     fn outpoint_or(&self, default_txid: Txid) -> OutPoint {
-        OutPoint::new(self.txid.unwrap_or(default_txid), self.vout as u32)
+        OutPoint::new(self.txid.unwrap_or(default_txid), self.vout.into_u32())
     }
 }
 
 impl RevealedSeal {
+    /// Marker of the versioned seal strict encoding, written as the first
+    /// byte of the encoded data. The marker does not match any of the close
+    /// methods, which start the unversioned encoding.
+    pub const VERSION_MARKER: u8 = 0xFF;
+
+    /// Version of the seal strict encoding, written after
+    /// [`RevealedSeal::VERSION_MARKER`].
+    pub const VERSION: u8 = 1;
+
+    /// Strict-encodes the seal using version 1 of the encoding.
+    #[inline]
+    pub fn encode_v1(&self) -> Vec<u8> {
+        strict_serialize(self).expect("memory encoders do not fail")
+    }
+
+    /// Decodes seal encoded with any of the encoding versions supported by
+    /// this library, including the unversioned encoding, failing with
+    /// [`SealDecodeError::UnsupportedSealVersion`] on unknown versions. The
+    /// data must not contain anything after the encoded seal.
+    pub fn decode_any(
+        data: impl AsRef<[u8]>,
+    ) -> Result<RevealedSeal, SealDecodeError> {
+        match data.as_ref() {
+            [RevealedSeal::VERSION_MARKER, version, ..]
+                if *version != RevealedSeal::VERSION =>
+            {
+                Err(SealDecodeError::UnsupportedSealVersion(*version))
+            }
+            data => Ok(strict_deserialize(data)?),
+        }
+    }
+
+    /// Decodes seal like [`RevealedSeal::decode_any`], additionally failing
+    /// with [`SealDecodeError::VoutOutOfRange`] if the output number exceeds
+    /// [`MAX_VOUT`](crate::limits::MAX_VOUT). Should be used for the data
+    /// from untrusted sources.
+    pub fn decode_checked(
+        data: impl AsRef<[u8]>,
+    ) -> Result<RevealedSeal, SealDecodeError> {
+        let seal = RevealedSeal::decode_any(data)?;
+        seal.vout.check_decoded()?;
+        Ok(seal)
+    }
+
     /// Constructs seal for the provided outpoint and seal closing method. Uses
     /// `thread_rng` to initialize blinding factor.
     #[inline]
     pub fn new(method: CloseMethod, outpoint: OutPoint) -> RevealedSeal {
-        Self {
-            method,
-            blinding: thread_rng().next_u64(),
-            txid: Some(outpoint.txid),
-            vout: outpoint.vout as u32,
-        }
+        RevealedSeal::with_blinder(method, outpoint, &mut thread_rng())
+    }
+
+    /// Constructs seal for the provided outpoint and seal closing method,
+    /// taking blinding factor from the provided `blinder`.
+    #[inline]
+    pub fn with_blinder(
+        method: CloseMethod,
+        outpoint: OutPoint,
+        blinder: &mut impl Blinder,
+    ) -> RevealedSeal {
+        RevealedSeal::with(method, Some(outpoint.txid), outpoint.vout, blinder)
     }
 
-    /// Constructs seal using the provided random number generator for creating
-    /// blinding factor value
+    /// Constructs seal taking blinding factor from the provided `blinder`,
+    /// which may be any random number generator.
     #[inline]
     pub fn with(
         method: CloseMethod,
         txid: Option<Txid>,
-        vout: u32,
-        rng: &mut impl RngCore,
+        vout: impl Into<Vout>,
+        blinder: &mut impl Blinder,
     ) -> RevealedSeal {
         RevealedSeal {
             method,
             txid,
-            vout,
-            blinding: rng.next_u64(),
+            vout: vout.into(),
+            blinding: blinder.blinding(),
+        }
+    }
+
+    /// Constructs seal for the provided outpoint and seal closing method,
+    /// deterministically deriving blinding factor from the provided `seed` and
+    /// `index`.
+    ///
+    /// The blinding factor is computed as the first 8 bytes (interpreted as a
+    /// little-endian integer) of the BIP-340-style tagged hash with the
+    /// [`SEAL_BLINDING_TAG`] tag (see [`SealBlindingTag`]) of the seed,
+    /// followed by the outpoint txid, vout and `index`, all serialized
+    /// in little-endian order. Thus, the same inputs always produce the same
+    /// seal on all platforms, while distinct indexes produce distinct blinding
+    /// factors, allowing wallets to restore their seals from a seed.
+    pub fn with_deterministic_blinding(
+        method: CloseMethod,
+        outpoint: OutPoint,
+        seed: &[u8],
+        index: u64,
+    ) -> RevealedSeal {
+        let mut engine = sha256t::Hash::<SealBlindingTag>::engine();
+        engine.input(seed);
+        engine.input(&outpoint.txid[..]);
+        engine.input(&outpoint.vout.to_le_bytes()[..]);
+        engine.input(&index.to_le_bytes()[..]);
+        let hash = sha256t::Hash::<SealBlindingTag>::from_engine(engine);
+        let mut blinding = [0u8; 8];
+        blinding.copy_from_slice(&hash[..8]);
+        RevealedSeal {
+            method,
+            txid: Some(outpoint.txid),
+            vout: outpoint.vout.into(),
+            blinding: u64::from_le_bytes(blinding),
+        }
+    }
+
+    /// Converts revealed seal into [`ExplicitSeal`], dropping the blinding
+    /// factor.
+    #[inline]
+    pub fn to_explicit(&self) -> ExplicitSeal {
+        ExplicitSeal {
+            method: self.method,
+            txid: self.txid,
+            vout: self.vout,
         }
     }
 
-    /// Converts revealed seal into concealed.
+    /// Converts revealed seal into concealed, using the v2 derivation (see
+    /// [`RevealedSeal::conceal`]).
+    #[inline]
+    pub fn to_concealed_seal(&self) -> ConcealedSeal { self.conceal() }
+
+    /// Returns blinding factor of the seal.
+    #[inline]
+    pub fn blinding(&self) -> u64 { self.blinding }
+
+    /// Replaces blinding factor of the seal.
+    #[inline]
+    pub fn set_blinding(&mut self, blinding: u64) { self.blinding = blinding }
+
+    /// Compares seals in constant time, which does not depend on the values
+    /// of the seal fields (including the secret blinding factor).
+    ///
+    /// All of the fields are compared without branching, so the result
+    /// matches the derived [`PartialEq`] implementation.
+    pub fn ct_eq(&self, other: &RevealedSeal) -> bool {
+        let txid = |seal: &RevealedSeal| seal.txid.unwrap_or_default();
+        let diff = txid(self)
+            .iter()
+            .zip(txid(other).iter())
+            .fold(0u64, |diff, (a, b)| diff | (a ^ b) as u64)
+            | (self.method as u8 ^ other.method as u8) as u64
+            | (self.txid.is_some() as u8 ^ other.txid.is_some() as u8) as u64
+            | (self.vout.into_u32() ^ other.vout.into_u32()) as u64
+            | (self.blinding ^ other.blinding);
+        diff == 0
+    }
+
+    /// Conceals the seal using the v2 derivation: [`CONCEALED_SEAL_TAG`]
+    /// BIP-340-style tagged hash of the [`RevealedSeal::VERSION`] byte
+    /// followed by the strict-encoded seal fields, i.e. of the seal strict
+    /// encoding without [`RevealedSeal::VERSION_MARKER`].
+    ///
+    /// This is the canonical derivation used by all of the library APIs
+    /// producing concealed seals, including the [`CommitConceal`]
+    /// implementation, [`conceal_all`] and the FFI and WASM bindings.
+    ///
+    /// Unlike the legacy derivation, the v2 one commits to the encoding
+    /// version and distinguishes seals over the witness transaction outputs
+    /// from the seals over the all-zero txid.
+    ///
+    /// The tagged hash prefix is not recomputed: the engine is initialized
+    /// from the precomputed [`CONCEALED_SEAL_TAG`] midstate.
+    #[inline]
+    pub fn conceal(&self) -> ConcealedSeal {
+        self.conceal_with_engine(&RevealedSeal::conceal_engine())
+    }
+
+    /// Returns [`ConcealEngine`] initialized with the [`CONCEALED_SEAL_TAG`]
+    /// midstate, which can be used with [`RevealedSeal::conceal_with_engine`].
+    #[inline]
+    pub fn conceal_engine() -> ConcealEngine {
+        ConcealEngine(sha256t::Hash::<ConcealedSealV2Tag>::engine())
+    }
+
+    /// Conceals the seal using the v2 derivation, like
+    /// [`RevealedSeal::conceal`], with the cached `engine` midstate. The
+    /// engine is not modified, so a single engine can be reused for
+    /// concealing many seals.
+    pub fn conceal_with_engine(&self, engine: &ConcealEngine) -> ConcealedSeal {
+        let mut engine = engine.0.clone();
+        RevealedSeal::VERSION
+            .strict_encode(&mut engine)
+            .and_then(|_| self.strict_encode_fields(&mut engine))
+            .expect("hash engines do not error");
+        ConcealedSeal(sha256t::Hash::from_engine(engine))
+    }
+
+    /// Conceals the seal using the legacy derivation with
+    /// [`CONCEALED_SEAL_LEGACY_TAG`] tagged hash of the seal fields. The
+    /// derivation is not used by any other API of the library; seals
+    /// concealed with it can be checked with
+    /// [`ConcealedSeal::verify_legacy_reveal`].
+    #[deprecated(
+        since = "0.8.1",
+        note = "use `RevealedSeal::conceal`; the legacy derivation is kept \
+                only for verifying previously concealed seals"
+    )]
+    #[inline]
+    pub fn conceal_legacy(&self) -> ConcealedSeal {
+        ConcealedSeal::with_legacy_engine(
+            sha256t::Hash::<ConcealedSealTag>::engine(),
+            self,
+        )
+    }
+}
+
+/// Tag of the deterministic blinding factor derivation, see
+/// [`RevealedSeal::with_deterministic_blinding`].
+pub const SEAL_BLINDING_TAG: &str = "bp:txout:blinding";
+
+static MIDSTATE_SEAL_BLINDING: [u8; 32] = [
+    136, 74, 107, 45, 152, 224, 139, 29, 13, 228, 38, 131, 179, 87, 24, 199,
+    179, 196, 3, 197, 93, 139, 40, 82, 196, 117, 197, 66, 240, 98, 220, 78,
+];
+
+/// Tag used for deterministic derivation of [`RevealedSeal`] blinding factors
+/// by [`RevealedSeal::with_deterministic_blinding`].
+pub struct SealBlindingTag;
+
+impl sha256t::Tag for SealBlindingTag {
     #[inline]
-    pub fn to_concealed_seal(&self) -> ConcealedSeal { self.commit_conceal() }
+    fn engine() -> sha256::HashEngine {
+        let midstate = sha256::Midstate::from_inner(MIDSTATE_SEAL_BLINDING);
+        sha256::HashEngine::from_midstate(midstate, 64)
+    }
 }
 
 /// Errors happening during parsing string representation of different forms of
@@ -209,105 +583,224 @@ pub enum ParseError {
     #[from]
     WrongMethod(MethodParseError),
 
-    /// unable to parse blinding value; it must be a hexadecimal string
-    /// starting with `0x`
+    /// unable to parse blinding value; it must be a 64-bit unsigned integer
+    /// in decimal form or in hexadecimal form starting with `0x`
     WrongBlinding,
 
-    /// unable to parse transaction id value; it must be 64-character
-    /// hexadecimal string
-    WrongTxid,
+    /// unable to parse transaction id value, which must be 64-character
+    /// hexadecimal string, {0}
+    WrongTxid(ParseContext),
 
-    /// unable to parse transaction vout value; it must be a decimal unsigned
-    /// integer
-    WrongVout,
+    /// unable to parse transaction vout value, which must be a decimal
+    /// unsigned integer, {0}
+    WrongVout(ParseContext),
 
-    /// wrong structure of seal string representation
-    WrongStructure,
+    /// transaction output number {0} exceeds the limit on the number of
+    /// transaction outputs
+    VoutOutOfRange(u32),
 
-    /// blinding secret must be represented by a 64-bit hexadecimal value
-    /// starting with `0x` and not with a decimal
-    NonHexBlinding,
+    /// wrong structure of seal string representation {0}
+    WrongStructure(ParseContext),
 
     /// wrong Bech32 representation of the blinded TxOut seal – {0}
     #[from]
     Bech32(lnpbp_bech32::Error),
+
+    /// Bech32 string with `{0}` HRP is not a blinded TxOut seal, which must
+    /// use `txob` HRP or one of its network-specific variants
+    WrongHrp(String),
+
+    /// blinded TxOut seal is expected to belong to {expected} network, while
+    /// it uses HRP of {found}
+    NetworkMismatch {
+        /// Network which the seal was expected to belong to.
+        expected: Network,
+        /// Network which HRP is used by the seal string.
+        found: Network,
+    },
+
+    /// seal URI must start with `seal:` scheme
+    UriSchemeRequired,
+
+    /// wrong percent-encoding in the seal URI
+    WrongPercentEncoding,
 }
 
 impl FromStr for RevealedSeal {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(&[':', '#'][..]);
-        match (
-            split.next(),
-            split.next(),
-            split.next(),
-            split.next(),
-            split.next(),
-        ) {
-            (Some("~"), ..) | (Some(""), ..) => Err(ParseError::MethodRequired),
-            (Some(_), Some(""), ..) => Err(ParseError::TxidRequired),
-            (Some(_), Some(_), None, ..) if s.contains(':') => {
-                Err(ParseError::BlindingRequired)
+        let (method, rest) = split_field(s);
+        if method.is_empty() || method == "~" {
+            return Err(ParseError::MethodRequired);
+        }
+        let rest = rest
+            .ok_or_else(|| ParseError::WrongStructure(ParseContext::end(s)))?;
+        let (txid, rest) = split_field(rest);
+        if txid.is_empty() {
+            return Err(ParseError::TxidRequired);
+        }
+        let (vout, blinding) = match rest {
+            Some(rest) => split_field(rest),
+            None if s.contains(':') => {
+                return Err(ParseError::BlindingRequired)
             }
-            (Some(_), Some(_), Some(_), Some(blinding), None)
-                if !blinding.starts_with("0x") =>
-            {
-                Err(ParseError::NonHexBlinding)
+            None => {
+                return Err(ParseError::WrongStructure(ParseContext::end(s)))
             }
-            (Some(method), Some("~"), Some(vout), Some(blinding), None) => {
-                Ok(RevealedSeal {
-                    method: method.parse()?,
-                    blinding: u64::from_str_radix(
-                        blinding.trim_start_matches("0x"),
-                        16,
-                    )
-                    .map_err(|_| ParseError::WrongBlinding)?,
-                    txid: None,
-                    vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
-                })
+        };
+        let blinding = match blinding {
+            Some(blinding) if !blinding.contains(SEPARATORS) => blinding,
+            Some(blinding) => {
+                return Err(ParseError::WrongStructure(
+                    ParseContext::separator(s, blinding),
+                ))
             }
-            (Some(method), Some(txid), Some(vout), Some(blinding), None) => {
-                Ok(RevealedSeal {
-                    method: method.parse()?,
-                    blinding: u64::from_str_radix(
-                        blinding.trim_start_matches("0x"),
-                        16,
-                    )
-                    .map_err(|_| ParseError::WrongBlinding)?,
-                    txid: Some(
-                        txid.parse().map_err(|_| ParseError::WrongTxid)?,
-                    ),
-                    vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
-                })
+            None => {
+                return Err(ParseError::WrongStructure(ParseContext::end(s)))
             }
-            _ => Err(ParseError::WrongStructure),
+        };
+        if blinding == REDACTED_BLINDING {
+            return Err(ParseError::BlindingRequired);
         }
+        Ok(RevealedSeal {
+            method: method.parse()?,
+            blinding: parse_blinding(blinding)
+                .ok_or(ParseError::WrongBlinding)?,
+            txid: match txid {
+                "~" => None,
+                txid => Some(parse_txid(txid).ok_or_else(|| {
+                    ParseError::WrongTxid(ParseContext::with(s, txid))
+                })?),
+            },
+            vout: match vout.parse() {
+                Ok(vout) if vout > MAX_VOUT => {
+                    return Err(ParseError::VoutOutOfRange(vout))
+                }
+                Ok(vout) => Vout::from(vout),
+                Err(_) => {
+                    return Err(ParseError::WrongVout(ParseContext::with(
+                        s, vout,
+                    )))
+                }
+            },
+        })
+    }
+}
+
+/// Parses blinding factor either from its decimal representation or from the
+/// hexadecimal one prefixed with `0x`. Signs and empty values are not allowed,
+/// and values not fitting into 64 bits are rejected.
+fn parse_blinding(s: &str) -> Option<u64> {
+    let (digits, radix) = match s.strip_prefix("0x") {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
     }
+    u64::from_str_radix(digits, radix).ok()
 }
 
+/// Placeholder used instead of the blinding factor value by the alternate
+/// (`{:#}`) [`Display`] form of [`RevealedSeal`].
+pub const REDACTED_BLINDING: &str = "********";
+
 impl Display for RevealedSeal {
+    /// Formats seal as `method:txid:vout#blinding`, where the blinding factor
+    /// is always written in the canonical decimal form (while [`FromStr`]
+    /// also accepts its hexadecimal form starting with `0x`). The alternate
+    /// form (`{:#}`) redacts the blinding factor with [`REDACTED_BLINDING`],
+    /// which is suitable for logs but can't be parsed back.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}:{}:{}#{:#010x}",
+            "{}:{}:{}#",
             self.method,
             self.txid
                 .as_ref()
                 .map(Txid::to_string)
                 .unwrap_or_else(|| s!("~")),
             self.vout,
-            self.blinding
-        )
+        )?;
+        if f.alternate() {
+            f.write_str(REDACTED_BLINDING)
+        } else {
+            write!(f, "{}", self.blinding)
+        }
+    }
+}
+
+impl RevealedSeal {
+    /// Returns compact representation of the seal for logs in form of
+    /// `method:txid_prefix…txid_suffix:vout`, containing the first and the
+    /// last four bytes of the txid and not containing the blinding factor.
+    /// The representation can't be parsed back.
+    #[inline]
+    pub fn to_compact_string(&self) -> String { self.redacted().to_string() }
+}
+
+/// Human-readable part of the Bech32 representation of [`ConcealedSeal`].
+///
+/// This is the HRP for the bitcoin mainnet, which is also used by the bare
+/// [`Display`] and [`FromStr`] implementations; see [`concealed_seal_hrp`] for
+/// HRPs of other networks.
+pub const CONCEALED_SEAL_HRP: &str = "txob";
+
+/// Human-readable part of the Bech32 representation of [`ConcealedSeal`] on
+/// bitcoin testnet.
+pub const CONCEALED_SEAL_HRP_TESTNET: &str = "txobtest";
+
+/// Human-readable part of the Bech32 representation of [`ConcealedSeal`] on
+/// bitcoin signet.
+pub const CONCEALED_SEAL_HRP_SIGNET: &str = "txobsig";
+
+/// Human-readable part of the Bech32 representation of [`ConcealedSeal`] on
+/// bitcoin regtest.
+pub const CONCEALED_SEAL_HRP_REGTEST: &str = "txobreg";
+
+/// Returns human-readable part of the Bech32 representation of
+/// [`ConcealedSeal`] for the given `network`.
+pub fn concealed_seal_hrp(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => CONCEALED_SEAL_HRP,
+        Network::Testnet => CONCEALED_SEAL_HRP_TESTNET,
+        Network::Signet => CONCEALED_SEAL_HRP_SIGNET,
+        Network::Regtest => CONCEALED_SEAL_HRP_REGTEST,
     }
 }
 
+fn hrp_network(hrp: &str) -> Option<Network> {
+    [
+        Network::Bitcoin,
+        Network::Testnet,
+        Network::Signet,
+        Network::Regtest,
+    ]
+    .into_iter()
+    .find(|network| concealed_seal_hrp(*network) == hrp)
+}
+
+/// Tag of the legacy [`ConcealedSeal`] derivation, see
+/// [`RevealedSeal::conceal_legacy`].
+pub const CONCEALED_SEAL_LEGACY_TAG: &str = "bp:txout:concealed";
+
+/// Tag of the v2 [`ConcealedSeal`] derivation, see [`RevealedSeal::conceal`].
+pub const CONCEALED_SEAL_TAG: &str = "urn:lnpbp:seals:v1";
+
+static MIDSTATE_CONCEALED_SEAL_V2: [u8; 32] = [
+    128, 149, 125, 186, 64, 227, 46, 142, 62, 80, 130, 65, 203, 216, 66, 230,
+    159, 15, 12, 203, 41, 48, 99, 232, 41, 138, 18, 250, 212, 118, 89, 249,
+];
+
 static MIDSTATE_CONCEALED_SEAL: [u8; 32] = [
     250, 13, 163, 5, 178, 220, 248, 173, 139, 222, 67, 198, 134, 127, 63, 153,
     147, 236, 172, 33, 17, 167, 176, 30, 70, 99, 185, 129, 217, 110, 183, 27,
 ];
 
-/// Tag used for [`ConcealedSeal`] hash type
+/// Tag of the legacy [`ConcealedSeal`] derivation with the
+/// [`CONCEALED_SEAL_LEGACY_TAG`] midstate, used only by
+/// [`RevealedSeal::conceal_legacy`].
 pub struct ConcealedSealTag;
 
 impl sha256t::Tag for ConcealedSealTag {
@@ -319,20 +812,67 @@ impl sha256t::Tag for ConcealedSealTag {
 }
 
 impl lnpbp_bech32::Strategy for ConcealedSealTag {
-    const HRP: &'static str = "txob";
+    const HRP: &'static str = CONCEALED_SEAL_HRP;
     type Strategy = lnpbp_bech32::strategies::UsingStrictEncoding;
 }
 
+/// Tag of the v2 [`ConcealedSeal`] derivation with the [`CONCEALED_SEAL_TAG`]
+/// midstate, used for the [`ConcealedSeal`] hash type: hashing the seal
+/// strict encoding without [`RevealedSeal::VERSION_MARKER`] with it produces
+/// the same result as [`RevealedSeal::conceal`].
+pub struct ConcealedSealV2Tag;
+
+impl sha256t::Tag for ConcealedSealV2Tag {
+    #[inline]
+    fn engine() -> sha256::HashEngine {
+        let midstate = sha256::Midstate::from_inner(MIDSTATE_CONCEALED_SEAL_V2);
+        sha256::HashEngine::from_midstate(midstate, 64)
+    }
+}
+
 /// Blind version of transaction outpoint-based single-use-seal
-#[derive(
-    Wrapper, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
-    Display, From
-)]
+///
+/// Concealed seals are ordered lexicographically by their byte representation
+/// (see [`ConcealedSeal::to_byte_array`]), which is also the way they are
+/// strict-encoded; thus the order of the seals used as map keys is stable
+/// across library versions and encodings.
+#[derive(Wrapper, Copy, Clone, PartialEq, Eq, Hash, Default, Display, From)]
 #[wrapper(Debug, LowerHex, Index, IndexRange, IndexFrom, IndexTo, IndexFull)]
 #[display(ConcealedSeal::to_bech32_string)]
-pub struct ConcealedSeal(sha256t::Hash<ConcealedSealTag>);
+pub struct ConcealedSeal(sha256t::Hash<ConcealedSealV2Tag>);
+
+impl Ord for ConcealedSeal {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_byte_array().cmp(&other.to_byte_array())
+    }
+}
+
+impl PartialOrd for ConcealedSeal {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ConcealedSeal {
+    /// Returns byte representation of the concealed seal, matching its strict
+    /// encoding.
+    #[inline]
+    pub fn to_byte_array(&self) -> [u8; 32] { self.0.into_inner() }
+
+    /// Constructs concealed seal from its byte representation produced by
+    /// [`ConcealedSeal::to_byte_array`].
+    #[inline]
+    pub fn from_byte_array(bytes: [u8; 32]) -> ConcealedSeal {
+        ConcealedSeal(sha256t::Hash::from_inner(bytes))
+    }
+}
 
-// TODO: Make this part of `lnpbp::bech32`
+/// Serializes concealed seal as a hexadecimal string of its bytes for
+/// human-readable formats and as raw bytes for binary formats.
+/// Deserialization from human-readable formats also accepts Bech32m (and
+/// legacy Bech32) strings produced by the previous versions of the library.
 #[cfg(feature = "serde")]
 impl serde::Serialize for ConcealedSeal {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -340,7 +880,7 @@ impl serde::Serialize for ConcealedSeal {
         S: serde::Serializer,
     {
         if serializer.is_human_readable() {
-            serializer.serialize_str(&self.to_bech32_string())
+            serializer.serialize_str(&self.to_byte_array().to_hex())
         } else {
             serializer.serialize_bytes(&self[..])
         }
@@ -361,16 +901,35 @@ impl<'de> serde::Deserialize<'de> for ConcealedSeal {
                 &self,
                 formatter: &mut std::fmt::Formatter,
             ) -> std::fmt::Result {
-                formatter.write_str("Bech32 string with `txob` HRP")
+                write!(
+                    formatter,
+                    "32-byte hexadecimal string or Bech32m string with `{}` \
+                     HRP",
+                    CONCEALED_SEAL_HRP
+                )
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
             {
+                if let Ok(bytes) = <[u8; 32]>::from_hex(v) {
+                    return Ok(ConcealedSeal::from_byte_array(bytes));
+                }
                 ConcealedSeal::from_str(v).map_err(serde::de::Error::custom)
             }
 
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                <[u8; 32]>::try_from(v)
+                    .map(ConcealedSeal::from_byte_array)
+                    .map_err(|_| {
+                        serde::de::Error::invalid_length(v.len(), &"32 bytes")
+                    })
+            }
+
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
@@ -382,9 +941,7 @@ impl<'de> serde::Deserialize<'de> for ConcealedSeal {
             where
                 E: serde::de::Error,
             {
-                ConcealedSeal::from_bytes(&v).map_err(|_| {
-                    serde::de::Error::invalid_length(v.len(), &"32 bytes")
-                })
+                self.visit_bytes(&v)
             }
         }
 
@@ -399,85 +956,798 @@ impl<'de> serde::Deserialize<'de> for ConcealedSeal {
 impl FromStr for ConcealedSeal {
     type Err = ParseError;
 
+    /// Parses Bech32m representation of the concealed seal, checking that it
+    /// uses [`CONCEALED_SEAL_HRP`]. Strings with the legacy Bech32 checksum
+    /// are accepted as well; use [`SealBech32`] to detect them.
+    ///
+    /// Strings using HRPs of other networks result in
+    /// [`ParseError::NetworkMismatch`]; use
+    /// [`ConcealedSeal::parse_for_network`] to parse them.
+    #[inline]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(ConcealedSeal::from_bech32_str(s)?)
+        ConcealedSeal::parse_for_network(s, Network::Bitcoin)
     }
 }
 
-impl From<OutPoint> for ConcealedSeal {
-    #[inline]
-    fn from(outpoint: OutPoint) -> Self {
-        RevealedSeal::from(outpoint).commit_conceal()
+/// Bech32m representation of [`ConcealedSeal`] using HRP of a specific
+/// network, returned by [`ConcealedSeal::display_for`].
+struct NetworkDisplay {
+    seal: ConcealedSeal,
+    network: Network,
+}
+
+impl Display for NetworkDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        bech32::encode_to_fmt(
+            f,
+            concealed_seal_hrp(self.network),
+            self.seal.to_byte_array().to_base32(),
+            Variant::Bech32m,
+        )
+        .expect("concealed seal HRPs are valid")
     }
 }
 
-impl strict_encoding::Strategy for ConcealedSeal {
-    type Strategy = strict_encoding::strategies::Wrapped;
+impl ConcealedSeal {
+    /// Returns Bech32m representation of the concealed seal using HRP of the
+    /// given `network` (see [`concealed_seal_hrp`]). For the bitcoin mainnet
+    /// the representation matches the one produced by [`Display`].
+    pub fn display_for(&self, network: Network) -> impl Display {
+        NetworkDisplay {
+            seal: *self,
+            network,
+        }
+    }
+
+    /// Parses Bech32 representation of the concealed seal, requiring it to
+    /// use HRP of the `expected` network. Strings using HRPs of other networks
+    /// result in [`ParseError::NetworkMismatch`].
+    pub fn parse_for_network(
+        s: &str,
+        expected: Network,
+    ) -> Result<ConcealedSeal, ParseError> {
+        let seal = SealBech32::from_str(s)?;
+        if seal.network != expected {
+            return Err(ParseError::NetworkMismatch {
+                expected,
+                found: seal.network,
+            });
+        }
+        Ok(seal.seal)
+    }
 }
 
-impl commit_encode::Strategy for ConcealedSeal {
-    type Strategy = commit_encode::strategies::UsingStrict;
+/// Concealed seal parsed from its Bech32 representation together with the
+/// checksum variant and the network of the HRP used by the parsed string.
+///
+/// The concealed seals are always displayed using Bech32m checksum; the legacy
+/// Bech32 checksum is accepted by the parser for compatibility with the old
+/// invoices and can be detected with [`SealBech32::encoding`]. The seal is
+/// displayed using HRP of its network.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SealBech32 {
+    seal: ConcealedSeal,
+    encoding: Variant,
+    network: Network,
 }
 
-impl CommitVerify<RevealedSeal, Lnpbp6> for ConcealedSeal {
-    fn commit(reveal: &RevealedSeal) -> Self {
-        let mut engine = sha256t::Hash::<ConcealedSealTag>::engine();
-        engine.input(&[reveal.method as u8]);
-        engine.input(&reveal.txid.unwrap_or_default()[..]);
-        engine.input(&reveal.vout.to_le_bytes()[..]);
-        engine.input(&reveal.blinding.to_le_bytes()[..]);
-        let inner = sha256t::Hash::<ConcealedSealTag>::from_engine(engine);
-        ConcealedSeal::from_hash(inner)
+impl Display for SealBech32 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.seal.display_for(self.network), f)
     }
 }
 
-impl lnpbp_bech32::Strategy for ConcealedSeal {
-    const HRP: &'static str = "txob";
-    type Strategy = lnpbp_bech32::strategies::UsingStrictEncoding;
+impl From<ConcealedSeal> for SealBech32 {
+    #[inline]
+    fn from(seal: ConcealedSeal) -> Self {
+        SealBech32 {
+            seal,
+            encoding: Variant::Bech32m,
+            network: Network::Bitcoin,
+        }
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use amplify::Wrapper;
-    use bitcoin::hashes::hex::FromHex;
-    use commit_verify::tagged_hash;
+impl SealBech32 {
+    /// Returns concealed seal.
+    #[inline]
+    pub fn seal(self) -> ConcealedSeal { self.seal }
 
-    use super::*;
+    /// Returns checksum variant used by the parsed string.
+    #[inline]
+    pub fn encoding(self) -> Variant { self.encoding }
 
-    #[test]
-    fn outpoint_hash_midstate() {
-        let midstate = tagged_hash::Midstate::with(b"bp:txout:concealed");
-        assert_eq!(midstate.into_inner().into_inner(), MIDSTATE_CONCEALED_SEAL);
+    /// Detects whether the parsed string uses the legacy Bech32 checksum.
+    #[inline]
+    pub fn is_legacy(self) -> bool { self.encoding == Variant::Bech32 }
+
+    /// Returns network which HRP is used by the parsed string.
+    #[inline]
+    pub fn network(self) -> Network { self.network }
+}
+
+impl FromStr for SealBech32 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, encoding) =
+            bech32::decode(s).map_err(lnpbp_bech32::Error::from)?;
+        let network = hrp_network(&hrp).ok_or(ParseError::WrongHrp(hrp))?;
+        let data =
+            Vec::<u8>::from_base32(&data).map_err(lnpbp_bech32::Error::from)?;
+        let seal = ConcealedSeal::strict_deserialize(data)
+            .map_err(lnpbp_bech32::Error::from)?;
+        Ok(SealBech32 {
+            seal,
+            encoding,
+            network,
+        })
     }
+}
 
-    #[test]
-    fn outpoint_hash_is_sha256d() {
-        let reveal = RevealedSeal {
-            method: CloseMethod::TapretFirst,
-            blinding: 54683213134637,
-            txid: Some(Txid::from_hex("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839").unwrap()),
-            vout: 2,
-        };
-        let outpoint_hash = reveal.to_concealed_seal();
-        let mut engine = sha256t::Hash::<ConcealedSealTag>::engine();
+impl ConcealedSeal {
+    /// Returns URI representation of the concealed seal in form of
+    /// `seal:<bech32m>`.
+    pub fn to_uri(&self) -> String {
+        format!("{}:{}", SEAL_URI_SCHEME, self.to_bech32_string())
+    }
+
+    /// Parses URI representation of the concealed seal produced by
+    /// [`ConcealedSeal::to_uri`].
+    pub fn from_uri(uri: &str) -> Result<ConcealedSeal, ParseError> {
+        let path = uri::strip_scheme(uri)?;
+        ConcealedSeal::from_str(&uri::percent_decode(path)?)
+    }
+}
+
+impl ConcealedSeal {
+    /// Checks that the `revealed` seal is a reveal of this concealed seal,
+    /// i.e. that it conceals to this seal with the v2 derivation
+    /// ([`RevealedSeal::conceal`]), used by all of the library APIs.
+    ///
+    /// The comparison is performed in constant time, which does not depend
+    /// on the seal data.
+    #[inline]
+    pub fn verify_reveal(&self, revealed: &RevealedSeal) -> bool {
+        self.ct_eq(revealed.conceal())
+    }
+
+    /// Checks that the `revealed` seal is a reveal of this concealed seal
+    /// produced with the legacy derivation
+    /// ([`RevealedSeal::conceal_legacy`]) by the previous versions of the
+    /// library. Like [`ConcealedSeal::verify_reveal`], the comparison is
+    /// performed in constant time.
+    #[inline]
+    #[allow(deprecated)]
+    pub fn verify_legacy_reveal(&self, revealed: &RevealedSeal) -> bool {
+        self.ct_eq(revealed.conceal_legacy())
+    }
+
+    fn ct_eq(&self, other: ConcealedSeal) -> bool {
+        self.to_byte_array()
+            .iter()
+            .zip(other.to_byte_array().iter())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+
+    /// Checks that the `revealed` seal is a reveal of this concealed seal like
+    /// [`ConcealedSeal::verify_reveal`], returning [`RevealMismatch`] with the
+    /// v2 concealed form of the `revealed` seal when it is not.
+    pub fn require_reveal(
+        &self,
+        revealed: &RevealedSeal,
+    ) -> Result<(), RevealMismatch> {
+        if self.verify_reveal(revealed) {
+            Ok(())
+        } else {
+            Err(RevealMismatch {
+                expected: *self,
+                actual: revealed.conceal(),
+            })
+        }
+    }
+}
+
+/// Checks all of the concealed seals against the seals claimed to be their
+/// reveals (for instance, provided as a map from the concealed seals to the
+/// revealed ones), reporting all of the mismatches at once in the order of
+/// `reveals`.
+///
+/// See [`ConcealedSeal::require_reveal`] for details.
+pub fn verify_reveals<'seals>(
+    reveals: impl IntoIterator<Item = (&'seals ConcealedSeal, &'seals RevealedSeal)>,
+) -> Result<(), Vec<RevealMismatch>> {
+    let mismatches = reveals
+        .into_iter()
+        .filter_map(|(concealed, revealed)| {
+            concealed.require_reveal(revealed).err()
+        })
+        .collect::<Vec<_>>();
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+impl From<OutPoint> for ConcealedSeal {
+    #[inline]
+    fn from(outpoint: OutPoint) -> Self {
+        RevealedSeal::from(outpoint).conceal()
+    }
+}
+
+impl DumbDefault for ConcealedSeal {
+    /// Returns placeholder seal consisting of all-zero bytes. It can't be
+    /// produced by concealing any revealed seal (including
+    /// [`RevealedSeal::dumb_default`]) without breaking the hash function.
+    fn dumb_default() -> Self { ConcealedSeal::from_byte_array([0u8; 32]) }
+}
+
+impl strict_encoding::Strategy for ConcealedSeal {
+    type Strategy = strict_encoding::strategies::Wrapped;
+}
+
+impl commit_encode::Strategy for ConcealedSeal {
+    type Strategy = commit_encode::strategies::UsingStrict;
+}
+
+impl CommitVerify<RevealedSeal, Lnpbp6> for ConcealedSeal {
+    #[inline]
+    fn commit(reveal: &RevealedSeal) -> Self { reveal.conceal() }
+}
+
+impl ConcealedSeal {
+    fn with_legacy_engine(
+        mut engine: sha256::HashEngine,
+        reveal: &RevealedSeal,
+    ) -> ConcealedSeal {
+        engine.input(&[reveal.method as u8]);
+        engine.input(&reveal.txid.unwrap_or_default()[..]);
+        engine.input(&reveal.vout.into_u32().to_le_bytes()[..]);
+        engine.input(&reveal.blinding.to_le_bytes()[..]);
+        let inner = sha256t::Hash::<ConcealedSealTag>::from_engine(engine);
+        ConcealedSeal::from_byte_array(inner.into_inner())
+    }
+}
+
+/// Hash engine for the v2 seal concealment holding the precomputed
+/// [`CONCEALED_SEAL_TAG`] midstate. Can be constructed only with
+/// [`RevealedSeal::conceal_engine`] and used with
+/// [`RevealedSeal::conceal_with_engine`].
+#[derive(Clone)]
+pub struct ConcealEngine(sha256::HashEngine);
+
+impl Debug for ConcealEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConcealEngine")
+            .field(&CONCEALED_SEAL_TAG)
+            .finish()
+    }
+}
+
+/// Conceals all provided seals with [`RevealedSeal::conceal`], returning
+/// concealed seals in the same order.
+///
+/// The function initializes tagged hash engine only once, reusing it for all
+/// the seals. No deduplication is performed: duplicated revealed seals produce
+/// equal concealed seals.
+pub fn conceal_all<'seal>(
+    seals: impl IntoIterator<Item = &'seal RevealedSeal>,
+) -> Vec<ConcealedSeal> {
+    let engine = RevealedSeal::conceal_engine();
+    seals
+        .into_iter()
+        .map(|reveal| reveal.conceal_with_engine(&engine))
+        .collect()
+}
+
+/// Tag prefix used for the LNPBP-81 merklization of the concealed seal sets by
+/// [`commitment_of_seal_set`].
+pub const SEAL_SET_MERKLE_PREFIX: &str = "seals";
+
+/// Conceals all provided seals, returning concealed seals sorted by their byte
+/// representation (see [`ConcealedSeal::to_byte_array`]) without duplicates.
+///
+/// Unlike [`conceal_all`], the result does not depend on the order of the
+/// provided seals and on the number of times each of the seals is repeated,
+/// so it can be used for constructing commitments to seal sets.
+pub fn conceal_sorted_dedup<'seal>(
+    seals: impl IntoIterator<Item = &'seal RevealedSeal>,
+) -> Vec<ConcealedSeal> {
+    let mut concealed = conceal_all(seals);
+    concealed.sort_unstable();
+    concealed.dedup();
+    concealed
+}
+
+/// Computes commitment to a set of seals as LNPBP-81 merkle root (tagged with
+/// [`SEAL_SET_MERKLE_PREFIX`]) over the concealed seals produced by
+/// [`conceal_sorted_dedup`]. The commitment is stable under reordering and
+/// duplication of the seals; empty set produces a valid commitment as well.
+pub fn commitment_of_seal_set(seals: &[RevealedSeal]) -> [u8; 32] {
+    let leaves = conceal_sorted_dedup(seals)
+        .into_iter()
+        .map(|seal| MerkleNode::from_inner(seal.to_byte_array()));
+    let (root, _) = merklize(SEAL_SET_MERKLE_PREFIX, leaves);
+    root.into_inner()
+}
+
+impl lnpbp_bech32::Strategy for ConcealedSeal {
+    const HRP: &'static str = CONCEALED_SEAL_HRP;
+    type Strategy = lnpbp_bech32::strategies::UsingStrictEncoding;
+}
+
+/// Alias for [`ConcealedSeal`], which is the only seal form which may be
+/// published or put into consignments.
+pub type SecretSeal = ConcealedSeal;
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for super::Confidential {}
+    impl Sealed for super::Revealed {}
+    impl Sealed for super::ConcealedSeal {}
+    impl Sealed for super::RevealedSeal {}
+}
+
+/// State of the seal data, used to require the seals in a specific state at
+/// the type level with [`Seal`]. The trait is sealed and is implemented only
+/// by [`Confidential`] and [`Revealed`].
+pub trait SealState: private::Sealed {
+    /// Seal type holding data in this state.
+    type Seal: Copy + Eq + Ord + std::hash::Hash + Debug + Display;
+}
+
+/// State of the seals which do not reveal the seal definition.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Confidential {}
+
+/// State of the seals containing seal definition and blinding factor.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Revealed {}
+
+impl SealState for Confidential {
+    type Seal = ConcealedSeal;
+}
+
+impl SealState for Revealed {
+    type Seal = RevealedSeal;
+}
+
+/// Seal in a specific `State`: `Seal<Confidential>` is [`ConcealedSeal`] and
+/// `Seal<Revealed>` is [`RevealedSeal`].
+///
+/// APIs which must never receive revealed seals (like consignment fields
+/// meant to be public) should require `Seal<Confidential>` or
+/// [`SealConfidential`]. The only way to get there from the revealed state is
+/// [`RevealedSeal::conceal`]; there are no implicit conversions, which is
+/// checked by the examples below failing with the specific type errors:
+///
+/// ```compile_fail,E0308
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::blind::{Confidential, RevealedSeal, Seal};
+/// fn publish(seal: Seal<Confidential>) {}
+///
+/// let revealed = RevealedSeal::from(OutPoint::default());
+/// publish(revealed);
+/// ```
+///
+/// ```compile_fail,E0277
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::blind::{Confidential, RevealedSeal, Seal};
+/// let revealed = RevealedSeal::from(OutPoint::default());
+/// let concealed: Seal<Confidential> = revealed.into();
+/// ```
+///
+/// ```compile_fail,E0277
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::blind::{RevealedSeal, SealConfidential};
+/// fn publish(seal: impl SealConfidential) {}
+///
+/// publish(RevealedSeal::from(OutPoint::default()));
+/// ```
+///
+/// ```
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::blind::{Confidential, RevealedSeal, Seal};
+/// fn publish(seal: Seal<Confidential>) {}
+///
+/// let revealed = RevealedSeal::from(OutPoint::default());
+/// publish(revealed.conceal());
+/// ```
+pub type Seal<State> = <State as SealState>::Seal;
+
+/// Marker trait of the seal types which do not reveal the seal definition.
+/// The trait is sealed and is implemented only by [`ConcealedSeal`].
+pub trait SealConfidential: private::Sealed {}
+
+/// Marker trait of the seal types revealing seal definition and blinding
+/// factor. The trait is sealed and is implemented only by [`RevealedSeal`].
+pub trait SealRevealed: private::Sealed {}
+
+impl SealConfidential for ConcealedSeal {}
+
+impl SealRevealed for RevealedSeal {}
+
+#[cfg(test)]
+mod test {
+    use amplify::Wrapper;
+    use bitcoin::hashes::hex::{FromHex, ToHex};
+    use bitcoin::secp256k1::rand::RngCore;
+    use commit_verify::tagged_hash;
+
+    use super::*;
+
+    #[test]
+    fn outpoint_hash_midstate() {
+        let midstate =
+            tagged_hash::Midstate::with(CONCEALED_SEAL_LEGACY_TAG.as_bytes());
+        assert_eq!(midstate.into_inner().into_inner(), MIDSTATE_CONCEALED_SEAL);
+    }
+
+    #[test]
+    fn concealed_seal_v2_midstate() {
+        let midstate =
+            tagged_hash::Midstate::with(CONCEALED_SEAL_TAG.as_bytes());
+        assert_eq!(
+            midstate.into_inner().into_inner(),
+            MIDSTATE_CONCEALED_SEAL_V2
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn verify_reveal() {
+        let seal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(Txid::hash(b"funding")),
+            vout: 2u32.into(),
+            blinding: 54683969839,
+        };
+        let concealed = seal.conceal();
+        assert!(concealed.verify_reveal(&seal));
+        assert_eq!(concealed.require_reveal(&seal), Ok(()));
+        assert!(!concealed.verify_legacy_reveal(&seal));
+        // Seals concealed with the legacy derivation are accepted only by the
+        // explicit legacy check
+        let legacy = seal.conceal_legacy();
+        assert!(!legacy.verify_reveal(&seal));
+        assert!(legacy.verify_legacy_reveal(&seal));
+        assert!(legacy.require_reveal(&seal).is_err());
+
+        let mut wrong_blinding = seal;
+        wrong_blinding.blinding += 1;
+        let mut wrong_vout = seal;
+        wrong_vout.vout = 3u32.into();
+        let mut wrong_method = seal;
+        wrong_method.method = CloseMethod::OpretFirst;
+        let mut witness = seal;
+        witness.txid = None;
+        for revealed in [wrong_blinding, wrong_vout, wrong_method, witness] {
+            assert!(!concealed.verify_reveal(&revealed));
+            assert!(!legacy.verify_legacy_reveal(&revealed));
+            assert_eq!(
+                concealed.require_reveal(&revealed),
+                Err(RevealMismatch {
+                    expected: concealed,
+                    actual: revealed.conceal(),
+                })
+            );
+        }
+
+        let other = RevealedSeal::from(OutPoint::new(Txid::hash(b"other"), 0));
+        let reveals = bmap! {
+            concealed => seal,
+            other.conceal() => other
+        };
+        assert_eq!(verify_reveals(&reveals), Ok(()));
+        assert_eq!(verify_reveals(&bmap! {}), Ok(()));
+
+        let reveals = bmap! {
+            concealed => wrong_blinding,
+            other.conceal() => other,
+            witness.conceal() => wrong_vout
+        };
+        let mismatches = verify_reveals(&reveals).unwrap_err();
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches.contains(&RevealMismatch {
+            expected: concealed,
+            actual: wrong_blinding.conceal(),
+        }));
+        assert!(mismatches.contains(&RevealMismatch {
+            expected: witness.conceal(),
+            actual: wrong_vout.conceal(),
+        }));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn conceal_v2_vectors() {
+        let seal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(
+                Txid::from_hex(
+                    "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+                )
+                .unwrap(),
+            ),
+            vout: 2u32.into(),
+            blinding: 54683969839,
+        };
+        assert_eq!(
+            seal.conceal_legacy().to_byte_array().to_hex(),
+            "1ef42ef4ded9fa24c0398fac48bdda568258c7d5403c4a5a20c7423f6b76ec98"
+        );
+        assert_eq!(seal.conceal(), seal.commit_conceal());
+        assert_eq!(seal.conceal(), ConcealedSeal::commit(&seal));
+        assert_eq!(
+            seal.conceal().to_byte_array().to_hex(),
+            "08a492ab41942a4f7021c261ac249b895328bb77846f9965ff57153405b4b777"
+        );
+
+        // The v2 tagged hash is computed over the seal strict encoding
+        // without the version marker
+        let mut engine = sha256::Hash::engine();
+        let tag = sha256::Hash::hash(CONCEALED_SEAL_TAG.as_bytes());
+        engine.input(&tag[..]);
+        engine.input(&tag[..]);
+        let data = strict_serialize(&seal).unwrap();
+        assert_eq!(data[0], RevealedSeal::VERSION_MARKER);
+        engine.input(&data[1..]);
+        assert_eq!(
+            seal.conceal().to_byte_array(),
+            sha256::Hash::from_engine(engine).into_inner()
+        );
+
+        // Witness seals are distinguished from the all-zero txid only by v2
+        let witness = RevealedSeal { txid: None, ..seal };
+        let zero = RevealedSeal {
+            txid: Some(Txid::default()),
+            ..seal
+        };
+        assert_eq!(witness.conceal_legacy(), zero.conceal_legacy());
+        assert_ne!(witness.conceal(), zero.conceal());
+        assert_ne!(seal.conceal(), seal.conceal_legacy());
+    }
+
+    #[test]
+    fn seal_blinding_midstate() {
+        let midstate =
+            tagged_hash::Midstate::with(SEAL_BLINDING_TAG.as_bytes());
+        assert_eq!(midstate.into_inner().into_inner(), MIDSTATE_SEAL_BLINDING);
+
+        // Derivation reproduced from the documented tagged hash construction
+        let outpoint = OutPoint::new(Txid::hash(b"funding"), 3);
+        let tag = sha256::Hash::hash(SEAL_BLINDING_TAG.as_bytes());
+        let mut engine = sha256::Hash::engine();
+        engine.input(&tag[..]);
+        engine.input(&tag[..]);
+        engine.input(b"seed");
+        engine.input(&outpoint.txid[..]);
+        engine.input(&3u32.to_le_bytes());
+        engine.input(&7u64.to_le_bytes());
+        let hash = sha256::Hash::from_engine(engine);
+        let mut blinding = [0u8; 8];
+        blinding.copy_from_slice(&hash[..8]);
+        let seal = RevealedSeal::with_deterministic_blinding(
+            CloseMethod::TapretFirst,
+            outpoint,
+            b"seed",
+            7,
+        );
+        assert_eq!(seal.blinding, u64::from_le_bytes(blinding));
+    }
+
+    #[test]
+    fn deterministic_blinding() {
+        let outpoint = OutPoint::new(
+            Txid::from_hex(
+                "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+            )
+            .unwrap(),
+            2,
+        );
+        let seed = b"deterministic blinding test seed";
+        let derive = |index| {
+            RevealedSeal::with_deterministic_blinding(
+                CloseMethod::TapretFirst,
+                outpoint,
+                seed,
+                index,
+            )
+        };
+
+        let seal = derive(0);
+        assert_eq!(seal, derive(0));
+        assert_eq!(seal.outpoint(), Some(outpoint));
+        assert_eq!(seal.method, CloseMethod::TapretFirst);
+
+        // Distinct indexes must give distinct blinding factors
+        let mut blindings = (0..1000u64)
+            .map(|index| derive(index).blinding)
+            .collect::<Vec<_>>();
+        blindings.sort_unstable();
+        blindings.dedup();
+        assert_eq!(blindings.len(), 1000);
+
+        // Different seeds must give different blinding factors
+        let other = RevealedSeal::with_deterministic_blinding(
+            CloseMethod::TapretFirst,
+            outpoint,
+            b"another seed",
+            0,
+        );
+        assert_ne!(seal.blinding, other.blinding);
+
+        // Pinned values guarantee the same result across platforms and
+        // endianness
+        assert_eq!(seal.blinding, 1924399079813480461);
+        assert_eq!(derive(1).blinding, 8676810135372355627);
+        assert_eq!(
+            seal.to_concealed_seal().to_string(),
+            "txob1ustgs8tvxc7pdz4nssq3xf0sdjzaukzy7k4vhclxqk8kssy93qjsnqaxkq"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn outpoint_hash_is_sha256d() {
+        let reveal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            blinding: 54683213134637,
+            txid: Some(Txid::from_hex("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839").unwrap()),
+            vout: 2.into(),
+        };
+        let outpoint_hash = reveal.conceal_legacy();
+        let mut engine = sha256t::Hash::<ConcealedSealTag>::engine();
         engine.input(&[reveal.method as u8]);
         engine.input(&reveal.txid.unwrap()[..]);
-        engine.input(&reveal.vout.to_le_bytes()[..]);
+        engine.input(&reveal.vout.into_u32().to_le_bytes()[..]);
         engine.input(&reveal.blinding.to_le_bytes()[..]);
         assert_eq!(
-            **outpoint_hash,
-            *sha256t::Hash::<ConcealedSealTag>::from_engine(engine)
+            outpoint_hash.to_byte_array(),
+            sha256t::Hash::<ConcealedSealTag>::from_engine(engine).into_inner()
+        )
+    }
+
+    #[test]
+    fn conceal_all_order() {
+        let mut rng = thread_rng();
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let mut seals = (0..100u32)
+            .map(|vout| {
+                RevealedSeal::with(
+                    CloseMethod::TapretFirst,
+                    Some(txid),
+                    vout,
+                    &mut rng,
+                )
+            })
+            .collect::<Vec<_>>();
+        seals.push(seals[0]);
+
+        let concealed = conceal_all(&seals);
+        assert_eq!(concealed.len(), seals.len());
+        for (reveal, conceal) in seals.iter().zip(&concealed) {
+            assert_eq!(reveal.to_concealed_seal(), *conceal);
+        }
+        assert_eq!(concealed[0], concealed[100]);
+        assert!(conceal_all(&[]).is_empty());
+    }
+
+    fn seal_set_fixture() -> Vec<RevealedSeal> {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
         )
+        .unwrap();
+        (0..5u32)
+            .map(|vout| {
+                ExplicitSeal::new(CloseMethod::TapretFirst, OutPoint {
+                    txid,
+                    vout,
+                })
+                .blind(54683213134637 + vout as u64)
+            })
+            .chain([ExplicitSeal::new_witness(CloseMethod::OpretFirst, 1)
+                .blind(0x31bbed7e7b2d)])
+            .collect()
     }
 
     #[test]
+    fn conceal_sorted_dedup_order() {
+        let seals = seal_set_fixture();
+        let mut with_duplicates = seals.clone();
+        with_duplicates.extend(seals.iter().rev());
+
+        let concealed = conceal_sorted_dedup(&with_duplicates);
+        assert_eq!(concealed.len(), seals.len());
+        assert!(concealed.windows(2).all(|pair| pair[0] < pair[1]));
+        let mut expected = conceal_all(&seals);
+        expected.sort();
+        assert_eq!(concealed, expected);
+        assert!(conceal_sorted_dedup(&[]).is_empty());
+    }
+
+    #[test]
+    fn seal_set_commitment() {
+        let seals = seal_set_fixture();
+        let root = commitment_of_seal_set(&seals);
+        assert_eq!(
+            root.to_hex(),
+            "d5419ffd60d6908f36ec0bcb296e9bd2fd1bfbe9a9e2e2d78de1bd24d111d50e"
+        );
+
+        let mut permuted = seals.clone();
+        permuted.reverse();
+        permuted.swap(0, 3);
+        assert_eq!(commitment_of_seal_set(&permuted), root);
+        permuted.extend_from_slice(&seals[1..3]);
+        assert_eq!(commitment_of_seal_set(&permuted), root);
+
+        assert_ne!(commitment_of_seal_set(&seals[1..]), root);
+        assert_ne!(commitment_of_seal_set(&[]), root);
+        assert_ne!(
+            commitment_of_seal_set(&[]),
+            commitment_of_seal_set(&seals[..1])
+        );
+    }
+
+    fn seal_data(seal: impl TxoSeal, witness_txid: Txid) -> (Txid, OutPoint) {
+        (seal.txid_or(witness_txid), seal.outpoint_or(witness_txid))
+    }
+
+    fn seal_data_ref<S: TxoSeal>(
+        seal: &S,
+        witness_txid: Txid,
+    ) -> (Txid, OutPoint) {
+        seal_data(seal, witness_txid)
+    }
+
+    #[test]
+    fn txo_seal_generic() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let witness_txid = Txid::from_hex(
+            "0b3cf9f5e1c3f8bb0b3cf9f5e1c3f8bb0b3cf9f5e1c3f8bb0b3cf9f5e1c3f8bb",
+        )
+        .unwrap();
+        let outpoint = OutPoint::new(txid, 5);
+        let witness_outpoint = OutPoint::new(witness_txid, 5);
+
+        let explicit = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let revealed = explicit.blind(0x31bbed7e7b2d);
+        assert_eq!(seal_data(explicit, witness_txid), (txid, outpoint));
+        assert_eq!(seal_data_ref(&explicit, witness_txid), (txid, outpoint));
+        assert_eq!(seal_data(revealed, witness_txid), (txid, outpoint));
+        assert_eq!(seal_data_ref(&revealed, witness_txid), (txid, outpoint));
+
+        let explicit = ExplicitSeal::with(CloseMethod::TapretFirst, None, 5);
+        let revealed = explicit.blind(0x31bbed7e7b2d);
+        assert_eq!(
+            seal_data(explicit, witness_txid),
+            (witness_txid, witness_outpoint)
+        );
+        assert_eq!(
+            seal_data_ref(&revealed, witness_txid),
+            (witness_txid, witness_outpoint)
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn outpoint_hash_bech32() {
         let outpoint_hash = RevealedSeal {
             method: CloseMethod::TapretFirst,
             blinding: 54683213134637,
             txid: Some(Txid::from_hex("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839").unwrap()),
-            vout: 2,
-        }.to_concealed_seal();
+            vout: 2.into(),
+        }.conceal_legacy();
         let bech32 =
             "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgszt65je";
         assert_eq!(bech32, outpoint_hash.to_string());
@@ -486,45 +1756,266 @@ mod test {
         assert_eq!(reconstructed, outpoint_hash);
     }
 
+    #[test]
+    fn concealed_seal_str_roundtrip() {
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            let seal = ConcealedSeal::from_byte_array(bytes);
+            let s = seal.to_string();
+            assert!(s.starts_with(CONCEALED_SEAL_HRP));
+            assert_eq!(s, seal.to_bech32_string());
+            assert_eq!(ConcealedSeal::from_str(&s), Ok(seal));
+        }
+    }
+
+    #[test]
+    fn concealed_seal_str_invalid() {
+        use bech32::{ToBase32, Variant};
+
+        let bech32 =
+            "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgszt65je";
+
+        // typo in the data part
+        let typo = bech32.replacen("a9peq", "a9pex", 1);
+        assert!(matches!(
+            ConcealedSeal::from_str(&typo),
+            Err(ParseError::Bech32(lnpbp_bech32::Error::Bech32Error(
+                bech32::Error::InvalidChecksum
+            )))
+        ));
+
+        let seal = ConcealedSeal::from_str(bech32).unwrap();
+
+        // wrong HRP
+        let foreign = bech32::encode(
+            "txoc",
+            seal[..].to_vec().to_base32(),
+            Variant::Bech32m,
+        )
+        .unwrap();
+        assert_eq!(
+            ConcealedSeal::from_str(&foreign),
+            Err(ParseError::WrongHrp(s!("txoc")))
+        );
+        let foreign = bech32::encode(
+            "txoc",
+            seal[..].to_vec().to_base32(),
+            Variant::Bech32,
+        )
+        .unwrap();
+        assert_eq!(
+            SealBech32::from_str(&foreign),
+            Err(ParseError::WrongHrp(s!("txoc")))
+        );
+
+        // wrong payload length
+        let short = bech32::encode(
+            CONCEALED_SEAL_HRP,
+            seal[..31].to_vec().to_base32(),
+            Variant::Bech32m,
+        )
+        .unwrap();
+        assert!(matches!(
+            ConcealedSeal::from_str(&short),
+            Err(ParseError::Bech32(lnpbp_bech32::Error::NotStrictEncoded(_)))
+        ));
+    }
+
+    #[test]
+    fn concealed_seal_networks() {
+        let bech32 =
+            "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgszt65je";
+        let seal = ConcealedSeal::from_str(bech32).unwrap();
+        assert_eq!(seal.display_for(Network::Bitcoin).to_string(), bech32);
+        assert_eq!(
+            SealBech32::from_str(bech32).unwrap().network(),
+            Network::Bitcoin
+        );
+
+        for (network, hrp) in [
+            (Network::Bitcoin, "txob1"),
+            (Network::Testnet, "txobtest1"),
+            (Network::Signet, "txobsig1"),
+            (Network::Regtest, "txobreg1"),
+        ] {
+            let s = seal.display_for(network).to_string();
+            assert!(s.starts_with(hrp));
+            assert_eq!(ConcealedSeal::parse_for_network(&s, network), Ok(seal));
+
+            let parsed = SealBech32::from_str(&s).unwrap();
+            assert_eq!(parsed.seal(), seal);
+            assert_eq!(parsed.network(), network);
+            assert!(!parsed.is_legacy());
+            assert_eq!(parsed.to_string(), s);
+        }
+
+        // Seals are not accepted across networks
+        let testnet = seal.display_for(Network::Testnet).to_string();
+        assert_eq!(
+            ConcealedSeal::from_str(&testnet),
+            Err(ParseError::NetworkMismatch {
+                expected: Network::Bitcoin,
+                found: Network::Testnet
+            })
+        );
+        assert_eq!(
+            ConcealedSeal::parse_for_network(bech32, Network::Regtest),
+            Err(ParseError::NetworkMismatch {
+                expected: Network::Regtest,
+                found: Network::Bitcoin
+            })
+        );
+        assert_eq!(
+            ConcealedSeal::parse_for_network(&testnet, Network::Signet),
+            Err(ParseError::NetworkMismatch {
+                expected: Network::Signet,
+                found: Network::Testnet
+            })
+        );
+    }
+
+    #[test]
+    fn ct_eq() {
+        use bitcoin::secp256k1::rand::rngs::ThreadRng;
+
+        let random_seal = |rng: &mut ThreadRng| {
+            let mut txid = [0u8; 32];
+            rng.fill_bytes(&mut txid);
+            RevealedSeal {
+                method: if rng.next_u32() % 2 == 0 {
+                    CloseMethod::OpretFirst
+                } else {
+                    CloseMethod::TapretFirst
+                },
+                txid: match rng.next_u32() % 3 {
+                    0 => None,
+                    1 => Some(Txid::default()),
+                    _ => Some(Txid::from_inner(txid)),
+                },
+                vout: (rng.next_u32() % 4).into(),
+                blinding: rng.next_u64() % 4,
+            }
+        };
+        for _ in 0..1000 {
+            let seal = random_seal(&mut thread_rng());
+            let other = random_seal(&mut thread_rng());
+            assert!(seal.ct_eq(&seal));
+            assert_eq!(seal.ct_eq(&other), seal == other);
+            assert_eq!(other.ct_eq(&seal), seal == other);
+
+            let mut changed = seal;
+            let bit = thread_rng().next_u32() % 64;
+            changed.set_blinding(seal.blinding() ^ 1 << bit);
+            assert!(!seal.ct_eq(&changed));
+            assert_ne!(seal, changed);
+        }
+    }
+
+    #[test]
+    fn seal_bech32_variants() {
+        use bech32::ToBase32;
+
+        let bech32m =
+            "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgszt65je";
+        let legacy =
+            "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgshh2chm";
+
+        let parsed = SealBech32::from_str(bech32m).unwrap();
+        assert_eq!(parsed.encoding(), Variant::Bech32m);
+        assert!(!parsed.is_legacy());
+        assert_eq!(parsed.to_string(), bech32m);
+        let seal = parsed.seal();
+        assert_eq!(SealBech32::from(seal), parsed);
+
+        let parsed = SealBech32::from_str(legacy).unwrap();
+        assert_eq!(parsed.encoding(), Variant::Bech32);
+        assert!(parsed.is_legacy());
+        assert_eq!(parsed.seal(), seal);
+        // Legacy strings are always re-encoded with Bech32m checksum
+        assert_eq!(parsed.to_string(), bech32m);
+        assert_eq!(ConcealedSeal::from_str(legacy), Ok(seal));
+
+        assert_eq!(
+            bech32::encode(
+                CONCEALED_SEAL_HRP,
+                seal[..].to_vec().to_base32(),
+                Variant::Bech32
+            )
+            .unwrap(),
+            legacy
+        );
+    }
+
+    #[test]
+    fn concealed_seal_uri() {
+        let seal = RevealedSeal::from_str(
+            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:21#0x31bbed7e7b2d",
+        )
+        .unwrap()
+        .commit_conceal();
+        let uri = seal.to_uri();
+        assert_eq!(uri, format!("seal:{}", seal));
+        assert_eq!(ConcealedSeal::from_uri(&uri), Ok(seal));
+        assert_eq!(
+            ConcealedSeal::from_uri(&format!("SEAL:{}", seal)),
+            Ok(seal)
+        );
+
+        assert_eq!(
+            ConcealedSeal::from_uri(&seal.to_string()),
+            Err(ParseError::UriSchemeRequired)
+        );
+        assert_eq!(
+            ConcealedSeal::from_uri(&format!("seal:{}%", seal)),
+            Err(ParseError::WrongPercentEncoding)
+        );
+        assert!(matches!(
+            ConcealedSeal::from_uri("seal:tapret1st/~/1"),
+            Err(ParseError::Bech32(_))
+        ));
+    }
+
     #[test]
     fn outpoint_reveal_str() {
         let mut outpoint_reveal = RevealedSeal {
             method: CloseMethod::TapretFirst,
             blinding: 54683213134637,
             txid: Some(Txid::from_hex("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839").unwrap()),
-            vout: 21,
+            vout: 21.into(),
         };
 
         let s = outpoint_reveal.to_string();
         assert_eq!(
             &s,
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:\
-             21#0x31bbed7e7b2d"
+             21#54683213134637"
         );
         // round-trip
         assert_eq!(RevealedSeal::from_str(&s).unwrap(), outpoint_reveal);
 
         outpoint_reveal.txid = None;
         let s = outpoint_reveal.to_string();
-        assert_eq!(&s, "tapret1st:~:21#0x31bbed7e7b2d");
+        assert_eq!(&s, "tapret1st:~:21#54683213134637");
         // round-trip
         assert_eq!(RevealedSeal::from_str(&s).unwrap(), outpoint_reveal);
 
         // wrong method
         assert_eq!(RevealedSeal::from_str(
-            "tapret:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:0x765#0x78ca95"
-        ), Err(ParseError::WrongMethod(MethodParseError(s!("tapret")))));
+            "tapret2nd:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:0x765#0x78ca95"
+        ), Err(ParseError::WrongMethod(MethodParseError::new("tapret2nd"))));
 
         // wrong vout value
-        assert_eq!(RevealedSeal::from_str(
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:0x765#0x78ca95"
-        ), Err(ParseError::WrongVout));
-        assert_eq!(RevealedSeal::from_str(
+        ), Err(ParseError::WrongVout(_))));
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:i9#0x78ca95"
-        ), Err(ParseError::WrongVout));
-        assert_eq!(RevealedSeal::from_str(
+        ), Err(ParseError::WrongVout(_))));
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:-5#0x78ca95"
-        ), Err(ParseError::WrongVout));
+        ), Err(ParseError::WrongVout(_))));
 
         // wrong blinding secret value
         assert_eq!(RevealedSeal::from_str(
@@ -532,46 +2023,43 @@ mod test {
         ), Err(ParseError::WrongBlinding));
         assert_eq!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:5#78ca95"
-        ), Err(ParseError::NonHexBlinding));
-        assert_eq!(RevealedSeal::from_str(
-            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:5#857"
-        ), Err(ParseError::NonHexBlinding));
+        ), Err(ParseError::WrongBlinding));
         assert_eq!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:5#-5"
-        ), Err(ParseError::NonHexBlinding));
+        ), Err(ParseError::WrongBlinding));
 
         // wrong txid value
-        assert_eq!(RevealedSeal::from_str(
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d607719dfd820551fb773e4dc8c4ed67965a8d1fae839:5#0x78ca69"
-        ), Err(ParseError::WrongTxid));
-        assert_eq!(
+        ), Err(ParseError::WrongTxid(_))));
+        assert!(matches!(
             RevealedSeal::from_str("tapret1st:rvgbdg:5#0x78ca69"),
-            Err(ParseError::WrongTxid)
-        );
-        assert_eq!(RevealedSeal::from_str(
+            Err(ParseError::WrongTxid(_))
+        ));
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:10@646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:5#0x78ca69"
-        ), Err(ParseError::WrongTxid));
+        ), Err(ParseError::WrongTxid(_))));
 
         // wrong structure
-        assert_eq!(RevealedSeal::from_str(
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:1"
-        ), Err(ParseError::WrongStructure));
-        assert_eq!(RevealedSeal::from_str(
+        ), Err(ParseError::WrongStructure(_))));
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839#0x78ca"
-        ), Err(ParseError::WrongStructure));
+        ), Err(ParseError::WrongStructure(_))));
         assert_eq!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839"
         ), Err(ParseError::BlindingRequired));
-        assert_eq!(RevealedSeal::from_str(
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839##0x78ca"
-        ), Err(ParseError::WrongVout));
-        assert_eq!(RevealedSeal::from_str(
+        ), Err(ParseError::WrongVout(_))));
+        assert!(matches!(RevealedSeal::from_str(
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:#0x78ca95"
-        ), Err(ParseError::WrongVout));
-        assert_eq!(
+        ), Err(ParseError::WrongVout(_))));
+        assert!(matches!(
             RevealedSeal::from_str("tapret1st:_:5#0x78ca"),
-            Err(ParseError::WrongTxid)
-        );
+            Err(ParseError::WrongTxid(_))
+        ));
         assert_eq!(
             RevealedSeal::from_str(":5#0x78ca"),
             Err(ParseError::MethodRequired)
@@ -581,4 +2069,446 @@ mod test {
             Err(ParseError::MethodRequired)
         );
     }
+
+    #[test]
+    fn strict_encoding_v1() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let seal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            blinding: 54683213134637,
+            txid: Some(txid),
+            vout: 2.into(),
+        };
+        let data = Vec::<u8>::from_hex(
+            "ff010101\
+             39e8fad1a86579d64e8cdce473b71f5520d8dfc97107d6a2e2192606c1a56c64\
+             02000000\
+             2d7b7eedbb310000",
+        )
+        .unwrap();
+        assert_eq!(seal.encode_v1(), data);
+        assert_eq!(strict_serialize(&seal).unwrap(), data);
+        assert_eq!(RevealedSeal::decode_any(&data), Ok(seal));
+        assert_eq!(strict_deserialize::<RevealedSeal>(&data).unwrap(), seal);
+
+        let seal = RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            txid: None,
+            vout: 21.into(),
+            ..seal
+        };
+        let data =
+            Vec::<u8>::from_hex("ff010000150000002d7b7eedbb310000").unwrap();
+        assert_eq!(seal.encode_v1(), data);
+        assert_eq!(RevealedSeal::decode_any(&data), Ok(seal));
+    }
+
+    #[test]
+    fn strict_decoding_versions() {
+        let seal =
+            RevealedSeal::from_str("opret1st:~:21#0x31bbed7e7b2d").unwrap();
+        let mut data = seal.encode_v1();
+        assert_eq!(data[0], RevealedSeal::VERSION_MARKER);
+        assert_eq!(data[1], RevealedSeal::VERSION);
+
+        for version in [0x00, 0x02, 0xFF] {
+            data[1] = version;
+            assert_eq!(
+                RevealedSeal::decode_any(&data),
+                Err(SealDecodeError::UnsupportedSealVersion(version))
+            );
+            assert_eq!(
+                strict_deserialize::<RevealedSeal>(&data),
+                Err(strict_encoding::Error::DataIntegrityError(
+                    SealDecodeError::UnsupportedSealVersion(version)
+                        .to_string()
+                ))
+            );
+        }
+
+        data[1] = RevealedSeal::VERSION;
+        data.push(0);
+        assert_eq!(
+            RevealedSeal::decode_any(&data),
+            Err(SealDecodeError::Encoding(
+                strict_encoding::Error::DataNotEntirelyConsumed
+            ))
+        );
+        assert!(matches!(
+            RevealedSeal::decode_any([]),
+            Err(SealDecodeError::Encoding(strict_encoding::Error::Io(_)))
+        ));
+        assert!(matches!(
+            RevealedSeal::decode_any([RevealedSeal::VERSION_MARKER]),
+            Err(SealDecodeError::Encoding(strict_encoding::Error::Io(_)))
+        ));
+    }
+
+    #[test]
+    fn strict_decoding_unversioned() {
+        // Tapret seal encoded before the versioning: the first byte is the
+        // close method id, which is equal to the encoding version
+        let data = Vec::<u8>::from_hex(
+            "0101\
+             39e8fad1a86579d64e8cdce473b71f5520d8dfc97107d6a2e2192606c1a56c64\
+             02000000\
+             2d7b7eedbb310000",
+        )
+        .unwrap();
+        assert_eq!(data[0], RevealedSeal::VERSION);
+        let seal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            blinding: 54683213134637,
+            txid: Some(
+                Txid::from_hex(
+                    "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+                )
+                .unwrap(),
+            ),
+            vout: 2.into(),
+        };
+        assert_eq!(RevealedSeal::decode_any(&data), Ok(seal));
+        assert_eq!(strict_deserialize::<RevealedSeal>(&data).unwrap(), seal);
+        assert_eq!(seal.encode_v1()[2..], data[..]);
+
+        let data = Vec::<u8>::from_hex("0000150000002d7b7eedbb310000").unwrap();
+        assert_eq!(
+            RevealedSeal::decode_any(&data),
+            Ok(RevealedSeal::from_str("opret1st:~:21#0x31bbed7e7b2d").unwrap())
+        );
+
+        assert!(matches!(
+            RevealedSeal::decode_any([0x02]),
+            Err(SealDecodeError::Encoding(_))
+        ));
+    }
+
+    #[test]
+    fn blinding_forms() {
+        let seal = |blinding: &str| {
+            RevealedSeal::from_str(&format!("opret1st:~:21#{}", blinding))
+        };
+        let expected = RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            blinding: 54683213134637,
+            txid: None,
+            vout: 21.into(),
+        };
+        assert_eq!(seal("0x31bbed7e7b2d"), Ok(expected));
+        assert_eq!(seal("0x31BBED7E7B2D"), Ok(expected));
+        assert_eq!(seal("54683213134637"), Ok(expected));
+        assert_eq!(seal("0054683213134637"), Ok(expected));
+        assert_eq!(expected.to_string(), "opret1st:~:21#54683213134637");
+
+        for blinding in [0, 1, u64::MAX] {
+            let revealed = RevealedSeal {
+                blinding,
+                ..expected
+            };
+            assert_eq!(
+                RevealedSeal::from_str(&revealed.to_string()),
+                Ok(revealed)
+            );
+            assert_eq!(seal(&format!("{:#x}", blinding)), Ok(revealed));
+        }
+
+        // Overflows
+        assert_eq!(seal("18446744073709551615").unwrap().blinding, u64::MAX);
+        assert_eq!(seal("0xffffffffffffffff").unwrap().blinding, u64::MAX);
+        for blinding in [
+            "18446744073709551616",
+            "99999999999999999999999",
+            "0x10000000000000000",
+        ] {
+            assert_eq!(seal(blinding), Err(ParseError::WrongBlinding));
+        }
+
+        // Malformed values
+        for blinding in ["", "0x", "+5", "0x+5", "-0", "0X1f", "1f", "5 "] {
+            assert_eq!(seal(blinding), Err(ParseError::WrongBlinding));
+        }
+
+        // Missing blinding suffix is reported instead of parsing the string
+        // as an explicit seal
+        let explicit = "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:21";
+        assert!(ExplicitSeal::from_str(explicit).is_ok());
+        assert!(matches!(
+            RevealedSeal::from_str(explicit),
+            Err(ParseError::WrongStructure(ctx)) if ctx.offset == explicit.len()
+        ));
+        assert_eq!(
+            RevealedSeal::from_str(&format!("{}#", explicit)),
+            Err(ParseError::WrongBlinding)
+        );
+    }
+
+    #[test]
+    fn outpoint_reveal_error_offset() {
+        let context = |s: &str| match RevealedSeal::from_str(s) {
+            Err(ParseError::WrongTxid(ctx))
+            | Err(ParseError::WrongVout(ctx))
+            | Err(ParseError::WrongStructure(ctx)) => {
+                (ctx.offset, ctx.fragment)
+            }
+            res => panic!("unexpected result {:?}", res),
+        };
+        assert_eq!(context("tapret1st:xyz:5#0x1"), (10, s!("xyz")));
+        assert_eq!(context("opret1st:~:i9#0x1"), (11, s!("i9")));
+        assert_eq!(context("opret1st:~:1#0x1#0x2"), (16, s!("#0x2")));
+        assert_eq!(context("opret1st:~:1"), (12, s!("")));
+        assert_eq!(context("opret1st#~"), (10, s!("")));
+        assert_eq!(context("opret1st"), (8, s!("")));
+    }
+
+    #[test]
+    fn outpoint_reveal_redacted() {
+        let mut seal = RevealedSeal::from_str(
+            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:21#0x31bbed7e7b2d",
+        )
+        .unwrap();
+
+        let redacted = format!("{:#}", seal);
+        assert_eq!(
+            redacted,
+            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:21#********"
+        );
+        assert!(!redacted.contains("31bbed7e7b2d"));
+        assert_eq!(
+            RevealedSeal::from_str(&redacted),
+            Err(ParseError::BlindingRequired)
+        );
+        assert_eq!(seal.to_compact_string(), "tapret1st:646ca5c1…d1fae839:21");
+
+        seal.txid = None;
+        let redacted = format!("{:#}", seal);
+        assert_eq!(redacted, "tapret1st:~:21#********");
+        assert_eq!(
+            RevealedSeal::from_str(&redacted),
+            Err(ParseError::BlindingRequired)
+        );
+        assert_eq!(seal.to_compact_string(), "tapret1st:~:21");
+        assert!(matches!(
+            RevealedSeal::from_str(&seal.to_compact_string()),
+            Err(ParseError::WrongStructure(_))
+        ));
+    }
+
+    fn random_concealed(count: usize) -> Vec<ConcealedSeal> {
+        let mut rng = thread_rng();
+        (0..count)
+            .map(|_| {
+                let mut bytes = [0u8; 32];
+                rng.fill_bytes(&mut bytes);
+                ConcealedSeal::from_byte_array(bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn concealed_seal_bytes() {
+        for seal in random_concealed(100) {
+            let bytes = seal.to_byte_array();
+            assert_eq!(ConcealedSeal::from_byte_array(bytes), seal);
+            assert_eq!(&seal[..], &bytes[..]);
+            assert_eq!(strict_serialize(&seal).unwrap(), bytes.to_vec());
+        }
+    }
+
+    #[test]
+    fn concealed_seal_ordering() {
+        let mut seals = random_concealed(1000);
+        seals.push(ConcealedSeal::from_byte_array([0u8; 32]));
+        seals.push(ConcealedSeal::from_byte_array([0xFFu8; 32]));
+        // Seal which is the largest one if compared in reverse byte order
+        let mut last = [0xFFu8; 32];
+        last[0] = 0;
+        seals.push(ConcealedSeal::from_byte_array(last));
+
+        let mut by_bytes = seals.clone();
+        by_bytes.sort_by_key(ConcealedSeal::to_byte_array);
+        let mut by_encoding = seals.clone();
+        by_encoding.sort_by_key(|seal| strict_serialize(seal).unwrap());
+        seals.sort();
+        assert_eq!(seals, by_bytes);
+        assert_eq!(seals, by_encoding);
+        assert_eq!(seals[0], ConcealedSeal::from_byte_array([0u8; 32]));
+        assert_eq!(
+            seals.last(),
+            Some(&ConcealedSeal::from_byte_array([0xFF; 32]))
+        );
+        assert!(
+            seals.iter().position(|seal| seal[0] == 0xFF)
+                > seals.iter().position(|seal| seal.to_byte_array() == last)
+        );
+
+        let map = seals
+            .iter()
+            .map(|seal| (*seal, ()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let data = strict_serialize(&map).unwrap();
+        let decoded = strict_deserialize::<
+            std::collections::BTreeMap<ConcealedSeal, ()>,
+        >(&data)
+        .unwrap();
+        assert!(decoded.keys().eq(seals.iter()));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn revealed_seal_serde() {
+        let seal = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: None,
+            vout: Vout::from(1),
+            blinding: u64::MAX,
+        };
+        let json = serde_json::to_string(&seal).unwrap();
+        assert_eq!(
+            json,
+            r#"{"method":"TapretFirst","txid":null,"vout":1,"blinding":"18446744073709551615"}"#
+        );
+        assert_eq!(serde_json::from_str::<RevealedSeal>(&json).unwrap(), seal);
+        let yaml = serde_yaml::to_string(&seal).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<serde_yaml::Value>(&yaml).unwrap()
+                ["blinding"],
+            serde_yaml::Value::from("18446744073709551615")
+        );
+        assert_eq!(serde_yaml::from_str::<RevealedSeal>(&yaml).unwrap(), seal);
+
+        // Numeric form produced by the previous versions
+        let legacy = r#"{"method":"TapretFirst","txid":null,"vout":1,"blinding":18446744073709551615}"#;
+        assert_eq!(serde_json::from_str::<RevealedSeal>(legacy).unwrap(), seal);
+        let small = legacy.replace("18446744073709551615", "7");
+        assert_eq!(
+            serde_json::from_str::<RevealedSeal>(&small)
+                .unwrap()
+                .blinding,
+            7
+        );
+
+        for invalid in
+            ["-1", "\"-1\"", "\"0x1\"", "\"18446744073709551616\"", "1.5"]
+        {
+            let json = legacy.replace("18446744073709551615", invalid);
+            assert!(serde_json::from_str::<RevealedSeal>(&json).is_err());
+        }
+
+        let data = bincode::serialize(&seal).unwrap();
+        assert_eq!(&data[data.len() - 8..], &u64::MAX.to_le_bytes());
+        assert_eq!(bincode::deserialize::<RevealedSeal>(&data).unwrap(), seal);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn concealed_seal_serde() {
+        let bech32 =
+            "txob1a9peq6yx9x6ajt584qp5ge4jk9v7tmtgs3x2gntk2nf425cvpdgszt65je";
+        let seal = ConcealedSeal::from_str(bech32).unwrap();
+        let hex = seal.to_byte_array().to_hex();
+
+        let json = serde_json::to_string(&seal).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex));
+        assert_eq!(serde_json::from_str::<ConcealedSeal>(&json).unwrap(), seal);
+        assert_eq!(
+            serde_json::from_str::<ConcealedSeal>(&format!("\"{}\"", bech32))
+                .unwrap(),
+            seal
+        );
+        assert!(serde_json::from_str::<ConcealedSeal>("\"00ff\"").is_err());
+
+        let data = bincode::serialize(&seal).unwrap();
+        assert_eq!(&data[..8], &32u64.to_le_bytes());
+        assert_eq!(&data[8..], &seal.to_byte_array());
+        assert_eq!(bincode::deserialize::<ConcealedSeal>(&data).unwrap(), seal);
+    }
+
+    #[test]
+    fn dumb_seals() {
+        let revealed = RevealedSeal::dumb_default();
+        assert_eq!(revealed.method, CloseMethod::OpretFirst);
+        assert_eq!(revealed.txid, Some(Txid::default()));
+        assert_eq!(revealed.vout, Vout::from(0));
+        assert_eq!(revealed.blinding, 0);
+        assert_eq!(revealed.to_explicit(), ExplicitSeal::dumb_default());
+
+        let data = strict_serialize(&revealed).unwrap();
+        assert_eq!(
+            strict_deserialize::<RevealedSeal>(&data).unwrap(),
+            revealed
+        );
+
+        let concealed = ConcealedSeal::dumb_default();
+        assert_eq!(concealed.to_byte_array(), [0u8; 32]);
+        let data = strict_serialize(&concealed).unwrap();
+        assert_eq!(
+            strict_deserialize::<ConcealedSeal>(&data).unwrap(),
+            concealed
+        );
+        assert_eq!(
+            ConcealedSeal::from_str(&concealed.to_string()).unwrap(),
+            concealed
+        );
+
+        for method in CloseMethod::iter() {
+            for txid in [Some(Txid::default()), None] {
+                let seal = RevealedSeal {
+                    method,
+                    txid,
+                    ..revealed
+                };
+                assert_ne!(seal.to_concealed_seal(), concealed);
+            }
+        }
+    }
+
+    #[test]
+    fn seal_typestate() {
+        fn publish(seal: Seal<Confidential>) -> SecretSeal { seal }
+        fn reveal(seal: Seal<Revealed>) -> RevealedSeal { seal }
+        fn confidential(_: &impl SealConfidential) {}
+        fn revealed(_: &impl SealRevealed) {}
+
+        let seal = reveal(RevealedSeal::from(OutPoint::default()));
+        revealed(&seal);
+        let concealed = publish(seal.conceal());
+        confidential(&concealed);
+        assert_eq!(concealed, seal.conceal());
+    }
+
+    #[test]
+    fn conceal_cached_midstate() {
+        let tag = sha256::Hash::hash(CONCEALED_SEAL_TAG.as_bytes());
+        let engine = RevealedSeal::conceal_engine();
+        for no in 0..16u32 {
+            let txid = Txid::hash(&no.to_le_bytes());
+            let seal = RevealedSeal {
+                method: CloseMethod::TapretFirst,
+                txid: if no % 2 == 0 { Some(txid) } else { None },
+                vout: no.into(),
+                blinding: 0x31bbed7e7b2d + no as u64,
+            };
+
+            // Tagged hash computed from scratch
+            let mut uncached = sha256::Hash::engine();
+            uncached.input(&tag[..]);
+            uncached.input(&tag[..]);
+            uncached.input(&seal.encode_v1()[1..]);
+            let uncached = ConcealedSeal::from_byte_array(
+                sha256::Hash::from_engine(uncached).into_inner(),
+            );
+
+            assert_eq!(seal.conceal(), uncached);
+            assert_eq!(seal.conceal_with_engine(&engine), uncached);
+            assert_eq!(
+                *uncached,
+                sha256t::Hash::<ConcealedSealV2Tag>::hash(
+                    &seal.encode_v1()[1..]
+                )
+            );
+        }
+    }
 }
@@ -0,0 +1,169 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Entropy sources for the seal blinding factors.
+
+use bitcoin::secp256k1::rand::RngCore;
+
+/// Source of entropy for the seal blinding factors.
+///
+/// The trait is implemented for all random number generators, and can be
+/// implemented by integrators for other entropy sources, like hardware
+/// security modules. Blinding factors must be unpredictable: anybody knowing
+/// the blinding factor and guessing the outpoint can reveal the concealed
+/// seal.
+pub trait Blinder {
+    /// Returns next 64-bit blinding factor.
+    fn blinding(&mut self) -> u64;
+
+    /// Returns next 32 bytes of blinding entropy. Not used by the seals
+    /// defined in this library; the default implementation concatenates four
+    /// [`Blinder::blinding`] values in little-endian order.
+    fn blinding32(&mut self) -> [u8; 32] {
+        let mut entropy = [0u8; 32];
+        for chunk in entropy.chunks_exact_mut(8) {
+            chunk.copy_from_slice(&self.blinding().to_le_bytes());
+        }
+        entropy
+    }
+}
+
+impl<R> Blinder for R
+where
+    R: RngCore,
+{
+    #[inline]
+    fn blinding(&mut self) -> u64 { self.next_u64() }
+
+    #[inline]
+    fn blinding32(&mut self) -> [u8; 32] {
+        let mut entropy = [0u8; 32];
+        self.fill_bytes(&mut entropy);
+        entropy
+    }
+}
+
+/// Blinder always returning zero blinding factors.
+///
+/// # Security
+///
+/// NEVER use it in production: seals with zero blinding factors are not
+/// concealed at all, since their concealed form can be matched against all
+/// known outpoints. Intended for tests and test vectors only.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ZeroBlinder;
+
+impl Blinder for ZeroBlinder {
+    #[inline]
+    fn blinding(&mut self) -> u64 { 0 }
+}
+
+/// Blinder returning sequential blinding factors, starting with the provided
+/// value and incrementing it (with wrapping) on each call.
+///
+/// # Security
+///
+/// The blinding factors are predictable, so the blinder must not be used in
+/// production. Intended for tests which need distinct but reproducible seals.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SeqBlinder(u64);
+
+impl SeqBlinder {
+    /// Constructs blinder starting the sequence from `start`.
+    #[inline]
+    pub fn new(start: u64) -> SeqBlinder { SeqBlinder(start) }
+
+    /// Returns blinding factor which will be produced by the next call.
+    #[inline]
+    pub fn next_value(&self) -> u64 { self.0 }
+}
+
+impl Blinder for SeqBlinder {
+    #[inline]
+    fn blinding(&mut self) -> u64 {
+        let blinding = self.0;
+        self.0 = self.0.wrapping_add(1);
+        blinding
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::rand::rngs::mock::StepRng;
+    use bitcoin::{OutPoint, Txid};
+
+    use super::*;
+    use crate::txout::blind::RevealedSeal;
+    use crate::txout::{CloseMethod, ExplicitSeal};
+
+    fn outpoint() -> OutPoint { OutPoint::new(Txid::hash(b"blinder"), 3) }
+
+    #[test]
+    fn blinders() {
+        assert_eq!(ZeroBlinder.blinding(), 0);
+        assert_eq!(ZeroBlinder.blinding32(), [0u8; 32]);
+
+        let mut blinder = SeqBlinder::new(u64::MAX - 1);
+        assert_eq!(blinder.blinding(), u64::MAX - 1);
+        assert_eq!(blinder.blinding(), u64::MAX);
+        assert_eq!(blinder.next_value(), 0);
+        assert_eq!(blinder.blinding(), 0);
+        let entropy = SeqBlinder::new(1).blinding32();
+        assert_eq!(&entropy[..8], &1u64.to_le_bytes());
+        assert_eq!(&entropy[24..], &4u64.to_le_bytes());
+
+        let mut rng = StepRng::new(7, 2);
+        assert_eq!(Blinder::blinding(&mut rng), 7);
+        assert_eq!(Blinder::blinding(&mut rng), 9);
+    }
+
+    #[test]
+    fn entropy_flows_into_concealed_seal() {
+        let explicit = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint());
+
+        let mut blinder = SeqBlinder::new(0x31bbed7e7b2d);
+        let first = RevealedSeal::with_blinder(
+            CloseMethod::TapretFirst,
+            outpoint(),
+            &mut blinder,
+        );
+        let second = explicit.blind_random(&mut blinder);
+        assert_eq!(first, explicit.blind(0x31bbed7e7b2d));
+        assert_eq!(second, explicit.blind(0x31bbed7e7b2e));
+        assert_eq!(first.conceal(), explicit.blind(0x31bbed7e7b2d).conceal());
+        assert_ne!(first.conceal(), second.conceal());
+
+        let zero = RevealedSeal::with(
+            CloseMethod::TapretFirst,
+            Some(outpoint().txid),
+            3u32,
+            &mut ZeroBlinder,
+        );
+        assert_eq!(zero.blinding, 0);
+        assert_eq!(zero.conceal(), explicit.blind(0).conceal());
+
+        let mut rng = StepRng::new(42, 0);
+        assert_eq!(
+            RevealedSeal::with_blinder(
+                CloseMethod::TapretFirst,
+                outpoint(),
+                &mut rng
+            )
+            .conceal(),
+            explicit.blind(42).conceal()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Seals tagged with the bitcoin network their transaction ids belong to.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use bitcoin::Network;
+
+use crate::txout::explicit::{ParseContext, ParseError};
+use crate::txout::ExplicitSeal;
+
+/// Separator between the network name and the seal in the string
+/// representation of [`ChainSeal`].
+const CHAIN_SEPARATOR: char = '@';
+
+/// Seal definition tagged with the bitcoin network which its transaction id
+/// belongs to, preventing the seal from being used on a wrong network.
+///
+/// String representation prefixes the seal with the network name like
+/// `testnet@tapret1st:<txid>:<vout>`; seals without the prefix are untagged.
+/// The tag is not part of the seal strict encoding or commitment.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ChainSeal {
+    /// Network which the seal transaction id belongs to, if known.
+    pub chain: Option<Network>,
+
+    /// Seal definition.
+    pub seal: ExplicitSeal,
+}
+
+impl From<ExplicitSeal> for ChainSeal {
+    #[inline]
+    fn from(seal: ExplicitSeal) -> Self { ChainSeal { chain: None, seal } }
+}
+
+impl From<ChainSeal> for ExplicitSeal {
+    #[inline]
+    fn from(chain_seal: ChainSeal) -> Self { chain_seal.seal }
+}
+
+impl ChainSeal {
+    /// Constructs seal tagged with the given network.
+    #[inline]
+    pub fn with(chain: Network, seal: ExplicitSeal) -> ChainSeal {
+        ChainSeal {
+            chain: Some(chain),
+            seal,
+        }
+    }
+
+    /// Parses seal string representation, requiring the seal to be tagged with
+    /// the `expected` network.
+    ///
+    /// Untagged seals result in [`ParseError::ChainRequired`]; seals tagged
+    /// with other network result in [`ParseError::ChainMismatch`]. Use
+    /// [`ChainSeal::parse_for_chain_lenient`] to accept untagged seals.
+    pub fn parse_for_chain(
+        s: &str,
+        expected: Network,
+    ) -> Result<ChainSeal, ParseError> {
+        let chain_seal = ChainSeal::from_str(s)?;
+        match chain_seal.chain {
+            None => Err(ParseError::ChainRequired),
+            Some(found) if found != expected => {
+                Err(ParseError::ChainMismatch { expected, found })
+            }
+            Some(_) => Ok(chain_seal),
+        }
+    }
+
+    /// Parses seal string representation like [`ChainSeal::parse_for_chain`],
+    /// but accepts untagged seals, tagging them with the `expected` network.
+    pub fn parse_for_chain_lenient(
+        s: &str,
+        expected: Network,
+    ) -> Result<ChainSeal, ParseError> {
+        let chain_seal = ChainSeal::from_str(s)?;
+        match chain_seal.chain {
+            Some(found) if found != expected => {
+                Err(ParseError::ChainMismatch { expected, found })
+            }
+            _ => Ok(ChainSeal::with(expected, chain_seal.seal)),
+        }
+    }
+}
+
+impl FromStr for ChainSeal {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(CHAIN_SEPARATOR) {
+            Some((chain, seal)) => Ok(ChainSeal {
+                chain: Some(Network::from_str(chain).map_err(|_| {
+                    ParseError::WrongChain(ParseContext::with(s, chain))
+                })?),
+                seal: seal
+                    .parse()
+                    .map_err(|err: ParseError| err.within(s, seal))?,
+            }),
+            None => s.parse().map(ExplicitSeal::into),
+        }
+    }
+}
+
+impl Display for ChainSeal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(chain) = self.chain {
+            write!(f, "{}{}", chain, CHAIN_SEPARATOR)?;
+        }
+        Display::fmt(&self.seal, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::strict_serialize;
+
+    use super::*;
+
+    const SEAL: &str = "tapret1st:\
+                        646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:\
+                        2";
+
+    #[test]
+    fn chain_roundtrip() {
+        let seal = ExplicitSeal::from_str(SEAL).unwrap();
+
+        let untagged = ChainSeal::from_str(SEAL).unwrap();
+        assert_eq!(untagged, ChainSeal::from(seal));
+        assert_eq!(untagged.to_string(), SEAL);
+
+        for chain in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            let s = format!("{}@{}", chain, SEAL);
+            let tagged = ChainSeal::from_str(&s).unwrap();
+            assert_eq!(tagged, ChainSeal::with(chain, seal));
+            assert_eq!(tagged.to_string(), s);
+            assert_eq!(ExplicitSeal::from(tagged), seal);
+        }
+
+        let witness = ChainSeal::from_str("regtest@opret1st:~:0").unwrap();
+        assert_eq!(witness.chain, Some(Network::Regtest));
+        assert_eq!(witness.seal.txid, None);
+
+        // The tag does not affect the seal encoding
+        assert_eq!(
+            strict_serialize(&ChainSeal::with(Network::Testnet, seal).seal),
+            strict_serialize(&seal)
+        );
+    }
+
+    #[test]
+    fn chain_invalid() {
+        let s = format!("liquid@{}", SEAL);
+        assert_eq!(
+            ChainSeal::from_str(&s),
+            Err(ParseError::WrongChain(ParseContext {
+                input: s.clone(),
+                offset: 0,
+                fragment: s!("liquid"),
+            }))
+        );
+        assert_eq!(
+            ChainSeal::from_str("@opret1st:~:0"),
+            Err(ParseError::WrongChain(ParseContext {
+                input: s!("@opret1st:~:0"),
+                offset: 0,
+                fragment: s!(""),
+            }))
+        );
+        assert_eq!(
+            ChainSeal::from_str("testnet@:~:0"),
+            Err(ParseError::MethodRequired)
+        );
+        // Error offsets are relative to the whole string
+        assert_eq!(
+            ChainSeal::from_str("testnet@tapret1st:00:2"),
+            Err(ParseError::WrongTxid(ParseContext {
+                input: s!("testnet@tapret1st:00:2"),
+                offset: 18,
+                fragment: s!("00"),
+            }))
+        );
+        // Untagged seal parser does not accept network prefix
+        assert!(ExplicitSeal::from_str(&format!("testnet@{}", SEAL)).is_err());
+    }
+
+    #[test]
+    fn parse_for_chain() {
+        let seal = ExplicitSeal::from_str(SEAL).unwrap();
+        assert_eq!(
+            ChainSeal::parse_for_chain(
+                &format!("testnet@{}", SEAL),
+                Network::Testnet
+            ),
+            Ok(ChainSeal::with(Network::Testnet, seal))
+        );
+        assert_eq!(
+            ChainSeal::parse_for_chain(SEAL, Network::Bitcoin),
+            Err(ParseError::ChainRequired)
+        );
+        assert_eq!(
+            ChainSeal::parse_for_chain_lenient(SEAL, Network::Bitcoin),
+            Ok(ChainSeal::with(Network::Bitcoin, seal))
+        );
+        for parse in [
+            ChainSeal::parse_for_chain,
+            ChainSeal::parse_for_chain_lenient,
+        ] {
+            assert_eq!(
+                parse(&format!("testnet@{}", SEAL), Network::Bitcoin),
+                Err(ParseError::ChainMismatch {
+                    expected: Network::Bitcoin,
+                    found: Network::Testnet
+                })
+            );
+        }
+    }
+}
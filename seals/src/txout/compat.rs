@@ -0,0 +1,190 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Conversions of the seals from and to raw parts, which do not depend on the
+//! types of a specific `rust-bitcoin` version. Crates using other version of
+//! `rust-bitcoin` can convert their outpoints with
+//! `(txid.to_byte_array(), vout)` (or `txid.into_inner()` in the older
+//! versions) without depending on the version used by this library.
+//!
+//! Transaction ids are represented by their 32 bytes in the internal byte
+//! order of [`Txid`], which is the reverse of the byte order of the
+//! hexadecimal txid strings:
+//!
+//! ```
+//! # use std::str::FromStr;
+//! # use bitcoin::Txid;
+//! # use seals::txout::{CloseMethod, ExplicitSeal};
+//! let txid = Txid::from_str(
+//!     "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+//! )
+//! .unwrap();
+//! let seal = ExplicitSeal::new(
+//!     CloseMethod::TapretFirst,
+//!     bitcoin::OutPoint::new(txid, 1),
+//! );
+//! let (_, raw_txid, vout) = seal.to_raw_parts();
+//! assert_eq!(raw_txid.unwrap()[0], 0x39);
+//! assert_eq!(raw_txid.unwrap()[31], 0x64);
+//! assert_eq!(vout, 1);
+//! ```
+
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Txid};
+
+use crate::txout::blind::RevealedSeal;
+use crate::txout::{CloseMethod, ExplicitSeal, TxoSeal};
+
+/// Constructs outpoint from raw txid bytes in the internal byte order and
+/// output number.
+#[inline]
+pub fn outpoint_from_raw(txid: [u8; 32], vout: u32) -> OutPoint {
+    OutPoint::new(Txid::from_inner(txid), vout)
+}
+
+/// Returns raw txid bytes in the internal byte order and output number of the
+/// outpoint.
+#[inline]
+pub fn outpoint_to_raw(outpoint: OutPoint) -> ([u8; 32], u32) {
+    (outpoint.txid.into_inner(), outpoint.vout)
+}
+
+impl ExplicitSeal {
+    /// Constructs seal over the transaction output defined by raw txid bytes
+    /// in the internal byte order and output number.
+    #[inline]
+    pub fn from_raw_parts(
+        method: CloseMethod,
+        txid: [u8; 32],
+        vout: u32,
+    ) -> ExplicitSeal {
+        ExplicitSeal::new(method, outpoint_from_raw(txid, vout))
+    }
+
+    /// Constructs seal over the output of the witness transaction with the
+    /// given number.
+    #[inline]
+    pub fn from_raw_witness_vout(
+        method: CloseMethod,
+        vout: u32,
+    ) -> ExplicitSeal {
+        ExplicitSeal::new_witness(method, vout)
+    }
+
+    /// Returns seal closing method, raw txid bytes in the internal byte order
+    /// (`None` for the seals over witness transaction outputs) and output
+    /// number.
+    #[inline]
+    pub fn to_raw_parts(&self) -> (CloseMethod, Option<[u8; 32]>, u32) {
+        raw_parts(self)
+    }
+}
+
+impl RevealedSeal {
+    /// Constructs seal over the transaction output defined by raw txid bytes
+    /// in the internal byte order and output number, using the provided
+    /// blinding factor.
+    #[inline]
+    pub fn from_raw_parts(
+        method: CloseMethod,
+        txid: [u8; 32],
+        vout: u32,
+        blinding: u64,
+    ) -> RevealedSeal {
+        ExplicitSeal::from_raw_parts(method, txid, vout).blind(blinding)
+    }
+
+    /// Returns seal closing method, raw txid bytes in the internal byte order
+    /// (`None` for the seals over witness transaction outputs), output number
+    /// and blinding factor.
+    #[inline]
+    pub fn to_raw_parts(&self) -> (CloseMethod, Option<[u8; 32]>, u32, u64) {
+        let (method, txid, vout) = raw_parts(self);
+        (method, txid, vout, self.blinding)
+    }
+}
+
+fn raw_parts(seal: &impl TxoSeal) -> (CloseMethod, Option<[u8; 32]>, u32) {
+    (
+        seal.method(),
+        seal.txid().map(Txid::into_inner),
+        seal.vout().into_u32(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::hashes::hex::FromHex;
+
+    use super::*;
+
+    const TXID: &str =
+        "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+
+    #[test]
+    fn byte_order() {
+        let txid = Txid::from_str(TXID).unwrap();
+        let raw = txid.into_inner();
+        // Internal byte order is the reverse of the hex representation
+        let mut hex_bytes = <[u8; 32]>::from_hex(TXID).unwrap();
+        assert_ne!(raw, hex_bytes);
+        hex_bytes.reverse();
+        assert_eq!(raw, hex_bytes);
+
+        let outpoint = OutPoint::new(txid, 7);
+        assert_eq!(outpoint_to_raw(outpoint), (raw, 7));
+        assert_eq!(outpoint_from_raw(raw, 7), outpoint);
+        assert_ne!(
+            outpoint_from_raw(<[u8; 32]>::from_hex(TXID).unwrap(), 7),
+            outpoint
+        );
+    }
+
+    #[test]
+    fn seal_raw_parts() {
+        let txid = Txid::from_str(TXID).unwrap();
+        let raw = txid.into_inner();
+        let seal =
+            ExplicitSeal::from_raw_parts(CloseMethod::OpretFirst, raw, 2);
+        assert_eq!(
+            seal,
+            ExplicitSeal::new(CloseMethod::OpretFirst, OutPoint::new(txid, 2))
+        );
+        assert_eq!(seal.to_string(), format!("opret1st:{}:2", TXID));
+        assert_eq!(
+            seal.to_raw_parts(),
+            (CloseMethod::OpretFirst, Some(raw), 2)
+        );
+
+        let witness =
+            ExplicitSeal::from_raw_witness_vout(CloseMethod::TapretFirst, 3);
+        assert_eq!(witness.to_raw_parts(), (CloseMethod::TapretFirst, None, 3));
+        assert_eq!(witness.to_string(), "tapret1st:~:3");
+
+        let revealed =
+            RevealedSeal::from_raw_parts(CloseMethod::OpretFirst, raw, 2, 5);
+        assert_eq!(revealed, seal.blind(5));
+        assert_eq!(
+            revealed.to_raw_parts(),
+            (CloseMethod::OpretFirst, Some(raw), 2, 5)
+        );
+        assert_eq!(
+            witness.blind(5).to_raw_parts(),
+            (CloseMethod::TapretFirst, None, 3, 5)
+        );
+    }
+}
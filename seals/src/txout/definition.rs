@@ -0,0 +1,344 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Seal definitions distinguishing seals over known transaction outputs from
+//! seals over outputs of a yet-unknown witness transaction.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use bitcoin::{OutPoint, Txid};
+use commit_verify::commit_encode;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::txout::explicit::ParseError;
+use crate::txout::{CloseMethod, ExplicitSeal, TxoSeal, Vout};
+
+/// Seal defined over an output of the witness transaction, i.e. the
+/// transaction closing some other seal, which id is not known at the moment
+/// of the seal definition.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(StrictEncode, StrictDecode)]
+pub struct WitnessVoutSeal {
+    /// Commitment to the specific seal close method [`CloseMethod`] which must
+    /// be used to close this seal.
+    pub method: CloseMethod,
+
+    /// Output number within the witness transaction.
+    pub vout: Vout,
+}
+
+impl WitnessVoutSeal {
+    /// Constructs seal for the given output number of the witness transaction.
+    #[inline]
+    pub fn new(method: CloseMethod, vout: impl Into<Vout>) -> WitnessVoutSeal {
+        WitnessVoutSeal {
+            method,
+            vout: vout.into(),
+        }
+    }
+
+    /// Constructs full outpoint of the seal once the witness transaction id
+    /// becomes known.
+    #[inline]
+    pub fn resolve(self, witness_txid: Txid) -> OutPoint {
+        OutPoint::new(witness_txid, self.vout.into_u32())
+    }
+}
+
+impl TxoSeal for WitnessVoutSeal {
+    #[inline]
+    fn method(&self) -> CloseMethod { self.method }
+
+    #[inline]
+    fn txid(&self) -> Option<Txid> { None }
+
+    #[inline]
+    fn vout(&self) -> Vout { self.vout }
+
+    #[inline]
+    fn outpoint(&self) -> Option<OutPoint> { None }
+
+    #[inline]
+    fn txid_or(&self, default_txid: Txid) -> Txid { default_txid }
+
+    #[inline]
+    fn outpoint_or(&self, default_txid: Txid) -> OutPoint {
+        self.resolve(default_txid)
+    }
+}
+
+impl From<WitnessVoutSeal> for ExplicitSeal {
+    #[inline]
+    fn from(seal: WitnessVoutSeal) -> Self {
+        ExplicitSeal::with(seal.method, None, seal.vout)
+    }
+}
+
+impl FromStr for WitnessVoutSeal {
+    type Err = ParseError;
+
+    /// Parses seal from `method:~:vout` string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match SealDefinition::from_str(s)? {
+            SealDefinition::WitnessVout(seal) => Ok(seal),
+            SealDefinition::Explicit(_) => Err(ParseError::UnexpectedTxid),
+        }
+    }
+}
+
+impl Display for WitnessVoutSeal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:~:{}", self.method, self.vout)
+    }
+}
+
+/// Definition of a transaction output-based seal, which may either point to an
+/// output of a known transaction, or to an output of the witness transaction.
+///
+/// Conversion from [`ExplicitSeal`] always puts seals without txid into
+/// [`SealDefinition::WitnessVout`] variant. Strict encoding and serde
+/// representations are the same as for [`ExplicitSeal`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, From)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", from = "ExplicitSeal", into = "ExplicitSeal")
+)]
+pub enum SealDefinition {
+    /// Seal defined over an output of a transaction with a known id.
+    Explicit(ExplicitSeal),
+
+    /// Seal defined over an output of the witness transaction.
+    #[from]
+    WitnessVout(WitnessVoutSeal),
+}
+
+impl From<ExplicitSeal> for SealDefinition {
+    fn from(seal: ExplicitSeal) -> Self {
+        match seal.txid {
+            Some(_) => SealDefinition::Explicit(seal),
+            None => SealDefinition::WitnessVout(WitnessVoutSeal::new(
+                seal.method,
+                seal.vout,
+            )),
+        }
+    }
+}
+
+impl From<SealDefinition> for ExplicitSeal {
+    fn from(seal: SealDefinition) -> Self {
+        match seal {
+            SealDefinition::Explicit(seal) => seal,
+            SealDefinition::WitnessVout(seal) => seal.into(),
+        }
+    }
+}
+
+impl TxoSeal for SealDefinition {
+    fn method(&self) -> CloseMethod {
+        match self {
+            SealDefinition::Explicit(seal) => seal.method(),
+            SealDefinition::WitnessVout(seal) => seal.method(),
+        }
+    }
+
+    fn txid(&self) -> Option<Txid> {
+        match self {
+            SealDefinition::Explicit(seal) => seal.txid(),
+            SealDefinition::WitnessVout(seal) => seal.txid(),
+        }
+    }
+
+    fn vout(&self) -> Vout {
+        match self {
+            SealDefinition::Explicit(seal) => seal.vout(),
+            SealDefinition::WitnessVout(seal) => seal.vout(),
+        }
+    }
+
+    fn outpoint(&self) -> Option<OutPoint> {
+        match self {
+            SealDefinition::Explicit(seal) => seal.outpoint(),
+            SealDefinition::WitnessVout(seal) => seal.outpoint(),
+        }
+    }
+
+    fn txid_or(&self, default_txid: Txid) -> Txid {
+        match self {
+            SealDefinition::Explicit(seal) => seal.txid_or(default_txid),
+            SealDefinition::WitnessVout(seal) => seal.txid_or(default_txid),
+        }
+    }
+
+    fn outpoint_or(&self, default_txid: Txid) -> OutPoint {
+        match self {
+            SealDefinition::Explicit(seal) => seal.outpoint_or(default_txid),
+            SealDefinition::WitnessVout(seal) => seal.outpoint_or(default_txid),
+        }
+    }
+}
+
+impl StrictEncode for SealDefinition {
+    #[inline]
+    fn strict_encode<E: Write>(
+        &self,
+        e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        ExplicitSeal::from(*self).strict_encode(e)
+    }
+}
+
+impl StrictDecode for SealDefinition {
+    #[inline]
+    fn strict_decode<D: Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        ExplicitSeal::strict_decode(d).map(SealDefinition::from)
+    }
+}
+
+impl commit_encode::Strategy for SealDefinition {
+    type Strategy = commit_encode::strategies::UsingStrict;
+}
+
+impl FromStr for SealDefinition {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExplicitSeal::from_str(s).map(SealDefinition::from)
+    }
+}
+
+impl Display for SealDefinition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SealDefinition::Explicit(seal) => Display::fmt(seal, f),
+            SealDefinition::WitnessVout(seal) => Display::fmt(seal, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::FromHex;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+
+    fn txid() -> Txid {
+        Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn witness_vout_str() {
+        let seal = WitnessVoutSeal::from_str("tapret1st:~:5").unwrap();
+        assert_eq!(seal, WitnessVoutSeal::new(CloseMethod::TapretFirst, 5));
+        assert_eq!(seal.to_string(), "tapret1st:~:5");
+        assert_eq!(
+            SealDefinition::from_str("tapret1st:~:5"),
+            Ok(SealDefinition::WitnessVout(seal))
+        );
+        assert_eq!(
+            WitnessVoutSeal::from_str(&format!("tapret1st:{}:5", txid())),
+            Err(ParseError::UnexpectedTxid)
+        );
+
+        let s = format!("opret1st:{}:2", txid());
+        let definition = SealDefinition::from_str(&s).unwrap();
+        assert_eq!(
+            definition,
+            SealDefinition::Explicit(ExplicitSeal::with(
+                CloseMethod::OpretFirst,
+                Some(txid()),
+                2
+            ))
+        );
+        assert_eq!(definition.to_string(), s);
+    }
+
+    #[test]
+    fn witness_vout_resolve() {
+        let witness_txid = txid();
+        let seal = WitnessVoutSeal::new(CloseMethod::OpretFirst, 3);
+        assert_eq!(seal.resolve(witness_txid), OutPoint::new(witness_txid, 3));
+        assert_eq!(seal.txid(), None);
+        assert_eq!(seal.outpoint(), None);
+        assert_eq!(seal.txid_or(witness_txid), witness_txid);
+        assert_eq!(seal.outpoint_or(witness_txid), seal.resolve(witness_txid));
+
+        let definition = SealDefinition::from(seal);
+        assert_eq!(definition.method(), CloseMethod::OpretFirst);
+        assert_eq!(definition.vout(), Vout::from(3));
+        assert_eq!(definition.outpoint(), None);
+        assert_eq!(
+            definition.outpoint_or(witness_txid),
+            seal.resolve(witness_txid)
+        );
+    }
+
+    #[test]
+    fn explicit_conversion() {
+        let explicit = ExplicitSeal::with(CloseMethod::TapretFirst, None, 7);
+        let definition = SealDefinition::from(explicit);
+        assert_eq!(
+            definition,
+            SealDefinition::WitnessVout(WitnessVoutSeal::new(
+                CloseMethod::TapretFirst,
+                7
+            ))
+        );
+        assert_eq!(ExplicitSeal::from(definition), explicit);
+
+        let explicit =
+            ExplicitSeal::with(CloseMethod::TapretFirst, Some(txid()), 7);
+        let definition = SealDefinition::from(explicit);
+        assert_eq!(definition, SealDefinition::Explicit(explicit));
+        assert_eq!(definition.outpoint(), Some(OutPoint::new(txid(), 7)));
+        assert_eq!(ExplicitSeal::from(definition), explicit);
+    }
+
+    #[test]
+    fn strict_encoding_compatibility() {
+        for explicit in [
+            ExplicitSeal::with(CloseMethod::TapretFirst, None, 7),
+            ExplicitSeal::with(CloseMethod::OpretFirst, Some(txid()), 1),
+        ] {
+            let definition = SealDefinition::from(explicit);
+            let data = strict_serialize(&explicit).unwrap();
+            assert_eq!(strict_serialize(&definition).unwrap(), data);
+            assert_eq!(
+                strict_deserialize::<SealDefinition>(&data).unwrap(),
+                definition
+            );
+        }
+
+        // Witness seal layout: method, `None` tag, vout
+        let data = strict_serialize(&SealDefinition::from(
+            WitnessVoutSeal::new(CloseMethod::TapretFirst, 7),
+        ))
+        .unwrap();
+        assert_eq!(data, vec![0x01, 0x00, 0x07, 0x00, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,239 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Seal definitions over addresses derived from wallet output descriptors.
+
+use bitcoin::secp256k1::{Secp256k1, Verification};
+use bitcoin::{Address, Network, Script, TxOut, Txid};
+use miniscript::descriptor::{ConversionError, DescriptorPublicKey};
+use miniscript::{Descriptor, DescriptorTrait};
+
+use crate::txout::{CloseMethod, ExplicitSeal};
+
+/// Errors deriving seal from a wallet descriptor.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum DescriptorSealError {
+    /// unable to derive descriptor keys: {0}.
+    #[from]
+    Derivation(ConversionError),
+
+    /// descriptor does not have an address form: {0}.
+    Address(String),
+
+    /// descriptor outputs can't host {0} commitments.
+    MethodMismatch(CloseMethod),
+}
+
+impl From<miniscript::Error> for DescriptorSealError {
+    #[inline]
+    fn from(err: miniscript::Error) -> Self {
+        DescriptorSealError::Address(err.to_string())
+    }
+}
+
+/// Seal definition over the output which is not yet funded, recording the
+/// output `script_pubkey` and the closing method.
+///
+/// The template is completed into [`ExplicitSeal`] once the funding
+/// transaction is known with [`ExplicitSealTemplate::complete`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExplicitSealTemplate {
+    /// Closing method which must be used to close the seal.
+    pub method: CloseMethod,
+
+    /// Script of the output which will be defining the seal.
+    pub script_pubkey: Script,
+}
+
+impl ExplicitSealTemplate {
+    /// Constructs template for the given closing `method` and output script,
+    /// checking that the script can host commitments made with the method
+    /// according to [`CloseMethod::can_host`].
+    pub fn with(
+        method: CloseMethod,
+        script_pubkey: Script,
+    ) -> Result<ExplicitSealTemplate, DescriptorSealError> {
+        if !method.can_host(&script_pubkey) {
+            return Err(DescriptorSealError::MethodMismatch(method));
+        }
+        Ok(ExplicitSealTemplate {
+            method,
+            script_pubkey,
+        })
+    }
+
+    /// Detects whether the transaction output pays to the template script.
+    #[inline]
+    pub fn matches(&self, txout: &TxOut) -> bool {
+        txout.script_pubkey == self.script_pubkey
+    }
+
+    /// Completes the template into the seal over the output `vout` of the
+    /// funding transaction `txid`.
+    #[inline]
+    pub fn complete(&self, txid: Txid, vout: u32) -> ExplicitSeal {
+        ExplicitSeal::with(self.method, Some(txid), vout)
+    }
+}
+
+/// Derives address at the `index` from the descriptor and constructs seal
+/// template closed with the given `method` for the output paying to that
+/// address.
+///
+/// Fails if the descriptor requires hardened derivation, has no address form
+/// or the derived output can't host commitments made with the `method` (for
+/// instance, tapret seals are accepted only for `tr` descriptors, while opret
+/// seals can be defined over any standard output type).
+pub fn seal_from_descriptor<C: Verification>(
+    secp: &Secp256k1<C>,
+    desc: &Descriptor<DescriptorPublicKey>,
+    index: u32,
+    method: CloseMethod,
+    network: Network,
+) -> Result<(Address, ExplicitSealTemplate), DescriptorSealError> {
+    let derived = desc.derived_descriptor(secp, index)?;
+    let address = derived.address(network)?;
+    let template = ExplicitSealTemplate::with(method, derived.script_pubkey())?;
+    Ok((address, template))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::SECP256K1;
+
+    use super::*;
+
+    // BIP-86 and BIP-84 test vectors for the first account
+    const TR_DESC: &str = "tr(xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)";
+    const WPKH_DESC: &str = "wpkh(xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)";
+
+    fn descriptor(s: &str) -> Descriptor<DescriptorPublicKey> {
+        Descriptor::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn tr_descriptor() {
+        let desc = descriptor(TR_DESC);
+        for (index, addr) in [
+            (0, "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"),
+            (1, "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"),
+        ] {
+            for method in CloseMethod::iter() {
+                let (address, template) = seal_from_descriptor(
+                    SECP256K1,
+                    &desc,
+                    index,
+                    method,
+                    Network::Bitcoin,
+                )
+                .unwrap();
+                assert_eq!(address.to_string(), addr);
+                assert_eq!(template.method, method);
+                assert_eq!(template.script_pubkey, address.script_pubkey());
+            }
+        }
+    }
+
+    #[test]
+    fn wpkh_descriptor() {
+        let desc = descriptor(WPKH_DESC);
+        let (address, template) = seal_from_descriptor(
+            SECP256K1,
+            &desc,
+            0,
+            CloseMethod::OpretFirst,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(template.script_pubkey, address.script_pubkey());
+
+        assert_eq!(
+            seal_from_descriptor(
+                SECP256K1,
+                &desc,
+                0,
+                CloseMethod::TapretFirst,
+                Network::Bitcoin,
+            ),
+            Err(DescriptorSealError::MethodMismatch(
+                CloseMethod::TapretFirst
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_descriptors() {
+        let hardened = descriptor(&TR_DESC.replace("/0/*", "/0/*'"));
+        assert_eq!(
+            seal_from_descriptor(
+                SECP256K1,
+                &hardened,
+                0,
+                CloseMethod::OpretFirst,
+                Network::Bitcoin,
+            ),
+            Err(DescriptorSealError::Derivation(
+                ConversionError::HardenedChild
+            ))
+        );
+
+        let bare = descriptor(
+            "pk(02e6642fd69bd211f93f7f1f36ca51a26a5290eb2dd1b0d8279a87bb0d480c8443)",
+        );
+        assert!(matches!(
+            seal_from_descriptor(
+                SECP256K1,
+                &bare,
+                0,
+                CloseMethod::OpretFirst,
+                Network::Bitcoin,
+            ),
+            Err(DescriptorSealError::Address(_))
+        ));
+    }
+
+    #[test]
+    fn complete() {
+        let desc = descriptor(TR_DESC);
+        let (_, template) = seal_from_descriptor(
+            SECP256K1,
+            &desc,
+            0,
+            CloseMethod::TapretFirst,
+            Network::Testnet,
+        )
+        .unwrap();
+        let txout = TxOut {
+            value: 1000,
+            script_pubkey: template.script_pubkey.clone(),
+        };
+        assert!(template.matches(&txout));
+        assert!(!template.matches(&TxOut::default()));
+
+        let txid = Txid::hash(b"funding");
+        assert_eq!(
+            template.complete(txid, 3),
+            ExplicitSeal::with(CloseMethod::TapretFirst, Some(txid), 3)
+        );
+    }
+}
@@ -0,0 +1,517 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Transaction resolver using Electrum server as the publication medium.
+
+use bitcoin::{OutPoint, Transaction, Txid};
+use electrum_client::{Client, ElectrumApi};
+
+use crate::txout::TxResolve;
+
+/// Part of the error message returned by bitcoin core (and forwarded by
+/// Electrum servers) when the requested transaction is not known.
+const NO_SUCH_TX_MESSAGE: &str = "No such mempool or blockchain transaction";
+
+/// Errors of the Electrum-based transaction resolver.
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ElectrumResolverError {
+    /// transaction {0} is not known to the Electrum server.
+    UnknownTx(Txid),
+
+    /// transaction output {0} does not exist.
+    UnknownOutput(OutPoint),
+
+    /// Electrum server has returned an error. Details: {0}
+    Server(String),
+
+    /// unable to access Electrum server. Details: {0}
+    #[from]
+    Connection(electrum_client::Error),
+}
+
+impl ElectrumResolverError {
+    fn with_txid(err: electrum_client::Error, txid: Txid) -> Self {
+        match err {
+            electrum_client::Error::Protocol(value)
+                if value.to_string().contains(NO_SUCH_TX_MESSAGE) =>
+            {
+                ElectrumResolverError::UnknownTx(txid)
+            }
+            err => ElectrumResolverError::from_electrum(err),
+        }
+    }
+
+    fn from_electrum(err: electrum_client::Error) -> Self {
+        match err {
+            electrum_client::Error::Protocol(value) => {
+                ElectrumResolverError::Server(value.to_string())
+            }
+            err => ElectrumResolverError::Connection(err),
+        }
+    }
+}
+
+/// Transaction resolver querying Electrum server.
+///
+/// Transactions are retrieved using `blockchain.transaction.get` requests;
+/// spending transactions are found by scanning the history of the
+/// scriptPubkey of the spent output.
+#[derive(Debug)]
+pub struct ElectrumResolver<C: ElectrumApi = Client> {
+    client: C,
+}
+
+impl ElectrumResolver {
+    /// Connects to the Electrum server at `url`.
+    pub fn connect(url: &str) -> Result<Self, ElectrumResolverError> {
+        Ok(ElectrumResolver::with(Client::new(url)?))
+    }
+}
+
+impl<C> ElectrumResolver<C>
+where
+    C: ElectrumApi,
+{
+    /// Constructs resolver from an already connected Electrum client.
+    #[inline]
+    pub fn with(client: C) -> Self { ElectrumResolver { client } }
+
+    /// Returns reference to the underlying Electrum client.
+    #[inline]
+    pub fn client(&self) -> &C { &self.client }
+
+    /// Releases the underlying Electrum client.
+    #[inline]
+    pub fn into_client(self) -> C { self.client }
+}
+
+impl<C> TxResolve for ElectrumResolver<C>
+where
+    C: ElectrumApi,
+{
+    type Error = ElectrumResolverError;
+
+    fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error> {
+        self.client
+            .transaction_get(&txid)
+            .map_err(|err| ElectrumResolverError::with_txid(err, txid))
+    }
+
+    fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let prev_tx = self.resolve_tx(outpoint.txid)?;
+        let script_pubkey = &prev_tx
+            .output
+            .get(outpoint.vout as usize)
+            .ok_or(ElectrumResolverError::UnknownOutput(outpoint))?
+            .script_pubkey;
+
+        let history = self
+            .client
+            .script_get_history(script_pubkey)
+            .map_err(ElectrumResolverError::from_electrum)?;
+        for item in history {
+            if item.tx_hash == outpoint.txid {
+                continue;
+            }
+            let tx = self.resolve_tx(item.tx_hash)?;
+            if tx.input.iter().any(|txin| txin.previous_output == outpoint) {
+                return Ok(Some(tx));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use bitcoin::consensus::serialize;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Script, TxIn, TxOut, WPubkeyHash};
+    use electrum_client::{
+        Batch, Call, GetBalanceRes, GetHeadersRes, GetHistoryRes, GetMerkleRes,
+        ListUnspentRes, RawHeaderNotification, ScriptStatus, ServerFeaturesRes,
+    };
+
+    use super::*;
+
+    /// Error returned by electrs for unknown transactions.
+    const UNKNOWN_TX_RESPONSE: &str = r#"{"code":2,"message":"daemon error: DaemonError { code: -5, message: \"No such mempool or blockchain transaction. Use gettransaction for wallet transactions.\" }"}"#;
+
+    /// Error returned by electrs for the requests it can't process.
+    const HISTORY_TOO_LARGE_RESPONSE: &str =
+        r#"{"code":1,"message":"too many txs in history"}"#;
+
+    /// Electrum client replaying fixture responses.
+    #[derive(Default)]
+    struct FixtureClient {
+        txs: HashMap<Txid, Vec<u8>>,
+        history: HashMap<Script, Vec<Txid>>,
+        failing_history: bool,
+    }
+
+    impl FixtureClient {
+        fn add_tx(&mut self, tx: &Transaction) {
+            let txid = tx.txid();
+            self.txs.insert(txid, serialize(tx));
+            for txout in &tx.output {
+                self.history
+                    .entry(txout.script_pubkey.clone())
+                    .or_default()
+                    .push(txid);
+            }
+        }
+
+        fn add_spending_history(&mut self, script_pubkey: &Script, txid: Txid) {
+            self.history
+                .entry(script_pubkey.clone())
+                .or_default()
+                .push(txid);
+        }
+    }
+
+    fn protocol_error(response: &str) -> electrum_client::Error {
+        electrum_client::Error::Protocol(
+            serde_json::from_str(response).unwrap(),
+        )
+    }
+
+    fn unsupported() -> electrum_client::Error {
+        electrum_client::Error::Message(s!("not supported by fixture client"))
+    }
+
+    impl ElectrumApi for FixtureClient {
+        fn raw_call(
+            &self,
+            _: &Call,
+        ) -> Result<serde_json::Value, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn batch_call(
+            &self,
+            _: &Batch,
+        ) -> Result<Vec<serde_json::Value>, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn block_headers_subscribe_raw(
+            &self,
+        ) -> Result<RawHeaderNotification, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn block_headers_pop_raw(
+            &self,
+        ) -> Result<Option<RawHeaderNotification>, electrum_client::Error>
+        {
+            Err(unsupported())
+        }
+
+        fn block_header_raw(
+            &self,
+            _: usize,
+        ) -> Result<Vec<u8>, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn block_headers(
+            &self,
+            _: usize,
+            _: usize,
+        ) -> Result<GetHeadersRes, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn estimate_fee(
+            &self,
+            _: usize,
+        ) -> Result<f64, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn relay_fee(&self) -> Result<f64, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn script_subscribe(
+            &self,
+            _: &Script,
+        ) -> Result<Option<ScriptStatus>, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn script_unsubscribe(
+            &self,
+            _: &Script,
+        ) -> Result<bool, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn script_pop(
+            &self,
+            _: &Script,
+        ) -> Result<Option<ScriptStatus>, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn script_get_balance(
+            &self,
+            _: &Script,
+        ) -> Result<GetBalanceRes, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn batch_script_get_balance<'s, I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<GetBalanceRes>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = &'s Script> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn script_get_history(
+            &self,
+            script: &Script,
+        ) -> Result<Vec<GetHistoryRes>, electrum_client::Error> {
+            if self.failing_history {
+                return Err(protocol_error(HISTORY_TOO_LARGE_RESPONSE));
+            }
+            Ok(self
+                .history
+                .get(script)
+                .into_iter()
+                .flatten()
+                .map(|txid| GetHistoryRes {
+                    height: 1,
+                    tx_hash: *txid,
+                    fee: None,
+                })
+                .collect())
+        }
+
+        fn batch_script_get_history<'s, I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<Vec<GetHistoryRes>>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = &'s Script> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn script_list_unspent(
+            &self,
+            _: &Script,
+        ) -> Result<Vec<ListUnspentRes>, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn batch_script_list_unspent<'s, I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<Vec<ListUnspentRes>>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = &'s Script> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn transaction_get_raw(
+            &self,
+            txid: &Txid,
+        ) -> Result<Vec<u8>, electrum_client::Error> {
+            self.txs
+                .get(txid)
+                .cloned()
+                .ok_or_else(|| protocol_error(UNKNOWN_TX_RESPONSE))
+        }
+
+        fn batch_transaction_get_raw<'t, I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<Vec<u8>>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = &'t Txid> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn batch_block_header_raw<I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<Vec<u8>>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = u32> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn batch_estimate_fee<I>(
+            &self,
+            _: I,
+        ) -> Result<Vec<f64>, electrum_client::Error>
+        where
+            I: IntoIterator<Item = usize> + Clone,
+        {
+            Err(unsupported())
+        }
+
+        fn transaction_broadcast_raw(
+            &self,
+            _: &[u8],
+        ) -> Result<Txid, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn transaction_get_merkle(
+            &self,
+            _: &Txid,
+            _: usize,
+        ) -> Result<GetMerkleRes, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn server_features(
+            &self,
+        ) -> Result<ServerFeaturesRes, electrum_client::Error> {
+            Err(unsupported())
+        }
+
+        fn ping(&self) -> Result<(), electrum_client::Error> {
+            Err(unsupported())
+        }
+    }
+
+    fn script_pubkey(tag: &[u8]) -> Script {
+        Script::new_v0_p2wpkh(&WPubkeyHash::hash(tag))
+    }
+
+    fn tx(prevout: OutPoint, outputs: &[Script]) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: prevout,
+                ..default!()
+            }],
+            output: outputs
+                .iter()
+                .map(|script_pubkey| TxOut {
+                    value: 10_000,
+                    script_pubkey: script_pubkey.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn fixture() -> (FixtureClient, Transaction, Transaction, Transaction) {
+        let funding = tx(OutPoint::new(Txid::hash(b"coinbase"), 0), &[
+            script_pubkey(b"seal"),
+            script_pubkey(b"change"),
+        ]);
+        // Unrelated transaction paying to the same scriptPubkey
+        let unrelated =
+            tx(OutPoint::new(Txid::hash(b"other"), 0), &[script_pubkey(
+                b"seal",
+            )]);
+        let spending =
+            tx(OutPoint::new(funding.txid(), 0), &[script_pubkey(b"next")]);
+
+        let mut client = FixtureClient::default();
+        client.add_tx(&funding);
+        client.add_tx(&unrelated);
+        client.add_tx(&spending);
+        client.add_spending_history(&script_pubkey(b"seal"), spending.txid());
+        (client, funding, unrelated, spending)
+    }
+
+    #[test]
+    fn resolve_tx() {
+        let (client, funding, _, spending) = fixture();
+        let resolver = ElectrumResolver::with(client);
+        assert_eq!(resolver.resolve_tx(funding.txid()).unwrap(), funding);
+        assert_eq!(resolver.resolve_tx(spending.txid()).unwrap(), spending);
+
+        let unknown = Txid::hash(b"unknown");
+        assert!(matches!(
+            resolver.resolve_tx(unknown),
+            Err(ElectrumResolverError::UnknownTx(txid)) if txid == unknown
+        ));
+    }
+
+    #[test]
+    fn spending_tx() {
+        let (client, funding, unrelated, spending) = fixture();
+        let resolver = ElectrumResolver::with(client);
+
+        assert_eq!(
+            resolver
+                .spending_tx(OutPoint::new(funding.txid(), 0))
+                .unwrap(),
+            Some(spending)
+        );
+        assert_eq!(
+            resolver
+                .spending_tx(OutPoint::new(funding.txid(), 1))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            resolver
+                .spending_tx(OutPoint::new(unrelated.txid(), 0))
+                .unwrap(),
+            None
+        );
+
+        let outpoint = OutPoint::new(funding.txid(), 2);
+        assert!(matches!(
+            resolver.spending_tx(outpoint),
+            Err(ElectrumResolverError::UnknownOutput(op)) if op == outpoint
+        ));
+        let unknown = Txid::hash(b"unknown");
+        assert!(matches!(
+            resolver.spending_tx(OutPoint::new(unknown, 0)),
+            Err(ElectrumResolverError::UnknownTx(txid)) if txid == unknown
+        ));
+    }
+
+    #[test]
+    fn server_error() {
+        let (mut client, funding, _, _) = fixture();
+        client.failing_history = true;
+        let resolver = ElectrumResolver::with(client);
+        assert!(matches!(
+            resolver.spending_tx(OutPoint::new(funding.txid(), 0)),
+            Err(ElectrumResolverError::Server(msg))
+                if msg.contains("too many txs in history")
+        ));
+    }
+
+    #[test]
+    fn connection_error() {
+        assert!(matches!(
+            ElectrumResolver::connect("tcp://127.0.0.1:1"),
+            Err(ElectrumResolverError::Connection(_))
+        ));
+    }
+}
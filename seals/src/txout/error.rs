@@ -13,33 +13,305 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+use std::fmt::{self, Display, Formatter};
+
 use bitcoin::{OutPoint, Txid};
 use bitcoin_onchain::TxResolverError;
 
+use crate::limits::MAX_VOUT;
+use crate::txout::blind::ConcealedSeal;
+use crate::txout::redact::redacted;
+use crate::txout::CloseMethod;
+
 /// Seal verification errors.
-#[derive(Debug, Display, From, Error)]
-#[display(doc_comments)]
+///
+/// Errors distinguish seals which are not closed yet ([`Self::SealNotClosed`],
+/// which may be recovered by waiting for the closing transaction) from the
+/// invalid closings ([`Self::WitnessNotSpendingSeal`],
+/// [`Self::InvalidCommitment`]) and from the failures to access the
+/// publication medium ([`Self::ResolverError`]).
+///
+/// Seal outpoints are displayed in [`Redacted`](crate::txout::Redacted) form;
+/// the alternate formatter (`{:#}`) displays them in full. Since the derived
+/// display can't do that, the error messages are defined only by the
+/// [`Display`] implementation.
+#[derive(Debug, From, Error)]
 pub enum VerifyError {
-    /// seals provided for a batch verification have inconsistent close method.
+    /// Batch of seals mixes different close methods.
     InconsistentCloseMethod,
 
-    /// witness transaction can't be found in the publication medium
-    /// (blockchain or channel) by the given id {0}.
+    /// Empty set of seals.
+    NoSeals,
+
+    /// Unknown witness transaction id.
     WitnessTxUnknown(Txid),
 
-    /// the provided witness transaction {0} does not closes seal {1}.
-    WitnessNotClosingSeal(Txid, OutPoint),
+    /// Unspent seal output.
+    SealNotClosed(OutPoint),
+
+    /// Witness transaction not spending the seal output.
+    WitnessNotSpendingSeal {
+        /// Witness transaction id.
+        witness: Txid,
+        /// Seal outpoint.
+        seal: OutPoint,
+    },
 
-    /// tapret commitment is invalid.
-    ///
-    /// Details: {0}
+    /// Seal and commitment proof close methods differ.
+    MethodMismatch {
+        /// Closing method required by the seal.
+        seal: CloseMethod,
+        /// Closing method of the commitment proof.
+        proof: CloseMethod,
+    },
+
+    /// Unsupported commitment proof type.
+    UnsupportedProof,
+
+    /// Commitment not matching the message.
+    InvalidCommitment(CloseMethod),
+
+    /// Seal output absent in the transaction.
+    SealOutputUnknown(OutPoint),
+
+    /// Invalid tapret commitment.
     #[from]
     InvalidTapretCommitment(dbc::tapret::TapretError),
 
-    /// unable to access commitment publication medium.
+    /// Malformed witness transaction.
     #[from]
-    #[display(inner)]
-    TxResolverError(TxResolverError),
+    WitnessCheck(WitnessCheckError),
+
+    /// Witness transaction not confirmed deep enough.
+    InsufficientConfirmations {
+        /// Number of confirmations of the witness transaction; zero for the
+        /// unmined transactions.
+        have: u32,
+        /// Number of confirmations required by the verification policy.
+        need: u32,
+    },
+
+    /// Required number of confirmations which can't be checked without the
+    /// transaction resolver.
+    ConfirmationsUnchecked(u32),
+
+    /// Witness transaction height above the best block height.
+    InconsistentHeight {
+        /// Height of the block containing the witness transaction.
+        height: u32,
+        /// Height of the best block.
+        tip: u32,
+    },
+
+    /// Failure accessing the publication medium.
+    ResolverError(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let full = f.alternate();
+        let seal = |outpoint: &OutPoint| redacted(outpoint, full);
+        match self {
+            VerifyError::InconsistentCloseMethod => f.write_str(
+                "seals provided for a batch verification have inconsistent \
+                 close method.",
+            ),
+            VerifyError::NoSeals => {
+                f.write_str("no seals are provided for the verification.")
+            }
+            VerifyError::WitnessTxUnknown(txid) => write!(
+                f,
+                "witness transaction can't be found in the publication medium \
+                 (blockchain or channel) by the given id {}.",
+                txid
+            ),
+            VerifyError::SealNotClosed(outpoint) => write!(
+                f,
+                "seal {} is not closed: its output is not spent by any \
+                 transaction.",
+                seal(outpoint)
+            ),
+            VerifyError::WitnessNotSpendingSeal {
+                witness,
+                seal: outpoint,
+            } => write!(
+                f,
+                "the provided witness transaction {} does not spend seal {}.",
+                witness,
+                seal(outpoint)
+            ),
+            VerifyError::MethodMismatch { seal, proof } => write!(
+                f,
+                "seal requires {} closing method, while the witness \
+                 commitment proof uses {}.",
+                seal, proof
+            ),
+            VerifyError::UnsupportedProof => f.write_str(
+                "the witness commitment proof type is not supported by this \
+                 version of the library.",
+            ),
+            VerifyError::InvalidCommitment(method) => write!(
+                f,
+                "{} commitment of the witness transaction does not match the \
+                 message.",
+                method
+            ),
+            VerifyError::SealOutputUnknown(outpoint) => write!(
+                f,
+                "seal is defined over transaction output {} which does not \
+                 exist.",
+                seal(outpoint)
+            ),
+            VerifyError::InvalidTapretCommitment(err) => {
+                write!(f, "tapret commitment is invalid.\nDetails: {}", err)
+            }
+            VerifyError::WitnessCheck(err) => {
+                write!(f, "witness transaction is malformed: {}", err)
+            }
+            VerifyError::InsufficientConfirmations { have, need } => write!(
+                f,
+                "witness transaction has {} confirmations, while the \
+                 verification policy requires at least {}.",
+                have, need
+            ),
+            VerifyError::ConfirmationsUnchecked(need) => write!(
+                f,
+                "verification policy requires {} confirmations of the witness \
+                 transaction, which can't be checked without the transaction \
+                 resolver.",
+                need
+            ),
+            VerifyError::InconsistentHeight { height, tip } => write!(
+                f,
+                "resolver reports height {} of the witness transaction above \
+                 the best block height {}.",
+                height, tip
+            ),
+            VerifyError::ResolverError(err) => write!(
+                f,
+                "unable to access commitment publication medium. Details: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl From<TxResolverError> for VerifyError {
+    /// Keeps only the error message, since [`TxResolverError`] may hold
+    /// non-thread-safe error source.
+    #[inline]
+    fn from(err: TxResolverError) -> Self {
+        VerifyError::ResolverError(err.to_string().into())
+    }
+}
+
+/// Errors of the witness transaction sanity checks performed by
+/// [`check_witness_tx`](crate::txout::check_witness_tx) before the commitment
+/// verification.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum WitnessCheckError {
+    /// transaction {0} has no inputs.
+    NoInputs(Txid),
+
+    /// transaction {0} is a coinbase transaction, which can't spend seal
+    /// outputs.
+    Coinbase(Txid),
+
+    /// transaction {0} has no outputs.
+    NoOutputs(Txid),
+
+    /// transaction has id {found}, while {expected} was expected.
+    TxidMismatch {
+        /// Transaction id expected by the caller.
+        expected: Txid,
+        /// Id of the provided transaction.
+        found: Txid,
+    },
+}
+
+/// Errors closing seals.
+///
+/// Seal outpoints are displayed in [`Redacted`](crate::txout::Redacted) form;
+/// the alternate formatter (`{:#}`) displays them in full. Like with
+/// [`VerifyError`], the error messages are defined only by the [`Display`]
+/// implementation.
+#[derive(Clone, PartialEq, Eq, Debug, From, Error)]
+pub enum CloseError {
+    /// Seal and commitment proof close methods differ.
+    MethodMismatch {
+        /// Closing method required by the seal.
+        seal: CloseMethod,
+        /// Closing method of the commitment proof.
+        proof: CloseMethod,
+    },
+
+    /// Unsupported commitment proof type.
+    UnsupportedProof,
+
+    /// Transaction not spending the seal output.
+    NotClosingSeal(Txid, OutPoint),
+
+    /// Transaction without commitment to the message.
+    NoCommitment(Txid),
+
+    /// Invalid tapret commitment.
+    #[from]
+    InvalidTapretCommitment(dbc::tapret::TapretError),
+
+    /// Malformed transaction.
+    #[from]
+    WitnessCheck(WitnessCheckError),
+}
+
+impl Display for CloseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseError::MethodMismatch { seal, proof } => write!(
+                f,
+                "seal requires {} closing method, while the provided \
+                 commitment proof uses {}.",
+                seal, proof
+            ),
+            CloseError::UnsupportedProof => f.write_str(
+                "the commitment proof type is not supported by this version \
+                 of the library.",
+            ),
+            CloseError::NotClosingSeal(txid, outpoint) => write!(
+                f,
+                "the transaction {} does not spend seal outpoint {}.",
+                txid,
+                redacted(outpoint, f.alternate())
+            ),
+            CloseError::NoCommitment(txid) => write!(
+                f,
+                "the transaction {} does not contain commitment to the \
+                 message.",
+                txid
+            ),
+            CloseError::InvalidTapretCommitment(err) => {
+                write!(f, "tapret commitment is invalid.\nDetails: {}", err)
+            }
+            CloseError::WitnessCheck(err) => {
+                write!(f, "the transaction is malformed: {}", err)
+            }
+        }
+    }
+}
+
+/// Errors decoding versioned seal data.
+#[derive(Clone, PartialEq, Eq, Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum SealDecodeError {
+    /// seal data use unsupported encoding version {0}.
+    UnsupportedSealVersion(u8),
+
+    /// transaction output number {0} exceeds the limit of {MAX_VOUT}.
+    VoutOutOfRange(u32),
+
+    /// invalid seal data. Details: {0}
+    #[from]
+    Encoding(strict_encoding::Error),
 }
 
 /// Error happening if the seal data holds only witness transaction output
@@ -52,7 +324,131 @@ pub enum VerifyError {
 #[display("witness txid is unknown; unable to reconstruct full outpoint data")]
 pub struct WitnessVoutError;
 
-/// wrong transaction output-based single-use-seal closing method id '{0}'.
-#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+/// Error happening if the revealed seal does not correspond to the concealed
+/// seal it is claimed to be a reveal of.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
+#[display("revealed seal conceals to {actual} instead of {expected}")]
+pub struct RevealMismatch {
+    /// Concealed seal which was expected to be revealed.
+    pub expected: ConcealedSeal,
+    /// Concealed form of the revealed seal, computed with
+    /// [`RevealedSeal::conceal`](crate::txout::blind::RevealedSeal::conceal).
+    pub actual: ConcealedSeal,
+}
+
+/// Error happening if the seal defined over the witness transaction output
+/// references output which is not present in the witness transaction.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
+#[display(
+    "seal references output #{vout} of the witness transaction {txid}, which \
+     has only {outputs} outputs"
+)]
+pub struct WitnessOutputError {
+    /// Witness transaction id.
+    pub txid: Txid,
+    /// Output number referenced by the seal.
+    pub vout: u32,
+    /// Number of outputs in the witness transaction.
+    pub outputs: usize,
+}
+
+/// Error parsing transaction output-based single-use-seal closing method.
+///
+/// The error is displayed together with the list of valid method names and, if
+/// the input is a near miss of one of them, the suggested method.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub struct MethodParseError {
+    input: String,
+    suggestion: Option<CloseMethod>,
+}
+
+/// Maximal edit distance between the unparsable input and a method name for
+/// the method to be suggested in [`MethodParseError`].
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+impl MethodParseError {
+    /// Constructs error for the unparsable `input`, detecting the closest
+    /// valid method name within edit distance of 2 (ignoring ASCII case) to
+    /// be suggested to the user.
+    pub fn new(input: impl Into<String>) -> MethodParseError {
+        let input = input.into();
+        let lowercase = input.to_ascii_lowercase();
+        let suggestion = CloseMethod::iter()
+            .map(|method| {
+                (edit_distance(&lowercase, &method.to_string()), method)
+            })
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, method)| method);
+        MethodParseError { input, suggestion }
+    }
+
+    /// Returns the string which was failed to parse.
+    #[inline]
+    pub fn input(&self) -> &str { &self.input }
+
+    /// Returns the method with the name closest to the input, if any.
+    #[inline]
+    pub fn suggestion(&self) -> Option<CloseMethod> { self.suggestion }
+}
+
+impl From<String> for MethodParseError {
+    #[inline]
+    fn from(input: String) -> Self { MethodParseError::new(input) }
+}
+
+impl Display for MethodParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wrong transaction output-based single-use-seal closing method id \
+             '{}'; valid methods are ",
+            self.input
+        )?;
+        for (no, method) in CloseMethod::iter().enumerate() {
+            if no > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{}`", method)?;
+        }
+        if let Some(method) = self.suggestion {
+            write!(f, "; did you mean `{}`?", method)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev + usize::from(ca != *cb);
+            prev = row[j + 1];
+            row[j + 1] = substitution.min(prev + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// unknown transaction output-based single-use-seal closing method id {0}.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
+#[display(doc_comments)]
+pub struct UnknownMethodId(pub u8);
+
+/// close method set contains bits {0:#04x} not corresponding to any known
+/// transaction output-based single-use-seal closing method.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
 #[display(doc_comments)]
-pub struct MethodParseError(pub String);
+pub struct UnknownMethodBits(pub u8);
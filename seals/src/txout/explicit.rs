@@ -15,14 +15,23 @@
 
 //! TxOut single-use-seals.
 
-use std::convert::{TryFrom, TryInto};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
-use bitcoin::{OutPoint, Txid};
+use amplify::DumbDefault;
+use bitcoin::blockdata::transaction::ParseOutPointError;
+use bitcoin::hashes::Hash;
+use bitcoin::{Network, OutPoint, Transaction, Txid};
 use commit_verify::commit_encode;
 
-use crate::txout::{CloseMethod, MethodParseError, TxoSeal, WitnessVoutError};
+use crate::limits::MAX_VOUT;
+use crate::txout::blind::RevealedSeal;
+use crate::txout::{
+    uri, Blinder, CloseMethod, MethodParseError, SealDecodeError, TxoSeal,
+    Vout, WitnessOutputError, WitnessVoutError, SEAL_URI_SCHEME,
+};
 
 /// Revealed seal definition which may point to a witness transactions and does
 /// not contain blinding data.
@@ -30,12 +39,31 @@ use crate::txout::{CloseMethod, MethodParseError, TxoSeal, WitnessVoutError};
 /// These data are not used within RGB contract data, thus we do not have a
 /// commitment and conceal procedures (since without knowing a blinding factor
 /// we can't perform them).
+///
+/// Seals can't be constructed from an outpoint alone, since this would
+/// require an implicit choice of the closing method; use
+/// [`ExplicitSeal::tapret_first`], [`ExplicitSeal::opret_first`] or
+/// conversion from `(CloseMethod, OutPoint)` tuple instead:
+///
+/// ```compile_fail
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::ExplicitSeal;
+/// let seal = ExplicitSeal::from(OutPoint::default());
+/// ```
+///
+/// ```
+/// # use bitcoin::OutPoint;
+/// # use seals::txout::{CloseMethod, ExplicitSeal};
+/// let outpoint = OutPoint::default();
+/// let seal = ExplicitSeal::from((CloseMethod::OpretFirst, outpoint));
+/// assert_eq!(seal, ExplicitSeal::opret_first(outpoint));
+/// ```
+///
+/// Human-readable serde formats represent the seal by its string form
+/// ([`Display`]); binary formats use a structure with `method`, `txid` and
+/// `vout` fields. For compatibility with the previous versions the structure
+/// is also accepted when deserializing human-readable formats.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-#[cfg_attr(
-    feature = "serde",
-    derive(Serialize, Deserialize),
-    serde(crate = "serde_crate")
-)]
 #[derive(StrictEncode, StrictDecode)]
 pub struct ExplicitSeal {
     /// Commitment to the specific seal close method [`CloseMethod`] which must
@@ -51,7 +79,88 @@ pub struct ExplicitSeal {
     pub txid: Option<Txid>,
 
     /// Tx output number, which should be always known.
-    pub vout: u32,
+    pub vout: Vout,
+}
+
+/// Structured serde representation of [`ExplicitSeal`].
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_crate", rename = "ExplicitSeal")]
+struct ExplicitSealFields {
+    method: CloseMethod,
+    txid: Option<Txid>,
+    vout: Vout,
+}
+
+#[cfg(feature = "serde")]
+impl From<ExplicitSealFields> for ExplicitSeal {
+    #[inline]
+    fn from(fields: ExplicitSealFields) -> Self {
+        ExplicitSeal::with(fields.method, fields.txid, fields.vout)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ExplicitSeal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            ExplicitSealFields {
+                method: self.method,
+                txid: self.txid,
+                vout: self.vout,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ExplicitSeal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = ExplicitSeal;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "seal string in `method:txid:vout` form or structure with \
+                     `method`, `txid` and `vout` fields",
+                )
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                ExplicitSeal::from_str(v).map_err(serde::de::Error::custom)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                serde::Deserialize::deserialize(
+                    serde::de::value::MapAccessDeserializer::new(map),
+                )
+                .map(ExplicitSealFields::into)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            ExplicitSealFields::deserialize(deserializer)
+                .map(ExplicitSeal::from)
+        }
+    }
 }
 
 impl TryFrom<&ExplicitSeal> for OutPoint {
@@ -61,7 +170,7 @@ impl TryFrom<&ExplicitSeal> for OutPoint {
     fn try_from(reveal: &ExplicitSeal) -> Result<Self, Self::Error> {
         reveal
             .txid
-            .map(|txid| OutPoint::new(txid, reveal.vout as u32))
+            .map(|txid| OutPoint::new(txid, reveal.vout.into_u32()))
             .ok_or(WitnessVoutError)
     }
 }
@@ -75,20 +184,26 @@ impl TryFrom<ExplicitSeal> for OutPoint {
     }
 }
 
-impl From<&OutPoint> for ExplicitSeal {
+impl From<(CloseMethod, OutPoint)> for ExplicitSeal {
     #[inline]
-    fn from(outpoint: &OutPoint) -> Self {
-        Self {
-            method: CloseMethod::TapretFirst,
-            txid: Some(outpoint.txid),
-            vout: outpoint.vout as u32,
-        }
+    fn from((method, outpoint): (CloseMethod, OutPoint)) -> Self {
+        ExplicitSeal::new(method, outpoint)
     }
 }
 
-impl From<OutPoint> for ExplicitSeal {
+impl From<(CloseMethod, &OutPoint)> for ExplicitSeal {
     #[inline]
-    fn from(outpoint: OutPoint) -> Self { ExplicitSeal::from(&outpoint) }
+    fn from((method, outpoint): (CloseMethod, &OutPoint)) -> Self {
+        ExplicitSeal::new(method, *outpoint)
+    }
+}
+
+impl DumbDefault for ExplicitSeal {
+    /// Returns placeholder seal over the output `0` of the all-zero txid using
+    /// [`CloseMethod::OpretFirst`].
+    fn dumb_default() -> Self {
+        ExplicitSeal::with(CloseMethod::OpretFirst, Some(Txid::default()), 0u32)
+    }
 }
 
 impl commit_encode::Strategy for ExplicitSeal {
@@ -103,10 +218,10 @@ impl TxoSeal for ExplicitSeal {
     fn txid(&self) -> Option<Txid> { self.txid }
 
     #[inline]
-    fn vout(&self) -> usize { self.vout as usize }
+    fn vout(&self) -> Vout { self.vout }
 
     #[inline]
-    fn outpoint(&self) -> Option<OutPoint> { self.try_into().ok() }
+    fn outpoint(&self) -> Option<OutPoint> { self.require_explicit().ok() }
 
     #[inline]
     fn txid_or(&self, default_txid: Txid) -> Txid {
@@ -115,7 +230,7 @@ impl TxoSeal for ExplicitSeal {
 
     #[inline]
     fn outpoint_or(&self, default_txid: Txid) -> OutPoint {
-        OutPoint::new(self.txid.unwrap_or(default_txid), self.vout as u32)
+        OutPoint::new(self.txid.unwrap_or(default_txid), self.vout.into_u32())
     }
 }
 
@@ -126,21 +241,154 @@ impl ExplicitSeal {
         Self {
             method,
             txid: Some(outpoint.txid),
-            vout: outpoint.vout as u32,
+            vout: outpoint.vout.into(),
         }
     }
 
+    /// Constructs seal for the provided outpoint using
+    /// [`CloseMethod::TapretFirst`] closing method.
+    #[inline]
+    pub fn tapret_first(outpoint: OutPoint) -> ExplicitSeal {
+        ExplicitSeal::new(CloseMethod::TapretFirst, outpoint)
+    }
+
+    /// Constructs seal for the provided outpoint using
+    /// [`CloseMethod::OpretFirst`] closing method.
+    #[inline]
+    pub fn opret_first(outpoint: OutPoint) -> ExplicitSeal {
+        ExplicitSeal::new(CloseMethod::OpretFirst, outpoint)
+    }
+
+    /// Constructs seal which must always be defined over an existing
+    /// transaction output, like the genesis seals, which can't be defined over
+    /// the outputs of a witness transaction. The resulting seal always has
+    /// explicit txid.
+    #[inline]
+    pub fn new_genesis(
+        method: CloseMethod,
+        outpoint: OutPoint,
+    ) -> ExplicitSeal {
+        ExplicitSeal::new(method, outpoint)
+    }
+
+    /// Constructs seal over the output `vout` of the witness transaction, i.e.
+    /// the seal without txid.
+    #[inline]
+    pub fn new_witness(
+        method: CloseMethod,
+        vout: impl Into<Vout>,
+    ) -> ExplicitSeal {
+        ExplicitSeal::with(method, None, vout)
+    }
+
     /// Constructs seal.
+    ///
+    /// If `txid` is `None` the seal is defined over the output of the witness
+    /// transaction; the contexts which forbid such seals must use
+    /// [`ExplicitSeal::new_genesis`] or check seals with
+    /// [`ExplicitSeal::require_explicit`].
     #[inline]
     pub fn with(
         method: CloseMethod,
         txid: Option<Txid>,
-        vout: u32,
+        vout: impl Into<Vout>,
     ) -> ExplicitSeal {
-        ExplicitSeal { method, txid, vout }
+        ExplicitSeal {
+            method,
+            txid,
+            vout: vout.into(),
+        }
+    }
+
+    /// Detects whether the seal is defined over the output of the witness
+    /// transaction, i.e. has no txid.
+    #[inline]
+    pub fn is_witness(&self) -> bool { self.txid.is_none() }
+
+    /// Returns outpoint of the seal, failing with [`WitnessVoutError`] for the
+    /// seals defined over the witness transaction outputs.
+    #[inline]
+    pub fn require_explicit(&self) -> Result<OutPoint, WitnessVoutError> {
+        OutPoint::try_from(self)
+    }
+
+    /// Returns canonical string representation of the seal, which is the one
+    /// produced by `Display`: it uses the canonical closing method name,
+    /// lowercase txid hex (or `~` for the witness transaction) and decimal
+    /// vout without leading zeros.
+    ///
+    /// Parsing accepts method aliases and txids in any case, so the string the
+    /// seal was parsed from may differ from its canonical representation; use
+    /// [`ExplicitSeal::is_canonical_str`] to require the canonical form.
+    #[inline]
+    pub fn canonical_string(&self) -> String { self.to_string() }
+
+    /// Detects whether `s` is a valid seal string in the canonical form, i.e.
+    /// it is parsed into a seal which [`ExplicitSeal::canonical_string`]
+    /// matches `s`.
+    pub fn is_canonical_str(s: &str) -> bool {
+        ExplicitSeal::from_str(s)
+            .map(|seal| seal.canonical_string() == s)
+            .unwrap_or_default()
+    }
+
+    /// Converts seal into [`RevealedSeal`] using the provided blinding factor.
+    #[inline]
+    pub fn blind(self, blinding: u64) -> RevealedSeal {
+        RevealedSeal {
+            method: self.method,
+            txid: self.txid,
+            vout: self.vout,
+            blinding,
+        }
+    }
+
+    /// Converts seal into [`RevealedSeal`] taking blinding factor from the
+    /// provided `blinder`, which may be any random number generator.
+    #[inline]
+    pub fn blind_random<B: Blinder>(self, blinder: &mut B) -> RevealedSeal {
+        self.blind(blinder.blinding())
     }
 }
 
+/// Resolves seals into the outpoints they are defined over, using the provided
+/// witness transaction id for the seals over witness transaction outputs (i.e.
+/// seals without txid).
+///
+/// The function does not check that the witness transaction contains the
+/// referenced outputs; use [`resolve_seals_checked`] when the witness
+/// transaction is known.
+pub fn resolve_seals<'a>(
+    seals: impl Iterator<Item = &'a ExplicitSeal>,
+    witness_txid: Txid,
+) -> Vec<OutPoint> {
+    seals.map(|seal| seal.outpoint_or(witness_txid)).collect()
+}
+
+/// Resolves seals into the outpoints they are defined over, like
+/// [`resolve_seals`], checking that each of the seals over witness
+/// transaction outputs references an output present in the `witness_tx`.
+pub fn resolve_seals_checked<'a>(
+    seals: impl Iterator<Item = &'a ExplicitSeal>,
+    witness_tx: &Transaction,
+) -> Result<Vec<OutPoint>, WitnessOutputError> {
+    let txid = witness_tx.txid();
+    let outputs = witness_tx.output.len();
+    seals
+        .map(|seal| {
+            let vout = seal.vout.into_u32();
+            if seal.txid.is_none() && vout as usize >= outputs {
+                return Err(WitnessOutputError {
+                    txid,
+                    vout,
+                    outputs,
+                });
+            }
+            Ok(seal.outpoint_or(txid))
+        })
+        .collect()
+}
+
 /// Errors happening during parsing string representation of different forms of
 /// single-use-seals
 #[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
@@ -157,42 +405,530 @@ pub enum ParseError {
     #[from]
     WrongMethod(MethodParseError),
 
-    /// unable to parse transaction id value; it must be 64-character
-    /// hexadecimal string
-    WrongTxid,
+    /// unable to parse transaction id value, which must be 64-character
+    /// hexadecimal string, {0}
+    WrongTxid(ParseContext),
+
+    /// unable to parse transaction vout value, which must be a decimal
+    /// unsigned integer, {0}
+    WrongVout(ParseContext),
+
+    /// transaction output number {0} exceeds the limit on the number of
+    /// transaction outputs
+    VoutOutOfRange(u32),
 
-    /// unable to parse transaction vout value; it must be a decimal unsigned
-    /// integer
-    WrongVout,
+    /// wrong structure of seal string representation {0}
+    WrongStructure(ParseContext),
 
-    /// wrong structure of seal string representation
-    WrongStructure,
+    /// seal over witness transaction output must use `~` in place of the
+    /// transaction id
+    UnexpectedTxid,
 
     /// wrong Bech32 representation of the blinded TxOut seal – {0}
     #[from]
     Bech32(lnpbp_bech32::Error),
+
+    /// seal URI must start with `seal:` scheme
+    UriSchemeRequired,
+
+    /// wrong percent-encoding in the seal URI
+    WrongPercentEncoding,
+
+    /// seal uses {found} closing method, while {expected} method is
+    /// required
+    MethodMismatch {
+        /// Closing method which the seal was expected to use.
+        expected: CloseMethod,
+        /// Closing method specified in the seal string.
+        found: CloseMethod,
+    },
+
+    /// unknown bitcoin network name, which must be one of `bitcoin`,
+    /// `testnet`, `signet` or `regtest`, {0}
+    WrongChain(ParseContext),
+
+    /// seal must be tagged with the name of the bitcoin network it belongs to
+    ChainRequired,
+
+    /// seal is expected to belong to {expected} network, while it is tagged
+    /// with {found}
+    ChainMismatch {
+        /// Network which the seal was expected to belong to.
+        expected: Network,
+        /// Network the seal is tagged with.
+        found: Network,
+    },
+
+    /// invalid outpoint string in `txid:vout` format – {0}
+    #[from]
+    WrongOutPoint(ParseOutPointError),
+
+    /// transaction output range must be non-empty and must not overlap with
+    /// other outputs in the list, {0}
+    WrongVoutRange(ParseContext),
+
+    /// seal definition string defines {0} seals, exceeding the limit of
+    /// {MAX_SEALS_PER_DEFINITION} seals
+    TooManySeals(u64),
+}
+
+impl ParseError {
+    /// Rebases context of the error produced by parsing `part` to be relative
+    /// to the `input` string, which the `part` must be a subslice of.
+    pub(super) fn within(self, input: &str, part: &str) -> ParseError {
+        match self {
+            ParseError::WrongTxid(ctx) => {
+                ParseError::WrongTxid(ctx.within(input, part))
+            }
+            ParseError::WrongVout(ctx) => {
+                ParseError::WrongVout(ctx.within(input, part))
+            }
+            ParseError::WrongStructure(ctx) => {
+                ParseError::WrongStructure(ctx.within(input, part))
+            }
+            ParseError::WrongChain(ctx) => {
+                ParseError::WrongChain(ctx.within(input, part))
+            }
+            ParseError::WrongVoutRange(ctx) => {
+                ParseError::WrongVoutRange(ctx.within(input, part))
+            }
+            err => err,
+        }
+    }
+}
+
+/// Maximal number of seals which can be defined by a single string parsed with
+/// [`ExplicitSeal::parse_many`].
+pub const MAX_SEALS_PER_DEFINITION: usize = 256;
+
+/// Checks that the parsed transaction output number does not exceed the
+/// `max_vout` limit.
+#[inline]
+pub(super) fn check_vout(vout: u32, max_vout: u32) -> Result<u32, ParseError> {
+    if vout > max_vout {
+        return Err(ParseError::VoutOutOfRange(vout));
+    }
+    Ok(vout)
+}
+
+/// Characters separating components of the seal string representation.
+pub(super) const SEPARATORS: &[char] = &[':', '#'];
+
+/// Splits the next component of the seal string representation, returning it
+/// together with the rest of the string, if any.
+#[inline]
+pub(super) fn split_field(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(SEPARATORS) {
+        Some((field, rest)) => (field, Some(rest)),
+        None => (s, None),
+    }
+}
+
+/// Parses transaction id from its 64-character hexadecimal representation
+/// (in the reversed byte order used by bitcoin) without intermediate
+/// allocations.
+pub(super) fn parse_txid(s: &str) -> Option<Txid> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let hex = s.as_bytes();
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().rev().zip(hex.chunks_exact(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Some(Txid::from_inner(bytes))
+}
+
+/// Location of the malformed fragment within the parsed seal string, reported
+/// by the parse errors.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ParseContext {
+    /// String which was parsed.
+    pub input: String,
+
+    /// Byte offset of the malformed fragment within the parsed string.
+    pub offset: usize,
+
+    /// Malformed fragment; empty if the string ends prematurely.
+    pub fragment: String,
+}
+
+impl ParseContext {
+    /// Constructs context for the `fragment`, which must be a subslice of the
+    /// `input`.
+    pub(super) fn with(input: &str, fragment: &str) -> ParseContext {
+        let offset = fragment.as_ptr() as usize - input.as_ptr() as usize;
+        debug_assert!(offset + fragment.len() <= input.len());
+        ParseContext {
+            input: input.to_owned(),
+            offset,
+            fragment: fragment.to_owned(),
+        }
+    }
+
+    /// Constructs context pointing at the end of the `input`.
+    #[inline]
+    pub(super) fn end(input: &str) -> ParseContext {
+        ParseContext::with(input, &input[input.len()..])
+    }
+
+    /// Constructs context for the rest of the `input` following `part`, which
+    /// must be a subslice of the `input`.
+    #[inline]
+    pub(super) fn after(input: &str, part: &str) -> ParseContext {
+        let offset = part.as_ptr() as usize - input.as_ptr() as usize;
+        ParseContext::with(input, &input[offset + part.len()..])
+    }
+
+    /// Rebases context of the `part` parsing to be relative to the `input`,
+    /// which the `part` must be a subslice of.
+    fn within(self, input: &str, part: &str) -> ParseContext {
+        let offset = part.as_ptr() as usize - input.as_ptr() as usize;
+        debug_assert_eq!(self.input, part);
+        ParseContext {
+            input: input.to_owned(),
+            offset: offset + self.offset,
+            fragment: self.fragment,
+        }
+    }
+
+    /// Constructs context for the rest of the `input` starting from the first
+    /// separator found in its `tail`, which must be a subslice of the `input`.
+    pub(super) fn separator(input: &str, tail: &str) -> ParseContext {
+        match tail.find(SEPARATORS) {
+            Some(pos) => ParseContext::with(input, &tail[pos..]),
+            None => ParseContext::after(input, tail),
+        }
+    }
+}
+
+impl Display for ParseContext {
+    /// Formats context as the byte offset followed by the parsed string and
+    /// carets pointing at the malformed fragment on the next line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let column = self
+            .input
+            .get(..self.offset)
+            .map(|prefix| prefix.chars().count())
+            .unwrap_or(self.offset);
+        writeln!(f, "at byte {}:", self.offset)?;
+        writeln!(f, "    {}", self.input)?;
+        write!(
+            f,
+            "    {}{}",
+            " ".repeat(column),
+            "^".repeat(self.fragment.chars().count().max(1))
+        )
+    }
+}
+
+impl ExplicitSeal {
+    /// Parses seal from its components, which must be subslices of the
+    /// `input`. If `percent_encoded` is set, the components are
+    /// percent-decoded first. Output numbers above `max_vout` are rejected.
+    fn from_parts(
+        input: &str,
+        method: &str,
+        txid: &str,
+        vout: &str,
+        percent_encoded: bool,
+        max_vout: u32,
+    ) -> Result<ExplicitSeal, ParseError> {
+        let decode = |part| {
+            if percent_encoded {
+                uri::percent_decode(part)
+            } else {
+                Ok(Cow::Borrowed(part))
+            }
+        };
+        let (method_str, txid_str, vout_str) =
+            (decode(method)?, decode(txid)?, decode(vout)?);
+        match (method_str.as_ref(), txid_str.as_ref()) {
+            ("~", _) | ("", _) => Err(ParseError::MethodRequired),
+            (_, "") => Err(ParseError::TxidRequired),
+            (method_str, _) => ExplicitSeal::from_outpoint_parts(
+                input,
+                method_str.parse()?,
+                (txid, &txid_str),
+                (vout, &vout_str),
+                max_vout,
+            ),
+        }
+    }
+
+    /// Parses `txid` and `vout` components of the seal closed with the given
+    /// `method`. Each component is provided as a subslice of the `input`
+    /// together with its decoded form.
+    fn from_outpoint_parts(
+        input: &str,
+        method: CloseMethod,
+        (txid, txid_str): (&str, &str),
+        (vout, vout_str): (&str, &str),
+        max_vout: u32,
+    ) -> Result<ExplicitSeal, ParseError> {
+        Ok(ExplicitSeal {
+            method,
+            txid: match txid_str {
+                "" => return Err(ParseError::TxidRequired),
+                "~" => None,
+                txid_str => Some(parse_txid(txid_str).ok_or_else(|| {
+                    ParseError::WrongTxid(ParseContext::with(input, txid))
+                })?),
+            },
+            vout: vout_str
+                .parse()
+                .map_err(|_| {
+                    ParseError::WrongVout(ParseContext::with(input, vout))
+                })
+                .and_then(|vout| check_vout(vout, max_vout))?
+                .into(),
+        })
+    }
+
+    /// Parses seal either from its full `method:txid:vout` representation or
+    /// from the `txid:vout` one, using `default` closing method in the second
+    /// case. Seals over witness transaction outputs use `~` in place of the
+    /// txid in both forms.
+    ///
+    /// If the string specifies the closing method, it must match the
+    /// `default` one, otherwise [`ParseError::MethodMismatch`] is returned:
+    /// the method which was given explicitly in two places is never silently
+    /// overridden.
+    ///
+    /// Unlike [`ExplicitSeal::from_str`], which always requires the method,
+    /// this is intended for tools taking the closing method as a separate
+    /// parameter.
+    pub fn parse_with_method(
+        s: &str,
+        default: CloseMethod,
+    ) -> Result<ExplicitSeal, ParseError> {
+        match split_field(s) {
+            (txid, Some(vout)) if !vout.contains(SEPARATORS) => {
+                ExplicitSeal::from_outpoint_parts(
+                    s,
+                    default,
+                    (txid, txid),
+                    (vout, vout),
+                    MAX_VOUT,
+                )
+            }
+            (_, None) => Err(ParseError::WrongStructure(ParseContext::end(s))),
+            _ => {
+                let seal = ExplicitSeal::from_str(s)?;
+                if seal.method != default {
+                    return Err(ParseError::MethodMismatch {
+                        expected: default,
+                        found: seal.method,
+                    });
+                }
+                Ok(seal)
+            }
+        }
+    }
+
+    /// Parses seal closed with the given `method` from the string
+    /// representation of the [`OutPoint`] as used by rust-bitcoin, i.e.
+    /// `txid:vout`.
+    ///
+    /// The string is parsed with [`OutPoint::from_str`], so it follows its
+    /// rules, which are different from [`ExplicitSeal::parse_with_method`]:
+    /// the vout must not have leading zeros or a `+` sign, and the seals over
+    /// witness transaction outputs (`~:vout`) are not supported, since they
+    /// don't have an outpoint yet. Output numbers above [`MAX_VOUT`] are
+    /// rejected.
+    pub fn from_outpoint_str(
+        method: CloseMethod,
+        s: &str,
+    ) -> Result<ExplicitSeal, ParseError> {
+        let outpoint = OutPoint::from_str(s)?;
+        check_vout(outpoint.vout, MAX_VOUT)?;
+        Ok(ExplicitSeal::new(method, outpoint))
+    }
+
+    /// Parses multiple seals over the outputs of the same transaction closed
+    /// with the same method from `method:txid:vouts` string, where `vouts` is
+    /// a comma-separated list of items, each being either a single output
+    /// number or a range of outputs `start..end` (with `end` not included).
+    /// The txid may be replaced with `~` for the seals over the witness
+    /// transaction outputs. For instance, `tapret1st:~:0..3,5` defines seals
+    /// over outputs 0, 1, 2 and 5 of the witness transaction.
+    ///
+    /// Ranges must be non-empty and the items must not overlap. The string may
+    /// define at most [`MAX_SEALS_PER_DEFINITION`] seals over the outputs not
+    /// exceeding [`MAX_VOUT`]. The seals are returned in the order they are
+    /// listed in the string.
+    ///
+    /// [`ExplicitSeal::from_str`] parses only strings defining a single seal.
+    pub fn parse_many(s: &str) -> Result<Vec<ExplicitSeal>, ParseError> {
+        let (method, rest) = split_field(s);
+        if method.is_empty() || method == "~" {
+            return Err(ParseError::MethodRequired);
+        }
+        let rest = rest
+            .ok_or_else(|| ParseError::WrongStructure(ParseContext::end(s)))?;
+        let (txid, vouts) = split_field(rest);
+        let vouts = match vouts {
+            _ if txid.is_empty() => return Err(ParseError::TxidRequired),
+            Some(vouts) if !vouts.contains(SEPARATORS) => vouts,
+            Some(vouts) => {
+                return Err(ParseError::WrongStructure(
+                    ParseContext::separator(s, vouts),
+                ))
+            }
+            None => {
+                return Err(ParseError::WrongStructure(ParseContext::end(s)))
+            }
+        };
+
+        let method = CloseMethod::from_str(method)?;
+        let txid = match txid {
+            "~" => None,
+            txid => Some(parse_txid(txid).ok_or_else(|| {
+                ParseError::WrongTxid(ParseContext::with(s, txid))
+            })?),
+        };
+
+        let parse_vout = |vout: &str| {
+            vout.parse::<u32>()
+                .map(u64::from)
+                .map_err(|_| ParseError::WrongVout(ParseContext::with(s, vout)))
+        };
+        let mut ranges = Vec::new();
+        let mut count = 0u64;
+        for item in vouts.split(',') {
+            let range = match item.split_once("..") {
+                Some((start, end)) => parse_vout(start)?..parse_vout(end)?,
+                None => parse_vout(item).map(|vout| vout..vout + 1)?,
+            };
+            if range.is_empty() {
+                return Err(ParseError::WrongVoutRange(ParseContext::with(
+                    s, item,
+                )));
+            }
+            check_vout((range.end - 1) as u32, MAX_VOUT)?;
+            count += range.end - range.start;
+            if count > MAX_SEALS_PER_DEFINITION as u64 {
+                return Err(ParseError::TooManySeals(count));
+            }
+            ranges.push((item, range));
+        }
+
+        let mut sorted = ranges.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|(_, range)| range.start);
+        for pair in sorted.windows(2) {
+            if pair[1].1.start < pair[0].1.end {
+                return Err(ParseError::WrongVoutRange(ParseContext::with(
+                    s, pair[1].0,
+                )));
+            }
+        }
+
+        Ok(ranges
+            .into_iter()
+            .flat_map(|(_, range)| range)
+            .map(|vout| ExplicitSeal::with(method, txid, vout as u32))
+            .collect())
+    }
+
+    /// Returns URI representation of the seal in form of
+    /// `seal:<method>/<txid>/<vout>`, where `txid` is replaced with `~` for
+    /// the seals defined over witness transaction outputs.
+    ///
+    /// All of the seal components consist of URI-unreserved characters only,
+    /// so no percent-encoding is required.
+    pub fn to_uri(&self) -> String {
+        format!(
+            "{}:{}/{}/{}",
+            SEAL_URI_SCHEME,
+            self.method,
+            self.txid
+                .as_ref()
+                .map(Txid::to_string)
+                .unwrap_or_else(|| s!("~")),
+            self.vout,
+        )
+    }
+
+    /// Parses URI representation of the seal produced by
+    /// [`ExplicitSeal::to_uri`]. Percent-encoded path segments are decoded
+    /// before parsing.
+    pub fn from_uri(uri: &str) -> Result<ExplicitSeal, ParseError> {
+        let path = uri::strip_scheme(uri)?;
+        let mut split = path.split('/');
+        match (split.next(), split.next(), split.next(), split.next()) {
+            (Some(method), Some(txid), Some(vout), None) => {
+                ExplicitSeal::from_parts(
+                    uri, method, txid, vout, true, MAX_VOUT,
+                )
+            }
+            (Some(""), ..) => Err(ParseError::MethodRequired),
+            (Some(_), Some(_), Some(vout), Some(_)) => {
+                Err(ParseError::WrongStructure(ParseContext::after(uri, vout)))
+            }
+            _ => Err(ParseError::WrongStructure(ParseContext::end(uri))),
+        }
+    }
+}
+
+impl ExplicitSeal {
+    /// Strict-decodes seal, failing with [`SealDecodeError::VoutOutOfRange`]
+    /// if the output number exceeds [`MAX_VOUT`]. Should be used for the data
+    /// from untrusted sources. The data must not contain anything after the
+    /// encoded seal.
+    pub fn decode_checked(
+        data: impl AsRef<[u8]>,
+    ) -> Result<ExplicitSeal, SealDecodeError> {
+        let seal: ExplicitSeal = strict_encoding::strict_deserialize(data)?;
+        seal.vout.check_decoded()?;
+        Ok(seal)
+    }
+
+    /// Parses seal like [`ExplicitSeal::from_str`], rejecting output numbers
+    /// above `max_vout` instead of the default [`MAX_VOUT`] limit. Passing
+    /// `u32::MAX` disables the check.
+    pub fn from_str_with_limits(
+        s: &str,
+        max_vout: u32,
+    ) -> Result<ExplicitSeal, ParseError> {
+        let (method, rest) = split_field(s);
+        if method.is_empty() || method == "~" {
+            return Err(ParseError::MethodRequired);
+        }
+        let rest = rest
+            .ok_or_else(|| ParseError::WrongStructure(ParseContext::end(s)))?;
+        let (txid, vout) = split_field(rest);
+        if txid.is_empty() {
+            return Err(ParseError::TxidRequired);
+        }
+        match vout {
+            Some(vout) if !vout.contains(SEPARATORS) => {
+                ExplicitSeal::from_parts(s, method, txid, vout, false, max_vout)
+            }
+            Some(vout) => Err(ParseError::WrongStructure(
+                ParseContext::separator(s, vout),
+            )),
+            None => Err(ParseError::WrongStructure(ParseContext::end(s))),
+        }
+    }
 }
 
 impl FromStr for ExplicitSeal {
     type Err = ParseError;
 
+    /// Parses seal from `method:txid:vout` string, rejecting output numbers
+    /// above [`MAX_VOUT`].
+    #[inline]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(&[':', '#'][..]);
-        match (split.next(), split.next(), split.next(), split.next()) {
-            (Some("~"), ..) | (Some(""), ..) => Err(ParseError::MethodRequired),
-            (Some(_), Some(""), ..) => Err(ParseError::TxidRequired),
-            (Some(method), Some("~"), Some(vout), None) => Ok(ExplicitSeal {
-                method: method.parse()?,
-                txid: None,
-                vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
-            }),
-            (Some(method), Some(txid), Some(vout), None) => Ok(ExplicitSeal {
-                method: method.parse()?,
-                txid: Some(txid.parse().map_err(|_| ParseError::WrongTxid)?),
-                vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
-            }),
-            _ => Err(ParseError::WrongStructure),
-        }
+        ExplicitSeal::from_str_with_limits(s, MAX_VOUT)
     }
 }
 
@@ -210,3 +946,853 @@ impl Display for ExplicitSeal {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::secp256k1::rand::thread_rng;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+
+    #[test]
+    fn vout_roundtrip() {
+        assert_eq!(Vout::from_str("5"), Ok(Vout::from(5)));
+        assert_eq!(Vout::from(5).to_string(), "5");
+        assert_eq!(u32::from(Vout::from(5)), 5);
+        assert!(Vout::from_str("-5").is_err());
+    }
+
+    #[test]
+    fn witness_vout_seal() {
+        let seal = ExplicitSeal::from_str("tapret1st:~:5").unwrap();
+        assert_eq!(seal.txid, None);
+        assert_eq!(seal.vout(), Vout::from(5));
+        assert_eq!(seal.to_string(), "tapret1st:~:5");
+        assert_eq!(OutPoint::try_from(seal), Err(WitnessVoutError));
+    }
+
+    #[test]
+    fn blind_roundtrip() {
+        let seal = ExplicitSeal::from_str(
+            "opret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:3",
+        )
+        .unwrap();
+
+        let revealed = seal.blind(0x31bbed7e7b2d);
+        assert_eq!(revealed.method, seal.method);
+        assert_eq!(revealed.txid, seal.txid);
+        assert_eq!(revealed.vout, seal.vout);
+        assert_eq!(revealed.blinding, 0x31bbed7e7b2d);
+        assert_eq!(revealed.to_explicit(), seal);
+
+        let revealed = seal.blind_random(&mut thread_rng());
+        assert_eq!(revealed.to_explicit(), seal);
+        assert_eq!(ExplicitSeal::from(revealed), seal);
+
+        let witness_seal = ExplicitSeal::from_str("tapret1st:~:1").unwrap();
+        let revealed = witness_seal.blind_random(&mut thread_rng());
+        assert_eq!(revealed.txid, None);
+        assert_eq!(revealed.to_explicit(), witness_seal);
+    }
+
+    #[test]
+    fn outpoint_conversion() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let outpoint = OutPoint::new(txid, 21);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        assert_eq!(seal.vout, Vout::from(21));
+        assert_eq!(seal.outpoint(), Some(outpoint));
+        assert_eq!(OutPoint::try_from(seal), Ok(outpoint));
+    }
+
+    #[test]
+    fn resolve_witness_seals() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let witness_tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: vec![default!(); 2],
+        };
+        let witness_txid = witness_tx.txid();
+
+        let seals = [
+            ExplicitSeal::with(CloseMethod::TapretFirst, None, 1u32),
+            ExplicitSeal::with(CloseMethod::OpretFirst, Some(txid), 5u32),
+            ExplicitSeal::with(CloseMethod::OpretFirst, None, 0u32),
+        ];
+        let expected = vec![
+            OutPoint::new(witness_txid, 1),
+            OutPoint::new(txid, 5),
+            OutPoint::new(witness_txid, 0),
+        ];
+        assert_eq!(resolve_seals(seals.iter(), witness_txid), expected);
+        assert_eq!(
+            resolve_seals_checked(seals.iter(), &witness_tx),
+            Ok(expected)
+        );
+        assert_eq!(resolve_seals(None.iter(), witness_txid), vec![]);
+
+        // vout equal to the number of witness outputs is out of range
+        let seals = [
+            ExplicitSeal::with(CloseMethod::OpretFirst, None, 0u32),
+            ExplicitSeal::with(CloseMethod::TapretFirst, None, 2u32),
+        ];
+        assert_eq!(resolve_seals(seals.iter(), witness_txid), vec![
+            OutPoint::new(witness_txid, 0),
+            OutPoint::new(witness_txid, 2),
+        ]);
+        assert_eq!(
+            resolve_seals_checked(seals.iter(), &witness_tx),
+            Err(WitnessOutputError {
+                txid: witness_txid,
+                vout: 2,
+                outputs: 2
+            })
+        );
+
+        // seals with explicit txid are not checked against the witness
+        let seal =
+            ExplicitSeal::with(CloseMethod::OpretFirst, Some(txid), 2u32);
+        assert_eq!(
+            resolve_seals_checked([seal].iter(), &witness_tx),
+            Ok(vec![OutPoint::new(txid, 2)])
+        );
+    }
+
+    #[test]
+    fn uri_roundtrip() {
+        let seal = ExplicitSeal::from_str(
+            "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:3",
+        )
+        .unwrap();
+        let uri = seal.to_uri();
+        assert_eq!(
+            uri,
+            "seal:tapret1st/\
+             646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839/\
+             3"
+        );
+        assert_eq!(ExplicitSeal::from_uri(&uri), Ok(seal));
+        assert_eq!(ExplicitSeal::from_uri(&uri.to_uppercase()), Ok(seal));
+
+        let witness_seal = ExplicitSeal::from_str("opret1st:~:1").unwrap();
+        assert_eq!(witness_seal.to_uri(), "seal:opret1st/~/1");
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:opret1st/~/1"),
+            Ok(witness_seal)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:opret1st/%7E/1"),
+            Ok(witness_seal)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:%6Fpret1st/%7e/%31"),
+            Ok(witness_seal)
+        );
+    }
+
+    #[test]
+    fn uri_invalid() {
+        assert_eq!(
+            ExplicitSeal::from_uri("tapret1st/~/1"),
+            Err(ParseError::UriSchemeRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("bitcoin:tapret1st/~/1"),
+            Err(ParseError::UriSchemeRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("tapret1st:~:1"),
+            Err(ParseError::UriSchemeRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:"),
+            Err(ParseError::MethodRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:~/~/1"),
+            Err(ParseError::MethodRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:tapret1st//1"),
+            Err(ParseError::TxidRequired)
+        );
+        assert!(matches!(
+            ExplicitSeal::from_uri("seal:tapret1st/~"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_uri("seal:tapret1st/~/1/2"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_uri("seal:tapret1st/~%2F1"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:tapret1st/~/1%"),
+            Err(ParseError::WrongPercentEncoding)
+        );
+        assert!(matches!(
+            ExplicitSeal::from_uri("seal:tapret1st/abcd/1"),
+            Err(ParseError::WrongTxid(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_uri("seal:tapret1st/~/x"),
+            Err(ParseError::WrongVout(_))
+        ));
+        assert_eq!(
+            ExplicitSeal::from_uri("seal:tapret2nd/~/1"),
+            Err(ParseError::WrongMethod(MethodParseError::new("tapret2nd")))
+        );
+    }
+
+    #[test]
+    fn strict_encoding() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        let seal = ExplicitSeal::with(CloseMethod::TapretFirst, Some(txid), 2);
+        let data = Vec::<u8>::from_hex(
+            "0101\
+             39e8fad1a86579d64e8cdce473b71f5520d8dfc97107d6a2e2192606c1a56c64\
+             02000000",
+        )
+        .unwrap();
+        assert_eq!(strict_serialize(&seal).unwrap(), data);
+        assert_eq!(strict_deserialize::<ExplicitSeal>(&data).unwrap(), seal);
+
+        let seal = ExplicitSeal::with(CloseMethod::OpretFirst, None, 21);
+        let data = Vec::<u8>::from_hex("000015000000").unwrap();
+        assert_eq!(strict_serialize(&seal).unwrap(), data);
+        assert_eq!(strict_deserialize::<ExplicitSeal>(&data).unwrap(), seal);
+    }
+
+    #[test]
+    fn vout_limit() {
+        let txid =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let seal = |vout: u32| {
+            ExplicitSeal::with(
+                CloseMethod::TapretFirst,
+                Some(Txid::from_hex(txid).unwrap()),
+                vout,
+            )
+        };
+        let s = |vout: u32| format!("tapret1st:{}:{}", txid, vout);
+
+        assert_eq!(ExplicitSeal::from_str(&s(MAX_VOUT)), Ok(seal(MAX_VOUT)));
+        assert_eq!(
+            ExplicitSeal::from_str(&s(MAX_VOUT + 1)),
+            Err(ParseError::VoutOutOfRange(MAX_VOUT + 1))
+        );
+        // Timestamp pasted in place of the vout
+        assert_eq!(
+            ExplicitSeal::from_str(&s(1_665_000_000)),
+            Err(ParseError::VoutOutOfRange(1_665_000_000))
+        );
+        assert!(matches!(
+            ExplicitSeal::from_str(&format!("tapret1st:{}:4294967296", txid)),
+            Err(ParseError::WrongVout(_))
+        ));
+        assert_eq!(
+            ExplicitSeal::from_str("opret1st:~:65536"),
+            Err(ParseError::VoutOutOfRange(65536))
+        );
+
+        assert_eq!(ExplicitSeal::from_str_with_limits(&s(3), 3), Ok(seal(3)));
+        assert_eq!(
+            ExplicitSeal::from_str_with_limits(&s(4), 3),
+            Err(ParseError::VoutOutOfRange(4))
+        );
+        assert_eq!(
+            ExplicitSeal::from_str_with_limits(&s(u32::MAX), u32::MAX),
+            Ok(seal(u32::MAX))
+        );
+
+        assert_eq!(
+            ExplicitSeal::from_uri(&format!("seal:tapret1st/{}/65536", txid)),
+            Err(ParseError::VoutOutOfRange(65536))
+        );
+        assert_eq!(
+            ExplicitSeal::parse_with_method(
+                &format!("{}:65536", txid),
+                CloseMethod::TapretFirst
+            ),
+            Err(ParseError::VoutOutOfRange(65536))
+        );
+        assert_eq!(
+            ExplicitSeal::from_outpoint_str(
+                CloseMethod::TapretFirst,
+                &format!("{}:65535", txid)
+            ),
+            Ok(seal(MAX_VOUT))
+        );
+        assert_eq!(
+            ExplicitSeal::from_outpoint_str(
+                CloseMethod::TapretFirst,
+                &format!("{}:65536", txid)
+            ),
+            Err(ParseError::VoutOutOfRange(65536))
+        );
+        assert_eq!(
+            RevealedSeal::from_str(&format!("{}#1", s(MAX_VOUT))),
+            Ok(seal(MAX_VOUT).blind(1))
+        );
+        assert_eq!(
+            RevealedSeal::from_str(&format!("{}#1", s(MAX_VOUT + 1))),
+            Err(crate::txout::blind::ParseError::VoutOutOfRange(
+                MAX_VOUT + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn vout_limit_round_trip() {
+        let txid =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        for vout in [MAX_VOUT - 1, MAX_VOUT, MAX_VOUT + 1, u32::MAX] {
+            let seal = ExplicitSeal::with(
+                CloseMethod::TapretFirst,
+                Some(Txid::from_hex(txid).unwrap()),
+                vout,
+            );
+            let witness =
+                ExplicitSeal::with(CloseMethod::OpretFirst, None, vout);
+            let revealed = seal.blind(1);
+
+            // Plain binary decoding does not apply the sanity limit, while
+            // the checked decoding does
+            let checked = |seal| match vout {
+                vout if vout > MAX_VOUT => {
+                    Err(SealDecodeError::VoutOutOfRange(vout))
+                }
+                _ => Ok(seal),
+            };
+            for seal in [seal, witness] {
+                let data = strict_serialize(&seal).unwrap();
+                assert_eq!(data[data.len() - 4..], vout.to_le_bytes());
+                assert_eq!(strict_deserialize::<ExplicitSeal>(&data), Ok(seal));
+                assert_eq!(ExplicitSeal::decode_checked(&data), checked(seal));
+            }
+            assert_eq!(
+                RevealedSeal::decode_any(revealed.encode_v1()),
+                Ok(revealed)
+            );
+            assert_eq!(
+                RevealedSeal::decode_checked(revealed.encode_v1()),
+                checked(seal).map(|_| revealed)
+            );
+            assert_eq!(Vout::from_str(&vout.to_string()), Ok(Vout::from(vout)));
+
+            // Seal strings are checked against the limit unless it is
+            // overridden
+            let s = seal.to_string();
+            assert_eq!(ExplicitSeal::from_str(&s), match vout {
+                vout if vout > MAX_VOUT => {
+                    Err(ParseError::VoutOutOfRange(vout))
+                }
+                _ => Ok(seal),
+            });
+            assert_eq!(
+                ExplicitSeal::from_str_with_limits(&s, u32::MAX),
+                Ok(seal)
+            );
+        }
+    }
+
+    #[test]
+    fn outpoint_constructors() {
+        let outpoint = OutPoint::new(
+            Txid::from_hex(
+                "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+            )
+            .unwrap(),
+            3,
+        );
+
+        // Replacements for the removed `From<OutPoint>`, which implied tapret
+        let tapret = ExplicitSeal::tapret_first(outpoint);
+        assert_eq!(tapret.method, CloseMethod::TapretFirst);
+        assert_eq!(tapret.outpoint(), Some(outpoint));
+        assert_eq!(
+            tapret,
+            ExplicitSeal::from((CloseMethod::TapretFirst, outpoint))
+        );
+        assert_eq!(
+            tapret,
+            ExplicitSeal::from((CloseMethod::TapretFirst, &outpoint))
+        );
+
+        let opret = ExplicitSeal::opret_first(outpoint);
+        assert_eq!(opret.method, CloseMethod::OpretFirst);
+        assert_eq!(opret.outpoint(), Some(outpoint));
+        assert_eq!(opret, (CloseMethod::OpretFirst, outpoint).into());
+        assert_eq!(opret, ExplicitSeal::new(CloseMethod::OpretFirst, outpoint));
+        assert_ne!(opret, tapret);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let txid = Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap();
+        for (seal, s) in [
+            (
+                ExplicitSeal::with(CloseMethod::TapretFirst, Some(txid), 2),
+                "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:2",
+            ),
+            (
+                ExplicitSeal::with(CloseMethod::OpretFirst, None, 21),
+                "opret1st:~:21",
+            ),
+        ] {
+            let json = serde_json::to_string(&seal).unwrap();
+            assert_eq!(json, format!("\"{}\"", s));
+            assert_eq!(serde_json::from_str::<ExplicitSeal>(&json).unwrap(), seal);
+
+            let yaml = serde_yaml::to_string(&seal).unwrap();
+            assert_eq!(
+                serde_yaml::from_str::<serde_yaml::Value>(&yaml).unwrap(),
+                serde_yaml::Value::from(s)
+            );
+            assert_eq!(serde_yaml::from_str::<ExplicitSeal>(&yaml).unwrap(), seal);
+
+            let data = bincode::serialize(&seal).unwrap();
+            assert_eq!(data[..4], [seal.method as u8, 0, 0, 0]);
+            assert_eq!(bincode::deserialize::<ExplicitSeal>(&data).unwrap(), seal);
+
+            // Structured form produced by the previous versions
+            let legacy = serde_json::json!({
+                "method": seal.method,
+                "txid": seal.txid,
+                "vout": seal.vout
+            });
+            assert_eq!(
+                serde_json::from_value::<ExplicitSeal>(legacy.clone()).unwrap(),
+                seal
+            );
+            let legacy_yaml = serde_yaml::to_string(&legacy).unwrap();
+            assert_eq!(
+                serde_yaml::from_str::<ExplicitSeal>(&legacy_yaml).unwrap(),
+                seal
+            );
+        }
+
+        assert!(
+            serde_json::from_str::<ExplicitSeal>("\"tapret1st:~\"").is_err()
+        );
+        assert!(serde_json::from_str::<ExplicitSeal>("5").is_err());
+    }
+
+    #[test]
+    fn parse_with_method() {
+        let hex =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let txid = Txid::from_hex(hex).unwrap();
+        let method = CloseMethod::TapretFirst;
+        let parse = |s: &str| ExplicitSeal::parse_with_method(s, method);
+
+        // Full form
+        let seal = ExplicitSeal::with(method, Some(txid), 2);
+        assert_eq!(parse(&format!("tapret1st:{}:2", hex)), Ok(seal));
+        // Bare outpoint form
+        assert_eq!(parse(&format!("{}:2", hex)), Ok(seal));
+        // Full witness form
+        let witness = ExplicitSeal::with(method, None, 21);
+        assert_eq!(parse("tapret1st:~:21"), Ok(witness));
+        // Bare witness form
+        assert_eq!(parse("~:21"), Ok(witness));
+
+        // Strict parser still requires the method
+        assert!(ExplicitSeal::from_str(&format!("{}:2", hex)).is_err());
+        assert!(ExplicitSeal::from_str("~:21").is_err());
+
+        // Explicit method must match the default one
+        assert_eq!(
+            parse(&format!("opret1st:{}:2", hex)),
+            Err(ParseError::MethodMismatch {
+                expected: CloseMethod::TapretFirst,
+                found: CloseMethod::OpretFirst,
+            })
+        );
+        assert_eq!(
+            ExplicitSeal::parse_with_method(
+                "opret1st:~:21",
+                CloseMethod::OpretFirst
+            ),
+            Ok(ExplicitSeal::with(CloseMethod::OpretFirst, None, 21))
+        );
+
+        assert_eq!(parse(":2"), Err(ParseError::TxidRequired));
+        assert!(matches!(
+            parse("tapret1st:2"),
+            Err(ParseError::WrongTxid(_))
+        ));
+        assert!(matches!(parse("~:x"), Err(ParseError::WrongVout(_))));
+        assert!(matches!(parse("~"), Err(ParseError::WrongStructure(_))));
+        assert!(matches!(
+            parse("unknown:~:1"),
+            Err(ParseError::WrongMethod(_))
+        ));
+    }
+
+    #[test]
+    fn parse_many() {
+        let hex =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let txid = Some(Txid::from_hex(hex).unwrap());
+        let method = CloseMethod::TapretFirst;
+        let seals = |txid, vouts: &[u32]| {
+            vouts
+                .iter()
+                .map(|vout| ExplicitSeal::with(method, txid, *vout))
+                .collect::<Vec<_>>()
+        };
+        let parse = |vouts: &str| {
+            ExplicitSeal::parse_many(&format!("tapret1st:{}:{}", hex, vouts))
+        };
+
+        assert_eq!(parse("3"), Ok(seals(txid, &[3])));
+        assert_eq!(parse("0..5"), Ok(seals(txid, &[0, 1, 2, 3, 4])));
+        assert_eq!(parse("0,2,7"), Ok(seals(txid, &[0, 2, 7])));
+        assert_eq!(parse("7,0..2,4"), Ok(seals(txid, &[7, 0, 1, 4])));
+        assert_eq!(parse("4..5"), Ok(seals(txid, &[4])));
+        assert_eq!(parse("65535"), Ok(seals(txid, &[MAX_VOUT])));
+        assert_eq!(parse("65534..65536"), Ok(seals(txid, &[65534, 65535])));
+        assert_eq!(
+            ExplicitSeal::parse_many("tapret1st:~:0..3,5"),
+            Ok(seals(None, &[0, 1, 2, 5]))
+        );
+
+        // Degenerate ranges
+        for vouts in ["3..3", "5..2"] {
+            assert!(matches!(
+                parse(vouts),
+                Err(ParseError::WrongVoutRange(ctx)) if ctx.fragment == vouts
+            ));
+        }
+        // Overlapping items
+        for (vouts, fragment) in [
+            ("0..3,2", "2"),
+            ("1,1", "1"),
+            ("0..4,2..6", "2..6"),
+            ("5,0..6", "5"),
+        ] {
+            assert!(matches!(
+                parse(vouts),
+                Err(ParseError::WrongVoutRange(ctx)) if ctx.fragment == fragment
+            ));
+        }
+        // Adjacent ranges do not overlap
+        assert_eq!(parse("0..2,2..3"), Ok(seals(txid, &[0, 1, 2])));
+
+        // Limit on the number of seals
+        assert_eq!(parse("0..256").unwrap().len(), MAX_SEALS_PER_DEFINITION);
+        assert_eq!(parse("0..257"), Err(ParseError::TooManySeals(257)));
+        assert_eq!(
+            parse("0..200,300..400"),
+            Err(ParseError::TooManySeals(300))
+        );
+        assert_eq!(
+            parse("0..65536"),
+            Err(ParseError::TooManySeals(MAX_VOUT as u64 + 1))
+        );
+
+        // Limit on the output numbers
+        assert_eq!(parse("65536"), Err(ParseError::VoutOutOfRange(65536)));
+        assert_eq!(
+            parse("65535..65537"),
+            Err(ParseError::VoutOutOfRange(65536))
+        );
+        assert_eq!(
+            parse("4294967295"),
+            Err(ParseError::VoutOutOfRange(u32::MAX))
+        );
+
+        for vouts in ["", "x", "1..", "..2", "1...3", "1,", "-1"] {
+            assert!(matches!(parse(vouts), Err(ParseError::WrongVout(_))));
+        }
+        assert!(matches!(parse("1:2"), Err(ParseError::WrongStructure(_))));
+        assert!(matches!(
+            ExplicitSeal::parse_many("tapret1st:~"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert_eq!(
+            ExplicitSeal::parse_many("~:~:0..2"),
+            Err(ParseError::MethodRequired)
+        );
+        assert_eq!(
+            ExplicitSeal::parse_many("tapret1st::0..2"),
+            Err(ParseError::TxidRequired)
+        );
+        assert!(matches!(
+            ExplicitSeal::parse_many("tapret1st:00:0..2"),
+            Err(ParseError::WrongTxid(_))
+        ));
+
+        // Normal parser accepts only a single seal
+        assert!(matches!(
+            ExplicitSeal::from_str(&format!("tapret1st:{}:0..5", hex)),
+            Err(ParseError::WrongVout(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_str(&format!("tapret1st:{}:0,2", hex)),
+            Err(ParseError::WrongVout(_))
+        ));
+    }
+
+    #[test]
+    fn witness_constructors() {
+        let outpoint = OutPoint::new(
+            Txid::from_hex(
+                "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+            )
+            .unwrap(),
+            2,
+        );
+        for method in CloseMethod::iter() {
+            let witness = ExplicitSeal::new_witness(method, 2u32);
+            assert!(witness.is_witness());
+            assert_eq!(witness, ExplicitSeal::with(method, None, 2u32));
+            assert_eq!(witness.require_explicit(), Err(WitnessVoutError));
+            assert_eq!(witness.outpoint(), None);
+
+            // Genesis seals have txid even for the all-zero outpoint
+            for outpoint in [outpoint, OutPoint::default(), OutPoint::null()] {
+                let genesis = ExplicitSeal::new_genesis(method, outpoint);
+                assert!(!genesis.is_witness());
+                assert_eq!(genesis.txid, Some(outpoint.txid));
+                assert_eq!(genesis.require_explicit(), Ok(outpoint));
+                assert_eq!(genesis.outpoint(), Some(outpoint));
+                assert_eq!(genesis, ExplicitSeal::new(method, outpoint));
+            }
+        }
+    }
+
+    #[test]
+    fn outpoint_str() {
+        let hex =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let s = format!("{}:2", hex);
+        let outpoint = OutPoint::from_str(&s).unwrap();
+        let seal = ExplicitSeal::from_outpoint_str(CloseMethod::OpretFirst, &s)
+            .unwrap();
+        assert_eq!(seal, ExplicitSeal::new(CloseMethod::OpretFirst, outpoint));
+        assert_eq!(seal.to_outpoint_string(), Some(s.clone()));
+        assert_eq!(seal.to_outpoint_string(), Some(outpoint.to_string()));
+        assert_eq!(
+            OutPoint::from_str(&seal.to_outpoint_string().unwrap()),
+            Ok(outpoint)
+        );
+        // Seal string has the method prepended to the outpoint string
+        assert_eq!(seal.to_string(), format!("opret1st:{}", s));
+
+        // Seals over witness transaction outputs have no outpoint
+        let witness = ExplicitSeal::with(CloseMethod::TapretFirst, None, 2);
+        assert_eq!(witness.to_outpoint_string(), None);
+        assert!(matches!(
+            ExplicitSeal::from_outpoint_str(CloseMethod::TapretFirst, "~:2"),
+            Err(ParseError::WrongOutPoint(ParseOutPointError::Txid(_)))
+        ));
+
+        // Unlike our parser, rust-bitcoin rejects non-canonical vouts
+        let padded = format!("{}:02", hex);
+        assert_eq!(
+            ExplicitSeal::parse_with_method(&padded, CloseMethod::OpretFirst),
+            Ok(seal)
+        );
+        assert_eq!(
+            ExplicitSeal::from_outpoint_str(CloseMethod::OpretFirst, &padded),
+            Err(ParseError::WrongOutPoint(
+                ParseOutPointError::VoutNotCanonical
+            ))
+        );
+        assert_eq!(
+            ExplicitSeal::from_outpoint_str(CloseMethod::OpretFirst, hex),
+            Err(ParseError::WrongOutPoint(ParseOutPointError::Format))
+        );
+        assert!(matches!(
+            ExplicitSeal::from_outpoint_str(
+                CloseMethod::OpretFirst,
+                &format!("opret1st:{}", s)
+            ),
+            Err(ParseError::WrongOutPoint(_))
+        ));
+    }
+
+    #[test]
+    fn txid_parsing() {
+        let hex =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let txid = Txid::from_hex(hex).unwrap();
+        assert_eq!(parse_txid(hex), Some(txid));
+        assert_eq!(parse_txid(&hex.to_uppercase()), Some(txid));
+        assert_eq!(parse_txid(&hex[..62]), None);
+        assert_eq!(parse_txid(&format!("{}00", hex)), None);
+        assert_eq!(parse_txid(&hex.replace('c', "g")), None);
+        assert_eq!(parse_txid(&hex.replace("6c", "ä")), None);
+    }
+
+    #[test]
+    fn parse_error_fragment() {
+        let err = ExplicitSeal::from_str("tapret2nd:~:1").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::WrongMethod(MethodParseError::new("tapret2nd"))
+        );
+        assert!(err.to_string().contains("tapret2nd"));
+
+        let err = RevealedSeal::from_str("opret2nd:~:1#0x1").unwrap_err();
+        assert!(err.to_string().contains("opret2nd"));
+
+        assert!(matches!(
+            ExplicitSeal::from_str("tapret1st:~"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_str("tapret1st:~:1:2"),
+            Err(ParseError::WrongStructure(_))
+        ));
+        assert!(matches!(
+            ExplicitSeal::from_str("tapret1st:X:1"),
+            Err(ParseError::WrongTxid(_))
+        ));
+    }
+
+    fn context(err: ParseError) -> (usize, String) {
+        match err {
+            ParseError::WrongTxid(ctx)
+            | ParseError::WrongVout(ctx)
+            | ParseError::WrongStructure(ctx) => (ctx.offset, ctx.fragment),
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn parse_error_offset() {
+        for (s, offset, fragment) in [
+            ("tapret1st:xyz:5", 10, "xyz"),
+            ("opret1st:~:5x", 11, "5x"),
+            ("opret1st:~:", 11, ""),
+            ("tapret1st:~:1:2", 13, ":2"),
+            ("tapret1st:~#1#2", 13, "#2"),
+            ("tapret1st:~", 11, ""),
+            ("tapret1st", 9, ""),
+        ] {
+            let err = ExplicitSeal::from_str(s).unwrap_err();
+            assert_eq!(context(err), (offset, fragment.to_owned()), "{}", s);
+        }
+
+        for (s, offset, fragment) in [
+            ("seal:tapret1st/xyz/5", 15, "xyz"),
+            ("seal:tapret1st/%7E/x%31", 19, "x%31"),
+            ("seal:tapret1st/~/1/2", 18, "/2"),
+            ("seal:tapret1st/~", 16, ""),
+        ] {
+            let err = ExplicitSeal::from_uri(s).unwrap_err();
+            assert_eq!(context(err), (offset, fragment.to_owned()), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_error_display() {
+        assert_eq!(
+            ExplicitSeal::from_str("tapret1st:xyz:5")
+                .unwrap_err()
+                .to_string(),
+            "unable to parse transaction id value, which must be 64-character \
+             hexadecimal string, at byte 10:\n    tapret1st:xyz:5\n              ^^^"
+        );
+        assert_eq!(
+            ExplicitSeal::from_str("tapret1st:~")
+                .unwrap_err()
+                .to_string(),
+            "wrong structure of seal string representation at byte 11:\n    \
+             tapret1st:~\n               ^"
+        );
+        // Carets are aligned by characters and not bytes
+        assert_eq!(
+            ExplicitSeal::from_str("tapret1st:ä:x")
+                .unwrap_err()
+                .to_string(),
+            "unable to parse transaction id value, which must be 64-character \
+             hexadecimal string, at byte 10:\n    tapret1st:ä:x\n              \
+             ^"
+        );
+    }
+
+    #[test]
+    fn dumb_seal() {
+        let seal = ExplicitSeal::dumb_default();
+        assert_eq!(
+            seal.to_string(),
+            "opret1st:0000000000000000000000000000000000000000000000000000000000000000:0"
+        );
+        let data = strict_serialize(&seal).unwrap();
+        assert_eq!(strict_deserialize::<ExplicitSeal>(&data).unwrap(), seal);
+    }
+
+    #[test]
+    fn canonical_string() {
+        const TXID: &str =
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let lower = format!("tapret1st:{}:2", TXID);
+        let upper = format!("tapret1st:{}:2", TXID.to_uppercase());
+        let mixed = format!(
+            "tapret1st:{}{}:2",
+            &TXID[..32].to_uppercase(),
+            &TXID[32..]
+        );
+        let seal = ExplicitSeal::from_str(&lower).unwrap();
+
+        // Parsing accepts any case and method aliases, display is canonical
+        for s in [&lower, &upper, &mixed, &format!("TAPRET:{}:02", TXID)] {
+            let parsed = ExplicitSeal::from_str(s).unwrap();
+            assert_eq!(parsed, seal);
+            assert_eq!(parsed.to_string(), lower);
+            assert_eq!(parsed.canonical_string(), lower);
+        }
+        assert!(ExplicitSeal::is_canonical_str(&lower));
+        assert!(!ExplicitSeal::is_canonical_str(&upper));
+        assert!(!ExplicitSeal::is_canonical_str(&mixed));
+        assert!(!ExplicitSeal::is_canonical_str(&format!(
+            "tapret:{}:2",
+            TXID
+        )));
+        assert!(!ExplicitSeal::is_canonical_str(&format!(
+            "tapret1st:{}:02",
+            TXID
+        )));
+
+        // Witness transaction placeholder
+        let witness = ExplicitSeal::new_witness(CloseMethod::OpretFirst, 5);
+        assert_eq!(witness.canonical_string(), "opret1st:~:5");
+        assert!(ExplicitSeal::is_canonical_str("opret1st:~:5"));
+        assert_eq!(
+            ExplicitSeal::from_str("OPRET1ST:~:5")
+                .unwrap()
+                .canonical_string(),
+            "opret1st:~:5"
+        );
+        assert!(!ExplicitSeal::is_canonical_str("OPRET1ST:~:5"));
+
+        // Invalid strings are not canonical
+        assert!(!ExplicitSeal::is_canonical_str(""));
+        assert!(!ExplicitSeal::is_canonical_str("tapret1st:~"));
+        assert!(!ExplicitSeal::is_canonical_str(&format!("{} ", lower)));
+    }
+}
@@ -0,0 +1,295 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Short identifiers of concealed seals used for indexing.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use bech32::{FromBase32, ToBase32, Variant};
+use bitcoin::hashes::{hash160, Hash};
+
+use crate::txout::blind::ConcealedSeal;
+
+/// Human-readable part of the Bech32m representation of [`SealId`].
+pub const SEAL_ID_HRP: &str = "sealid";
+
+/// Errors parsing [`SealId`] from its Bech32m representation.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum SealIdParseError {
+    /// wrong Bech32m representation of the seal id – {0}
+    #[from]
+    Bech32(bech32::Error),
+
+    /// Bech32 string with `{0}` HRP is not a seal id, which must use `sealid`
+    /// HRP
+    WrongHrp(String),
+
+    /// seal id must use Bech32m checksum
+    WrongVariant,
+
+    /// seal id must be 20 bytes long, while {0} bytes were provided
+    WrongLength(usize),
+}
+
+/// Short identifier of the seal computed as `HASH160` (RIPEMD160 of SHA256) of
+/// the [`ConcealedSeal`] bytes, see [`ConcealedSeal::seal_id`].
+///
+/// The identifier is non-invertible: it can't be used to recover the concealed
+/// seal and is not a commitment recognized by any protocol. It must be used
+/// only for indexing seals in databases and user interfaces.
+///
+/// Seal ids are displayed using Bech32m with [`SEAL_ID_HRP`] and ordered
+/// lexicographically by their bytes, which are also used by the strict
+/// encoding and binary serde formats.
+#[derive(Wrapper, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, From)]
+#[wrapper(Debug, LowerHex, Index, IndexRange, IndexFrom, IndexTo, IndexFull)]
+pub struct SealId(hash160::Hash);
+
+impl SealId {
+    /// Returns byte representation of the seal id.
+    #[inline]
+    pub fn to_byte_array(&self) -> [u8; 20] { self.0.into_inner() }
+
+    /// Constructs seal id from its byte representation.
+    #[inline]
+    pub fn from_byte_array(bytes: [u8; 20]) -> SealId {
+        SealId(hash160::Hash::from_inner(bytes))
+    }
+}
+
+impl ConcealedSeal {
+    /// Computes [`SealId`] identifying the seal in indexes.
+    #[inline]
+    pub fn seal_id(&self) -> SealId {
+        SealId(hash160::Hash::hash(&self.to_byte_array()))
+    }
+}
+
+impl From<ConcealedSeal> for SealId {
+    #[inline]
+    fn from(seal: ConcealedSeal) -> Self { seal.seal_id() }
+}
+
+impl Display for SealId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = bech32::encode(
+            SEAL_ID_HRP,
+            self.to_byte_array().to_base32(),
+            Variant::Bech32m,
+        )
+        .expect("seal id HRP is valid");
+        f.write_str(&s)
+    }
+}
+
+impl FromStr for SealId {
+    type Err = SealIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, variant) = bech32::decode(s)?;
+        if hrp != SEAL_ID_HRP {
+            return Err(SealIdParseError::WrongHrp(hrp));
+        }
+        if variant != Variant::Bech32m {
+            return Err(SealIdParseError::WrongVariant);
+        }
+        let data = Vec::<u8>::from_base32(&data)?;
+        let bytes = <[u8; 20]>::try_from(data.as_slice())
+            .map_err(|_| SealIdParseError::WrongLength(data.len()))?;
+        Ok(SealId::from_byte_array(bytes))
+    }
+}
+
+impl strict_encoding::Strategy for SealId {
+    type Strategy = strict_encoding::strategies::Wrapped;
+}
+
+/// Serializes seal id as a Bech32m string for human-readable formats and as raw
+/// bytes for binary formats.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SealId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self[..])
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SealId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = SealId;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "Bech32m string with `{}` HRP or 20 bytes",
+                    SEAL_ID_HRP
+                )
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                SealId::from_str(v).map_err(serde::de::Error::custom)
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                <[u8; 20]>::try_from(v)
+                    .map(SealId::from_byte_array)
+                    .map_err(|_| {
+                        serde::de::Error::invalid_length(v.len(), &"20 bytes")
+                    })
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                self.visit_bytes(&v)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Visitor)
+        } else {
+            deserializer.deserialize_byte_buf(Visitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::{FromHex, ToHex};
+    use bitcoin::Txid;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+    use crate::txout::blind::RevealedSeal;
+    use crate::txout::CloseMethod;
+
+    fn concealed() -> ConcealedSeal {
+        RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(
+                Txid::from_hex(
+                    "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+                )
+                .unwrap(),
+            ),
+            vout: 2u32.into(),
+            blinding: 54683969839,
+        }
+        .conceal()
+    }
+
+    #[test]
+    fn vectors() {
+        // Seal ids must never change across releases
+        for (seal, hex, bech32) in [
+            (
+                concealed(),
+                "145d098a40841b6d82e58c7ca4b3f43fc8ac2296",
+                "sealid1z3wsnzjqssdkmqh93372fvl58ly2cg5kfvdunl",
+            ),
+            (
+                ConcealedSeal::from_byte_array([0u8; 32]),
+                "b8bcb07f6344b42ab04250c86a6e8b75d3fdbbc6",
+                "sealid1hz7tqlmrgj6z4vzz2ryx5m5twhflmw7xn5kkpn",
+            ),
+        ] {
+            let id = seal.seal_id();
+            assert_eq!(id.to_byte_array().to_hex(), hex);
+            assert_eq!(id.to_string(), bech32);
+            assert_eq!(SealId::from_str(bech32), Ok(id));
+            assert_eq!(SealId::from(seal), id);
+            assert_eq!(strict_serialize(&id).unwrap(), id.to_byte_array());
+            assert_eq!(strict_deserialize::<SealId>(&id[..]).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        let low = SealId::from_byte_array([0u8; 20]);
+        let mut bytes = [0u8; 20];
+        bytes[0] = 1;
+        let high = SealId::from_byte_array(bytes);
+        assert!(low < high);
+        bytes[0] = 0;
+        bytes[19] = 0xFF;
+        assert!(SealId::from_byte_array(bytes) < high);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let id = concealed().seal_id();
+        let data = id.to_byte_array().to_base32();
+        let encode = |hrp, data: &[bech32::u5], variant| {
+            bech32::encode(hrp, data, variant).unwrap()
+        };
+
+        assert_eq!(
+            SealId::from_str(&encode("txob", &data, Variant::Bech32m)),
+            Err(SealIdParseError::WrongHrp(s!("txob")))
+        );
+        assert_eq!(
+            SealId::from_str(&encode(SEAL_ID_HRP, &data, Variant::Bech32)),
+            Err(SealIdParseError::WrongVariant)
+        );
+        assert_eq!(
+            SealId::from_str(&encode(
+                SEAL_ID_HRP,
+                &concealed().to_byte_array().to_base32(),
+                Variant::Bech32m
+            )),
+            Err(SealIdParseError::WrongLength(32))
+        );
+        assert!(matches!(
+            SealId::from_str("sealid1qqqqqq"),
+            Err(SealIdParseError::Bech32(_))
+        ));
+        // Concealed seal string is not a seal id
+        assert!(SealId::from_str(&concealed().to_string()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let id = concealed().seal_id();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"{}\"", id));
+        assert_eq!(serde_json::from_str::<SealId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<SealId>("\"sealid1qqqqqq\"").is_err());
+
+        let bin = bincode::serialize(&id).unwrap();
+        assert_eq!(bincode::deserialize::<SealId>(&bin).unwrap(), id);
+    }
+}
@@ -16,13 +16,85 @@
 //! Bitcoin single-use-seals defined by a transaction output and closed by
 //! spending that output ("TxOut seals").
 
+mod batch;
 pub mod blind;
+mod blinder;
+mod chain;
+#[cfg(feature = "bitcoin_compat")]
+mod compat;
+mod definition;
+#[cfg(feature = "descriptors")]
+mod descriptor;
+#[cfg(feature = "electrum")]
+mod electrum;
 mod error;
 pub mod explicit;
+mod id;
 mod proto;
+#[cfg(feature = "wallet")]
+mod psbt;
+mod redact;
+mod report;
+mod resolver;
+#[cfg(feature = "json-schema")]
+mod schema;
 mod seal;
+mod set;
+mod status;
+mod template;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_helpers;
+mod tx;
+mod uri;
+mod witness;
 
-pub use error::{MethodParseError, VerifyError, WitnessVoutError};
-pub use explicit::ExplicitSeal;
-pub use proto::TxoProtocol;
-pub use seal::{CloseMethod, TxoSeal};
+pub use batch::verify_seals_batch;
+pub use blinder::{Blinder, SeqBlinder, ZeroBlinder};
+pub use chain::ChainSeal;
+#[cfg(feature = "bitcoin_compat")]
+pub use compat::{outpoint_from_raw, outpoint_to_raw};
+pub use definition::{SealDefinition, WitnessVoutSeal};
+#[cfg(feature = "descriptors")]
+pub use descriptor::{
+    seal_from_descriptor, DescriptorSealError, ExplicitSealTemplate,
+};
+#[cfg(feature = "electrum")]
+pub use electrum::{ElectrumResolver, ElectrumResolverError};
+pub use error::{
+    CloseError, MethodParseError, RevealMismatch, SealDecodeError,
+    UnknownMethodBits, UnknownMethodId, VerifyError, WitnessCheckError,
+    WitnessOutputError, WitnessVoutError,
+};
+pub use explicit::{
+    resolve_seals, resolve_seals_checked, ExplicitSeal,
+    MAX_SEALS_PER_DEFINITION,
+};
+pub use id::{SealId, SealIdParseError, SEAL_ID_HRP};
+#[cfg(feature = "async")]
+pub use proto::TxoProtocolAsync;
+pub use proto::{TxWitness, TxoProtocol, TxoVerifier, VerifyTxoSeal};
+pub use redact::{Redacted, RedactedDisplay};
+pub use report::{verify_seal_with_report, CheckOutcome, VerifyReport};
+#[cfg(feature = "async")]
+pub use resolver::{AsyncAdapter, AsyncTxResolve};
+pub use resolver::{MemResolver, MemResolverError, TxResolve};
+#[cfg(feature = "json-schema")]
+pub use schema::seals_json_schema;
+pub use seal::{
+    acceptable_methods, CloseMethod, CloseMethodSet, TxoSeal, TxoSealDyn, Vout,
+};
+pub use set::{SealConflict, SealSet};
+pub use status::{seal_status, seal_status_with, SealStatus};
+pub use template::SealTemplate;
+pub use tx::{TxExt, TxSeals};
+pub use uri::SEAL_URI_SCHEME;
+pub use witness::{
+    check_witness_tx, check_witness_txid, verify_seal_closure,
+    verify_seal_closure_with, CloseSeal, VerifyOpts, Witness,
+};
+
+#[cfg(feature = "wallet")]
+pub use self::psbt::{
+    InputSeals, ProprietaryKeySeal, SealKeyError, PSBT_IN_CLOSED_SEAL,
+    PSBT_SEAL_PREFIX,
+};
@@ -13,60 +13,182 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use bitcoin::Txid;
-use bitcoin_onchain::ResolveTx;
+use bitcoin::{Transaction, Txid};
 use commit_verify::lnpbp4;
-use dbc::{Anchor, Proof};
+use dbc::Proof;
+#[cfg(feature = "async")]
+use single_use_seals::SealProtocolAsync;
 use single_use_seals::{SealProtocol, SealStatus, VerifySeal};
 
-use crate::txout::{TxoSeal, VerifyError};
+#[cfg(feature = "async")]
+use crate::txout::status::confirmations;
+use crate::txout::status::witness_confirmations;
+#[cfg(feature = "async")]
+use crate::txout::AsyncTxResolve;
+use crate::txout::{
+    verify_seal_closure, verify_seal_closure_with, TxResolve, TxoSeal,
+    VerifyError, VerifyOpts, Witness,
+};
 
 // TODO: #8 Implement proper operations with SealMedium
-// TODO: #9 Do asynchronous version
-// #[cfg(feature = "async")]
-// use single_use_seals::SealMediumAsync;
 
-pub struct Witness {
-    pub txid: Txid,
-    pub proof: Proof,
+/// Txo single-use-seal engine.
+pub struct TxoProtocol<Resolver: TxResolve> {
+    resolver: Resolver,
 }
 
-impl<L> From<Anchor<L>> for Witness
+impl<Resolver> TxoProtocol<Resolver>
 where
-    L: lnpbp4::Proof,
+    Resolver: TxResolve,
 {
-    fn from(anchor: Anchor<L>) -> Self {
-        Witness {
-            txid: anchor.txid,
-            proof: anchor.dbc_proof,
+    /// Constructs seal engine using the provided transaction resolver.
+    #[inline]
+    pub fn new(resolver: Resolver) -> Self { TxoProtocol { resolver } }
+
+    /// Returns reference to the transaction resolver used by the engine.
+    #[inline]
+    pub fn resolver(&self) -> &Resolver { &self.resolver }
+
+    /// Verifies that the seal was closed over the message `msg` by the
+    /// witness transaction `witness_tx` with [`verify_seal_closure`], also
+    /// checking that the seal output exists using the transaction resolver.
+    /// The output of seals defined over the witness transaction is checked
+    /// against the witness transaction itself.
+    ///
+    /// Seals not spent by the witness transaction result in
+    /// [`VerifyError::SealNotClosed`] if their outputs are not spent by any
+    /// transaction known to the resolver.
+    pub fn verify_seal_closure(
+        &self,
+        seal: &impl TxoSeal,
+        msg: &lnpbp4::CommitmentHash,
+        proof: &Proof,
+        witness_tx: &Transaction,
+    ) -> Result<(), VerifyError> {
+        self.verify_seal_closure_with(
+            seal,
+            msg,
+            proof,
+            witness_tx,
+            VerifyOpts::default(),
+        )
+    }
+
+    /// Verifies seal closing like [`TxoProtocol::verify_seal_closure`] using
+    /// the provided verification options. If the options require
+    /// confirmations, the witness transaction confirmations are queried from
+    /// the resolver after the successful verification of the closing, failing
+    /// with [`VerifyError::InsufficientConfirmations`] if the policy is not
+    /// met. Transactions mined at unknown best block height are treated as
+    /// unconfirmed.
+    pub fn verify_seal_closure_with(
+        &self,
+        seal: &impl TxoSeal,
+        msg: &lnpbp4::CommitmentHash,
+        proof: &Proof,
+        witness_tx: &Transaction,
+        opts: VerifyOpts,
+    ) -> Result<(), VerifyError> {
+        let outpoint = seal.outpoint_or(witness_tx.txid());
+        let output_count = match seal.outpoint() {
+            Some(_) => self
+                .resolver
+                .resolve_tx(outpoint.txid)
+                .map_err(resolver_error)?
+                .output
+                .len(),
+            None => witness_tx.output.len(),
+        };
+        if outpoint.vout as usize >= output_count {
+            return Err(VerifyError::SealOutputUnknown(outpoint));
+        }
+        verify_seal_closure_with(
+            seal,
+            msg,
+            proof,
+            witness_tx,
+            opts.without_confirmations(),
+        )
+        .map_err(|err| self.check_closed(err))?;
+        if opts.required_confirmations() > 0 {
+            let (_, confirmations) =
+                witness_confirmations(witness_tx.txid(), &self.resolver)?;
+            opts.check_confirmations(confirmations)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces [`VerifyError::WitnessNotSpendingSeal`] error with
+    /// [`VerifyError::SealNotClosed`] if the seal output is not spent by any
+    /// transaction known to the resolver.
+    fn check_closed(&self, err: VerifyError) -> VerifyError {
+        match err {
+            VerifyError::WitnessNotSpendingSeal { seal, .. } => {
+                match self.resolver.spending_tx(seal) {
+                    Ok(None) => VerifyError::SealNotClosed(seal),
+                    Ok(Some(_)) => err,
+                    Err(err) => resolver_error(err),
+                }
+            }
+            err => err,
         }
     }
 }
 
-/// Txo single-use-seal engine.
-pub struct TxoProtocol<Resolver: ResolveTx> {
-    resolver: Resolver,
+fn resolver_error(
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> VerifyError {
+    VerifyError::ResolverError(Box::new(err))
+}
+
+/// Converts result of the seal closing verification into the result of the
+/// generic [`VerifySeal`] API, which returns `Ok(false)` if the witness
+/// transaction does not commit to the message.
+fn commitment_status(
+    res: Result<(), VerifyError>,
+) -> Result<bool, VerifyError> {
+    match res {
+        Ok(()) => Ok(true),
+        Err(VerifyError::InvalidCommitment(_)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn seal_status(spending_tx: Option<Transaction>) -> SealStatus {
+    match spending_tx {
+        Some(_) => SealStatus::Closed,
+        None => SealStatus::Undefined,
+    }
 }
 
 impl<Seal, Resolver> SealProtocol<Seal> for TxoProtocol<Resolver>
 where
     Seal: TxoSeal,
-    Resolver: ResolveTx,
+    Resolver: TxResolve,
 {
     type Witness = Witness;
     type Message = lnpbp4::CommitmentHash;
     type PublicationId = Txid;
     type Error = VerifyError;
 
-    fn get_seal_status(&self, _seal: &Seal) -> Result<SealStatus, Self::Error> {
-        todo!()
+    /// Seals defined over the witness transaction outputs can't be checked
+    /// and always have [`SealStatus::Undefined`] status.
+    fn get_seal_status(&self, seal: &Seal) -> Result<SealStatus, Self::Error> {
+        let outpoint = match seal.outpoint() {
+            Some(outpoint) => outpoint,
+            None => return Ok(SealStatus::Undefined),
+        };
+        self.resolver
+            .spending_tx(outpoint)
+            .map(seal_status)
+            .map_err(resolver_error)
     }
 }
 
 impl<'seal, Seal, Resolver> VerifySeal<'seal, Seal> for TxoProtocol<Resolver>
 where
     Seal: TxoSeal + 'seal,
-    Resolver: ResolveTx,
+    Resolver: TxResolve,
 {
     fn verify_seal(
         &self,
@@ -74,20 +196,15 @@ where
         msg: &Self::Message,
         witness: &Self::Witness,
     ) -> Result<bool, Self::Error> {
-        // 1. Get tx
-        let tx = self.resolver.resolve_tx(witness.txid)?;
-
-        // 2. The seal must match tx inputs
-        let outpoint = seal.outpoint_or(witness.txid);
-        if !tx.input.iter().any(|txin| txin.previous_output == outpoint) {
-            return Err(VerifyError::WitnessNotClosingSeal(
-                witness.txid,
-                outpoint,
-            ));
-        }
-
-        // 3. Verify DBC with the giving closing method
-        witness.proof.verify(msg, tx).map_err(VerifyError::from)
+        let tx = self
+            .resolver
+            .resolve_tx(witness.txid)
+            .map_err(resolver_error)?;
+        commitment_status(
+            witness
+                .verify([seal], msg, &tx)
+                .map_err(|err| self.check_closed(err)),
+        )
     }
 
     fn verify_seal_all(
@@ -96,31 +213,691 @@ where
         msg: &Self::Message,
         witness: &Self::Witness,
     ) -> Result<bool, Self::Error> {
-        // 1. Get tx
-        let tx = self.resolver.resolve_tx(witness.txid)?;
+        let tx = self
+            .resolver
+            .resolve_tx(witness.txid)
+            .map_err(resolver_error)?;
+        commitment_status(
+            witness
+                .verify(seals, msg, &tx)
+                .map_err(|err| self.check_closed(err)),
+        )
+    }
+}
 
-        let mut method = None;
-        for seal in seals {
-            // 2. All seals must have the same closing method
-            if let Some(method) = method {
-                if method != seal.method() {
-                    return Err(VerifyError::InconsistentCloseMethod);
+/// Witness of the seal closing provided as the witness transaction together
+/// with the proof of the deterministic bitcoin commitment it contains.
+pub type TxWitness = (Transaction, Proof);
+
+/// Txo single-use-seal engine verifying seals against the witness transaction
+/// provided as a part of the witness ([`TxWitness`]), without accessing the
+/// publication medium.
+///
+/// The verification uses [`verify_seal_closure`], returning `Ok(false)` if the
+/// witness transaction spends the seal but does not commit to the message and
+/// an error for all other failures.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TxoVerifier;
+
+impl<Seal> SealProtocol<Seal> for TxoVerifier
+where
+    Seal: TxoSeal,
+{
+    type Witness = TxWitness;
+    type Message = lnpbp4::CommitmentHash;
+    type PublicationId = Txid;
+    type Error = VerifyError;
+
+    /// The verifier has no access to the publication medium, so the seals
+    /// always have [`SealStatus::Undefined`] status.
+    #[inline]
+    fn get_seal_status(&self, _: &Seal) -> Result<SealStatus, Self::Error> {
+        Ok(SealStatus::Undefined)
+    }
+}
+
+impl<'seal, Seal> VerifySeal<'seal, Seal> for TxoVerifier
+where
+    Seal: TxoSeal + 'seal,
+{
+    fn verify_seal(
+        &self,
+        seal: &'seal Seal,
+        msg: &Self::Message,
+        (witness_tx, proof): &Self::Witness,
+    ) -> Result<bool, Self::Error> {
+        commitment_status(verify_seal_closure(seal, msg, proof, witness_tx))
+    }
+}
+
+/// Verification of the seal closing through the generic [`VerifySeal`] API
+/// with [`TxoVerifier`], implemented for all transaction output-based seals.
+pub trait VerifyTxoSeal: TxoSeal + Sized {
+    /// Verifies that the seal was closed over the message `msg` by the
+    /// witness, see [`TxoVerifier`].
+    #[inline]
+    fn verify(
+        &self,
+        witness: &TxWitness,
+        msg: &lnpbp4::CommitmentHash,
+    ) -> Result<bool, VerifyError> {
+        TxoVerifier.verify_seal(self, msg, witness)
+    }
+}
+
+impl<Seal> VerifyTxoSeal for Seal where Seal: TxoSeal {}
+
+/// Asynchronous version of the Txo single-use-seal engine [`TxoProtocol`].
+///
+/// Seal verification shares the same logic with the synchronous engine, which
+/// is implemented by [`Witness::verify`].
+#[cfg(feature = "async")]
+pub struct TxoProtocolAsync<Resolver: AsyncTxResolve> {
+    resolver: Resolver,
+}
+
+#[cfg(feature = "async")]
+impl<Resolver> TxoProtocolAsync<Resolver>
+where
+    Resolver: AsyncTxResolve,
+{
+    /// Constructs seal engine using the provided transaction resolver.
+    #[inline]
+    pub fn new(resolver: Resolver) -> Self { TxoProtocolAsync { resolver } }
+
+    /// Returns reference to the transaction resolver used by the engine.
+    #[inline]
+    pub fn resolver(&self) -> &Resolver { &self.resolver }
+
+    /// Verifies that the seal was closed over the message with the provided
+    /// witness.
+    pub async fn verify_seal_async(
+        &self,
+        seal: &impl TxoSeal,
+        msg: &lnpbp4::CommitmentHash,
+        witness: &Witness,
+    ) -> Result<bool, VerifyError> {
+        self.verify_seal_all_async([seal], msg, witness).await
+    }
+
+    /// Verifies that all of the seals were closed over the message with the
+    /// provided witness.
+    #[inline]
+    pub async fn verify_seal_all_async<'seal, Seal>(
+        &self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        msg: &lnpbp4::CommitmentHash,
+        witness: &Witness,
+    ) -> Result<bool, VerifyError>
+    where
+        Seal: TxoSeal + 'seal,
+    {
+        self.verify_seal_all_async_with(
+            seals,
+            msg,
+            witness,
+            VerifyOpts::default(),
+        )
+        .await
+    }
+
+    /// Verifies that all of the seals were closed over the message with the
+    /// provided witness using the verification options. Confirmations
+    /// required by the options are checked with the heights reported by the
+    /// resolver like in [`TxoProtocol::verify_seal_closure_with`].
+    pub async fn verify_seal_all_async_with<'seal, Seal>(
+        &self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        msg: &lnpbp4::CommitmentHash,
+        witness: &Witness,
+        opts: VerifyOpts,
+    ) -> Result<bool, VerifyError>
+    where
+        Seal: TxoSeal + 'seal,
+    {
+        let tx = self
+            .resolver
+            .resolve_tx(witness.txid)
+            .await
+            .map_err(resolver_error)?;
+        let res =
+            witness.verify_with(seals, msg, &tx, opts.without_confirmations());
+        if res.is_ok() && opts.required_confirmations() > 0 {
+            let height = self
+                .resolver
+                .tx_height(witness.txid)
+                .await
+                .map_err(resolver_error)?;
+            let tip =
+                self.resolver.tip_height().await.map_err(resolver_error)?;
+            opts.check_confirmations(confirmations(height, tip)?)?;
+        }
+        commitment_status(match res {
+            Err(VerifyError::WitnessNotSpendingSeal { witness, seal }) => {
+                match self.resolver.spending_tx(seal).await {
+                    Ok(None) => Err(VerifyError::SealNotClosed(seal)),
+                    Ok(Some(_)) => Err(VerifyError::WitnessNotSpendingSeal {
+                        witness,
+                        seal,
+                    }),
+                    Err(err) => Err(resolver_error(err)),
+                }
+            }
+            res => res,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<Seal, Resolver> SealProtocolAsync<Seal> for TxoProtocolAsync<Resolver>
+where
+    Seal: TxoSeal + Sync + Send,
+    Resolver: AsyncTxResolve,
+{
+    type Witness = Witness;
+    type Message = lnpbp4::CommitmentHash;
+    type PublicationId = Txid;
+    type Error = VerifyError;
+
+    /// Seals defined over the witness transaction outputs can't be checked
+    /// and always have [`SealStatus::Undefined`] status.
+    async fn get_seal_status_async(
+        &self,
+        seal: &Seal,
+    ) -> Result<SealStatus, Self::Error> {
+        let outpoint = match seal.outpoint() {
+            Some(outpoint) => outpoint,
+            None => return Ok(SealStatus::Undefined),
+        };
+        self.resolver
+            .spending_tx(outpoint)
+            .await
+            .map(seal_status)
+            .map_err(resolver_error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use amplify::Wrapper;
+    use bitcoin::hashes::{sha256t, Hash};
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut};
+    use dbc::opret::OpretProof;
+
+    use super::*;
+    use crate::txout::{CloseMethod, ExplicitSeal, MemResolver};
+
+    fn msg(byte: u8) -> lnpbp4::CommitmentHash {
+        lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(
+            [byte; 32],
+        ))
+    }
+
+    fn closing_tx(outpoint: OutPoint) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                ..default!()
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Script::new_op_return(&msg(1)[..]),
+            }],
+        }
+    }
+
+    #[test]
+    fn verify_with_resolver() {
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let tx = closing_tx(outpoint);
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+
+        let proto = TxoProtocol::new(MemResolver::new());
+        assert!(matches!(
+            proto.verify_seal(&seal, &msg(1), &witness),
+            Err(VerifyError::ResolverError(_))
+        ));
+        assert!(matches!(
+            proto.get_seal_status(&seal),
+            Ok(SealStatus::Undefined)
+        ));
+
+        let proto = TxoProtocol::new(MemResolver::from_iter([tx]));
+        assert!(matches!(
+            proto.verify_seal(&seal, &msg(1), &witness),
+            Ok(true)
+        ));
+        assert!(matches!(
+            proto.verify_seal(&seal, &msg(2), &witness),
+            Ok(false)
+        ));
+        assert!(matches!(
+            proto.get_seal_status(&seal),
+            Ok(SealStatus::Closed)
+        ));
+
+        let witness_seal = ExplicitSeal::with(CloseMethod::OpretFirst, None, 0);
+        assert!(matches!(
+            proto.get_seal_status(&witness_seal),
+            Ok(SealStatus::Undefined)
+        ));
+    }
+
+    #[test]
+    fn verify_closure_with_resolver() {
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let tx = closing_tx(outpoint);
+        let prev_tx = Transaction {
+            input: vec![],
+            ..closing_tx(outpoint)
+        };
+        let proof = Proof::OpretFirst(OpretProof);
+
+        let proto = TxoProtocol::new(MemResolver::new());
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Err(VerifyError::ResolverError(_))
+        ));
+
+        let seal = ExplicitSeal::new(
+            CloseMethod::OpretFirst,
+            OutPoint::new(prev_tx.txid(), 0),
+        );
+        let tx = closing_tx(seal.outpoint().unwrap());
+        let proto = TxoProtocol::new(MemResolver::from_iter([prev_tx.clone()]));
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Ok(())
+        ));
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(2), &proof, &tx),
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+
+        let absent = OutPoint::new(prev_tx.txid(), 1);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, absent);
+        let tx = closing_tx(absent);
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Err(VerifyError::SealOutputUnknown(outpoint)) if outpoint == absent
+        ));
+
+        let seal = ExplicitSeal::with(CloseMethod::OpretFirst, None, 1);
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Err(VerifyError::SealOutputUnknown(outpoint))
+                if outpoint == OutPoint::new(tx.txid(), 1)
+        ));
+    }
+
+    #[test]
+    fn seal_not_closed() {
+        let prev_tx = Transaction {
+            input: vec![],
+            ..closing_tx(OutPoint::default())
+        };
+        let outpoint = OutPoint::new(prev_tx.txid(), 0);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let proof = Proof::OpretFirst(OpretProof);
+        let other_tx =
+            closing_tx(OutPoint::new(Txid::hash(b"other transaction"), 0));
+        let witness = Witness {
+            txid: other_tx.txid(),
+            proof: proof.clone(),
+        };
+
+        // Seal output is not spent by any transaction
+        let proto = TxoProtocol::new(MemResolver::from_iter([
+            prev_tx.clone(),
+            other_tx.clone(),
+        ]));
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &other_tx),
+            Err(VerifyError::SealNotClosed(op)) if op == outpoint
+        ));
+        assert!(matches!(
+            proto.verify_seal(&seal, &msg(1), &witness),
+            Err(VerifyError::SealNotClosed(op)) if op == outpoint
+        ));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &other_tx),
+            Err(VerifyError::WitnessNotSpendingSeal { witness, seal })
+                if witness == other_tx.txid() && seal == outpoint
+        ));
+
+        // Seal output is spent by a transaction other than the witness
+        let spending_tx = Transaction {
+            version: 1,
+            ..closing_tx(outpoint)
+        };
+        let proto = TxoProtocol::new(MemResolver::from_iter([
+            prev_tx,
+            other_tx.clone(),
+            spending_tx,
+        ]));
+        assert!(matches!(
+            proto.verify_seal_closure(&seal, &msg(1), &proof, &other_tx),
+            Err(VerifyError::WitnessNotSpendingSeal { witness, seal })
+                if witness == other_tx.txid() && seal == outpoint
+        ));
+        assert!(matches!(
+            proto.verify_seal(&seal, &msg(1), &witness),
+            Err(VerifyError::WitnessNotSpendingSeal { witness, seal })
+                if witness == other_tx.txid() && seal == outpoint
+        ));
+        assert_eq!(
+            format!("{:#}", VerifyError::SealNotClosed(outpoint)),
+            format!(
+                "seal {} is not closed: its output is not spent by any \
+                 transaction.",
+                outpoint
+            )
+        );
+    }
+
+    #[test]
+    fn confirmation_policy() {
+        let prev_tx = Transaction {
+            input: vec![],
+            ..closing_tx(OutPoint::default())
+        };
+        let outpoint = OutPoint::new(prev_tx.txid(), 0);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let proof = Proof::OpretFirst(OpretProof);
+        let tx = closing_tx(outpoint);
+        let mut resolver = MemResolver::from_iter([prev_tx, tx.clone()]);
+        let opts = |min_confirmations, allow_mempool| VerifyOpts {
+            min_confirmations,
+            allow_mempool,
+            ..VerifyOpts::default()
+        };
+        let verify = |resolver: &MemResolver, opts| {
+            TxoProtocol::new(resolver.clone()).verify_seal_closure_with(
+                &seal,
+                &msg(1),
+                &proof,
+                &tx,
+                opts,
+            )
+        };
+
+        // Default options do not query confirmations
+        assert_eq!(VerifyOpts::default().required_confirmations(), 0);
+        assert!(verify(&resolver, VerifyOpts::default()).is_ok());
+        assert!(verify(&resolver, opts(0, true)).is_ok());
+        assert!(matches!(
+            verify(&resolver, opts(0, false)),
+            Err(VerifyError::InsufficientConfirmations { have: 0, need: 1 })
+        ));
+        assert!(matches!(
+            verify(&resolver, opts(3, true)),
+            Err(VerifyError::InsufficientConfirmations { have: 0, need: 3 })
+        ));
+
+        // Tip below the witness height is inconsistent
+        resolver.set_height(tx.txid(), 100);
+        resolver.set_tip_height(99);
+        assert!(matches!(
+            verify(&resolver, opts(1, false)),
+            Err(VerifyError::InconsistentHeight {
+                height: 100,
+                tip: 99
+            })
+        ));
+        assert!(verify(&resolver, VerifyOpts::default()).is_ok());
+
+        // Verification without resolver can't check the policy
+        assert!(verify_seal_closure_with(
+            &seal,
+            &msg(1),
+            &proof,
+            &tx,
+            opts(0, true)
+        )
+        .is_ok());
+        for (opts, need) in [(opts(0, false), 1), (opts(3, true), 3)] {
+            assert!(matches!(
+                verify_seal_closure_with(&seal, &msg(1), &proof, &tx, opts),
+                Err(VerifyError::ConfirmationsUnchecked(n)) if n == need
+            ));
+            let witness = Witness {
+                txid: tx.txid(),
+                proof: proof.clone(),
+            };
+            assert!(matches!(
+                witness.verify_with([&seal], &msg(1), &tx, opts),
+                Err(VerifyError::ConfirmationsUnchecked(n)) if n == need
+            ));
+        }
+
+        for (tip, have) in [(100, 1), (101, 2), (105, 6), (1000, 901)] {
+            resolver.set_tip_height(tip);
+            for need in 0..=6 {
+                let res = verify(&resolver, opts(need, false));
+                if have >= need {
+                    assert!(res.is_ok());
+                } else {
+                    assert!(matches!(
+                        res,
+                        Err(VerifyError::InsufficientConfirmations { have: h, need: n })
+                            if h == have && n == need
+                    ));
                 }
-            } else {
-                method = Some(seal.method());
             }
+        }
+
+        // Policy is checked only for the valid closings
+        assert!(matches!(
+            TxoProtocol::new(resolver.clone()).verify_seal_closure_with(
+                &seal,
+                &msg(2),
+                &proof,
+                &tx,
+                opts(10_000, false)
+            ),
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+        assert_eq!(
+            VerifyError::InsufficientConfirmations { have: 2, need: 6 }
+                .to_string(),
+            "witness transaction has 2 confirmations, while the verification \
+             policy requires at least 6."
+        );
+    }
+
+    #[test]
+    fn generic_verifier_conformance() {
+        use crate::txout::blind::RevealedSeal;
+        use crate::txout::SealDefinition;
+
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let other = OutPoint::new(Txid::hash(b"other transaction"), 1);
+        let mut tx = closing_tx(outpoint);
+        tx.output.push(TxOut::default());
+        let seals = [
+            ExplicitSeal::new(CloseMethod::OpretFirst, outpoint),
+            ExplicitSeal::new(CloseMethod::TapretFirst, outpoint),
+            ExplicitSeal::new(CloseMethod::OpretFirst, other),
+            ExplicitSeal::with(CloseMethod::OpretFirst, None, 1),
+        ];
+        let proofs = [
+            Proof::OpretFirst(OpretProof),
+            Proof::TapretFirst(dbc::tapret::TapretProof {
+                path_proof: default!(),
+                internal_key: XOnlyPublicKey::from_str(
+                    "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf8\
+                     42723f3",
+                )
+                .unwrap(),
+            }),
+        ];
 
-            // 3. Each seal must match tx inputs
-            let outpoint = seal.outpoint_or(witness.txid);
-            if !tx.input.iter().any(|txin| txin.previous_output == outpoint) {
-                return Err(VerifyError::WitnessNotClosingSeal(
-                    witness.txid,
-                    outpoint,
-                ));
+        let mut accepted = 0;
+        for seal in seals {
+            for proof in &proofs {
+                for msg in [msg(1), msg(2)] {
+                    let witness = (tx.clone(), proof.clone());
+                    let concrete =
+                        verify_seal_closure(&seal, &msg, proof, &tx).is_ok();
+                    let generic =
+                        TxoVerifier.verify_seal(&seal, &msg, &witness);
+                    assert_eq!(matches!(generic, Ok(true)), concrete);
+                    assert_eq!(
+                        matches!(seal.verify(&witness, &msg), Ok(true)),
+                        concrete
+                    );
+
+                    let revealed = RevealedSeal::from(seal);
+                    assert_eq!(
+                        matches!(revealed.verify(&witness, &msg), Ok(true)),
+                        concrete
+                    );
+                    let definition = SealDefinition::from(seal);
+                    assert_eq!(
+                        matches!(definition.verify(&witness, &msg), Ok(true)),
+                        concrete
+                    );
+                    assert_eq!(
+                        matches!(
+                            TxoVerifier.verify_seal_all(
+                                [&seal],
+                                &msg,
+                                &witness
+                            ),
+                            Ok(true)
+                        ),
+                        concrete
+                    );
+                    accepted += concrete as usize;
+                }
             }
         }
+        // Only the opret seal over the spent outpoint closed over the committed
+        // message is accepted: the witness transaction can't spend its own
+        // output defining the witness seal
+        assert_eq!(accepted, 1);
 
-        // 4. Verify DBC with the giving closing method
-        witness.proof.verify(msg, tx).map_err(VerifyError::from)
+        let witness = (tx, proofs[0].clone());
+        assert!(matches!(seals[0].verify(&witness, &msg(2)), Ok(false)));
+        assert!(matches!(
+            seals[2].verify(&witness, &msg(1)),
+            Err(VerifyError::WitnessNotSpendingSeal { .. })
+        ));
+        assert!(matches!(
+            SealProtocol::<ExplicitSeal>::get_seal_status(
+                &TxoVerifier,
+                &seals[0]
+            ),
+            Ok(SealStatus::Undefined)
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn verify_async() {
+        use crate::txout::AsyncAdapter;
+
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let tx = closing_tx(outpoint);
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        let resolver = MemResolver::from_iter([tx.clone()]);
+
+        let proto = TxoProtocol::new(resolver.clone());
+        let proto_async = TxoProtocolAsync::new(AsyncAdapter::from(resolver));
+        for msg in [msg(1), msg(2)] {
+            assert_eq!(
+                proto_async
+                    .verify_seal_async(&seal, &msg, &witness)
+                    .await
+                    .unwrap(),
+                proto.verify_seal(&seal, &msg, &witness).unwrap()
+            );
+        }
+        assert!(matches!(
+            proto_async.get_seal_status_async(&seal).await,
+            Ok(SealStatus::Closed)
+        ));
+
+        let other_tx =
+            closing_tx(OutPoint::new(Txid::hash(b"other transaction"), 0));
+        let other_witness = Witness {
+            txid: other_tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        let proto_async = TxoProtocolAsync::new(AsyncAdapter::from(
+            MemResolver::from_iter([other_tx]),
+        ));
+        assert!(matches!(
+            proto_async
+                .verify_seal_async(&seal, &msg(1), &other_witness)
+                .await,
+            Err(VerifyError::SealNotClosed(op)) if op == outpoint
+        ));
+
+        let proto_async =
+            TxoProtocolAsync::new(AsyncAdapter::from(MemResolver::new()));
+        assert!(matches!(
+            proto_async
+                .verify_seal_async(&seal, &msg(1), &witness)
+                .await,
+            Err(VerifyError::ResolverError(_))
+        ));
+
+        // Confirmation policy is checked with the resolver heights
+        let confirmed = VerifyOpts {
+            min_confirmations: 2,
+            ..VerifyOpts::default()
+        };
+        let mut resolver = MemResolver::from_iter([tx.clone()]);
+        let verify = |resolver: &MemResolver| {
+            let proto_async =
+                TxoProtocolAsync::new(AsyncAdapter::from(resolver.clone()));
+            let witness = witness.clone();
+            async move {
+                proto_async
+                    .verify_seal_all_async_with(
+                        [&seal],
+                        &msg(1),
+                        &witness,
+                        confirmed,
+                    )
+                    .await
+            }
+        };
+        assert!(matches!(
+            verify(&resolver).await,
+            Err(VerifyError::InsufficientConfirmations { have: 0, need: 2 })
+        ));
+        resolver.set_height(tx.txid(), 100);
+        assert!(matches!(
+            verify(&resolver).await,
+            Err(VerifyError::InsufficientConfirmations { have: 1, need: 2 })
+        ));
+        resolver.set_tip_height(101);
+        assert!(matches!(verify(&resolver).await, Ok(true)));
+        resolver.set_tip_height(99);
+        assert!(matches!(
+            verify(&resolver).await,
+            Err(VerifyError::InconsistentHeight { .. })
+        ));
+        assert!(matches!(
+            proto_async.get_seal_status_async(&seal).await,
+            Ok(SealStatus::Undefined)
+        ));
     }
 }
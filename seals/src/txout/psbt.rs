@@ -0,0 +1,299 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! PSBT proprietary keys declaring inputs which close transaction output
+//! seals, allowing other signers to see the seal closures.
+
+use std::fmt::{self, Display, Formatter};
+
+use bitcoin::OutPoint;
+use psbt::{Input, ProprietaryKey};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::txout::redact::redacted;
+use crate::txout::{CloseMethod, ExplicitSeal, TxoSeal};
+
+/// PSBT proprietary key prefix used for transaction output seals.
+pub const PSBT_SEAL_PREFIX: &[u8] = b"TXOSEAL";
+
+/// Proprietary key subtype for PSBT inputs containing seal definition closed
+/// by spending the input. The key data contain the seal closing method, so a
+/// single input may close several seals defined over the same outpoint with
+/// different methods.
+pub const PSBT_IN_CLOSED_SEAL: u8 = 0x00;
+
+/// Extension trait for static functions returning seal-related proprietary
+/// keys.
+pub trait ProprietaryKeySeal {
+    /// Constructs [`PSBT_IN_CLOSED_SEAL`] proprietary key for the seal with
+    /// the given closing `method`.
+    fn closed_seal(method: CloseMethod) -> ProprietaryKey {
+        ProprietaryKey {
+            prefix: PSBT_SEAL_PREFIX.to_vec(),
+            subtype: PSBT_IN_CLOSED_SEAL,
+            key: vec![method as u8],
+        }
+    }
+}
+
+impl ProprietaryKeySeal for ProprietaryKey {}
+
+/// Errors processing seal-related proprietary PSBT keys and their values.
+///
+/// Outpoints are displayed in [`Redacted`](crate::txout::Redacted) form; the
+/// alternate formatter (`{:#}`) displays them in full.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Error)]
+pub enum SealKeyError {
+    /// seal is not defined over the outpoint {0} spent by the PSBT input.
+    OutpointMismatch(OutPoint),
+
+    /// PSBT input contains invalid seal data under {0} closing method key.
+    InvalidKeyValue(CloseMethod),
+
+    /// PSBT input contains seal key with unknown closing method id {0}.
+    InvalidKey(u8),
+}
+
+impl Display for SealKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SealKeyError::OutpointMismatch(outpoint) => write!(
+                f,
+                "seal is not defined over the outpoint {} spent by the PSBT \
+                 input.",
+                redacted(outpoint, f.alternate())
+            ),
+            SealKeyError::InvalidKeyValue(method) => write!(
+                f,
+                "PSBT input contains invalid seal data under {} closing \
+                 method key.",
+                method
+            ),
+            SealKeyError::InvalidKey(id) => write!(
+                f,
+                "PSBT input contains seal key with unknown closing method id \
+                 {}.",
+                id
+            ),
+        }
+    }
+}
+
+/// Extension trait for PSBT inputs storing definitions of the seals closed by
+/// spending the input.
+pub trait InputSeals {
+    /// Stores strict-encoded `seal` under [`PSBT_IN_CLOSED_SEAL`] key,
+    /// replacing previously stored seal with the same closing method, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the seal is not defined over the outpoint spent by the input,
+    /// including the seals defined over witness transaction outputs.
+    fn set_closed_seal(
+        &mut self,
+        seal: &ExplicitSeal,
+    ) -> Result<(), SealKeyError>;
+
+    /// Returns definitions of all seals closed by spending the input, ordered
+    /// by their closing method.
+    ///
+    /// # Errors
+    ///
+    /// If the key data or the value of some of the [`PSBT_IN_CLOSED_SEAL`]
+    /// keys are invalid or the stored seal is not defined over the outpoint
+    /// spent by the input.
+    fn closed_seals(&self) -> Result<Vec<ExplicitSeal>, SealKeyError>;
+}
+
+impl InputSeals for Input {
+    fn set_closed_seal(
+        &mut self,
+        seal: &ExplicitSeal,
+    ) -> Result<(), SealKeyError> {
+        if seal.outpoint() != Some(self.previous_outpoint) {
+            return Err(SealKeyError::OutpointMismatch(self.previous_outpoint));
+        }
+        self.proprietary.insert(
+            ProprietaryKey::closed_seal(seal.method),
+            seal.strict_serialize()
+                .expect("in-memory strict encoding does not fail"),
+        );
+        Ok(())
+    }
+
+    fn closed_seals(&self) -> Result<Vec<ExplicitSeal>, SealKeyError> {
+        self.proprietary
+            .iter()
+            .filter(|(key, _)| {
+                key.prefix == PSBT_SEAL_PREFIX
+                    && key.subtype == PSBT_IN_CLOSED_SEAL
+            })
+            .map(|(key, value)| {
+                let method = match key.key[..] {
+                    [id] => CloseMethod::strict_deserialize([id])
+                        .map_err(|_| SealKeyError::InvalidKey(id))?,
+                    _ => {
+                        return Err(SealKeyError::InvalidKey(
+                            key.key.first().copied().unwrap_or_default(),
+                        ))
+                    }
+                };
+                let seal = ExplicitSeal::strict_deserialize(value)
+                    .ok()
+                    .filter(|seal| seal.method == method)
+                    .ok_or(SealKeyError::InvalidKeyValue(method))?;
+                if seal.outpoint() != Some(self.previous_outpoint) {
+                    return Err(SealKeyError::OutpointMismatch(
+                        self.previous_outpoint,
+                    ));
+                }
+                Ok(seal)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Transaction, TxIn, TxOut, Txid};
+    use psbt::Psbt;
+
+    use super::*;
+
+    fn psbt() -> Psbt {
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: (0..2)
+                .map(|vout| TxIn {
+                    previous_output: OutPoint::new(
+                        Txid::hash(b"seal transaction"),
+                        vout,
+                    ),
+                    ..Default::default()
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: bitcoin::Script::new_op_return(&[]),
+            }],
+        };
+        Psbt::with(tx, psbt::PsbtVersion::V0).unwrap()
+    }
+
+    #[test]
+    fn psbt_roundtrip() {
+        let mut psbt = psbt();
+        let outpoint = psbt.inputs[1].previous_outpoint;
+        let seal = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint);
+        psbt.inputs[1].set_closed_seal(&seal).unwrap();
+        // Setting the seal again does not duplicate it
+        psbt.inputs[1].set_closed_seal(&seal).unwrap();
+
+        let psbt = Psbt::from_str(&psbt.to_string()).unwrap();
+        assert_eq!(psbt.inputs[0].closed_seals(), Ok(vec![]));
+        assert_eq!(psbt.inputs[1].closed_seals(), Ok(vec![seal]));
+    }
+
+    #[test]
+    fn multiple_seals() {
+        let mut psbt = psbt();
+        let input = &mut psbt.inputs[0];
+        let outpoint = input.previous_outpoint;
+        let tapret = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint);
+        let opret = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        input.set_closed_seal(&tapret).unwrap();
+        input.set_closed_seal(&opret).unwrap();
+        assert_eq!(input.closed_seals(), Ok(vec![opret, tapret]));
+
+        let other = OutPoint::new(outpoint.txid, 1);
+        assert_eq!(
+            input.set_closed_seal(&ExplicitSeal::new(
+                CloseMethod::OpretFirst,
+                other
+            )),
+            Err(SealKeyError::OutpointMismatch(outpoint))
+        );
+        assert_eq!(
+            input.set_closed_seal(&ExplicitSeal::new_witness(
+                CloseMethod::OpretFirst,
+                0
+            )),
+            Err(SealKeyError::OutpointMismatch(outpoint))
+        );
+        assert_eq!(input.closed_seals(), Ok(vec![opret, tapret]));
+    }
+
+    #[test]
+    fn malformed_data() {
+        let mut psbt = psbt();
+        let input = &mut psbt.inputs[0];
+        let outpoint = input.previous_outpoint;
+        let key = ProprietaryKey::closed_seal(CloseMethod::TapretFirst);
+
+        input.proprietary.insert(key.clone(), vec![0xFF; 3]);
+        assert_eq!(
+            input.closed_seals(),
+            Err(SealKeyError::InvalidKeyValue(CloseMethod::TapretFirst))
+        );
+
+        // Seal stored under the key for another method
+        let opret = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        input
+            .proprietary
+            .insert(key.clone(), opret.strict_serialize().unwrap());
+        assert_eq!(
+            input.closed_seals(),
+            Err(SealKeyError::InvalidKeyValue(CloseMethod::TapretFirst))
+        );
+
+        // Seal over other outpoint
+        let other = ExplicitSeal::new(
+            CloseMethod::TapretFirst,
+            OutPoint::new(outpoint.txid, 1),
+        );
+        input
+            .proprietary
+            .insert(key, other.strict_serialize().unwrap());
+        assert_eq!(
+            input.closed_seals(),
+            Err(SealKeyError::OutpointMismatch(outpoint))
+        );
+        input.proprietary.clear();
+
+        let mut key = ProprietaryKey::closed_seal(CloseMethod::TapretFirst);
+        key.key = vec![0x07];
+        input.proprietary.insert(key.clone(), vec![]);
+        assert_eq!(input.closed_seals(), Err(SealKeyError::InvalidKey(0x07)));
+        input.proprietary.clear();
+
+        key.key = vec![];
+        input.proprietary.insert(key, vec![]);
+        assert_eq!(input.closed_seals(), Err(SealKeyError::InvalidKey(0)));
+
+        // Keys with other subtypes and prefixes are ignored
+        input.proprietary.clear();
+        let mut key = ProprietaryKey::closed_seal(CloseMethod::TapretFirst);
+        key.subtype = 0x01;
+        input.proprietary.insert(key.clone(), vec![]);
+        key.subtype = PSBT_IN_CLOSED_SEAL;
+        key.prefix = b"TAPRET".to_vec();
+        input.proprietary.insert(key, vec![]);
+        assert_eq!(input.closed_seals(), Ok(vec![]));
+    }
+}
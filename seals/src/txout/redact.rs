@@ -0,0 +1,227 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Redacted representation of the seal data for logs and error messages.
+
+use std::fmt::{self, Display, Formatter};
+
+use bitcoin::{OutPoint, Txid};
+
+use crate::txout::blind::RevealedSeal;
+use crate::txout::ExplicitSeal;
+
+/// Data which can be displayed in a redacted form, not revealing full txids
+/// and blinding factors.
+pub trait RedactedDisplay: Display {
+    /// Formats redacted representation of the data.
+    fn fmt_redacted(&self, f: &mut Formatter<'_>) -> fmt::Result;
+}
+
+/// Display wrapper printing seal data in a redacted form suitable for logs and
+/// bug reports: txids are truncated to their first and last four bytes and
+/// blinding factors are omitted, like in `tapret1st:646ca5c1…d1fae839:3`.
+///
+/// The redacted form can't be parsed back. Full representation of the wrapped
+/// data can be requested with the alternate formatter (`{:#}`).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Redacted<T>(pub T);
+
+impl<T: RedactedDisplay> Display for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.0)
+        } else {
+            self.0.fmt_redacted(f)
+        }
+    }
+}
+
+impl<T: RedactedDisplay + ?Sized> RedactedDisplay for &T {
+    #[inline]
+    fn fmt_redacted(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (*self).fmt_redacted(f)
+    }
+}
+
+/// Formats `data` in [`Redacted`] form, or in full if `full` is set. Used by
+/// the error types passing the alternate formatter flag to the seal data.
+pub(crate) fn redacted(data: impl RedactedDisplay, full: bool) -> String {
+    if full {
+        format!("{:#}", Redacted(data))
+    } else {
+        Redacted(data).to_string()
+    }
+}
+
+/// Formats txid as its first and last four bytes, or `~` for the witness
+/// transaction.
+fn fmt_txid(txid: Option<&Txid>, f: &mut Formatter<'_>) -> fmt::Result {
+    match txid.map(Txid::to_string) {
+        Some(txid) => write!(f, "{}…{}", &txid[..8], &txid[56..]),
+        None => f.write_str("~"),
+    }
+}
+
+impl RedactedDisplay for OutPoint {
+    fn fmt_redacted(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_txid(Some(&self.txid), f)?;
+        write!(f, ":{}", self.vout)
+    }
+}
+
+impl RedactedDisplay for RevealedSeal {
+    fn fmt_redacted(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.method)?;
+        fmt_txid(self.txid.as_ref(), f)?;
+        write!(f, ":{}", self.vout)
+    }
+}
+
+impl RedactedDisplay for ExplicitSeal {
+    fn fmt_redacted(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.method)?;
+        fmt_txid(self.txid.as_ref(), f)?;
+        write!(f, ":{}", self.vout)
+    }
+}
+
+impl RevealedSeal {
+    /// Returns display wrapper printing the seal in redacted form, see
+    /// [`Redacted`].
+    #[inline]
+    pub fn redacted(&self) -> Redacted<&RevealedSeal> { Redacted(self) }
+}
+
+impl ExplicitSeal {
+    /// Returns display wrapper printing the seal in redacted form, see
+    /// [`Redacted`].
+    #[inline]
+    pub fn short(&self) -> Redacted<&ExplicitSeal> { Redacted(self) }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::txout::{CloseError, CloseMethod, SealConflict, VerifyError};
+
+    const TXID: &str =
+        "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+
+    #[test]
+    fn snapshots() {
+        let txid = Txid::from_str(TXID).unwrap();
+        let revealed = RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(txid),
+            vout: 3u32.into(),
+            blinding: 0x31bbed7e7b2d,
+        };
+        assert_eq!(
+            revealed.redacted().to_string(),
+            "tapret1st:646ca5c1…d1fae839:3"
+        );
+        assert_eq!(format!("{:#}", revealed.redacted()), revealed.to_string());
+        assert!(format!("{:#}", revealed.redacted()).contains("54683213134637"));
+
+        let witness = RevealedSeal {
+            txid: None,
+            ..revealed
+        };
+        assert_eq!(witness.redacted().to_string(), "tapret1st:~:3");
+
+        let explicit =
+            ExplicitSeal::new(CloseMethod::OpretFirst, OutPoint::new(txid, 21));
+        assert_eq!(
+            explicit.short().to_string(),
+            "opret1st:646ca5c1…d1fae839:21"
+        );
+        assert_eq!(format!("{:#}", explicit.short()), explicit.to_string());
+        assert_eq!(
+            ExplicitSeal::new_witness(CloseMethod::OpretFirst, 0)
+                .short()
+                .to_string(),
+            "opret1st:~:0"
+        );
+
+        let outpoint = OutPoint::new(txid, 2);
+        assert_eq!(Redacted(outpoint).to_string(), "646ca5c1…d1fae839:2");
+        assert_eq!(format!("{:#}", Redacted(&outpoint)), format!("{}:2", TXID));
+    }
+
+    #[test]
+    fn errors() {
+        let txid = Txid::from_str(TXID).unwrap();
+        let outpoint = OutPoint::new(txid, 2);
+
+        let err = VerifyError::SealNotClosed(outpoint);
+        assert_eq!(
+            err.to_string(),
+            "seal 646ca5c1…d1fae839:2 is not closed: its output is not spent \
+             by any transaction."
+        );
+        assert!(format!("{:#}", err).contains(TXID));
+
+        let err = VerifyError::WitnessNotSpendingSeal {
+            witness: txid,
+            seal: outpoint,
+        };
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "the provided witness transaction {} does not spend seal \
+                 646ca5c1…d1fae839:2.",
+                TXID
+            )
+        );
+
+        let err = CloseError::NotClosingSeal(txid, outpoint);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "the transaction {} does not spend seal outpoint \
+                 646ca5c1…d1fae839:2.",
+                TXID
+            )
+        );
+        assert_eq!(
+            format!("{:#}", err),
+            format!(
+                "the transaction {} does not spend seal outpoint {}.",
+                TXID, outpoint
+            )
+        );
+
+        let existing = RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            txid: Some(txid),
+            vout: 2u32.into(),
+            blinding: 0x31bbed7e7b2d,
+        };
+        let new = RevealedSeal {
+            blinding: 0x1d2c3b4a,
+            ..existing
+        };
+        let err = SealConflict { existing, new };
+        assert_eq!(
+            err.to_string(),
+            "seal opret1st:646ca5c1…d1fae839:2 conflicts with seal \
+             opret1st:646ca5c1…d1fae839:2 defined over the same transaction \
+             output"
+        );
+        assert!(format!("{:#}", err).contains("54683213134637"));
+    }
+}
@@ -0,0 +1,375 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Structured reports of the seal closing verification, intended for the
+//! audit logs.
+
+use bitcoin::{Transaction, Txid};
+use commit_verify::lnpbp4;
+use dbc::Proof;
+
+use crate::txout::{
+    verify_seal_closure, CloseMethod, ExplicitSeal, TxoSeal, VerifyError,
+};
+
+/// Outcome of a single stage of the seal closing verification, in the order
+/// the stages are performed by [`verify_seal_with_report`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum CheckOutcome {
+    /// Witness transaction passed sanity checks of
+    /// [`check_witness_tx`](crate::txout::check_witness_tx).
+    WitnessTx(bool),
+
+    /// Commitment proof uses the seal closing method.
+    MethodMatch(bool),
+
+    /// Witness transaction spends the seal outpoint.
+    OutpointSpent(bool),
+
+    /// Witness transaction contains output of the type hosting commitments of
+    /// the seal closing method (the first OP_RETURN or taproot output). The
+    /// output is not guaranteed to contain the commitment unless
+    /// [`CheckOutcome::ProofValid`] stage passes.
+    HostOutputPresent(bool),
+
+    /// Commitment proof is valid and the commitment matches the message.
+    ProofValid(bool),
+}
+
+impl CheckOutcome {
+    /// Detects whether the verification stage has passed.
+    pub fn passed(self) -> bool {
+        match self {
+            CheckOutcome::WitnessTx(passed)
+            | CheckOutcome::MethodMatch(passed)
+            | CheckOutcome::OutpointSpent(passed)
+            | CheckOutcome::HostOutputPresent(passed)
+            | CheckOutcome::ProofValid(passed) => passed,
+        }
+    }
+}
+
+/// Report of the seal closing verification listing all performed checks.
+///
+/// The verification stops at the first failed stage, so the failed check is
+/// always the last one in [`VerifyReport::checks`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct VerifyReport {
+    /// Verified seal.
+    pub seal: ExplicitSeal,
+
+    /// Id of the witness transaction.
+    pub witness_txid: Txid,
+
+    /// Closing method of the seal.
+    pub method: CloseMethod,
+
+    /// Number of the witness transaction output containing the commitment;
+    /// set only if the commitment proof is valid.
+    pub commitment_found_at: Option<u32>,
+
+    /// Outcomes of the performed verification stages.
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl VerifyReport {
+    /// Detects whether all verification stages have passed.
+    pub fn passed(&self) -> bool {
+        !self.checks.is_empty()
+            && self.checks.iter().copied().all(CheckOutcome::passed)
+    }
+}
+
+/// Verifies the seal closing with
+/// [`verify_seal_closure`](crate::txout::verify_seal_closure), returning
+/// report on the performed checks alongside the verification result. The
+/// report is constructed from the verification result, so the failed stage
+/// always corresponds to the returned error.
+pub fn verify_seal_with_report(
+    seal: &impl TxoSeal,
+    msg: &lnpbp4::CommitmentHash,
+    proof: &Proof,
+    witness_tx: &Transaction,
+) -> (VerifyReport, Result<(), VerifyError>) {
+    const STAGES: [fn(bool) -> CheckOutcome; 5] = [
+        CheckOutcome::WitnessTx,
+        CheckOutcome::MethodMatch,
+        CheckOutcome::OutpointSpent,
+        CheckOutcome::HostOutputPresent,
+        CheckOutcome::ProofValid,
+    ];
+
+    let method = seal.method();
+    let res = verify_seal_closure(seal, msg, proof, witness_tx);
+    let host = witness_tx
+        .output
+        .iter()
+        .position(|txout| match method {
+            CloseMethod::OpretFirst => txout.script_pubkey.is_op_return(),
+            CloseMethod::TapretFirst => txout.script_pubkey.is_v1_p2tr(),
+        })
+        .map(|vout| vout as u32);
+
+    let failed = match res {
+        Ok(()) => None,
+        Err(VerifyError::WitnessCheck(_)) => Some(0),
+        Err(VerifyError::MethodMismatch { .. })
+        | Err(VerifyError::UnsupportedProof) => Some(1),
+        Err(VerifyError::WitnessNotSpendingSeal { .. }) => Some(2),
+        // Remaining errors are produced by the commitment verification
+        Err(_) if host.is_none() => Some(3),
+        Err(_) => Some(4),
+    };
+    let mut checks = STAGES[..failed.unwrap_or(STAGES.len())]
+        .iter()
+        .map(|stage| stage(true))
+        .collect::<Vec<_>>();
+    if let Some(stage) = failed {
+        checks.push(STAGES[stage](false));
+    }
+
+    let report = VerifyReport {
+        seal: match seal.outpoint() {
+            Some(outpoint) => ExplicitSeal::new(method, outpoint),
+            None => ExplicitSeal::new_witness(method, seal.vout()),
+        },
+        witness_txid: witness_tx.txid(),
+        method,
+        commitment_found_at: host.filter(|_| res.is_ok()),
+        checks,
+    };
+    (report, res)
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::Wrapper;
+    use bitcoin::hashes::{sha256t, Hash};
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use dbc::opret::OpretProof;
+
+    use super::*;
+    use crate::fixtures;
+    use crate::txout::verify_seal_closure;
+
+    #[test]
+    fn report_passed() {
+        let seal = fixtures::seal_explicit(1);
+        let tx = fixtures::commit_tx(1);
+        let proof = fixtures::witness(1).proof;
+        let (report, res) = verify_seal_with_report(
+            &seal,
+            &fixtures::commitment(1),
+            &proof,
+            &tx,
+        );
+        assert!(res.is_ok());
+        assert!(report.passed());
+        assert_eq!(report, VerifyReport {
+            seal,
+            witness_txid: tx.txid(),
+            method: CloseMethod::TapretFirst,
+            commitment_found_at: Some(0),
+            checks: vec![
+                CheckOutcome::WitnessTx(true),
+                CheckOutcome::MethodMatch(true),
+                CheckOutcome::OutpointSpent(true),
+                CheckOutcome::HostOutputPresent(true),
+                CheckOutcome::ProofValid(true),
+            ],
+        });
+    }
+
+    #[test]
+    fn report_not_spent() {
+        let seal = fixtures::seal_explicit(2);
+        let tx = fixtures::commit_tx(1);
+        let proof = fixtures::witness(1).proof;
+        let msg = fixtures::commitment(1);
+        let (report, res) = verify_seal_with_report(&seal, &msg, &proof, &tx);
+        assert!(matches!(res, Err(VerifyError::WitnessNotSpendingSeal {
+            witness,
+            seal: outpoint
+        }) if witness == tx.txid() && outpoint == fixtures::seal_outpoint(2)));
+        assert_eq!(
+            format!("{:?}", verify_seal_closure(&seal, &msg, &proof, &tx)),
+            format!("{:?}", res)
+        );
+        assert!(!report.passed());
+        assert_eq!(report.seal, seal);
+        assert_eq!(report.commitment_found_at, None);
+        assert_eq!(report.checks, vec![
+            CheckOutcome::WitnessTx(true),
+            CheckOutcome::MethodMatch(true),
+            CheckOutcome::OutpointSpent(false),
+        ]);
+    }
+
+    #[test]
+    fn report_invalid_commitment() {
+        let seal = fixtures::seal_explicit(1);
+        let tx = fixtures::commit_tx(1);
+        let proof = fixtures::witness(1).proof;
+        let msg = fixtures::commitment(2);
+        let (report, res) = verify_seal_with_report(&seal, &msg, &proof, &tx);
+        assert!(res.is_err());
+        assert_eq!(
+            format!("{:?}", verify_seal_closure(&seal, &msg, &proof, &tx)),
+            format!("{:?}", res)
+        );
+        assert!(!report.passed());
+        assert_eq!(report.commitment_found_at, None);
+        assert_eq!(report.checks, vec![
+            CheckOutcome::WitnessTx(true),
+            CheckOutcome::MethodMatch(true),
+            CheckOutcome::OutpointSpent(true),
+            CheckOutcome::HostOutputPresent(true),
+            CheckOutcome::ProofValid(false),
+        ]);
+
+        // Witness transaction without taproot outputs
+        let mut opret_tx = tx;
+        opret_tx.output[0].script_pubkey =
+            bitcoin::Script::new_op_return(&[0u8; 32]);
+        let (report, res) =
+            verify_seal_with_report(&seal, &msg, &proof, &opret_tx);
+        assert!(res.is_err());
+        assert_eq!(report.commitment_found_at, None);
+        assert_eq!(
+            report.checks.last(),
+            Some(&CheckOutcome::HostOutputPresent(false))
+        );
+
+        // Witness seals are reported with the vout only
+        let witness_seal =
+            ExplicitSeal::new_witness(CloseMethod::OpretFirst, 0);
+        let coinbase = Transaction {
+            input: vec![bitcoin::TxIn {
+                previous_output: OutPoint::null(),
+                ..Default::default()
+            }],
+            ..opret_tx
+        };
+        let (report, res) =
+            verify_seal_with_report(&witness_seal, &msg, &proof, &coinbase);
+        assert!(matches!(res, Err(VerifyError::WitnessCheck(_))));
+        assert_eq!(report.seal, witness_seal);
+        assert_eq!(report.method, CloseMethod::OpretFirst);
+        assert_eq!(report.witness_txid, coinbase.txid());
+        assert_eq!(report.checks, vec![CheckOutcome::WitnessTx(false)]);
+    }
+
+    #[test]
+    fn report_preceding_host() {
+        let msg = lnpbp4::CommitmentHash::from_inner(
+            sha256t::Hash::from_inner([7; 32]),
+        );
+        let outpoint = OutPoint::new(Txid::hash(b"seal"), 1);
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint);
+        let proof = Proof::OpretFirst(OpretProof);
+        let output = |script_pubkey| TxOut {
+            value: 0,
+            script_pubkey,
+        };
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                ..Default::default()
+            }],
+            output: vec![
+                output(Script::new()),
+                output(Script::new_op_return(&msg[..])),
+            ],
+        };
+
+        let (report, res) = verify_seal_with_report(&seal, &msg, &proof, &tx);
+        assert!(res.is_ok());
+        assert_eq!(report.commitment_found_at, Some(1));
+
+        // Unrelated OP_RETURN output preceding the one with the commitment is
+        // used as the commitment host, so the verification fails
+        tx.output
+            .insert(0, output(Script::new_op_return(&[0xA5; 32])));
+        let (report, res) = verify_seal_with_report(&seal, &msg, &proof, &tx);
+        assert!(matches!(
+            res,
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+        assert_eq!(
+            format!("{:?}", verify_seal_closure(&seal, &msg, &proof, &tx)),
+            format!("{:?}", res)
+        );
+        assert!(!report.passed());
+        assert_eq!(report.commitment_found_at, None);
+        assert_eq!(report.checks, vec![
+            CheckOutcome::WitnessTx(true),
+            CheckOutcome::MethodMatch(true),
+            CheckOutcome::OutpointSpent(true),
+            CheckOutcome::HostOutputPresent(true),
+            CheckOutcome::ProofValid(false),
+        ]);
+
+        // Unrelated taproot output preceding the tapret commitment
+        let tapret_seal = fixtures::seal_explicit(1);
+        let mut tapret_tx = fixtures::commit_tx(1);
+        let host = tapret_tx.output[0].clone();
+        let mut unrelated = host.clone();
+        unrelated.script_pubkey =
+            fixtures::commit_tx(2).output[0].script_pubkey.clone();
+        tapret_tx.output = vec![unrelated, host];
+        let (report, res) = verify_seal_with_report(
+            &tapret_seal,
+            &fixtures::commitment(1),
+            &fixtures::witness(1).proof,
+            &tapret_tx,
+        );
+        assert!(res.is_err());
+        assert_eq!(report.commitment_found_at, None);
+        assert_eq!(
+            report.checks.last(),
+            Some(&CheckOutcome::ProofValid(false))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn report_serde() {
+        let (report, _) = verify_seal_with_report(
+            &fixtures::seal_explicit(1),
+            &fixtures::commitment(1),
+            &fixtures::witness(1).proof,
+            &fixtures::commit_tx(1),
+        );
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""commitment_found_at":0"#));
+        assert!(json.contains(r#"{"OutpointSpent":true}"#));
+        assert_eq!(
+            serde_json::from_str::<VerifyReport>(&json).unwrap(),
+            report
+        );
+    }
+}
@@ -0,0 +1,364 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Resolvers providing access to the witness transactions.
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use bitcoin::{OutPoint, Transaction, Txid};
+
+/// Access to the transaction publication medium (blockchain, mempool or an
+/// off-chain channel) required for the seal verification.
+pub trait TxResolve {
+    /// Error type returned by the resolver.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Retrieves transaction with the given id, returning error if the
+    /// transaction is not known to the resolver.
+    fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error>;
+
+    /// Finds transaction spending the given outpoint, returning `Ok(None)` if
+    /// no such transaction is known to the resolver.
+    fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error>;
+
+    /// Returns height of the block containing transaction with the given id,
+    /// or `Ok(None)` if the transaction is not mined yet (for instance, it is
+    /// in the mempool) or the resolver does not track blocks.
+    ///
+    /// Default implementation always returns `Ok(None)`.
+    #[inline]
+    fn tx_height(&self, _txid: Txid) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns height of the best block known to the resolver, or `Ok(None)`
+    /// if the resolver does not track blocks.
+    ///
+    /// Default implementation always returns `Ok(None)`.
+    #[inline]
+    fn tip_height(&self) -> Result<Option<u32>, Self::Error> { Ok(None) }
+}
+
+/// Asynchronous version of [`TxResolve`].
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncTxResolve: Send + Sync {
+    /// Error type returned by the resolver.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Retrieves transaction with the given id, returning error if the
+    /// transaction is not known to the resolver.
+    async fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error>;
+
+    /// Finds transaction spending the given outpoint, returning `Ok(None)` if
+    /// no such transaction is known to the resolver.
+    async fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error>;
+
+    /// Returns height of the block containing transaction with the given id,
+    /// or `Ok(None)` if the transaction is not mined yet (for instance, it is
+    /// in the mempool) or the resolver does not track blocks.
+    ///
+    /// Default implementation always returns `Ok(None)`.
+    async fn tx_height(&self, _txid: Txid) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns height of the best block known to the resolver, or `Ok(None)`
+    /// if the resolver does not track blocks.
+    ///
+    /// Default implementation always returns `Ok(None)`.
+    async fn tip_height(&self) -> Result<Option<u32>, Self::Error> { Ok(None) }
+}
+
+impl<T> TxResolve for &T
+where
+    T: TxResolve,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error> {
+        (*self).resolve_tx(txid)
+    }
+
+    #[inline]
+    fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        (*self).spending_tx(outpoint)
+    }
+
+    #[inline]
+    fn tx_height(&self, txid: Txid) -> Result<Option<u32>, Self::Error> {
+        (*self).tx_height(txid)
+    }
+
+    #[inline]
+    fn tip_height(&self) -> Result<Option<u32>, Self::Error> {
+        (*self).tip_height()
+    }
+}
+
+/// Errors of the in-memory transaction resolver.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error
+)]
+#[display(doc_comments)]
+pub enum MemResolverError {
+    /// transaction {0} is not known to the resolver.
+    UnknownTx(Txid),
+
+    /// output {0} is spent by multiple known transactions, including {1} and
+    /// {2}.
+    DoubleSpend(OutPoint, Txid, Txid),
+}
+
+/// In-memory transaction resolver, useful for tests and for the verification
+/// of the data containing all of the witness transactions (like consignments).
+///
+/// Transactions are considered unmined unless their block height is provided
+/// with [`MemResolver::set_height`].
+#[derive(Wrapper, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MemResolver {
+    #[wrap]
+    txs: HashMap<Txid, Transaction>,
+    heights: HashMap<Txid, u32>,
+    tip_height: Option<u32>,
+}
+
+impl From<HashMap<Txid, Transaction>> for MemResolver {
+    #[inline]
+    fn from(txs: HashMap<Txid, Transaction>) -> Self {
+        MemResolver { txs, ..default!() }
+    }
+}
+
+impl MemResolver {
+    /// Constructs empty resolver.
+    #[inline]
+    pub fn new() -> MemResolver { MemResolver::default() }
+
+    /// Adds transaction to the resolver, returning previously known
+    /// transaction with the same id, if any.
+    #[inline]
+    pub fn insert(&mut self, tx: Transaction) -> Option<Transaction> {
+        self.txs.insert(tx.txid(), tx)
+    }
+
+    /// Sets height of the block containing transaction with the given id,
+    /// raising the best known block height to it if required.
+    pub fn set_height(&mut self, txid: Txid, height: u32) {
+        self.heights.insert(txid, height);
+        self.tip_height = self.tip_height.max(Some(height));
+    }
+
+    /// Sets height of the best known block.
+    #[inline]
+    pub fn set_tip_height(&mut self, height: u32) {
+        self.tip_height = Some(height);
+    }
+}
+
+impl FromIterator<Transaction> for MemResolver {
+    fn from_iter<T: IntoIterator<Item = Transaction>>(iter: T) -> Self {
+        let mut resolver = MemResolver::new();
+        resolver.extend(iter);
+        resolver
+    }
+}
+
+impl Extend<Transaction> for MemResolver {
+    fn extend<T: IntoIterator<Item = Transaction>>(&mut self, iter: T) {
+        self.txs.extend(iter.into_iter().map(|tx| (tx.txid(), tx)))
+    }
+}
+
+impl TxResolve for MemResolver {
+    type Error = MemResolverError;
+
+    fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error> {
+        self.txs
+            .get(&txid)
+            .cloned()
+            .ok_or(MemResolverError::UnknownTx(txid))
+    }
+
+    /// Returns the known transaction spending the `outpoint`. Since the
+    /// resolver does not order transactions, the outpoint spent by multiple
+    /// transactions results in [`MemResolverError::DoubleSpend`] error
+    /// reporting the two smallest of their ids.
+    fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let mut spenders = self
+            .txs
+            .iter()
+            .filter(|(_, tx)| {
+                tx.input.iter().any(|txin| txin.previous_output == outpoint)
+            })
+            .collect::<Vec<_>>();
+        spenders.sort_unstable_by_key(|(txid, _)| *txid);
+        match spenders[..] {
+            [] => Ok(None),
+            [(_, tx)] => Ok(Some(tx.clone())),
+            [(first, _), (second, _), ..] => {
+                Err(MemResolverError::DoubleSpend(outpoint, *first, *second))
+            }
+        }
+    }
+
+    fn tx_height(&self, txid: Txid) -> Result<Option<u32>, Self::Error> {
+        if !self.txs.contains_key(&txid) {
+            return Err(MemResolverError::UnknownTx(txid));
+        }
+        Ok(self.heights.get(&txid).copied())
+    }
+
+    #[inline]
+    fn tip_height(&self) -> Result<Option<u32>, Self::Error> {
+        Ok(self.tip_height)
+    }
+}
+
+/// Adapter exposing synchronous resolver via [`AsyncTxResolve`] API. Should be
+/// used only with the resolvers which do not block for a long time, like
+/// [`MemResolver`].
+#[cfg(feature = "async")]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AsyncAdapter<R: TxResolve>(R);
+
+#[cfg(feature = "async")]
+impl<R> AsyncAdapter<R>
+where
+    R: TxResolve,
+{
+    /// Wraps synchronous resolver into the adapter.
+    #[inline]
+    pub fn new(resolver: R) -> Self { AsyncAdapter(resolver) }
+
+    /// Returns the wrapped synchronous resolver.
+    #[inline]
+    pub fn into_inner(self) -> R { self.0 }
+}
+
+#[cfg(feature = "async")]
+impl<R> From<R> for AsyncAdapter<R>
+where
+    R: TxResolve,
+{
+    #[inline]
+    fn from(resolver: R) -> Self { AsyncAdapter(resolver) }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<R> AsyncTxResolve for AsyncAdapter<R>
+where
+    R: TxResolve + Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn resolve_tx(&self, txid: Txid) -> Result<Transaction, Self::Error> {
+        self.0.resolve_tx(txid)
+    }
+
+    async fn spending_tx(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        self.0.spending_tx(outpoint)
+    }
+
+    async fn tx_height(&self, txid: Txid) -> Result<Option<u32>, Self::Error> {
+        self.0.tx_height(txid)
+    }
+
+    async fn tip_height(&self) -> Result<Option<u32>, Self::Error> {
+        self.0.tip_height()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::TxIn;
+
+    use super::*;
+
+    fn tx(prevout: OutPoint) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: prevout,
+                ..default!()
+            }],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn mem_resolver() {
+        let prevout1 = OutPoint::new(Txid::hash(b"first"), 0);
+        let prevout2 = OutPoint::new(Txid::hash(b"second"), 3);
+        let tx1 = tx(prevout1);
+        let tx2 = tx(prevout2);
+        let resolver = MemResolver::from_iter([tx1.clone(), tx2.clone()]);
+
+        assert_eq!(resolver.resolve_tx(tx1.txid()), Ok(tx1.clone()));
+        assert_eq!(resolver.resolve_tx(tx2.txid()), Ok(tx2.clone()));
+        assert_eq!(
+            resolver.resolve_tx(prevout1.txid),
+            Err(MemResolverError::UnknownTx(prevout1.txid))
+        );
+
+        assert_eq!(resolver.spending_tx(prevout1), Ok(Some(tx1)));
+        assert_eq!(resolver.spending_tx(prevout2), Ok(Some(tx2)));
+        assert_eq!(
+            resolver.spending_tx(OutPoint::new(prevout2.txid, 2)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn mem_resolver_double_spend() {
+        let prevout = OutPoint::new(Txid::hash(b"first"), 0);
+        let mut txs = (0..4i32)
+            .map(|version| Transaction {
+                version,
+                ..tx(prevout)
+            })
+            .collect::<Vec<_>>();
+        txs.push(tx(OutPoint::new(Txid::hash(b"second"), 0)));
+        let mut txids =
+            txs[..4].iter().map(Transaction::txid).collect::<Vec<_>>();
+        txids.sort_unstable();
+
+        let resolver = MemResolver::from_iter(txs);
+        assert_eq!(
+            resolver.spending_tx(prevout),
+            Err(MemResolverError::DoubleSpend(prevout, txids[0], txids[1]))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! JSON schema of the seal types, matching their serde representation in
+//! human-readable formats.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{
+    InstanceType, Metadata, RootSchema, Schema, SchemaObject, StringValidation,
+    SubschemaValidation,
+};
+use schemars::JsonSchema;
+
+use crate::txout::blind::{ConcealedSeal, RevealedSeal};
+use crate::txout::{
+    CloseMethod, CloseMethodSet, ExplicitSeal, SealDefinition, SealId, SealSet,
+    Vout, WitnessVoutSeal,
+};
+
+/// Schema of the transaction id, serialized as a hexadecimal string.
+pub(crate) enum TxidHex {}
+
+/// Schema of the seal blinding factor, serialized as a decimal string.
+pub(crate) enum BlindingString {}
+
+fn string_schema(description: &str, pattern: &str) -> SchemaObject {
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_owned()),
+            ..default!()
+        })),
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_owned()),
+            ..default!()
+        })),
+        ..default!()
+    }
+}
+
+impl JsonSchema for TxidHex {
+    fn schema_name() -> String { s!("Txid") }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "Transaction id as 64-character hexadecimal string.",
+            "^[0-9a-fA-F]{64}$",
+        )
+        .into()
+    }
+}
+
+impl JsonSchema for BlindingString {
+    fn schema_name() -> String { s!("Blinding") }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "64-bit blinding factor as a decimal string; numbers are also \
+             accepted.",
+            "^[0-9]{1,20}$",
+        )
+        .into()
+    }
+}
+
+impl JsonSchema for ExplicitSeal {
+    fn schema_name() -> String { s!("ExplicitSeal") }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "Seal in `method:txid:vout` form; seals over the witness \
+             transaction outputs use `~` in place of the txid.",
+            "^(opret1st|tapret1st):(~|[0-9a-fA-F]{64}):[0-9]+$",
+        )
+        .into()
+    }
+}
+
+impl JsonSchema for SealDefinition {
+    fn schema_name() -> String { s!("SealDefinition") }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        gen.subschema_for::<ExplicitSeal>()
+    }
+}
+
+impl JsonSchema for ConcealedSeal {
+    fn schema_name() -> String { s!("ConcealedSeal") }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(s!("Concealed seal as 32-byte hexadecimal \
+                                      string; Bech32m strings are also \
+                                      accepted.")),
+                ..default!()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    string_schema(
+                        "Hexadecimal representation.",
+                        "^[0-9a-fA-F]{64}$",
+                    )
+                    .into(),
+                    string_schema(
+                        "Bech32m representation.",
+                        "^txob1[02-9ac-hj-np-z]{58}$",
+                    )
+                    .into(),
+                ]),
+                ..default!()
+            })),
+            ..default!()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for SealId {
+    fn schema_name() -> String { s!("SealId") }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "Seal id as Bech32m string; used only for indexing and can't be \
+             converted back into the concealed seal.",
+            "^sealid1[02-9ac-hj-np-z]{38}$",
+        )
+        .into()
+    }
+}
+
+impl JsonSchema for CloseMethodSet {
+    fn schema_name() -> String { s!("CloseMethodSet") }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = u8::json_schema(gen).into_object();
+        schema.metadata().description =
+            Some(s!("Bit mask of close methods, where each method is \
+                     represented by the bit number matching its numeric id."));
+        schema.into()
+    }
+}
+
+impl JsonSchema for SealSet {
+    fn schema_name() -> String { s!("SealSet") }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = Vec::<RevealedSeal>::json_schema(gen).into_object();
+        schema.metadata().description =
+            Some(s!("Revealed seals ordered by the outpoint they are \
+                     defined over, with no two seals sharing the same \
+                     outpoint."));
+        schema.array().unique_items = Some(true);
+        schema.into()
+    }
+}
+
+/// Returns JSON schema (draft 7) defining all of the seal types under
+/// `definitions`.
+pub fn seals_json_schema() -> RootSchema {
+    let mut gen = SchemaGenerator::new(SchemaSettings::draft07());
+    gen.subschema_for::<CloseMethod>();
+    gen.subschema_for::<CloseMethodSet>();
+    gen.subschema_for::<Vout>();
+    gen.subschema_for::<ExplicitSeal>();
+    gen.subschema_for::<WitnessVoutSeal>();
+    gen.subschema_for::<SealDefinition>();
+    gen.subschema_for::<RevealedSeal>();
+    gen.subschema_for::<ConcealedSeal>();
+    gen.subschema_for::<SealId>();
+    gen.subschema_for::<SealSet>();
+    RootSchema {
+        meta_schema: gen.settings().meta_schema.clone(),
+        schema: SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                title: Some(s!("BP single-use-seals")),
+                ..default!()
+            })),
+            ..default!()
+        },
+        definitions: gen.take_definitions(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schema_snapshot() {
+        let schema =
+            serde_json::to_string_pretty(&seals_json_schema()).unwrap();
+        assert_eq!(
+            schema,
+            include_str!("../../tests/data/seals.schema.json").trim_end()
+        );
+    }
+}
@@ -13,11 +13,67 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::io::{Read, Write};
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
 use std::str::FromStr;
 
-use bitcoin::{OutPoint, Txid};
+use bitcoin::{OutPoint, Script, Txid};
+use strict_encoding::{StrictDecode, StrictEncode};
 
-use super::MethodParseError;
+use super::{
+    MethodParseError, SealDecodeError, UnknownMethodBits, UnknownMethodId,
+};
+use crate::limits::MAX_VOUT;
+
+/// Transaction output number.
+///
+/// Used by the transaction output-based seals instead of raw `u32` in order to
+/// prevent accidental mixing with transaction input indexes.
+///
+/// Any `u32` value is a valid output number: the
+/// [`MAX_VOUT`](crate::limits::MAX_VOUT) sanity limit applies only to parsing
+/// of the seal strings and to the checked decoders, so all other constructors,
+/// serde and strict encodings round-trip the full range of the values.
+#[derive(
+    Wrapper, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug,
+    Display, From
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[display(inner)]
+#[wrapper(FromStr, LowerHex, UpperHex, Octal)]
+pub struct Vout(u32);
+
+impl strict_encoding::Strategy for Vout {
+    type Strategy = strict_encoding::strategies::Wrapped;
+}
+
+impl Vout {
+    /// Checks decoded output number against the
+    /// [`MAX_VOUT`](crate::limits::MAX_VOUT) limit.
+    pub(super) fn check_decoded(self) -> Result<Vout, SealDecodeError> {
+        if self.0 > MAX_VOUT {
+            return Err(SealDecodeError::VoutOutOfRange(self.0));
+        }
+        Ok(self)
+    }
+
+    /// Returns `u32` representation of the output number.
+    #[inline]
+    pub fn into_u32(self) -> u32 { self.0 }
+
+    /// Returns `usize` representation of the output number, which can be used
+    /// for indexing transaction outputs.
+    #[inline]
+    pub fn into_usize(self) -> usize { self.0 as usize }
+}
 
 /// Methods common for all transaction-output based seal types.
 pub trait TxoSeal {
@@ -28,7 +84,7 @@ pub trait TxoSeal {
     fn txid(&self) -> Option<Txid>;
 
     /// Returns transaction output number containing the defined seal.
-    fn vout(&self) -> usize;
+    fn vout(&self) -> Vout;
 
     /// Returns [`OutPoint`] defining the seal, if txid is known.
     fn outpoint(&self) -> Option<OutPoint>;
@@ -40,8 +96,118 @@ pub trait TxoSeal {
     /// Returns [`OutPoint`] defining the seal, if txid is known, or constructs
     /// one using the provided `default_txid`.
     fn outpoint_or(&self, default_txid: Txid) -> OutPoint;
+
+    /// Returns string representation of the seal outpoint in the format used
+    /// by rust-bitcoin [`OutPoint`] `Display` implementation (`txid:vout`),
+    /// or `None` for the seals defined over witness transaction outputs.
+    ///
+    /// Unlike the seal string representation, it does not contain the
+    /// closing method, which must be provided separately to get the seal
+    /// back with [`crate::txout::ExplicitSeal::from_outpoint_str`].
+    #[inline]
+    fn to_outpoint_string(&self) -> Option<String> {
+        self.outpoint().as_ref().map(OutPoint::to_string)
+    }
+
+    /// Checks whether the seal is defined over the `outpoint`, using
+    /// `witness_txid` for the seals which txid is not known (i.e. defined over
+    /// the witness transaction outputs).
+    #[inline]
+    fn matches(&self, outpoint: OutPoint, witness_txid: Txid) -> bool {
+        self.outpoint_or(witness_txid) == outpoint
+    }
+
+    /// Returns index of the first outpoint from `outpoints` which the seal
+    /// [`TxoSeal::matches`], if any.
+    fn matches_any(
+        &self,
+        outpoints: &[OutPoint],
+        witness_txid: Txid,
+    ) -> Option<usize> {
+        let seal_outpoint = self.outpoint_or(witness_txid);
+        outpoints
+            .iter()
+            .position(|outpoint| *outpoint == seal_outpoint)
+    }
+}
+
+impl<T> TxoSeal for &T
+where
+    T: TxoSeal,
+{
+    #[inline]
+    fn method(&self) -> CloseMethod { (*self).method() }
+
+    #[inline]
+    fn txid(&self) -> Option<Txid> { (*self).txid() }
+
+    #[inline]
+    fn vout(&self) -> Vout { (*self).vout() }
+
+    #[inline]
+    fn outpoint(&self) -> Option<OutPoint> { (*self).outpoint() }
+
+    #[inline]
+    fn txid_or(&self, default_txid: Txid) -> Txid {
+        (*self).txid_or(default_txid)
+    }
+
+    #[inline]
+    fn outpoint_or(&self, default_txid: Txid) -> OutPoint {
+        (*self).outpoint_or(default_txid)
+    }
+}
+
+/// Object-safe subset of [`TxoSeal`] methods, allowing to keep seals of
+/// different types in a single collection, like `Vec<Box<dyn TxoSealDyn>>`.
+///
+/// The trait is implemented for all [`TxoSeal`] types. Its accessors are
+/// prefixed with `seal_`, so the method calls on the concrete seal types are
+/// not ambiguous when both traits are in scope (e.g. with
+/// `use seals::txout::*`). Trait objects are compared by the closing method,
+/// txid and output number; the blinding factors of the revealed seals are not
+/// taken into account.
+pub trait TxoSealDyn {
+    /// Returns method which must be used for seal closing.
+    fn seal_method(&self) -> CloseMethod;
+
+    /// Returns [`Txid`] part of the seal definition, if known.
+    fn seal_txid(&self) -> Option<Txid>;
+
+    /// Returns transaction output number containing the defined seal.
+    fn seal_vout(&self) -> Vout;
+
+    /// Returns [`OutPoint`] defining the seal, if txid is known.
+    fn seal_outpoint(&self) -> Option<OutPoint>;
+}
+
+impl<T> TxoSealDyn for T
+where
+    T: TxoSeal,
+{
+    #[inline]
+    fn seal_method(&self) -> CloseMethod { self.method() }
+
+    #[inline]
+    fn seal_txid(&self) -> Option<Txid> { self.txid() }
+
+    #[inline]
+    fn seal_vout(&self) -> Vout { self.vout() }
+
+    #[inline]
+    fn seal_outpoint(&self) -> Option<OutPoint> { self.outpoint() }
+}
+
+impl PartialEq for dyn TxoSealDyn + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.seal_method() == other.seal_method()
+            && self.seal_txid() == other.seal_txid()
+            && self.seal_vout() == other.seal_vout()
+    }
 }
 
+impl Eq for dyn TxoSealDyn + '_ {}
+
 /// Method of single-use-seal closing.
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[cfg_attr(
@@ -49,6 +215,7 @@ pub trait TxoSeal {
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(StrictEncode, StrictDecode)]
 #[strict_encoding(by_value)]
 #[repr(u8)]
@@ -65,18 +232,670 @@ pub enum CloseMethod {
     TapretFirst = 0x01,
 }
 
+impl CloseMethod {
+    /// All closing methods known to this version of the library, ordered by
+    /// their numeric id.
+    pub const ALL: &'static [CloseMethod] =
+        &[CloseMethod::OpretFirst, CloseMethod::TapretFirst];
+
+    /// Returns iterator over all closing methods known to this version of the
+    /// library.
+    #[inline]
+    pub fn iter() -> impl Iterator<Item = CloseMethod> {
+        Self::ALL.iter().copied()
+    }
+
+    /// Detects whether a transaction output with the given `script_pubkey`
+    /// can host commitment using this closing method. The policy is the
+    /// following:
+    /// - [`CloseMethod::TapretFirst`] commitment can be hosted only by v1 P2TR
+    ///   outputs, since it tweaks the output key;
+    /// - [`CloseMethod::OpretFirst`] commitment is placed into an OP_RETURN
+    ///   output, thus it can be hosted by an existing OP_RETURN output (which
+    ///   gets replaced with the commitment) or by any standard spendable output
+    ///   (P2PKH, P2SH, P2WPKH, P2WSH or P2TR) controlled by the wallet, which
+    ///   can augment the transaction with an OP_RETURN output.
+    ///
+    /// Non-standard scripts and outputs with unknown witness versions can't
+    /// host any commitments.
+    pub fn can_host(self, script_pubkey: &Script) -> bool {
+        match self {
+            CloseMethod::TapretFirst => script_pubkey.is_v1_p2tr(),
+            CloseMethod::OpretFirst => {
+                script_pubkey.is_op_return()
+                    || script_pubkey.is_p2pkh()
+                    || script_pubkey.is_p2sh()
+                    || script_pubkey.is_v0_p2wpkh()
+                    || script_pubkey.is_v0_p2wsh()
+                    || script_pubkey.is_v1_p2tr()
+            }
+        }
+    }
+}
+
+/// Returns set of the closing methods which can be used with a transaction
+/// output having the given `script_pubkey`, according to the policy described
+/// in [`CloseMethod::can_host`].
+pub fn acceptable_methods(script_pubkey: &Script) -> CloseMethodSet {
+    CloseMethod::iter()
+        .filter(|method| method.can_host(script_pubkey))
+        .collect()
+}
+
+impl From<CloseMethod> for dbc::Method {
+    fn from(method: CloseMethod) -> Self {
+        match method {
+            CloseMethod::OpretFirst => dbc::Method::OpretFirst,
+            CloseMethod::TapretFirst => dbc::Method::TapretFirst,
+        }
+    }
+}
+
+impl From<CloseMethod> for u8 {
+    #[inline]
+    fn from(method: CloseMethod) -> Self { method as u8 }
+}
+
+impl TryFrom<u8> for CloseMethod {
+    type Error = UnknownMethodId;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        CloseMethod::iter()
+            .find(|method| *method as u8 == id)
+            .ok_or(UnknownMethodId(id))
+    }
+}
+
+impl CloseMethod {
+    /// String representations accepted when parsing closing methods, including
+    /// legacy aliases emitted by older tooling. Matching is case-insensitive.
+    pub const ALIASES: &'static [(&'static str, CloseMethod)] = &[
+        ("opret1st", CloseMethod::OpretFirst),
+        ("opret", CloseMethod::OpretFirst),
+        ("tapret1st", CloseMethod::TapretFirst),
+        ("tapret", CloseMethod::TapretFirst),
+    ];
+}
+
 impl FromStr for CloseMethod {
     type Err = MethodParseError;
 
+    /// Parses closing method from its canonical name or one of the
+    /// [`CloseMethod::ALIASES`], ignoring ASCII case.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.to_lowercase() {
-            s if s == CloseMethod::OpretFirst.to_string() => {
-                CloseMethod::OpretFirst
-            }
-            s if s == CloseMethod::TapretFirst.to_string() => {
-                CloseMethod::TapretFirst
+        CloseMethod::ALIASES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, method)| *method)
+            .ok_or_else(|| MethodParseError::new(s))
+    }
+}
+
+/// Set of single-use-seal closing methods, encoded as a bit mask where
+/// each method is represented by the bit number matching its numeric id
+/// (`1 << method as u8`).
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", try_from = "u8", into = "u8")
+)]
+pub struct CloseMethodSet(u8);
+
+impl CloseMethodSet {
+    /// Empty set of closing methods.
+    pub const EMPTY: CloseMethodSet = CloseMethodSet(0);
+
+    /// Constructs set containing all closing methods known to this version of
+    /// the library.
+    pub fn all() -> CloseMethodSet { CloseMethod::iter().collect() }
+
+    /// Returns bit mask representing the method in the set.
+    #[inline]
+    const fn bit(method: CloseMethod) -> u8 { 1 << method as u8 }
+
+    /// Returns bit mask representation of the set.
+    #[inline]
+    pub fn bits(self) -> u8 { self.0 }
+
+    /// Checks whether the set contains no methods.
+    #[inline]
+    pub fn is_empty(self) -> bool { self.0 == 0 }
+
+    /// Checks whether the set contains given method.
+    #[inline]
+    pub fn contains(self, method: CloseMethod) -> bool {
+        self.0 & Self::bit(method) != 0
+    }
+
+    /// Adds method to the set.
+    #[inline]
+    pub fn insert(&mut self, method: CloseMethod) {
+        self.0 |= Self::bit(method)
+    }
+
+    /// Removes method from the set.
+    #[inline]
+    pub fn remove(&mut self, method: CloseMethod) {
+        self.0 &= !Self::bit(method)
+    }
+
+    /// Returns iterator over methods contained in the set, ordered by their
+    /// numeric id.
+    pub fn iter(self) -> impl Iterator<Item = CloseMethod> {
+        CloseMethod::iter().filter(move |method| self.contains(*method))
+    }
+
+    /// Picks closing method supported both by this and the `other` set,
+    /// returning `None` if the sets have no methods in common.
+    ///
+    /// The method is chosen according to the `preference` list, ordered from
+    /// the most to the least preferred method. Methods supported by both sets
+    /// but absent from the list are considered less preferred than any of the
+    /// listed methods and are ordered by their numeric id, so the result is
+    /// deterministic for any `preference` (including an empty one).
+    pub fn negotiate(
+        self,
+        other: CloseMethodSet,
+        preference: &[CloseMethod],
+    ) -> Option<CloseMethod> {
+        let common = self & other;
+        if common.0.count_ones() == 1 {
+            return common.iter().next();
+        }
+        preference
+            .iter()
+            .copied()
+            .find(|method| common.contains(*method))
+            .or_else(|| common.iter().next())
+    }
+}
+
+impl From<CloseMethod> for CloseMethodSet {
+    #[inline]
+    fn from(method: CloseMethod) -> Self {
+        CloseMethodSet(CloseMethodSet::bit(method))
+    }
+}
+
+impl FromIterator<CloseMethod> for CloseMethodSet {
+    fn from_iter<T: IntoIterator<Item = CloseMethod>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(CloseMethodSet::EMPTY, |set, method| set | method)
+    }
+}
+
+impl From<CloseMethodSet> for u8 {
+    #[inline]
+    fn from(set: CloseMethodSet) -> Self { set.0 }
+}
+
+impl TryFrom<u8> for CloseMethodSet {
+    type Error = UnknownMethodBits;
+
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        let unknown = bits & !CloseMethodSet::all().0;
+        if unknown != 0 {
+            return Err(UnknownMethodBits(unknown));
+        }
+        Ok(CloseMethodSet(bits))
+    }
+}
+
+impl<T> BitOr<T> for CloseMethodSet
+where
+    T: Into<CloseMethodSet>,
+{
+    type Output = CloseMethodSet;
+
+    #[inline]
+    fn bitor(self, rhs: T) -> Self::Output {
+        CloseMethodSet(self.0 | rhs.into().0)
+    }
+}
+
+impl<T> BitOrAssign<T> for CloseMethodSet
+where
+    T: Into<CloseMethodSet>,
+{
+    #[inline]
+    fn bitor_assign(&mut self, rhs: T) { self.0 |= rhs.into().0 }
+}
+
+impl<T> BitAnd<T> for CloseMethodSet
+where
+    T: Into<CloseMethodSet>,
+{
+    type Output = CloseMethodSet;
+
+    #[inline]
+    fn bitand(self, rhs: T) -> Self::Output {
+        CloseMethodSet(self.0 & rhs.into().0)
+    }
+}
+
+impl<T> BitAndAssign<T> for CloseMethodSet
+where
+    T: Into<CloseMethodSet>,
+{
+    #[inline]
+    fn bitand_assign(&mut self, rhs: T) { self.0 &= rhs.into().0 }
+}
+
+impl BitOr for CloseMethod {
+    type Output = CloseMethodSet;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CloseMethodSet::from(self) | rhs
+    }
+}
+
+impl Display for CloseMethodSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (no, method) in self.iter().enumerate() {
+            if no > 0 {
+                f.write_str("|")?;
             }
-            _ => return Err(MethodParseError(s.to_owned())),
+            Display::fmt(&method, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CloseMethodSet {
+    type Err = MethodParseError;
+
+    /// Parses `|`-separated list of closing methods; empty string corresponds
+    /// to an empty set.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(CloseMethodSet::EMPTY);
+        }
+        s.split('|').map(CloseMethod::from_str).collect()
+    }
+}
+
+impl StrictEncode for CloseMethodSet {
+    #[inline]
+    fn strict_encode<E: Write>(
+        &self,
+        e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        self.0.strict_encode(e)
+    }
+}
+
+impl StrictDecode for CloseMethodSet {
+    fn strict_decode<D: Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        CloseMethodSet::try_from(u8::strict_decode(d)?).map_err(|err| {
+            strict_encoding::Error::DataIntegrityError(err.to_string())
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+    use crate::txout::ExplicitSeal;
+
+    #[test]
+    fn seal_matches() {
+        let txid = Txid::hash(b"seal transaction");
+        let witness_txid = Txid::hash(b"witness transaction");
+        let explicit =
+            ExplicitSeal::with(CloseMethod::OpretFirst, Some(txid), 2);
+        let witness = ExplicitSeal::with(CloseMethod::OpretFirst, None, 2);
+
+        assert!(explicit.matches(OutPoint::new(txid, 2), witness_txid));
+        assert!(!explicit.matches(OutPoint::new(witness_txid, 2), witness_txid));
+        assert!(!explicit.matches(OutPoint::new(txid, 1), witness_txid));
+
+        assert!(witness.matches(OutPoint::new(witness_txid, 2), witness_txid));
+        assert!(!witness.matches(OutPoint::new(txid, 2), witness_txid));
+        assert!(!witness.matches(OutPoint::new(witness_txid, 3), witness_txid));
+
+        let outpoints = [
+            OutPoint::new(txid, 1),
+            OutPoint::new(witness_txid, 2),
+            OutPoint::new(txid, 2),
+            OutPoint::new(txid, 2),
+        ];
+        assert_eq!(explicit.matches_any(&outpoints, witness_txid), Some(2));
+        assert_eq!(witness.matches_any(&outpoints, witness_txid), Some(1));
+        assert_eq!(witness.matches_any(&outpoints, txid), Some(2));
+        assert_eq!(explicit.matches_any(&outpoints[..2], witness_txid), None);
+        assert_eq!(explicit.matches_any(&[], witness_txid), None);
+    }
+
+    #[test]
+    fn close_method_all() {
+        assert_eq!(CloseMethod::iter().count(), CloseMethod::ALL.len());
+        for method in CloseMethod::iter() {
+            assert_eq!(CloseMethod::from_str(&method.to_string()), Ok(method));
+            assert_eq!(CloseMethod::try_from(u8::from(method)), Ok(method));
+            assert_eq!(
+                strict_encoding::strict_serialize(&method).unwrap(),
+                vec![method as u8]
+            );
+        }
+        assert_eq!(CloseMethod::try_from(0x02), Err(UnknownMethodId(0x02)));
+        assert_eq!(CloseMethod::try_from(0xFF), Err(UnknownMethodId(0xFF)));
+    }
+
+    #[test]
+    fn close_method_aliases() {
+        for (name, method) in CloseMethod::ALIASES {
+            assert_eq!(CloseMethod::from_str(name), Ok(*method));
+            assert_eq!(
+                CloseMethod::from_str(&name.to_uppercase()),
+                Ok(*method)
+            );
+        }
+        for s in ["TapRet1st", "TAPRET", "tapRET", "TapRet1St"] {
+            assert_eq!(CloseMethod::from_str(s), Ok(CloseMethod::TapretFirst));
+        }
+        for s in ["OpRet1st", "OPRET", "opRET", "OPRET1ST"] {
+            assert_eq!(CloseMethod::from_str(s), Ok(CloseMethod::OpretFirst));
+        }
+    }
+
+    #[test]
+    fn close_method_invalid() {
+        for s in [
+            "",
+            "tap",
+            "op",
+            "ret",
+            "tapret2nd",
+            "opret2nd",
+            " tapret1st",
+            "tapret1st ",
+            "tapret1st:",
+            "TapretFirst",
+            "0",
+            "1",
+        ] {
+            assert_eq!(CloseMethod::from_str(s), Err(MethodParseError::new(s)));
+        }
+        // original input is preserved in the error, not its lowercased form
+        assert_eq!(
+            CloseMethod::from_str("TapRet2nd"),
+            Err(MethodParseError::new("TapRet2nd"))
+        );
+    }
+
+    #[test]
+    fn close_method_suggestion() {
+        // Exact names, including case variations, are parsed
+        assert_eq!(
+            CloseMethod::from_str("TAPRET1ST"),
+            Ok(CloseMethod::TapretFirst)
+        );
+
+        // Near misses get a suggestion
+        for (s, method) in [
+            ("taproot1st", CloseMethod::TapretFirst),
+            ("tapret1", CloseMethod::TapretFirst),
+            ("TapRet1sd", CloseMethod::TapretFirst),
+            ("opret1", CloseMethod::OpretFirst),
+            ("oprt1st", CloseMethod::OpretFirst),
+        ] {
+            let err = CloseMethod::from_str(s).unwrap_err();
+            assert_eq!(err.input(), s);
+            assert_eq!(err.suggestion(), Some(method));
+            assert!(err
+                .to_string()
+                .ends_with(&format!("; did you mean `{}`?", method)));
+        }
+        assert_eq!(
+            CloseMethod::from_str("taproot1st").unwrap_err().to_string(),
+            "wrong transaction output-based single-use-seal closing method id \
+             'taproot1st'; valid methods are `opret1st`, `tapret1st`; did you \
+             mean `tapret1st`?"
+        );
+
+        // Far-off inputs get only the list of valid methods
+        for s in ["", "tap", "tapret2nd", "opret2nd", "TapretFirst", "seal"] {
+            let err = CloseMethod::from_str(s).unwrap_err();
+            assert_eq!(err.suggestion(), None);
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "wrong transaction output-based single-use-seal closing \
+                     method id '{}'; valid methods are `opret1st`, `tapret1st`",
+                    s
+                )
+            );
+        }
+
+        // Seal parsing reports the same suggestion
+        let err = ExplicitSeal::from_str("taproot1st:~:1").unwrap_err();
+        assert!(err.to_string().contains("did you mean `tapret1st`?"));
+    }
+
+    #[test]
+    fn close_method_set_bits() {
+        // Bit assignments are part of the consensus-level data encoding and
+        // must never change
+        assert_eq!(CloseMethodSet::from(CloseMethod::OpretFirst).bits(), 0x01);
+        assert_eq!(CloseMethodSet::from(CloseMethod::TapretFirst).bits(), 0x02);
+        assert_eq!(CloseMethodSet::all().bits(), 0x03);
+        assert_eq!(CloseMethodSet::EMPTY.bits(), 0x00);
+        assert_eq!(
+            strict_encoding::strict_serialize(&CloseMethodSet::all()).unwrap(),
+            vec![0x03]
+        );
+        for bits in 0..=0x03u8 {
+            let set = CloseMethodSet::try_from(bits).unwrap();
+            assert_eq!(
+                strict_encoding::strict_deserialize::<CloseMethodSet>(&[bits])
+                    .unwrap(),
+                set
+            );
+        }
+        assert_eq!(
+            CloseMethodSet::try_from(0x07),
+            Err(UnknownMethodBits(0x04))
+        );
+        assert!(
+            strict_encoding::strict_deserialize::<CloseMethodSet>(&[0x80])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn close_method_set_ops() {
+        let opret = CloseMethodSet::from(CloseMethod::OpretFirst);
+        let tapret = CloseMethodSet::from(CloseMethod::TapretFirst);
+        let both = CloseMethod::TapretFirst | CloseMethod::OpretFirst;
+
+        assert_eq!(both, CloseMethodSet::all());
+        assert_eq!(opret | tapret, both);
+        assert_eq!(opret | CloseMethod::TapretFirst, both);
+        assert_eq!(both & tapret, tapret);
+        assert_eq!(opret & tapret, CloseMethodSet::EMPTY);
+        assert!((opret & tapret).is_empty());
+        assert!(both.contains(CloseMethod::OpretFirst));
+        assert!(both.contains(CloseMethod::TapretFirst));
+        assert!(!tapret.contains(CloseMethod::OpretFirst));
+        assert_eq!(both.iter().collect::<Vec<_>>(), CloseMethod::ALL);
+
+        let mut set = CloseMethodSet::EMPTY;
+        set |= CloseMethod::TapretFirst;
+        assert_eq!(set, tapret);
+        set.insert(CloseMethod::OpretFirst);
+        assert_eq!(set, both);
+        set &= opret;
+        assert_eq!(set, opret);
+        set.remove(CloseMethod::OpretFirst);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn close_method_set_negotiate() {
+        use CloseMethod::*;
+
+        let opret = CloseMethodSet::from(OpretFirst);
+        let tapret = CloseMethodSet::from(TapretFirst);
+        let both = CloseMethodSet::all();
+
+        // Disjoint sets
+        assert_eq!(opret.negotiate(tapret, &[OpretFirst, TapretFirst]), None);
+        assert_eq!(both.negotiate(CloseMethodSet::EMPTY, &[OpretFirst]), None);
+        assert_eq!(CloseMethodSet::EMPTY.negotiate(both, &[]), None);
+
+        // Preference ordering
+        assert_eq!(
+            both.negotiate(both, &[TapretFirst, OpretFirst]),
+            Some(TapretFirst)
+        );
+        assert_eq!(
+            both.negotiate(both, &[OpretFirst, TapretFirst]),
+            Some(OpretFirst)
+        );
+        assert_eq!(both.negotiate(both, &[TapretFirst]), Some(TapretFirst));
+        assert_eq!(
+            both.negotiate(both, &[TapretFirst, TapretFirst, OpretFirst]),
+            Some(TapretFirst)
+        );
+        // Unlisted methods are ordered by their id
+        assert_eq!(both.negotiate(both, &[]), Some(OpretFirst));
+
+        // Single-method fast path ignores preference
+        assert_eq!(both.negotiate(tapret, &[OpretFirst]), Some(TapretFirst));
+        assert_eq!(opret.negotiate(both, &[TapretFirst]), Some(OpretFirst));
+        assert_eq!(tapret.negotiate(tapret, &[]), Some(TapretFirst));
+
+        // Negotiation is symmetric
+        for a in 0..=0x03u8 {
+            for b in 0..=0x03u8 {
+                let a = CloseMethodSet::try_from(a).unwrap();
+                let b = CloseMethodSet::try_from(b).unwrap();
+                for pref in [&[][..], &[TapretFirst], &[OpretFirst]] {
+                    assert_eq!(a.negotiate(b, pref), b.negotiate(a, pref));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn close_method_set_str() {
+        let both = CloseMethodSet::all();
+        assert_eq!(both.to_string(), "opret1st|tapret1st");
+        assert_eq!(CloseMethodSet::EMPTY.to_string(), "");
+        assert_eq!(
+            CloseMethodSet::from(CloseMethod::TapretFirst).to_string(),
+            "tapret1st"
+        );
+        assert_eq!(CloseMethodSet::from_str("opret1st|tapret1st"), Ok(both));
+        assert_eq!(CloseMethodSet::from_str("tapret1st|opret1st"), Ok(both));
+        assert_eq!(CloseMethodSet::from_str("tapret|OPRET"), Ok(both));
+        assert_eq!(CloseMethodSet::from_str(""), Ok(CloseMethodSet::EMPTY));
+        assert_eq!(
+            CloseMethodSet::from_str("tapret1st|"),
+            Err(MethodParseError::new(""))
+        );
+        assert_eq!(
+            CloseMethodSet::from_str("tapret1st,opret1st"),
+            Err(MethodParseError::new("tapret1st,opret1st"))
+        );
+    }
+
+    #[test]
+    fn script_pubkey_methods() {
+        use bitcoin::blockdata::opcodes::all::OP_CHECKSIG;
+        use bitcoin::blockdata::script::Builder;
+        use bitcoin::schnorr::TweakedPublicKey;
+        use bitcoin::secp256k1::XOnlyPublicKey;
+        use bitcoin::{PubkeyHash, ScriptHash, WPubkeyHash, WScriptHash};
+
+        let output_key = TweakedPublicKey::dangerous_assume_tweaked(
+            XOnlyPublicKey::from_str(
+                "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+            )
+            .unwrap(),
+        );
+        let opret = CloseMethodSet::from(CloseMethod::OpretFirst);
+        let both = opret | CloseMethod::TapretFirst;
+
+        for (script_pubkey, methods) in [
+            (Script::new_v1_p2tr_tweaked(output_key), both),
+            (Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"key")), opret),
+            (Script::new_v0_p2wsh(&WScriptHash::hash(b"script")), opret),
+            (Script::new_p2sh(&ScriptHash::hash(b"script")), opret),
+            (Script::new_p2pkh(&PubkeyHash::hash(b"key")), opret),
+            (Script::new_op_return(&[0u8; 32]), opret),
+            (Script::new(), CloseMethodSet::EMPTY),
+            (
+                Builder::new().push_opcode(OP_CHECKSIG).into_script(),
+                CloseMethodSet::EMPTY,
+            ),
+            (
+                Script::new_witness_program(
+                    bitcoin::util::address::WitnessVersion::V2,
+                    &[0u8; 32],
+                ),
+                CloseMethodSet::EMPTY,
+            ),
+        ] {
+            assert_eq!(acceptable_methods(&script_pubkey), methods);
+            for method in CloseMethod::iter() {
+                assert_eq!(
+                    method.can_host(&script_pubkey),
+                    methods.contains(method)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dyn_seals() {
+        use crate::txout::blind::RevealedSeal;
+
+        let txid = Txid::hash(b"seal transaction");
+        let explicit =
+            ExplicitSeal::new(CloseMethod::TapretFirst, OutPoint::new(txid, 1));
+        let revealed = RevealedSeal::from(explicit);
+        let seals: Vec<Box<dyn TxoSealDyn>> = vec![
+            Box::new(explicit),
+            Box::new(ExplicitSeal::new_witness(CloseMethod::OpretFirst, 2)),
+            Box::new(revealed),
+            Box::new(
+                ExplicitSeal::new(
+                    CloseMethod::OpretFirst,
+                    OutPoint::new(txid, 3),
+                )
+                .blind(0x31bbed7e7b2d),
+            ),
+        ];
+
+        let tapret = seals
+            .iter()
+            .filter(|seal| seal.seal_method() == CloseMethod::TapretFirst)
+            .collect::<Vec<_>>();
+        assert_eq!(tapret.len(), 2);
+        assert!(tapret
+            .iter()
+            .all(|seal| seal.seal_outpoint() == Some(OutPoint::new(txid, 1))));
+        assert_eq!(seals[1].seal_txid(), None);
+        assert_eq!(seals[1].seal_vout(), Vout::from(2u32));
+
+        // Seals of different types are equal if defined over the same output
+        // with the same method
+        assert!(*seals[0] == *seals[2]);
+        assert!(*seals[0] != *seals[1]);
+        let other = RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            ..revealed
+        };
+        assert!(*seals[0] != *(&other as &dyn TxoSealDyn));
+
+        // Methods of the concrete seal types are not ambiguous with both of
+        // the seal traits in scope
+        assert_eq!(explicit.method(), CloseMethod::TapretFirst);
+        assert_eq!(revealed.vout(), Vout::from(1u32));
+    }
+}
@@ -0,0 +1,338 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Ordered sets of revealed seals with no two seals defined over the same
+//! transaction output.
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::io::{Read, Write};
+
+use bitcoin::OutPoint;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::limits::MAX_SEALS;
+use crate::txout::blind::{conceal_all, ConcealedSeal, RevealedSeal};
+use crate::txout::redact::redacted;
+
+/// Error inserting seal into [`SealSet`] which already contains a different
+/// seal defined over the same transaction output.
+///
+/// Seals are displayed in [`Redacted`](crate::txout::Redacted) form; the
+/// alternate formatter (`{:#}`) displays them in full, including the blinding
+/// factors.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Error)]
+pub struct SealConflict {
+    /// Seal already present in the set.
+    pub existing: RevealedSeal,
+    /// Seal which was attempted to be inserted.
+    pub new: RevealedSeal,
+}
+
+impl Display for SealConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seal {} conflicts with seal {} defined over the same transaction \
+             output",
+            redacted(self.new, f.alternate()),
+            redacted(self.existing, f.alternate())
+        )
+    }
+}
+
+/// Set of revealed seals keyed by the outpoint they are defined over and
+/// iterated in the outpoint order.
+///
+/// Seals over the witness transaction outputs (i.e. not having txid) are keyed
+/// by their output number under the all-zero sentinel txid, and thus conflict
+/// with each other only when they use the same output number. Explicit seals
+/// over the all-zero txid, which can't belong to a real transaction, share the
+/// keys with the witness seals.
+///
+/// Both strict encoding and serde serialization represent the set as a list of
+/// the seals in the outpoint order; deserialization fails on conflicting seals
+/// and strict decoding additionally requires the canonical order.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(
+        crate = "serde_crate",
+        try_from = "Vec<RevealedSeal>",
+        into = "Vec<RevealedSeal>"
+    )
+)]
+pub struct SealSet(BTreeMap<OutPoint, RevealedSeal>);
+
+impl SealSet {
+    /// Constructs empty seal set.
+    #[inline]
+    pub fn new() -> SealSet { SealSet::default() }
+
+    /// Returns outpoint under which the seal is stored in the set.
+    #[inline]
+    pub fn key(seal: &RevealedSeal) -> OutPoint {
+        OutPoint::new(seal.txid.unwrap_or_default(), seal.vout.into_u32())
+    }
+
+    /// Inserts seal into the set.
+    ///
+    /// Returns `Ok(true)` if the seal was added, `Ok(false)` if the same seal
+    /// was already present and [`SealConflict`] error if the set already
+    /// contains a different seal over the same outpoint; in the last case the
+    /// set is not modified.
+    pub fn insert(&mut self, seal: RevealedSeal) -> Result<bool, SealConflict> {
+        match self.0.entry(SealSet::key(&seal)) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(seal);
+                Ok(true)
+            }
+            btree_map::Entry::Occupied(entry) if *entry.get() == seal => {
+                Ok(false)
+            }
+            btree_map::Entry::Occupied(entry) => Err(SealConflict {
+                existing: *entry.get(),
+                new: seal,
+            }),
+        }
+    }
+
+    /// Returns seal defined over the given outpoint, if any. Seals over the
+    /// witness transaction outputs are looked up with the all-zero txid.
+    #[inline]
+    pub fn get(&self, outpoint: &OutPoint) -> Option<&RevealedSeal> {
+        self.0.get(outpoint)
+    }
+
+    /// Removes and returns seal defined over the given outpoint, if any.
+    #[inline]
+    pub fn remove(&mut self, outpoint: &OutPoint) -> Option<RevealedSeal> {
+        self.0.remove(outpoint)
+    }
+
+    /// Returns number of seals in the set.
+    #[inline]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether the set contains no seals.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Iterates over the seals in the outpoint order.
+    #[inline]
+    pub fn iter(&self) -> btree_map::Values<'_, OutPoint, RevealedSeal> {
+        self.0.values()
+    }
+
+    /// Conceals all seals, returning concealed seals in the same outpoint
+    /// order as [`SealSet::iter`].
+    #[inline]
+    pub fn conceal(&self) -> Vec<ConcealedSeal> { conceal_all(self.iter()) }
+}
+
+impl<'set> IntoIterator for &'set SealSet {
+    type Item = &'set RevealedSeal;
+    type IntoIter = btree_map::Values<'set, OutPoint, RevealedSeal>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+impl IntoIterator for SealSet {
+    type Item = RevealedSeal;
+    type IntoIter = btree_map::IntoValues<OutPoint, RevealedSeal>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_values() }
+}
+
+impl TryFrom<Vec<RevealedSeal>> for SealSet {
+    type Error = SealConflict;
+
+    fn try_from(seals: Vec<RevealedSeal>) -> Result<Self, Self::Error> {
+        let mut set = SealSet::new();
+        for seal in seals {
+            set.insert(seal)?;
+        }
+        Ok(set)
+    }
+}
+
+impl From<SealSet> for Vec<RevealedSeal> {
+    #[inline]
+    fn from(set: SealSet) -> Self { set.into_iter().collect() }
+}
+
+impl StrictEncode for SealSet {
+    fn strict_encode<E: Write>(
+        &self,
+        e: E,
+    ) -> Result<usize, strict_encoding::Error> {
+        self.iter().copied().collect::<Vec<_>>().strict_encode(e)
+    }
+}
+
+impl StrictDecode for SealSet {
+    fn strict_decode<D: Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        // Seals are decoded one by one, so the memory is allocated only for
+        // the actually present data
+        let len = usize::strict_decode(&mut d)?;
+        if len > MAX_SEALS {
+            return Err(strict_encoding::Error::ExceedMaxItems(len));
+        }
+        let mut set = SealSet::new();
+        let mut prev: Option<OutPoint> = None;
+        for _ in 0..len {
+            let seal = RevealedSeal::strict_decode(&mut d)?;
+            let key = SealSet::key(&seal);
+            if prev.map(|prev| prev >= key).unwrap_or_default() {
+                return Err(strict_encoding::Error::DataIntegrityError(s!(
+                    "seals in the set are not in the outpoint order or repeat \
+                     the same outpoint"
+                )));
+            }
+            prev = Some(key);
+            set.insert(seal)
+                .expect("outpoints are checked to be unique");
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::Txid;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+    use crate::txout::CloseMethod;
+
+    fn txid(byte: u8) -> Txid {
+        Txid::from_str(&format!("{:02x}", byte).repeat(32)).unwrap()
+    }
+
+    fn seal(txid: Option<Txid>, vout: u32, blinding: u64) -> RevealedSeal {
+        RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid,
+            vout: vout.into(),
+            blinding,
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        let seals = [
+            seal(Some(txid(2)), 0, 1),
+            seal(Some(txid(1)), 5, 2),
+            seal(None, 3, 3),
+            seal(Some(txid(1)), 1, 4),
+        ];
+        let mut set = SealSet::new();
+        for seal in seals {
+            assert_eq!(set.insert(seal), Ok(true));
+        }
+        assert_eq!(set.len(), 4);
+
+        let ordered = vec![seals[2], seals[3], seals[1], seals[0]];
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), ordered);
+        assert_eq!(set.conceal(), conceal_all(&ordered));
+        assert_eq!(
+            set.get(&OutPoint::new(txid(1), 5)).copied(),
+            Some(seals[1])
+        );
+
+        let data = strict_serialize(&set).unwrap();
+        assert_eq!(data, strict_serialize(&ordered).unwrap());
+        assert_eq!(strict_deserialize::<SealSet>(&data).unwrap(), set);
+        assert_eq!(SealSet::try_from(seals.to_vec()), Ok(set.clone()));
+
+        // Non-canonical order is rejected by strict decoding only
+        let unordered = strict_serialize(&seals.to_vec()).unwrap();
+        assert!(strict_deserialize::<SealSet>(&unordered).is_err());
+
+        // Declared length exceeding the data does not allocate or succeed
+        let mut truncated = data.clone();
+        truncated[0..2].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(strict_deserialize::<SealSet>(&truncated).is_err());
+    }
+
+    #[test]
+    fn conflict() {
+        let existing = seal(Some(txid(1)), 1, 10);
+        let mut set = SealSet::new();
+        assert_eq!(set.insert(existing), Ok(true));
+        assert_eq!(set.insert(existing), Ok(false));
+
+        let new = seal(Some(txid(1)), 1, 11);
+        assert_eq!(set.insert(new), Err(SealConflict { existing, new }));
+        let new = RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            ..existing
+        };
+        assert_eq!(set.insert(new), Err(SealConflict { existing, new }));
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.iter().next(), Some(&existing));
+
+        assert_eq!(
+            SealSet::try_from(vec![existing, new]),
+            Err(SealConflict { existing, new })
+        );
+        let data = strict_serialize(&vec![existing, new]).unwrap();
+        assert!(strict_deserialize::<SealSet>(&data).is_err());
+
+        // Error message does not disclose the blinding factors
+        assert!(!SealConflict { existing, new }.to_string().contains("#10"));
+    }
+
+    #[test]
+    fn witness_seals() {
+        let existing = seal(None, 2, 1);
+        let mut set = SealSet::new();
+        assert_eq!(set.insert(existing), Ok(true));
+        assert_eq!(set.insert(seal(None, 3, 2)), Ok(true));
+        assert_eq!(set.insert(seal(Some(txid(1)), 2, 3)), Ok(true));
+
+        let new = seal(None, 2, 4);
+        assert_eq!(set.insert(new), Err(SealConflict { existing, new }));
+
+        assert_eq!(SealSet::key(&existing), OutPoint::new(Txid::default(), 2));
+        assert_eq!(
+            set.get(&OutPoint::new(Txid::default(), 2)),
+            Some(&existing)
+        );
+        assert_eq!(set.iter().next(), Some(&existing));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let seals = vec![seal(None, 1, 1), seal(Some(txid(1)), 0, 2)];
+        let set = SealSet::try_from(seals.clone()).unwrap();
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, serde_json::to_string(&seals).unwrap());
+        assert_eq!(serde_json::from_str::<SealSet>(&json).unwrap(), set);
+
+        let conflicting = vec![seal(None, 1, 1), seal(None, 1, 2)];
+        let json = serde_json::to_string(&conflicting).unwrap();
+        assert!(serde_json::from_str::<SealSet>(&json).is_err());
+    }
+}
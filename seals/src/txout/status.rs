@@ -0,0 +1,317 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Status of the seals closing, including the confirmation depth of the
+//! closing transaction.
+
+use bitcoin::Txid;
+
+use crate::txout::{TxResolve, TxoSeal, VerifyError, VerifyOpts};
+
+/// Status of the seal as known to the transaction resolver.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SealStatus {
+    /// Seal status can't be determined, since the seal is defined over the
+    /// output of a witness transaction which is not known yet.
+    Undefined,
+
+    /// Seal output is not spent by any transaction known to the resolver.
+    Open,
+
+    /// Seal is closed by the transaction spending its output.
+    Closed {
+        /// Id of the transaction spending the seal output.
+        witness: Txid,
+
+        /// Height of the block containing the witness transaction, or `None`
+        /// if the transaction is not mined yet or the resolver does not track
+        /// blocks.
+        height: Option<u32>,
+
+        /// Number of confirmations of the witness transaction; zero for the
+        /// unmined transactions.
+        confirmations: u32,
+    },
+}
+
+impl SealStatus {
+    /// Detects whether the seal is closed, including seals closed by unmined
+    /// transactions.
+    #[inline]
+    pub fn is_closed(self) -> bool { matches!(self, SealStatus::Closed { .. }) }
+
+    /// Returns number of the confirmations of the seal closing; zero for the
+    /// open seals, seals with undefined status and seals closed by unmined
+    /// transactions.
+    #[inline]
+    pub fn confirmations(self) -> u32 {
+        match self {
+            SealStatus::Closed { confirmations, .. } => confirmations,
+            _ => 0,
+        }
+    }
+}
+
+/// Queries status of the seal closing using the provided transaction
+/// resolver.
+///
+/// Confirmations are counted up to the best block height reported by
+/// [`TxResolve::tip_height`]; if the resolver knows the height of the witness
+/// transaction, but not the best block height, the witness is reported as
+/// unconfirmed. Witness height above the best block height is reported as
+/// [`VerifyError::InconsistentHeight`]; resolver failures are reported as
+/// [`VerifyError::ResolverError`].
+pub fn seal_status<Resolver>(
+    seal: &impl TxoSeal,
+    resolver: &Resolver,
+) -> Result<SealStatus, VerifyError>
+where
+    Resolver: TxResolve,
+{
+    let outpoint = match seal.outpoint() {
+        Some(outpoint) => outpoint,
+        None => return Ok(SealStatus::Undefined),
+    };
+    let witness =
+        match resolver.spending_tx(outpoint).map_err(resolver_error)? {
+            Some(tx) => tx.txid(),
+            None => return Ok(SealStatus::Open),
+        };
+    let (height, confirmations) = witness_confirmations(witness, resolver)?;
+    Ok(SealStatus::Closed {
+        witness,
+        height,
+        confirmations,
+    })
+}
+
+/// Queries status of the seal closing like [`seal_status`], checking that
+/// the closing transaction meets the confirmation policy of `opts` (see
+/// [`VerifyOpts::check_confirmations`]). Open seals and seals with undefined
+/// status are returned without errors.
+pub fn seal_status_with<Resolver>(
+    seal: &impl TxoSeal,
+    resolver: &Resolver,
+    opts: VerifyOpts,
+) -> Result<SealStatus, VerifyError>
+where
+    Resolver: TxResolve,
+{
+    let status = seal_status(seal, resolver)?;
+    if status.is_closed() {
+        opts.check_confirmations(status.confirmations())?;
+    }
+    Ok(status)
+}
+
+/// Returns height of the block containing `witness` transaction and number of
+/// its confirmations, as described in [`seal_status`].
+pub(super) fn witness_confirmations<Resolver>(
+    witness: Txid,
+    resolver: &Resolver,
+) -> Result<(Option<u32>, u32), VerifyError>
+where
+    Resolver: TxResolve,
+{
+    let height = resolver.tx_height(witness).map_err(resolver_error)?;
+    let tip = resolver.tip_height().map_err(resolver_error)?;
+    Ok((height, confirmations(height, tip)?))
+}
+
+/// Returns number of confirmations of the transaction mined at `height` with
+/// the best block at `tip` height. Transactions are unconfirmed if any of the
+/// heights is unknown.
+pub(super) fn confirmations(
+    height: Option<u32>,
+    tip: Option<u32>,
+) -> Result<u32, VerifyError> {
+    match (height, tip) {
+        (Some(height), Some(tip)) if height > tip => {
+            Err(VerifyError::InconsistentHeight { height, tip })
+        }
+        (Some(height), Some(tip)) => Ok((tip - height).saturating_add(1)),
+        (None, _) | (_, None) => Ok(0),
+    }
+}
+
+fn resolver_error(
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> VerifyError {
+    VerifyError::ResolverError(Box::new(err))
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, Transaction, TxIn};
+
+    use super::*;
+    use crate::txout::{CloseMethod, ExplicitSeal, MemResolver};
+
+    fn spending_tx(outpoint: OutPoint) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                ..default!()
+            }],
+            output: vec![default!()],
+        }
+    }
+
+    #[test]
+    fn status() {
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let seal = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint);
+        let witness_tx = spending_tx(outpoint);
+        let witness = witness_tx.txid();
+
+        let mut resolver = MemResolver::new();
+        assert_eq!(seal_status(&seal, &resolver).unwrap(), SealStatus::Open);
+        let witness_seal =
+            ExplicitSeal::with(CloseMethod::TapretFirst, None, 0);
+        assert_eq!(
+            seal_status(&witness_seal, &resolver).unwrap(),
+            SealStatus::Undefined
+        );
+
+        // Closed by a mempool transaction
+        resolver.insert(witness_tx);
+        let status = seal_status(&seal, &resolver).unwrap();
+        assert_eq!(status, SealStatus::Closed {
+            witness,
+            height: None,
+            confirmations: 0
+        });
+        assert!(status.is_closed());
+        assert_eq!(status.confirmations(), 0);
+
+        // Mined in the best block
+        resolver.set_height(witness, 700_000);
+        assert_eq!(
+            seal_status(&seal, &resolver).unwrap(),
+            SealStatus::Closed {
+                witness,
+                height: Some(700_000),
+                confirmations: 1
+            }
+        );
+
+        // Best block below the witness height
+        resolver.set_tip_height(699_999);
+        assert!(matches!(
+            seal_status(&seal, &resolver),
+            Err(VerifyError::InconsistentHeight {
+                height: 700_000,
+                tip: 699_999
+            })
+        ));
+
+        // Buried under more blocks
+        resolver.set_tip_height(700_005);
+        let status = seal_status(&seal, &resolver).unwrap();
+        assert_eq!(status, SealStatus::Closed {
+            witness,
+            height: Some(700_000),
+            confirmations: 6
+        });
+        assert_eq!(status.confirmations(), 6);
+
+        // Other outputs of the same transaction remain open
+        let other = ExplicitSeal::new(
+            CloseMethod::TapretFirst,
+            OutPoint::new(outpoint.txid, 1),
+        );
+        let status = seal_status(&other, &resolver).unwrap();
+        assert_eq!(status, SealStatus::Open);
+        assert!(!status.is_closed());
+        assert_eq!(status.confirmations(), 0);
+    }
+
+    #[test]
+    fn confirmation_count() {
+        assert_eq!(confirmations(None, None).unwrap(), 0);
+        assert_eq!(confirmations(None, Some(100)).unwrap(), 0);
+        // Unknown best block height can't prove any confirmations
+        assert_eq!(confirmations(Some(100), None).unwrap(), 0);
+        assert_eq!(confirmations(Some(100), Some(100)).unwrap(), 1);
+        assert_eq!(confirmations(Some(0), Some(u32::MAX)).unwrap(), u32::MAX);
+        assert!(matches!(
+            confirmations(Some(101), Some(100)),
+            Err(VerifyError::InconsistentHeight {
+                height: 101,
+                tip: 100
+            })
+        ));
+    }
+
+    #[test]
+    fn status_policy() {
+        let outpoint = OutPoint::new(Txid::hash(b"previous transaction"), 0);
+        let seal = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint);
+        let witness_tx = spending_tx(outpoint);
+        let witness = witness_tx.txid();
+        let confirmed = VerifyOpts {
+            allow_mempool: false,
+            ..VerifyOpts::default()
+        };
+        let deep = VerifyOpts {
+            min_confirmations: 6,
+            ..VerifyOpts::default()
+        };
+
+        let mut resolver = MemResolver::new();
+        for opts in [VerifyOpts::default(), confirmed, deep] {
+            assert!(matches!(
+                seal_status_with(&seal, &resolver, opts),
+                Ok(SealStatus::Open)
+            ));
+        }
+
+        resolver.insert(witness_tx);
+        assert!(matches!(
+            seal_status_with(&seal, &resolver, VerifyOpts::default()),
+            Ok(SealStatus::Closed {
+                confirmations: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            seal_status_with(&seal, &resolver, confirmed),
+            Err(VerifyError::InsufficientConfirmations { have: 0, need: 1 })
+        ));
+
+        resolver.set_height(witness, 700_000);
+        resolver.set_tip_height(700_004);
+        assert!(matches!(
+            seal_status_with(&seal, &resolver, confirmed),
+            Ok(SealStatus::Closed {
+                confirmations: 5,
+                ..
+            })
+        ));
+        assert!(matches!(
+            seal_status_with(&seal, &resolver, deep),
+            Err(VerifyError::InsufficientConfirmations { have: 5, need: 6 })
+        ));
+        resolver.set_tip_height(700_005);
+        assert!(matches!(
+            seal_status_with(&seal, &resolver, deep),
+            Ok(SealStatus::Closed { witness: txid, height: Some(700_000), confirmations: 6 })
+                if txid == witness
+        ));
+    }
+}
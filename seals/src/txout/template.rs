@@ -0,0 +1,287 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Templates of seal definitions created before the transaction funding the
+//! seal exists.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use bitcoin::OutPoint;
+
+use crate::limits::MAX_VOUT;
+use crate::txout::explicit::{check_vout, ParseContext, ParseError};
+use crate::txout::{CloseMethod, ExplicitSeal, Vout};
+
+/// Template of a seal definition bound to a funding plan, used when the seal
+/// is required (for instance, for an invoice) before the funding transaction
+/// is known.
+///
+/// The template is not a seal: it can't be used in place of the seal and is
+/// completed into [`ExplicitSeal`] only explicitly, with
+/// [`SealTemplate::materialize`] or [`SealTemplate::materialize_witness`]. Its
+/// strict encoding is not decodable as any of the seal types and vice versa.
+///
+/// The string representation is `method:?` or `method:?:vout`, where `vout` is
+/// the output number hint:
+///
+/// ```compile_fail
+/// # use seals::txout::{CloseMethod, ExplicitSeal, SealTemplate};
+/// let template = SealTemplate::new(CloseMethod::TapretFirst);
+/// let seal = ExplicitSeal::from(template);
+/// ```
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use seals::txout::{CloseMethod, SealTemplate};
+/// let template = SealTemplate::from_str("tapret1st:?:2").unwrap();
+/// assert_eq!(template.method, CloseMethod::TapretFirst);
+/// assert_eq!(template.vout_hint, Some(2u32.into()));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(StrictEncode, StrictDecode)]
+pub struct SealTemplate {
+    /// Closing method which must be used by the materialized seal.
+    pub method: CloseMethod,
+
+    /// Number of the output which is planned to be used by the seal, if known.
+    pub vout_hint: Option<Vout>,
+}
+
+impl SealTemplate {
+    /// Constructs template for the seal with the given closing method and no
+    /// output number hint.
+    #[inline]
+    pub fn new(method: CloseMethod) -> SealTemplate {
+        SealTemplate {
+            method,
+            vout_hint: None,
+        }
+    }
+
+    /// Constructs template for the seal with the given closing method and the
+    /// planned output number.
+    #[inline]
+    pub fn with_vout_hint(
+        method: CloseMethod,
+        vout: impl Into<Vout>,
+    ) -> SealTemplate {
+        SealTemplate {
+            method,
+            vout_hint: Some(vout.into()),
+        }
+    }
+
+    /// Completes the template into the seal over the funding transaction
+    /// `outpoint`. The output number hint is ignored.
+    #[inline]
+    pub fn materialize(self, outpoint: OutPoint) -> ExplicitSeal {
+        ExplicitSeal::new(self.method, outpoint)
+    }
+
+    /// Completes the template into the seal over the output `vout` of the
+    /// witness transaction. The output number hint is ignored.
+    #[inline]
+    pub fn materialize_witness(self, vout: impl Into<Vout>) -> ExplicitSeal {
+        ExplicitSeal::new_witness(self.method, vout)
+    }
+}
+
+impl FromStr for SealTemplate {
+    type Err = ParseError;
+
+    /// Parses template from `method:?` or `method:?:vout` string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let method = match parts.next() {
+            Some("") | None => return Err(ParseError::MethodRequired),
+            Some(method) => CloseMethod::from_str(method)?,
+        };
+        match parts.next() {
+            Some("?") => {}
+            Some(part) => {
+                return Err(ParseError::WrongStructure(ParseContext::with(
+                    s, part,
+                )))
+            }
+            None => {
+                return Err(ParseError::WrongStructure(ParseContext::end(s)))
+            }
+        }
+        let vout_hint = parts
+            .next()
+            .map(|vout| {
+                vout.parse()
+                    .map_err(|_| {
+                        ParseError::WrongVout(ParseContext::with(s, vout))
+                    })
+                    .and_then(|vout| check_vout(vout, MAX_VOUT))
+                    .map(Vout::from)
+            })
+            .transpose()?;
+        if let Some(part) = parts.next() {
+            return Err(ParseError::WrongStructure(ParseContext::with(
+                s, part,
+            )));
+        }
+        Ok(SealTemplate { method, vout_hint })
+    }
+}
+
+impl Display for SealTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:?", self.method)?;
+        if let Some(vout) = self.vout_hint {
+            write!(f, ":{}", vout)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::Txid;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+    use crate::txout::blind::RevealedSeal;
+    use crate::txout::{SealDefinition, WitnessVoutSeal};
+
+    fn txid() -> Txid {
+        Txid::from_hex(
+            "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn str_roundtrip() {
+        for (s, template) in [
+            ("tapret1st:?", SealTemplate::new(CloseMethod::TapretFirst)),
+            (
+                "opret1st:?:2",
+                SealTemplate::with_vout_hint(CloseMethod::OpretFirst, 2),
+            ),
+        ] {
+            assert_eq!(SealTemplate::from_str(s), Ok(template));
+            assert_eq!(template.to_string(), s);
+        }
+        assert_eq!(
+            SealTemplate::from_str("tapret:?:0"),
+            Ok(SealTemplate::with_vout_hint(CloseMethod::TapretFirst, 0))
+        );
+    }
+
+    #[test]
+    fn str_errors() {
+        assert_eq!(SealTemplate::from_str(""), Err(ParseError::MethodRequired));
+        assert!(matches!(
+            SealTemplate::from_str("tapret2nd:?"),
+            Err(ParseError::WrongMethod(_))
+        ));
+        assert!(matches!(
+            SealTemplate::from_str("tapret1st:?:x"),
+            Err(ParseError::WrongVout(ctx)) if ctx.offset == 12
+        ));
+        for s in ["tapret1st", "tapret1st:~:2", "tapret1st:?:2:3"] {
+            assert!(matches!(
+                SealTemplate::from_str(s),
+                Err(ParseError::WrongStructure(_))
+            ));
+        }
+        let s = format!("tapret1st:{}:2", txid());
+        assert!(matches!(
+            SealTemplate::from_str(&s),
+            Err(ParseError::WrongStructure(ctx)) if ctx.offset == 10
+        ));
+
+        // Templates are not seals
+        assert!(ExplicitSeal::from_str("tapret1st:?:2").is_err());
+        assert!(SealDefinition::from_str("tapret1st:?:2").is_err());
+    }
+
+    #[test]
+    fn materialize() {
+        let template =
+            SealTemplate::with_vout_hint(CloseMethod::TapretFirst, 2);
+        let outpoint = OutPoint::new(txid(), 5);
+        assert_eq!(
+            template.materialize(outpoint),
+            ExplicitSeal::new(CloseMethod::TapretFirst, outpoint)
+        );
+        assert_eq!(
+            template.materialize_witness(3u32),
+            ExplicitSeal::new_witness(CloseMethod::TapretFirst, 3)
+        );
+        assert_eq!(
+            SealTemplate::new(CloseMethod::OpretFirst).materialize(outpoint),
+            ExplicitSeal::new(CloseMethod::OpretFirst, outpoint)
+        );
+    }
+
+    #[test]
+    fn strict_encoding() {
+        for template in [
+            SealTemplate::new(CloseMethod::TapretFirst),
+            SealTemplate::with_vout_hint(CloseMethod::OpretFirst, 2),
+        ] {
+            let data = strict_serialize(&template).unwrap();
+            assert_eq!(strict_deserialize::<SealTemplate>(&data), Ok(template));
+
+            // Template data can't be decoded as seals
+            assert!(strict_deserialize::<ExplicitSeal>(&data).is_err());
+            assert!(strict_deserialize::<SealDefinition>(&data).is_err());
+            assert!(strict_deserialize::<WitnessVoutSeal>(&data).is_err());
+            assert!(strict_deserialize::<RevealedSeal>(&data).is_err());
+        }
+
+        // Seal data can't be decoded as templates
+        for seal in [
+            ExplicitSeal::new_witness(CloseMethod::TapretFirst, 2),
+            ExplicitSeal::new(
+                CloseMethod::OpretFirst,
+                OutPoint::new(txid(), 2),
+            ),
+        ] {
+            let data = strict_serialize(&seal).unwrap();
+            assert!(strict_deserialize::<SealTemplate>(&data).is_err());
+        }
+        let data =
+            strict_serialize(&WitnessVoutSeal::new(CloseMethod::OpretFirst, 2))
+                .unwrap();
+        assert!(strict_deserialize::<SealTemplate>(&data).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let template =
+            SealTemplate::with_vout_hint(CloseMethod::TapretFirst, 2);
+        let json = serde_json::to_string(&template).unwrap();
+        assert_eq!(json, r#"{"method":"TapretFirst","vout_hint":2}"#);
+        assert_eq!(
+            serde_json::from_str::<SealTemplate>(&json).unwrap(),
+            template
+        );
+        assert!(serde_json::from_str::<ExplicitSeal>(&json).is_err());
+    }
+}
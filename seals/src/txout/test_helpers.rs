@@ -0,0 +1,179 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Canonical seal test vectors.
+//!
+//! Commitments to the seal definitions are consensus-critical for the client-
+//! side-validated protocols, so the vectors (and their `commit_serialize`
+//! output) must never change. The module is available to the downstream crates
+//! with `test-utils` feature.
+
+use std::str::FromStr;
+
+use bitcoin::Txid;
+
+use super::blind::RevealedSeal;
+use super::{CloseMethod, ExplicitSeal};
+
+/// Txid used by the test vectors.
+pub const VECTOR_TXID: &str =
+    "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+
+/// Hex-encoded `commit_serialize` output for each of the [`explicit_seals`]
+/// vectors, in the same order.
+pub const EXPLICIT_COMMITMENTS: [&str; 4] = [
+    "000139e8fad1a86579d64e8cdce473b71f5520d8dfc97107d6a2e2192606c1a56c640000\
+     0000",
+    "010139e8fad1a86579d64e8cdce473b71f5520d8dfc97107d6a2e2192606c1a56c640200\
+     0000",
+    "000015000000",
+    "01010000000000000000000000000000000000000000000000000000000000000000ffff\
+     ffff",
+];
+
+/// Hex-encoded `commit_serialize` output for each of the [`revealed_seals`]
+/// vectors, in the same order. Revealed seals commit to their concealed form
+/// produced with [`RevealedSeal::conceal`].
+pub const REVEALED_COMMITMENTS: [&str; 5] = [
+    "c3aa1bb320a8a6c893ab7de1ee6e93ae5992b85802b1e003157d5ad69d0a60fa",
+    "08a492ab41942a4f7021c261ac249b895328bb77846f9965ff57153405b4b777",
+    "d0ca74b5e76bc9e1211da6508b01f13d669eb01e02347ce188c861af4e468c99",
+    "ff8faab69de27d3a0a58190109164777d9205b1a503e175ae3971bdb542a09c0",
+    "9294910a699f5a5ea3ca918dac53ccfad431413d60acb827f855952a62f74adc",
+];
+
+/// Hex-encoded concealed forms of the [`revealed_seals`] vectors produced with
+/// the legacy derivation ([`RevealedSeal::conceal_legacy`]) used by the
+/// commitments of the previous versions of the library.
+pub const LEGACY_REVEALED_COMMITMENTS: [&str; 5] = [
+    "cd95374f569b88cd57de357edc779696a81faf7834f53c16492dfdce8e9b4724",
+    "1ef42ef4ded9fa24c0398fac48bdda568258c7d5403c4a5a20c7423f6b76ec98",
+    "c4f781e6f56c01d06dc4141e67356c3b083262b87bf873b3bf2a136f147b460b",
+    "4971982029a10ccd20c116a57370754f5bd495ff258e914d46d5c2c1c872bb3e",
+    "8d04cd1ab83f03665e4ae72df5cfdb5a5e72fd7de0a121491b3ebe3f872db90c",
+];
+
+/// Returns txid used by the test vectors.
+pub fn vector_txid() -> Txid {
+    Txid::from_str(VECTOR_TXID).expect("hardcoded txid")
+}
+
+/// Returns explicit seal test vectors, covering both close methods, seals
+/// without txid and the maximal vout value.
+pub fn explicit_seals() -> [ExplicitSeal; 4] {
+    [
+        ExplicitSeal::with(CloseMethod::OpretFirst, Some(vector_txid()), 0u32),
+        ExplicitSeal::with(CloseMethod::TapretFirst, Some(vector_txid()), 2u32),
+        ExplicitSeal::with(CloseMethod::OpretFirst, None, 21u32),
+        ExplicitSeal::with(
+            CloseMethod::TapretFirst,
+            Some(Txid::default()),
+            u32::MAX,
+        ),
+    ]
+}
+
+/// Returns revealed seal test vectors, covering both close methods, seals
+/// without txid, the maximal vout and the minimal and maximal blinding values.
+pub fn revealed_seals() -> [RevealedSeal; 5] {
+    [
+        RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            txid: Some(vector_txid()),
+            vout: 0u32.into(),
+            blinding: 0,
+        },
+        RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(vector_txid()),
+            vout: 2u32.into(),
+            blinding: 54683969839,
+        },
+        RevealedSeal {
+            method: CloseMethod::OpretFirst,
+            txid: None,
+            vout: 21u32.into(),
+            blinding: 54683969839,
+        },
+        RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(Txid::default()),
+            vout: u32::MAX.into(),
+            blinding: 54683969839,
+        },
+        RevealedSeal {
+            method: CloseMethod::TapretFirst,
+            txid: Some(vector_txid()),
+            vout: 2u32.into(),
+            blinding: u64::MAX,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::ToHex;
+    use commit_verify::{CommitConceal, CommitEncode};
+
+    use super::*;
+
+    #[test]
+    fn explicit_commitments() {
+        for (seal, commitment) in
+            explicit_seals().iter().zip(EXPLICIT_COMMITMENTS)
+        {
+            assert_eq!(seal.commit_serialize().to_hex(), commitment);
+        }
+    }
+
+    #[test]
+    fn revealed_commitments() {
+        for (seal, commitment) in
+            revealed_seals().iter().zip(REVEALED_COMMITMENTS)
+        {
+            assert_eq!(seal.commit_serialize().to_hex(), commitment);
+            // Revealed seals commit to their concealed form
+            assert_eq!(
+                seal.commit_conceal().to_byte_array().to_hex(),
+                commitment
+            );
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn legacy_commitments() {
+        for (seal, commitment) in
+            revealed_seals().iter().zip(LEGACY_REVEALED_COMMITMENTS)
+        {
+            let concealed = seal.conceal_legacy();
+            assert_eq!(concealed.to_byte_array().to_hex(), commitment);
+            assert!(concealed.verify_legacy_reveal(seal));
+            assert!(!concealed.verify_reveal(seal));
+        }
+    }
+
+    #[test]
+    fn vectors_distinct() {
+        let revealed = revealed_seals();
+        for (no, seal) in revealed.iter().enumerate() {
+            assert!(!revealed[no + 1..].contains(seal));
+        }
+        let mut commitments = REVEALED_COMMITMENTS.to_vec();
+        commitments.sort_unstable();
+        commitments.dedup();
+        assert_eq!(commitments.len(), REVEALED_COMMITMENTS.len());
+    }
+}
@@ -0,0 +1,198 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Candidate seal definitions over transaction outputs.
+
+use std::iter::Enumerate;
+use std::slice;
+
+use bitcoin::{Transaction, TxOut, Txid};
+
+use crate::txout::{CloseMethod, ExplicitSeal};
+
+/// Extension trait for bitcoin transactions producing candidate seal
+/// definitions over their outputs.
+pub trait TxExt {
+    /// Returns iterator over seals defined with the given closing `method`
+    /// over each of the transaction outputs, in the output order.
+    fn seals(&self, method: CloseMethod) -> TxSeals<'_>;
+
+    /// Returns seal defined with the given closing `method` over the output
+    /// number `vout`, or `None` if the transaction has no such output.
+    fn seal(&self, method: CloseMethod, vout: u32) -> Option<ExplicitSeal>;
+
+    /// Returns iterator over seals defined with the given closing `method`
+    /// over those of the transaction outputs which can be spent by a
+    /// transaction hosting the `method` commitment, as detected by
+    /// [`CloseMethod::can_host`]. OP_RETURN outputs are always skipped, since
+    /// they can't be spent and the seal would never be closed.
+    fn sealable_outputs(&self, method: CloseMethod) -> TxSeals<'_>;
+}
+
+impl TxExt for Transaction {
+    #[inline]
+    fn seals(&self, method: CloseMethod) -> TxSeals<'_> {
+        TxSeals::with(self, method, false)
+    }
+
+    fn seal(&self, method: CloseMethod, vout: u32) -> Option<ExplicitSeal> {
+        if vout as usize >= self.output.len() {
+            return None;
+        }
+        Some(ExplicitSeal::with(method, Some(self.txid()), vout))
+    }
+
+    #[inline]
+    fn sealable_outputs(&self, method: CloseMethod) -> TxSeals<'_> {
+        TxSeals::with(self, method, true)
+    }
+}
+
+/// Iterator over candidate seals produced by [`TxExt::seals`] and
+/// [`TxExt::sealable_outputs`].
+#[derive(Clone, Debug)]
+pub struct TxSeals<'tx> {
+    txid: Txid,
+    method: CloseMethod,
+    sealable_only: bool,
+    outputs: Enumerate<slice::Iter<'tx, TxOut>>,
+}
+
+impl<'tx> TxSeals<'tx> {
+    fn with(
+        tx: &'tx Transaction,
+        method: CloseMethod,
+        sealable_only: bool,
+    ) -> TxSeals<'tx> {
+        TxSeals {
+            txid: tx.txid(),
+            method,
+            sealable_only,
+            outputs: tx.output.iter().enumerate(),
+        }
+    }
+
+    fn is_sealable(&self, txout: &TxOut) -> bool {
+        !txout.script_pubkey.is_op_return()
+            && self.method.can_host(&txout.script_pubkey)
+    }
+}
+
+impl<'tx> Iterator for TxSeals<'tx> {
+    type Item = ExplicitSeal;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (vout, txout) = self.outputs.next()?;
+            if self.sealable_only && !self.is_sealable(txout) {
+                continue;
+            }
+            return Some(ExplicitSeal::with(
+                self.method,
+                Some(self.txid),
+                vout as u32,
+            ));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.outputs.size_hint();
+        (if self.sealable_only { 0 } else { min }, max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{XOnlyPublicKey, SECP256K1};
+    use bitcoin::{OutPoint, PubkeyHash, Script, TxIn, WPubkeyHash};
+
+    use super::*;
+
+    fn tx() -> Transaction {
+        let internal_key = XOnlyPublicKey::from_slice(&[
+            0xc5, 0xf9, 0x34, 0x79, 0x09, 0x3e, 0x2b, 0x8f, 0x72, 0x4a, 0x79,
+            0x84, 0x4c, 0xc1, 0x09, 0x28, 0xdd, 0x44, 0xe9, 0xa3, 0x90, 0xb5,
+            0x39, 0x84, 0x3f, 0xb8, 0x3f, 0xbf, 0x84, 0x27, 0x23, 0xf3,
+        ])
+        .unwrap();
+        let txout = |script_pubkey| TxOut {
+            value: 1000,
+            script_pubkey,
+        };
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::hash(b"prevout"), 0),
+                ..default!()
+            }],
+            output: vec![
+                txout(Script::new_v1_p2tr(SECP256K1, internal_key, None)),
+                txout(Script::new_op_return(&[0u8; 32])),
+                txout(Script::new_v0_p2wpkh(&WPubkeyHash::hash(b"change"))),
+                txout(Script::new_p2pkh(&PubkeyHash::hash(b"legacy"))),
+                txout(Script::from(vec![0x51])),
+            ],
+        }
+    }
+
+    fn vouts(seals: TxSeals) -> Vec<u32> {
+        seals.map(|seal| seal.vout.into_u32()).collect()
+    }
+
+    #[test]
+    fn seals() {
+        let tx = tx();
+        let txid = tx.txid();
+        for method in CloseMethod::iter() {
+            let seals = tx.seals(method).collect::<Vec<_>>();
+            assert_eq!(seals.len(), tx.output.len());
+            for (vout, seal) in seals.into_iter().enumerate() {
+                assert_eq!(
+                    seal,
+                    ExplicitSeal::with(method, Some(txid), vout as u32)
+                );
+                assert_eq!(tx.seal(method, vout as u32), Some(seal));
+            }
+            assert_eq!(tx.seal(method, 5), None);
+            assert_eq!(tx.seal(method, u32::MAX), None);
+        }
+        assert_eq!(tx.seals(CloseMethod::OpretFirst).size_hint(), (5, Some(5)));
+    }
+
+    #[test]
+    fn sealable_outputs() {
+        let tx = tx();
+        assert_eq!(vouts(tx.sealable_outputs(CloseMethod::TapretFirst)), [0]);
+        assert_eq!(vouts(tx.sealable_outputs(CloseMethod::OpretFirst)), [
+            0, 2, 3
+        ]);
+        assert!(tx
+            .sealable_outputs(CloseMethod::OpretFirst)
+            .all(|seal| seal.txid == Some(tx.txid())
+                && seal.method == CloseMethod::OpretFirst));
+
+        let empty = Transaction {
+            output: vec![],
+            ..tx
+        };
+        assert_eq!(empty.seals(CloseMethod::TapretFirst).next(), None);
+        assert_eq!(
+            empty.sealable_outputs(CloseMethod::OpretFirst).next(),
+            None
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! URI representation of the TxOut seals using `seal:` scheme.
+
+use std::borrow::Cow;
+
+use super::{blind, explicit};
+
+/// URI scheme used for representing seals inside URIs.
+pub const SEAL_URI_SCHEME: &str = "seal";
+
+/// Errors specific to the URI syntax, converted into the parse errors of the
+/// specific seal types.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(super) enum UriError {
+    SchemeRequired,
+    WrongPercentEncoding,
+}
+
+impl From<UriError> for explicit::ParseError {
+    fn from(err: UriError) -> Self {
+        match err {
+            UriError::SchemeRequired => explicit::ParseError::UriSchemeRequired,
+            UriError::WrongPercentEncoding => {
+                explicit::ParseError::WrongPercentEncoding
+            }
+        }
+    }
+}
+
+impl From<UriError> for blind::ParseError {
+    fn from(err: UriError) -> Self {
+        match err {
+            UriError::SchemeRequired => blind::ParseError::UriSchemeRequired,
+            UriError::WrongPercentEncoding => {
+                blind::ParseError::WrongPercentEncoding
+            }
+        }
+    }
+}
+
+/// Strips `seal:` scheme (matched case-insensitively, as required by
+/// RFC 3986) from the URI, returning its path part.
+pub(super) fn strip_scheme(uri: &str) -> Result<&str, UriError> {
+    let (scheme, path) = uri.split_once(':').ok_or(UriError::SchemeRequired)?;
+    if !scheme.eq_ignore_ascii_case(SEAL_URI_SCHEME) {
+        return Err(UriError::SchemeRequired);
+    }
+    Ok(path)
+}
+
+/// Decodes percent-encoded URI path segment. Segments without `%` characters
+/// are returned without allocation.
+pub(super) fn percent_decode(segment: &str) -> Result<Cow<'_, str>, UriError> {
+    if !segment.contains('%') {
+        return Ok(Cow::Borrowed(segment));
+    }
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut pos = 0usize;
+    while pos < bytes.len() {
+        if bytes[pos] != b'%' {
+            decoded.push(bytes[pos]);
+            pos += 1;
+            continue;
+        }
+        let hex = bytes
+            .get(pos + 1..pos + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .ok_or(UriError::WrongPercentEncoding)?;
+        let hex = std::str::from_utf8(hex)
+            .map_err(|_| UriError::WrongPercentEncoding)?;
+        decoded.push(
+            u8::from_str_radix(hex, 16)
+                .map_err(|_| UriError::WrongPercentEncoding)?,
+        );
+        pos += 3;
+    }
+    String::from_utf8(decoded)
+        .map(Cow::Owned)
+        .map_err(|_| UriError::WrongPercentEncoding)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scheme() {
+        assert_eq!(strip_scheme("seal:path"), Ok("path"));
+        assert_eq!(strip_scheme("SEAL:path"), Ok("path"));
+        assert_eq!(strip_scheme("seal:"), Ok(""));
+        assert_eq!(strip_scheme("bitcoin:path"), Err(UriError::SchemeRequired));
+        assert_eq!(strip_scheme("path"), Err(UriError::SchemeRequired));
+        assert_eq!(strip_scheme(":path"), Err(UriError::SchemeRequired));
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("tapret1st"), Ok(Cow::Borrowed("tapret1st")));
+        assert_eq!(percent_decode("%7E"), Ok(Cow::Owned(s!("~"))));
+        assert_eq!(
+            percent_decode("tap%72et1st"),
+            Ok(Cow::Owned(s!("tapret1st")))
+        );
+        assert_eq!(percent_decode("%"), Err(UriError::WrongPercentEncoding));
+        assert_eq!(percent_decode("%7"), Err(UriError::WrongPercentEncoding));
+        assert_eq!(percent_decode("%+7"), Err(UriError::WrongPercentEncoding));
+        assert_eq!(percent_decode("%zz"), Err(UriError::WrongPercentEncoding));
+        assert_eq!(percent_decode("%FF"), Err(UriError::WrongPercentEncoding));
+        assert_eq!(percent_decode("%C3%A9"), Ok(Cow::Owned(s!("é"))));
+    }
+}
@@ -0,0 +1,870 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use bitcoin::{Transaction, Txid};
+use commit_verify::lnpbp4;
+use dbc::tapret::TapretError;
+use dbc::{Anchor, Proof};
+
+use crate::txout::{
+    CloseError, CloseMethod, TxoSeal, VerifyError, WitnessCheckError,
+};
+
+/// Witness of the seal closing: id of the witness transaction and the proof of
+/// the deterministic bitcoin commitment it contains.
+///
+/// Witness can be verified with the seal definition, the message and the
+/// published witness transaction only.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[derive(StrictEncode, StrictDecode)]
+pub struct Witness {
+    /// Witness transaction id.
+    pub txid: Txid,
+
+    /// Proof of the deterministic bitcoin commitment.
+    pub proof: Proof,
+}
+
+impl<L> From<Anchor<L>> for Witness
+where
+    L: lnpbp4::Proof,
+{
+    fn from(anchor: Anchor<L>) -> Self {
+        Witness {
+            txid: anchor.txid,
+            proof: anchor.dbc_proof,
+        }
+    }
+}
+
+fn proof_method(proof: &Proof) -> Option<CloseMethod> {
+    match proof {
+        Proof::OpretFirst(_) => Some(CloseMethod::OpretFirst),
+        Proof::TapretFirst(_) => Some(CloseMethod::TapretFirst),
+        _ => None,
+    }
+}
+
+impl Witness {
+    /// Returns closing method used by the commitment proof, or `None` if the
+    /// proof type is not known to this version of the library.
+    #[inline]
+    pub fn method(&self) -> Option<CloseMethod> { proof_method(&self.proof) }
+
+    /// Checks that the witness transaction closes all of the provided seals,
+    /// i.e. that the seals use the same closing method as the commitment
+    /// proof and that the transaction spends their outpoints, and that the
+    /// transaction commits to the message.
+    ///
+    /// Each seal passes through the same checks as with
+    /// [`verify_seal_closure`]; the transaction not committing to the message
+    /// results in [`VerifyError::InvalidCommitment`]. At least a single seal
+    /// must be provided, otherwise the verification fails with
+    /// [`VerifyError::NoSeals`].
+    #[inline]
+    pub fn verify<'seal, Seal>(
+        &self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        msg: &lnpbp4::CommitmentHash,
+        tx: &Transaction,
+    ) -> Result<(), VerifyError>
+    where
+        Seal: TxoSeal + 'seal,
+    {
+        self.verify_with(seals, msg, tx, VerifyOpts::default())
+    }
+
+    /// Verifies the witness like [`Witness::verify`] using the provided
+    /// verification options. Options requiring confirmations of the witness
+    /// transaction are rejected with [`VerifyError::ConfirmationsUnchecked`].
+    pub fn verify_with<'seal, Seal>(
+        &self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        msg: &lnpbp4::CommitmentHash,
+        tx: &Transaction,
+        opts: VerifyOpts,
+    ) -> Result<(), VerifyError>
+    where
+        Seal: TxoSeal + 'seal,
+    {
+        opts.check_unconfirmed()?;
+        if tx.txid() != self.txid {
+            return Err(VerifyError::WitnessTxUnknown(self.txid));
+        }
+
+        let mut method = None;
+        for seal in seals {
+            // All seals must have the same closing method
+            match method {
+                Some(method) if method != seal.method() => {
+                    return Err(VerifyError::InconsistentCloseMethod)
+                }
+                Some(_) => {}
+                None => method = Some(seal.method()),
+            }
+            check_closing(seal, &self.proof, tx, self.txid, opts)?;
+        }
+        let method = method.ok_or(VerifyError::NoSeals)?;
+
+        // Verify DBC with the giving closing method
+        check_commitment(method, self.proof.verify(msg, tx.clone()))
+    }
+}
+
+/// Checks that the commitment `proof` uses the seal closing method, which is
+/// done before verifying the proof itself.
+pub(super) fn check_method(
+    seal: &impl TxoSeal,
+    proof: &Proof,
+) -> Result<(), VerifyError> {
+    match proof_method(proof) {
+        None => Err(VerifyError::UnsupportedProof),
+        Some(_) if proof.matches_method(seal.method()) => Ok(()),
+        Some(method) => Err(VerifyError::MethodMismatch {
+            seal: seal.method(),
+            proof: method,
+        }),
+    }
+}
+
+/// Options of the seal closing verification.
+///
+/// Default options perform all structural checks and do not require any
+/// confirmations of the witness transaction.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct VerifyOpts {
+    /// Skips sanity checks of the witness transaction with
+    /// [`check_witness_tx`], which may be required for exotic transactions
+    /// constructed outside of bitcoin consensus rules.
+    pub allow_unchecked: bool,
+
+    /// Minimal number of confirmations of the witness transaction.
+    ///
+    /// Confirmations can be checked only with a transaction resolver (see
+    /// [`crate::txout::TxoProtocol::verify_seal_closure_with`] and
+    /// [`crate::txout::seal_status_with`]); verification functions without
+    /// resolver fail with [`VerifyError::ConfirmationsUnchecked`] if the
+    /// options require any confirmations.
+    pub min_confirmations: u32,
+
+    /// Accepts seal closings by unconfirmed transactions (for instance, from
+    /// the mempool). If not set, the witness transaction must have at least a
+    /// single confirmation even if [`VerifyOpts::min_confirmations`] is zero.
+    pub allow_mempool: bool,
+}
+
+impl Default for VerifyOpts {
+    fn default() -> Self {
+        VerifyOpts {
+            allow_unchecked: false,
+            min_confirmations: 0,
+            allow_mempool: true,
+        }
+    }
+}
+
+impl VerifyOpts {
+    /// Returns number of confirmations required by the options.
+    pub fn required_confirmations(&self) -> u32 {
+        match self.allow_mempool {
+            true => self.min_confirmations,
+            false => self.min_confirmations.max(1),
+        }
+    }
+
+    /// Checks that the witness transaction with `confirmations` meets the
+    /// confirmation policy.
+    pub fn check_confirmations(
+        &self,
+        confirmations: u32,
+    ) -> Result<(), VerifyError> {
+        let need = self.required_confirmations();
+        if confirmations < need {
+            return Err(VerifyError::InsufficientConfirmations {
+                have: confirmations,
+                need,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fails with [`VerifyError::ConfirmationsUnchecked`] if the options
+    /// require confirmations, which can't be checked by the verification
+    /// functions without access to the transaction resolver.
+    pub(super) fn check_unconfirmed(&self) -> Result<(), VerifyError> {
+        match self.required_confirmations() {
+            0 => Ok(()),
+            need => Err(VerifyError::ConfirmationsUnchecked(need)),
+        }
+    }
+
+    /// Returns the options without the confirmation policy, for the
+    /// verifiers checking confirmations with the transaction resolver after
+    /// the structural verification.
+    pub(super) fn without_confirmations(self) -> VerifyOpts {
+        VerifyOpts {
+            min_confirmations: 0,
+            allow_mempool: true,
+            ..self
+        }
+    }
+}
+
+/// Checks that the witness transaction can close a seal, i.e. that it has
+/// inputs and outputs and is not a coinbase transaction, which can't spend
+/// any existing output. The check is performed before the commitment
+/// verification, which otherwise produces confusing errors for malformed
+/// transactions.
+pub fn check_witness_tx(tx: &Transaction) -> Result<(), WitnessCheckError> {
+    if tx.input.is_empty() {
+        return Err(WitnessCheckError::NoInputs(tx.txid()));
+    }
+    if tx.is_coin_base() {
+        return Err(WitnessCheckError::Coinbase(tx.txid()));
+    }
+    if tx.output.is_empty() {
+        return Err(WitnessCheckError::NoOutputs(tx.txid()));
+    }
+    Ok(())
+}
+
+/// Performs [`check_witness_tx`] checks, additionally checking that the
+/// witness transaction has the `expected` id.
+pub fn check_witness_txid(
+    tx: &Transaction,
+    expected: Txid,
+) -> Result<(), WitnessCheckError> {
+    check_witness_tx(tx)?;
+    let found = tx.txid();
+    if found != expected {
+        return Err(WitnessCheckError::TxidMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Verifies that the seal was closed over the message `msg` by the witness
+/// transaction `witness_tx`, i.e. that:
+/// - the commitment `proof` uses the seal closing method;
+/// - the witness transaction spends the seal outpoint; seals defined over the
+///   witness transaction outputs (with `~` in place of txid) are resolved using
+///   the id of the witness transaction itself;
+/// - the witness transaction contains commitment to the message described by
+///   the `proof`.
+///
+/// Seal outpoint not spent by the witness transaction results in
+/// [`VerifyError::WitnessNotSpendingSeal`] even if the seal is not closed
+/// yet. Use [`crate::txout::TxoProtocol::verify_seal_closure`] to distinguish
+/// such seals and to additionally check existence of the seal output with a
+/// transaction resolver.
+///
+/// Malformed witness transactions are rejected with
+/// [`VerifyError::WitnessCheck`] before the verification, see
+/// [`check_witness_tx`]; use [`verify_seal_closure_with`] to skip the checks.
+#[inline]
+pub fn verify_seal_closure(
+    seal: &impl TxoSeal,
+    msg: &lnpbp4::CommitmentHash,
+    proof: &Proof,
+    witness_tx: &Transaction,
+) -> Result<(), VerifyError> {
+    verify_seal_closure_with(
+        seal,
+        msg,
+        proof,
+        witness_tx,
+        VerifyOpts::default(),
+    )
+}
+
+/// Verifies seal closing like [`verify_seal_closure`] using the provided
+/// verification options. Options requiring confirmations of the witness
+/// transaction are rejected with [`VerifyError::ConfirmationsUnchecked`]; use
+/// [`crate::txout::TxoProtocol::verify_seal_closure_with`] to check them.
+pub fn verify_seal_closure_with(
+    seal: &impl TxoSeal,
+    msg: &lnpbp4::CommitmentHash,
+    proof: &Proof,
+    witness_tx: &Transaction,
+    opts: VerifyOpts,
+) -> Result<(), VerifyError> {
+    opts.check_unconfirmed()?;
+    check_closing(seal, proof, witness_tx, witness_tx.txid(), opts)?;
+    check_commitment(seal.method(), proof.verify(msg, witness_tx.clone()))
+}
+
+/// Performs all checks of the seal closing by the witness transaction with
+/// id `txid` preceding the commitment verification: sanity checks of the
+/// witness transaction (unless allowed by `opts`), the closing method of the
+/// `proof` and spending of the seal outpoint.
+///
+/// The checks are shared by all of the seal closing verification functions,
+/// which must produce the same results.
+pub(super) fn check_closing(
+    seal: &impl TxoSeal,
+    proof: &Proof,
+    witness_tx: &Transaction,
+    txid: Txid,
+    opts: VerifyOpts,
+) -> Result<(), VerifyError> {
+    if !opts.allow_unchecked {
+        check_witness_tx(witness_tx)?;
+    }
+    check_method(seal, proof)?;
+
+    let outpoint = seal.outpoint_or(txid);
+    if !witness_tx
+        .input
+        .iter()
+        .any(|txin| txin.previous_output == outpoint)
+    {
+        return Err(VerifyError::WitnessNotSpendingSeal {
+            witness: txid,
+            seal: outpoint,
+        });
+    }
+    Ok(())
+}
+
+/// Converts result of the commitment proof verification ([`Proof::verify`])
+/// into the result of the closing verification for seals with `method`.
+pub(super) fn check_commitment(
+    method: CloseMethod,
+    verified: Result<bool, TapretError>,
+) -> Result<(), VerifyError> {
+    if !verified? {
+        return Err(VerifyError::InvalidCommitment(method));
+    }
+    Ok(())
+}
+
+/// Seal closing procedure, implemented for all transaction output-based seals.
+pub trait CloseSeal: TxoSeal {
+    /// Closes the seal over the message `msg` with the transaction `tx`,
+    /// producing [`Witness`].
+    ///
+    /// The procedure checks that the transaction passes [`check_witness_tx`]
+    /// sanity checks, that it spends the seal outpoint and
+    /// that it contains the commitment to the message described by `proof`,
+    /// which must use the seal closing method. Opret commitments do not
+    /// require extra-transaction data and are located in the first OP_RETURN
+    /// output; tapret commitments are verified using the provided
+    /// [`dbc::tapret::TapretProof`].
+    fn close(
+        &self,
+        tx: &Transaction,
+        msg: &lnpbp4::CommitmentHash,
+        proof: Proof,
+    ) -> Result<Witness, CloseError> {
+        check_witness_tx(tx)?;
+        let txid = tx.txid();
+        let witness = Witness { txid, proof };
+
+        match witness.method() {
+            None => return Err(CloseError::UnsupportedProof),
+            Some(method) if method != self.method() => {
+                return Err(CloseError::MethodMismatch {
+                    seal: self.method(),
+                    proof: method,
+                })
+            }
+            _ => {}
+        }
+
+        let outpoint = self.outpoint_or(txid);
+        if !tx.input.iter().any(|txin| txin.previous_output == outpoint) {
+            return Err(CloseError::NotClosingSeal(txid, outpoint));
+        }
+
+        if !witness.proof.verify(msg, tx.clone())? {
+            return Err(CloseError::NoCommitment(txid));
+        }
+
+        Ok(witness)
+    }
+}
+
+impl<Seal> CloseSeal for Seal where Seal: TxoSeal {}
+
+#[cfg(test)]
+mod test {
+    use amplify::Wrapper;
+    use bitcoin::hashes::{sha256t, Hash};
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use dbc::opret::OpretProof;
+    use strict_encoding::{strict_deserialize, strict_serialize};
+
+    use super::*;
+    use crate::txout::ExplicitSeal;
+
+    fn msg(byte: u8) -> lnpbp4::CommitmentHash {
+        lnpbp4::CommitmentHash::from_inner(sha256t::Hash::from_inner(
+            [byte; 32],
+        ))
+    }
+
+    fn closing_tx(
+        outpoint: OutPoint,
+        msg: &lnpbp4::CommitmentHash,
+    ) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                ..default!()
+            }],
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new(),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: Script::new_op_return(&msg[..]),
+                },
+            ],
+        }
+    }
+
+    fn outpoint() -> OutPoint {
+        OutPoint::new(Txid::hash(b"previous transaction"), 1)
+    }
+
+    #[test]
+    fn close_opret() {
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint());
+        let tx = closing_tx(outpoint(), &msg(1));
+
+        let witness = seal
+            .close(&tx, &msg(1), Proof::OpretFirst(OpretProof))
+            .unwrap();
+        assert_eq!(witness.txid, tx.txid());
+        assert_eq!(witness.method(), Some(CloseMethod::OpretFirst));
+        assert!(matches!(witness.verify([&seal], &msg(1), &tx), Ok(())));
+        assert!(matches!(
+            witness.verify([&seal], &msg(2), &tx),
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+        assert!(matches!(
+            witness.verify::<ExplicitSeal>([], &msg(1), &tx),
+            Err(VerifyError::NoSeals)
+        ));
+
+        let data = strict_serialize(&witness).unwrap();
+        assert_eq!(strict_deserialize::<Witness>(&data).unwrap(), witness);
+
+        assert_eq!(
+            seal.close(&tx, &msg(2), Proof::OpretFirst(OpretProof)),
+            Err(CloseError::NoCommitment(tx.txid()))
+        );
+    }
+
+    #[test]
+    fn close_wrong_tx() {
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint());
+        let other_outpoint = OutPoint::new(outpoint().txid, 2);
+        let tx = closing_tx(other_outpoint, &msg(1));
+        assert_eq!(
+            seal.close(&tx, &msg(1), Proof::OpretFirst(OpretProof)),
+            Err(CloseError::NotClosingSeal(tx.txid(), outpoint()))
+        );
+
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        assert!(matches!(
+            witness.verify([&seal], &msg(1), &tx),
+            Err(VerifyError::WitnessNotSpendingSeal { witness: txid, seal: op })
+                if txid == tx.txid() && op == outpoint()
+        ));
+        let tx2 = closing_tx(outpoint(), &msg(1));
+        assert!(matches!(
+            witness.verify([&seal], &msg(1), &tx2),
+            Err(VerifyError::WitnessTxUnknown(txid)) if txid == tx.txid()
+        ));
+    }
+
+    #[test]
+    fn close_method_mismatch() {
+        let seal = ExplicitSeal::new(CloseMethod::TapretFirst, outpoint());
+        let tx = closing_tx(outpoint(), &msg(1));
+        assert_eq!(
+            seal.close(&tx, &msg(1), Proof::OpretFirst(OpretProof)),
+            Err(CloseError::MethodMismatch {
+                seal: CloseMethod::TapretFirst,
+                proof: CloseMethod::OpretFirst
+            })
+        );
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        assert!(matches!(
+            witness.verify([&seal], &msg(1), &tx),
+            Err(VerifyError::MethodMismatch {
+                seal: CloseMethod::TapretFirst,
+                proof: CloseMethod::OpretFirst
+            })
+        ));
+
+        // Method mismatch is reported before checking the seal outpoints and
+        // the commitment
+        let other = closing_tx(OutPoint::new(Txid::hash(b"other"), 0), &msg(2));
+        let witness = Witness {
+            txid: other.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        assert!(matches!(
+            witness.verify([&seal], &msg(1), &other),
+            Err(VerifyError::MethodMismatch {
+                seal: CloseMethod::TapretFirst,
+                proof: CloseMethod::OpretFirst
+            })
+        ));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &witness.proof, &other),
+            Err(VerifyError::MethodMismatch {
+                seal: CloseMethod::TapretFirst,
+                proof: CloseMethod::OpretFirst
+            })
+        ));
+        let opret = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint());
+        assert!(matches!(
+            witness.verify([&seal, &opret], &msg(1), &other),
+            Err(VerifyError::MethodMismatch { .. })
+        ));
+        let witness = Witness {
+            txid: tx.txid(),
+            proof: Proof::OpretFirst(OpretProof),
+        };
+        assert!(matches!(
+            witness.verify([&opret, &seal], &msg(1), &tx),
+            Err(VerifyError::InconsistentCloseMethod)
+        ));
+    }
+
+    mod regtest {
+        use std::str::FromStr;
+
+        use bitcoin::secp256k1::XOnlyPublicKey;
+        use bitcoin::WPubkeyHash;
+        use dbc::tapret::tapret_commit;
+
+        use super::*;
+
+        fn p2wpkh(seed: &[u8]) -> Script {
+            Script::new_v0_p2wpkh(&WPubkeyHash::hash(seed))
+        }
+
+        pub fn funding_tx() -> Transaction {
+            Transaction {
+                version: 2,
+                lock_time: 101,
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(
+                        Txid::hash(b"regtest coinbase"),
+                        0,
+                    ),
+                    ..default!()
+                }],
+                output: vec![
+                    TxOut {
+                        value: 50_000,
+                        script_pubkey: p2wpkh(b"seal"),
+                    },
+                    TxOut {
+                        value: 4_949_000,
+                        script_pubkey: p2wpkh(b"funding change"),
+                    },
+                ],
+            }
+        }
+
+        pub fn seal_outpoint() -> OutPoint {
+            OutPoint::new(funding_tx().txid(), 0)
+        }
+
+        pub fn witness_tx(host: TxOut) -> Transaction {
+            Transaction {
+                version: 2,
+                lock_time: 102,
+                input: vec![
+                    TxIn {
+                        previous_output: OutPoint::new(
+                            Txid::hash(b"fee input"),
+                            1,
+                        ),
+                        ..default!()
+                    },
+                    TxIn {
+                        previous_output: seal_outpoint(),
+                        ..default!()
+                    },
+                ],
+                output: vec![
+                    TxOut {
+                        value: 20_000,
+                        script_pubkey: p2wpkh(b"payment"),
+                    },
+                    host,
+                    TxOut {
+                        value: 29_000,
+                        script_pubkey: p2wpkh(b"change"),
+                    },
+                ],
+            }
+        }
+
+        pub fn opret_witness_tx(msg: &lnpbp4::CommitmentHash) -> Transaction {
+            witness_tx(TxOut {
+                value: 0,
+                script_pubkey: Script::new_op_return(&msg[..]),
+            })
+        }
+
+        pub fn tapret_witness_tx(
+            msg: &lnpbp4::CommitmentHash,
+        ) -> (Transaction, Proof) {
+            let internal_key = XOnlyPublicKey::from_str(
+                "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+            )
+            .unwrap();
+            let (output_key, proof) = tapret_commit(
+                internal_key,
+                &mut None,
+                msg.into_inner().into_inner(),
+            )
+            .unwrap();
+            let tx = witness_tx(TxOut {
+                value: 1_000,
+                script_pubkey: Script::new_v1_p2tr_tweaked(output_key),
+            });
+            (tx, Proof::TapretFirst(proof))
+        }
+    }
+
+    #[test]
+    fn proof_methods() {
+        let (_, tapret_proof) = regtest::tapret_witness_tx(&msg(1));
+        let proofs = [Proof::OpretFirst(OpretProof), tapret_proof];
+        for proof in &proofs {
+            let method = proof_method(proof).unwrap();
+            assert_eq!(u8::from(method), proof.method() as u8);
+        }
+        for method in CloseMethod::iter() {
+            assert!(proofs
+                .iter()
+                .any(|proof| proof_method(proof) == Some(method)));
+        }
+    }
+
+    #[test]
+    fn seal_closure_opret() {
+        let seal = ExplicitSeal::new(
+            CloseMethod::OpretFirst,
+            regtest::seal_outpoint(),
+        );
+        let tx = regtest::opret_witness_tx(&msg(1));
+        let proof = Proof::OpretFirst(OpretProof);
+
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Ok(())
+        ));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(2), &proof, &tx),
+            Err(VerifyError::InvalidCommitment(CloseMethod::OpretFirst))
+        ));
+
+        let other_seal = ExplicitSeal::new(
+            CloseMethod::OpretFirst,
+            OutPoint::new(seal.txid.unwrap(), 1),
+        );
+        assert!(matches!(
+            verify_seal_closure(&other_seal, &msg(1), &proof, &tx),
+            Err(VerifyError::WitnessNotSpendingSeal { witness: txid, seal: outpoint })
+                if txid == tx.txid() && outpoint == other_seal.outpoint().unwrap()
+        ));
+
+        let witness_seal = ExplicitSeal::with(CloseMethod::OpretFirst, None, 1);
+        assert!(matches!(
+            verify_seal_closure(&witness_seal, &msg(1), &proof, &tx),
+            Err(VerifyError::WitnessNotSpendingSeal { witness: txid, seal: outpoint })
+                if txid == tx.txid() && outpoint == OutPoint::new(txid, 1)
+        ));
+
+        let tapret_seal = ExplicitSeal::new(
+            CloseMethod::TapretFirst,
+            regtest::seal_outpoint(),
+        );
+        assert!(matches!(
+            verify_seal_closure(&tapret_seal, &msg(1), &proof, &tx),
+            Err(VerifyError::MethodMismatch {
+                seal: CloseMethod::TapretFirst,
+                proof: CloseMethod::OpretFirst
+            })
+        ));
+    }
+
+    #[test]
+    fn seal_closure_tapret() {
+        let seal = ExplicitSeal::new(
+            CloseMethod::TapretFirst,
+            regtest::seal_outpoint(),
+        );
+        let (tx, proof) = regtest::tapret_witness_tx(&msg(1));
+
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &tx),
+            Ok(())
+        ));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(2), &proof, &tx),
+            Err(VerifyError::InvalidCommitment(CloseMethod::TapretFirst))
+        ));
+
+        // Opret commitment to the same message is not a tapret commitment
+        let opret_tx = regtest::opret_witness_tx(&msg(1));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &opret_tx),
+            Err(VerifyError::InvalidTapretCommitment(
+                dbc::tapret::TapretError::NoTaprootOutput
+            ))
+        ));
+
+        let opret_seal = ExplicitSeal::new(
+            CloseMethod::OpretFirst,
+            regtest::seal_outpoint(),
+        );
+        assert!(matches!(
+            verify_seal_closure(&opret_seal, &msg(1), &proof, &tx),
+            Err(VerifyError::MethodMismatch {
+                seal: CloseMethod::OpretFirst,
+                proof: CloseMethod::TapretFirst
+            })
+        ));
+    }
+
+    #[test]
+    fn witness_tx_checks() {
+        let tx = closing_tx(outpoint(), &msg(1));
+        assert_eq!(check_witness_tx(&tx), Ok(()));
+        assert_eq!(check_witness_txid(&tx, tx.txid()), Ok(()));
+
+        let mut no_inputs = tx.clone();
+        no_inputs.input.clear();
+        assert_eq!(
+            check_witness_tx(&no_inputs),
+            Err(WitnessCheckError::NoInputs(no_inputs.txid()))
+        );
+
+        let coinbase = closing_tx(OutPoint::null(), &msg(1));
+        assert_eq!(
+            check_witness_tx(&coinbase),
+            Err(WitnessCheckError::Coinbase(coinbase.txid()))
+        );
+
+        let mut no_outputs = tx.clone();
+        no_outputs.output.clear();
+        assert_eq!(
+            check_witness_tx(&no_outputs),
+            Err(WitnessCheckError::NoOutputs(no_outputs.txid()))
+        );
+
+        let other = closing_tx(outpoint(), &msg(2));
+        assert_eq!(
+            check_witness_txid(&tx, other.txid()),
+            Err(WitnessCheckError::TxidMismatch {
+                expected: other.txid(),
+                found: tx.txid()
+            })
+        );
+        // Transaction sanity is checked before its id
+        assert_eq!(
+            check_witness_txid(&coinbase, tx.txid()),
+            Err(WitnessCheckError::Coinbase(coinbase.txid()))
+        );
+    }
+
+    #[test]
+    fn seal_closure_unchecked() {
+        let seal = ExplicitSeal::new(CloseMethod::OpretFirst, outpoint());
+        let proof = Proof::OpretFirst(OpretProof);
+        let unchecked = VerifyOpts {
+            allow_unchecked: true,
+            ..VerifyOpts::default()
+        };
+
+        let coinbase = closing_tx(OutPoint::null(), &msg(1));
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &coinbase),
+            Err(VerifyError::WitnessCheck(WitnessCheckError::Coinbase(txid)))
+                if txid == coinbase.txid()
+        ));
+        assert!(matches!(
+            verify_seal_closure_with(
+                &seal,
+                &msg(1),
+                &proof,
+                &coinbase,
+                unchecked
+            ),
+            Err(VerifyError::WitnessNotSpendingSeal { .. })
+        ));
+        let coinbase_seal =
+            ExplicitSeal::new(CloseMethod::OpretFirst, OutPoint::null());
+        assert_eq!(
+            coinbase_seal.close(&coinbase, &msg(1), proof.clone()),
+            Err(CloseError::WitnessCheck(WitnessCheckError::Coinbase(
+                coinbase.txid()
+            )))
+        );
+
+        let mut no_inputs = closing_tx(outpoint(), &msg(1));
+        no_inputs.input.clear();
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &no_inputs),
+            Err(VerifyError::WitnessCheck(WitnessCheckError::NoInputs(_)))
+        ));
+
+        let mut no_outputs = closing_tx(outpoint(), &msg(1));
+        no_outputs.output.clear();
+        assert!(matches!(
+            verify_seal_closure(&seal, &msg(1), &proof, &no_outputs),
+            Err(VerifyError::WitnessCheck(WitnessCheckError::NoOutputs(_)))
+        ));
+        let witness = Witness {
+            txid: no_outputs.txid(),
+            proof: proof.clone(),
+        };
+        assert!(matches!(
+            witness.verify([&seal], &msg(1), &no_outputs),
+            Err(VerifyError::WitnessCheck(WitnessCheckError::NoOutputs(_)))
+        ));
+
+        let tx = closing_tx(outpoint(), &msg(1));
+        assert!(matches!(
+            verify_seal_closure_with(&seal, &msg(1), &proof, &tx, unchecked),
+            Ok(())
+        ));
+        assert!(!VerifyOpts::default().allow_unchecked);
+    }
+}
@@ -0,0 +1,95 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! WebAssembly bindings for parsing, blinding and concealing TxOut seals
+//! inside browser wallets.
+//!
+//! Errors are returned as [`JsError`] and are thrown as JavaScript exceptions
+//! by the generated bindings.
+
+use std::str::FromStr;
+
+use bitcoin::Txid;
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::txout::blind::RevealedSeal;
+use crate::txout::{CloseMethod, ExplicitSeal};
+
+fn set(object: &Object, key: &str, value: JsValue) -> Result<(), JsError> {
+    Reflect::set(object, &JsValue::from_str(key), &value)
+        .map(|_| ())
+        .map_err(|_| JsError::new("unable to construct JavaScript object"))
+}
+
+/// Parses seal in `method:txid:vout` form into an object with `method`, `txid`
+/// (`null` for the seals over witness transaction outputs) and `vout` fields.
+#[wasm_bindgen]
+pub fn parse_explicit_seal(s: &str) -> Result<JsValue, JsError> {
+    let seal = ExplicitSeal::from_str(s)?;
+    let object = Object::new();
+    set(
+        &object,
+        "method",
+        JsValue::from_str(&seal.method.to_string()),
+    )?;
+    set(
+        &object,
+        "txid",
+        seal.txid
+            .map(|txid| JsValue::from_str(&txid.to_string()))
+            .unwrap_or(JsValue::NULL),
+    )?;
+    set(&object, "vout", JsValue::from(seal.vout.into_u32()))?;
+    Ok(object.into())
+}
+
+/// Constructs revealed seal with a random blinding factor, returning an object
+/// with `seal` field containing seal string in `method:txid:vout#blinding`
+/// form and `blinding` field containing the blinding factor as a `0x`-prefixed
+/// hexadecimal string (it does not fit into JavaScript number).
+///
+/// Missing `txid` defines seal over the witness transaction output. The
+/// entropy is provided by the `crypto.getRandomValues` browser API.
+#[wasm_bindgen]
+pub fn blind_seal(
+    method: &str,
+    txid: Option<String>,
+    vout: u32,
+) -> Result<JsValue, JsError> {
+    let method = CloseMethod::from_str(method)?;
+    let txid = txid.as_deref().map(Txid::from_str).transpose()?;
+    let mut entropy = [0u8; 8];
+    getrandom::getrandom(&mut entropy)?;
+    let seal = ExplicitSeal::with(method, txid, vout)
+        .blind(u64::from_le_bytes(entropy));
+
+    let object = Object::new();
+    set(&object, "seal", JsValue::from_str(&seal.to_string()))?;
+    set(
+        &object,
+        "blinding",
+        JsValue::from_str(&format!("{:#x}", seal.blinding)),
+    )?;
+    Ok(object.into())
+}
+
+/// Conceals revealed seal provided in its JSON representation, returning
+/// string representation of the concealed seal.
+#[wasm_bindgen]
+pub fn conceal(revealed_json: &str) -> Result<String, JsError> {
+    let seal: RevealedSeal = serde_json::from_str(revealed_json)?;
+    Ok(seal.conceal().to_string())
+}
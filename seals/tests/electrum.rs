@@ -0,0 +1,78 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Integration tests against a regtest electrs instance. The tests are run
+//! only if `BP_ELECTRUM_REGTEST` environment variable contains the server URL
+//! (like `tcp://localhost:60401`); `BP_ELECTRUM_REGTEST_TXID` may specify id
+//! of a transaction known to the server.
+
+#![cfg(feature = "electrum")]
+
+use std::env;
+use std::str::FromStr;
+
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Txid};
+use seals::txout::{ElectrumResolver, ElectrumResolverError, TxResolve};
+
+fn resolver() -> Option<ElectrumResolver> {
+    let url = env::var("BP_ELECTRUM_REGTEST").ok()?;
+    Some(ElectrumResolver::connect(&url).expect("electrs is not accessible"))
+}
+
+#[test]
+fn unknown_tx() {
+    let resolver = match resolver() {
+        Some(resolver) => resolver,
+        None => return,
+    };
+    let txid = Txid::hash(b"transaction unknown to the server");
+    assert!(matches!(
+        resolver.resolve_tx(txid),
+        Err(ElectrumResolverError::UnknownTx(id)) if id == txid
+    ));
+    assert!(matches!(
+        resolver.spending_tx(OutPoint::new(txid, 0)),
+        Err(ElectrumResolverError::UnknownTx(id)) if id == txid
+    ));
+}
+
+#[test]
+fn known_tx() {
+    let resolver = match resolver() {
+        Some(resolver) => resolver,
+        None => return,
+    };
+    let txid = match env::var("BP_ELECTRUM_REGTEST_TXID") {
+        Ok(txid) => Txid::from_str(&txid).expect("invalid txid"),
+        Err(_) => return,
+    };
+    let tx = resolver.resolve_tx(txid).unwrap();
+    assert_eq!(tx.txid(), txid);
+    for vout in 0..tx.output.len() as u32 {
+        let outpoint = OutPoint::new(txid, vout);
+        if let Some(spending) = resolver.spending_tx(outpoint).unwrap() {
+            assert!(spending
+                .input
+                .iter()
+                .any(|txin| txin.previous_output == outpoint));
+        }
+    }
+    let outpoint = OutPoint::new(txid, tx.output.len() as u32);
+    assert!(matches!(
+        resolver.spending_tx(outpoint),
+        Err(ElectrumResolverError::UnknownOutput(op)) if op == outpoint
+    ));
+}
@@ -0,0 +1,86 @@
+// BP Core Library implementing LNP/BP specifications & standards related to
+// bitcoin protocol
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License
+// along with this software.
+// If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Headless browser tests for the WebAssembly bindings. Run with
+//! `wasm-pack test --headless --firefox seals --features wasm`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use std::str::FromStr;
+
+use js_sys::{Error, Reflect};
+use seals::txout::blind::RevealedSeal;
+use seals::wasm::{blind_seal, conceal, parse_explicit_seal};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const TXID: &str =
+    "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+
+fn field(object: &JsValue, key: &str) -> JsValue {
+    Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+
+fn message(err: wasm_bindgen::JsError) -> String {
+    let err = Error::from(JsValue::from(err));
+    String::from(err.message())
+}
+
+#[wasm_bindgen_test]
+fn parse() {
+    let seal = parse_explicit_seal(&format!("tapret1st:{}:2", TXID)).unwrap();
+    assert_eq!(field(&seal, "method").as_string().unwrap(), "tapret1st");
+    assert_eq!(field(&seal, "txid").as_string().unwrap(), TXID);
+    assert_eq!(field(&seal, "vout").as_f64().unwrap(), 2.0);
+
+    let seal = parse_explicit_seal("opret1st:~:5").unwrap();
+    assert!(field(&seal, "txid").is_null());
+
+    let err = parse_explicit_seal("tapret2nd:~:5").unwrap_err();
+    assert!(message(err).contains("tapret2nd"));
+}
+
+#[wasm_bindgen_test]
+fn blind_and_conceal() {
+    let blinded = blind_seal("opret1st", Some(TXID.to_owned()), 3).unwrap();
+    let seal = field(&blinded, "seal").as_string().unwrap();
+    let blinding = field(&blinded, "blinding").as_string().unwrap();
+    let revealed = RevealedSeal::from_str(&seal).unwrap();
+    assert!(seal.ends_with(&blinding));
+    assert_eq!(revealed.vout.into_u32(), 3);
+
+    let json = format!(
+        r#"{{"method":"OpretFirst","txid":"{}","vout":3,"blinding":{}}}"#,
+        TXID, revealed.blinding
+    );
+    assert_eq!(
+        conceal(&json).unwrap(),
+        revealed.to_concealed_seal().to_string()
+    );
+
+    let witness = blind_seal("tapret1st", None, 0).unwrap();
+    let seal = field(&witness, "seal").as_string().unwrap();
+    assert!(seal.starts_with("tapret1st:~:0#"));
+}
+
+#[wasm_bindgen_test]
+fn errors() {
+    assert!(blind_seal("tapret2nd", None, 0).is_err());
+    assert!(blind_seal("tapret1st", Some("~".to_owned()), 0).is_err());
+    assert!(conceal("{}").is_err());
+    assert!(conceal("opret1st:~:1#0x1").is_err());
+}